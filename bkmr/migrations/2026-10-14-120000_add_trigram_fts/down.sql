@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER bookmarks_trigram_ad;
+DROP TRIGGER bookmarks_trigram_ai;
+DROP TRIGGER bookmarks_trigram_au;
+DROP TABLE bookmarks_fts_trigram;
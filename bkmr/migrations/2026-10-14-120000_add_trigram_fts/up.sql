@@ -0,0 +1,40 @@
+-- secondary index for languages without word boundaries (CJK)
+create virtual table bookmarks_fts_trigram using fts5
+(
+    URL,
+    metadata,
+    tags,
+    "desc",
+    content= 'bookmarks',
+    content_rowid= 'id',
+    tokenize= "trigram"
+);
+
+INSERT INTO bookmarks_fts_trigram (rowid, URL, metadata, tags, "desc")
+SELECT id, URL, metadata, tags, desc FROM bookmarks;
+
+CREATE TRIGGER bookmarks_trigram_ad
+    AFTER DELETE
+    ON bookmarks
+BEGIN
+    INSERT INTO bookmarks_fts_trigram (bookmarks_fts_trigram, rowid, URL, metadata, tags, "desc")
+    VALUES ('delete', old.id, old.URL, old.metadata, old.tags, old.desc);
+END;
+
+CREATE TRIGGER bookmarks_trigram_ai
+    AFTER INSERT
+    ON bookmarks
+BEGIN
+    INSERT INTO bookmarks_fts_trigram (rowid, URL, metadata, tags, "desc")
+    VALUES (new.id, new.URL, new.metadata, new.tags, new.desc);
+END;
+
+CREATE TRIGGER bookmarks_trigram_au
+    AFTER UPDATE
+    ON bookmarks
+BEGIN
+    INSERT INTO bookmarks_fts_trigram (bookmarks_fts_trigram, rowid, URL, metadata, tags, "desc")
+    VALUES ('delete', old.id, old.URL, old.metadata, old.tags, old.desc);
+    INSERT INTO bookmarks_fts_trigram (rowid, URL, metadata, tags, "desc")
+    VALUES (new.id, new.URL, new.metadata, new.tags, new.desc);
+END;
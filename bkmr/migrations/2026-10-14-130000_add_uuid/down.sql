@@ -0,0 +1,2 @@
+DROP INDEX bookmarks_uuid;
+ALTER TABLE bookmarks DROP COLUMN uuid;
@@ -0,0 +1,22 @@
+-- stable external reference, integer ids change by compaction and between machines
+ALTER TABLE bookmarks ADD COLUMN uuid VARCHAR NOT NULL DEFAULT '';
+
+-- backfilled values are no user change, keep last_update_ts
+DROP TRIGGER UpdateLastTime;
+
+-- random UUID v4 for existing bookmarks
+UPDATE bookmarks
+SET uuid = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' ||
+                 substr('89ab', 1 + abs(random()) % 4, 1) || substr(hex(randomblob(2)), 2) || '-' ||
+                 hex(randomblob(6)));
+
+CREATE TRIGGER [UpdateLastTime]
+    AFTER UPDATE
+    ON bookmarks
+    FOR EACH ROW
+    WHEN NEW.last_update_ts <= OLD.last_update_ts
+BEGIN
+    update bookmarks set last_update_ts=CURRENT_TIMESTAMP where id = OLD.id;
+END;
+
+CREATE UNIQUE INDEX bookmarks_uuid ON bookmarks (uuid);
@@ -0,0 +1,2 @@
+-- language of snippets and notes, e.g. rust, sql, en
+ALTER TABLE bookmarks ADD COLUMN language VARCHAR;
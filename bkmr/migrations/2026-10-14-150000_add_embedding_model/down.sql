@@ -0,0 +1,2 @@
+ALTER TABLE bookmarks DROP COLUMN embedding_dim;
+ALTER TABLE bookmarks DROP COLUMN embedding_model;
@@ -0,0 +1,21 @@
+ALTER TABLE bookmarks ADD COLUMN embedding_model VARCHAR;
+ALTER TABLE bookmarks ADD COLUMN embedding_dim INTEGER;
+
+-- backfilled values are no user change, keep last_update_ts
+DROP TRIGGER UpdateLastTime;
+
+-- existing embeddings were created with the former fixed model,
+-- blobs are bincode encoded: 8 byte length prefix + 4 bytes per f32
+UPDATE bookmarks
+SET embedding_model = 'text-embedding-ada-002',
+    embedding_dim   = (length(embedding) - 8) / 4
+WHERE embedding IS NOT NULL;
+
+CREATE TRIGGER [UpdateLastTime]
+    AFTER UPDATE
+    ON bookmarks
+    FOR EACH ROW
+    WHEN NEW.last_update_ts <= OLD.last_update_ts
+BEGIN
+    update bookmarks set last_update_ts=CURRENT_TIMESTAMP where id = OLD.id;
+END;
@@ -0,0 +1,2 @@
+-- Versioned hashes are not recognized by older versions, affected bookmarks get re-embedded there.
+SELECT 1;
@@ -0,0 +1,4 @@
+-- Content hashes become versioned md5 of the whitespace normalized content.
+-- SQLite has no md5, the hashes of unchanged bookmarks are recomputed by bkmr after this migration,
+-- see `upgrade_content_hashes`. Legacy hashes remain valid until then.
+SELECT 1;
@@ -0,0 +1,2 @@
+-- row generation for optimistic concurrency control, incremented by every update
+ALTER TABLE bookmarks ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+-- read-only snippet packs, synced into bookmarks tagged registry:<name>
+CREATE TABLE registries
+(
+    name      VARCHAR(255) NOT NULL PRIMARY KEY,
+    url       VARCHAR(2048) NOT NULL,
+    last_sync DATETIME
+);
@@ -0,0 +1 @@
+ALTER TABLE registries DROP COLUMN public_key;
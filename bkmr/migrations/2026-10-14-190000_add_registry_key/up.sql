@@ -0,0 +1,2 @@
+-- signing key of the registry packs, see export --sign
+ALTER TABLE registries ADD COLUMN public_key TEXT;
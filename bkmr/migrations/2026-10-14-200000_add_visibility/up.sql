@@ -0,0 +1,2 @@
+-- private, shared or public: what may leave the local database via export --sign and registries
+ALTER TABLE bookmarks ADD COLUMN visibility TEXT NOT NULL DEFAULT 'private';
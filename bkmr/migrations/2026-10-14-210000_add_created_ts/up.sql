@@ -0,0 +1,16 @@
+-- creation time for digests, existing bookmarks are approximated by their last update
+ALTER TABLE bookmarks ADD COLUMN created_ts DATETIME;
+
+-- backfilled values are no user change, keep last_update_ts
+DROP TRIGGER UpdateLastTime;
+
+UPDATE bookmarks SET created_ts = last_update_ts;
+
+CREATE TRIGGER [UpdateLastTime]
+    AFTER UPDATE
+    ON bookmarks
+    FOR EACH ROW
+    WHEN NEW.last_update_ts <= OLD.last_update_ts
+BEGIN
+    update bookmarks set last_update_ts=CURRENT_TIMESTAMP where id = OLD.id;
+END;
@@ -0,0 +1,6 @@
+DROP TRIGGER IF EXISTS readable_fts_au;
+DROP TRIGGER IF EXISTS readable_fts_ai;
+DROP TRIGGER IF EXISTS readable_fts_ad;
+DROP TABLE IF EXISTS readable_fts;
+DROP TRIGGER IF EXISTS readable_bookmark_ad;
+DROP TABLE IF EXISTS readable;
@@ -0,0 +1,51 @@
+-- readable text extracted from the fetched page, by bookmark uuid (ids change by compaction)
+CREATE TABLE readable
+(
+    uuid       VARCHAR NOT NULL PRIMARY KEY,
+    title      TEXT    NOT NULL,
+    byline     TEXT    NOT NULL,
+    text       TEXT    NOT NULL,
+    fetched_ts DATETIME NOT NULL
+);
+
+CREATE TRIGGER readable_bookmark_ad
+    AFTER DELETE
+    ON bookmarks
+BEGIN
+    DELETE FROM readable WHERE uuid = old.uuid;
+END;
+
+create virtual table readable_fts using fts5
+(
+    title,
+    byline,
+    text,
+    content= 'readable',
+    content_rowid= 'rowid'
+);
+
+CREATE TRIGGER readable_fts_ad
+    AFTER DELETE
+    ON readable
+BEGIN
+    INSERT INTO readable_fts (readable_fts, rowid, title, byline, text)
+    VALUES ('delete', old.rowid, old.title, old.byline, old.text);
+END;
+
+CREATE TRIGGER readable_fts_ai
+    AFTER INSERT
+    ON readable
+BEGIN
+    INSERT INTO readable_fts (rowid, title, byline, text)
+    VALUES (new.rowid, new.title, new.byline, new.text);
+END;
+
+CREATE TRIGGER readable_fts_au
+    AFTER UPDATE
+    ON readable
+BEGIN
+    INSERT INTO readable_fts (readable_fts, rowid, title, byline, text)
+    VALUES ('delete', old.rowid, old.title, old.byline, old.text);
+    INSERT INTO readable_fts (rowid, title, byline, text)
+    VALUES (new.rowid, new.title, new.byline, new.text);
+END;
@@ -0,0 +1,2 @@
+-- arbitrary key-value annotations of a bookmark as JSON object, NULL if there are none
+ALTER TABLE bookmarks ADD COLUMN meta TEXT;
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Debug;
 
@@ -5,18 +6,85 @@ use anyhow::{Context, Result};
 use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
 use diesel::result::Error as DieselError;
-use diesel::sql_types::{Integer, Text};
+use diesel::dsl::sql;
+use diesel::expression::SqlLiteral;
+use diesel::sql_types::{BigInt, Binary, Integer, Nullable, Text};
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
 use diesel::{sql_query, Connection, RunQueryDsl, SqliteConnection};
+use serde::Serialize;
 use tracing::{debug, instrument, trace};
 use schema::bookmarks::dsl::bookmarks;
 use schema::bookmarks::{
-    content_hash, desc, embedding, flags, id, metadata, tags, URL,
+    content_hash, desc, embedding, embedding_dim, embedding_model, flags, id, language, last_update_ts, metadata, tags, version, visibility, URL,
 };
-use crate::model::bookmark::{Bookmark, IdResult, NewBookmark, TagsFrequency};
+use crate::environment::{DatabaseConfig, CONFIG};
+use crate::model::bookmark::{Bookmark, IdResult, NewBookmark, RankResult, TagsFrequency};
+use crate::model::readable::Readable;
+use crate::model::registry::Registry;
+use crate::util::clock;
+use crate::util::helper::contains_cjk;
 
 pub mod schema;
 pub mod migration;
 
+/// Columns of `Bookmark` with a present embedding selected as empty placeholder instead of the blob
+type LazyColumns = (
+    id,
+    URL,
+    metadata,
+    tags,
+    desc,
+    flags,
+    last_update_ts,
+    SqlLiteral<Nullable<Binary>>,
+    content_hash,
+    schema::bookmarks::uuid,
+    language,
+    embedding_model,
+    embedding_dim,
+    version,
+    visibility,
+    schema::bookmarks::created_ts,
+    schema::bookmarks::meta,
+);
+
+fn lazy_columns() -> LazyColumns {
+    (
+        id,
+        URL,
+        metadata,
+        tags,
+        desc,
+        flags,
+        last_update_ts,
+        sql::<Nullable<Binary>>("CASE WHEN embedding IS NULL THEN NULL ELSE X'' END"),
+        content_hash,
+        schema::bookmarks::uuid,
+        language,
+        embedding_model,
+        embedding_dim,
+        version,
+        visibility,
+        schema::bookmarks::created_ts,
+        schema::bookmarks::meta,
+    )
+}
+
+/// Values of `journal_mode` in [database]
+const JOURNAL_MODES: [&str; 6] = ["delete", "truncate", "persist", "memory", "wal", "off"];
+
+/// Sets last_update_ts on updates which do not set it, as created by the initial migration
+const UPDATE_LAST_TIME_TRIGGER: &str = "
+    CREATE TRIGGER [UpdateLastTime]
+        AFTER UPDATE
+        ON bookmarks
+        FOR EACH ROW
+        WHEN NEW.last_update_ts <= OLD.last_update_ts
+    BEGIN
+        update bookmarks set last_update_ts=CURRENT_TIMESTAMP where id = OLD.id;
+    END;
+";
+
 // trait DalTrait {
 //     fn delete_bookmark(&mut self, id_: i32) -> Result<Vec<Bookmark>>;
 //     fn batch_execute(&mut self, id_: i32) -> Result<()>;
@@ -48,10 +116,10 @@ pub struct Dal {
 impl Dal {
     pub fn new(url: String) -> Self {
         debug!("{:?}", url);
-        Self {
-            conn: Dal::establish_connection(&url),
-            url,
-        }
+        let mut conn = Dal::establish_connection(&url);
+        Dal::configure(&mut conn, &CONFIG.database)
+            .unwrap_or_else(|e| panic!("Error configuring connection to {}: {:?}", url, e));
+        Self { conn, url }
     }
 
     fn establish_connection(database_url: &str) -> SqliteConnection {
@@ -59,6 +127,38 @@ impl Dal {
             .unwrap_or_else(|e| panic!("Error connecting to {}: {:?}", database_url, e))
     }
 
+    fn configure(conn: &mut SqliteConnection, config: &DatabaseConfig) -> Result<()> {
+        conn.batch_execute(&format!("PRAGMA busy_timeout = {};", config.busy_timeout_ms))?;
+        if let Some(mode) = &config.journal_mode {
+            if !JOURNAL_MODES.contains(&mode.to_lowercase().as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Invalid journal_mode {:?}, expected one of {}",
+                    mode,
+                    JOURNAL_MODES.join(", ")
+                ));
+            }
+            conn.batch_execute(&format!("PRAGMA journal_mode = {};", mode))?;
+        }
+        Ok(())
+    }
+
+    /// sqlite version and connection settings, for `bkmr info --db`
+    #[instrument(level = "debug")]
+    pub fn get_db_info(&mut self) -> Result<DbInfo> {
+        let settings: ConnectionSettings = sql_query(
+            "SELECT sqlite_version() AS sqlite_version, (SELECT * FROM pragma_journal_mode) AS journal_mode,
+            (SELECT * FROM pragma_busy_timeout) AS busy_timeout_ms;",
+        )
+        .get_result(&mut self.conn)
+        .with_context(|| "Failed to get connection settings")?;
+        Ok(DbInfo {
+            url: self.url.clone(),
+            sqlite_version: settings.sqlite_version,
+            journal_mode: settings.journal_mode,
+            busy_timeout_ms: settings.busy_timeout_ms,
+        })
+    }
+
     #[instrument(level = "debug")]
     pub fn delete_bookmark(&mut self, id_: i32) -> Result<Vec<Bookmark>> {
         diesel::delete(bookmarks.filter(id.eq(id_)))
@@ -81,12 +181,33 @@ impl Dal {
         Ok(())
     }
 
+    /// Unit of work: runs f within a transaction, all changes are rolled back if f fails.
+    /// Nested calls use savepoints, so multi-step operations compose into one atomic change.
+    pub fn transaction<T>(&mut self, f: impl FnOnce(&mut Dal) -> Result<T>) -> Result<T> {
+        AnsiTransactionManager::begin_transaction(&mut self.conn)
+            .with_context(|| "Failed to begin transaction")?;
+        match f(self) {
+            Ok(result) => {
+                AnsiTransactionManager::commit_transaction(&mut self.conn)
+                    .with_context(|| "Failed to commit transaction")?;
+                Ok(result)
+            }
+            Err(e) => {
+                AnsiTransactionManager::rollback_transaction(&mut self.conn)
+                    .with_context(|| "Failed to rollback transaction")?;
+                Err(e)
+            }
+        }
+    }
+
     #[instrument(level = "debug")]
     pub fn delete_bookmark2(&mut self, id_: i32) -> Result<usize> {
-        sql_query("BEGIN TRANSACTION;")
-            .execute(&mut self.conn)
-            .with_context(|| "Failed to begin transaction")?;
+        self.transaction(|dal| dal.delete_and_compact(id_))
+    }
 
+    /// Deletes the bookmark and closes the gap in ids, must run within a transaction
+    #[instrument(level = "debug")]
+    pub fn delete_and_compact(&mut self, id_: i32) -> Result<usize> {
         // Gotcha: 'returning *' not working within transaction
         let n = sql_query(
             "
@@ -110,10 +231,6 @@ impl Dal {
         .execute(&mut self.conn)
         .with_context(|| "Failed to compact bookmarks table")?;
 
-        sql_query("COMMIT;")
-            .execute(&mut self.conn)
-            .with_context(|| "Failed to commit transaction")?;
-
         debug!("Deleted and Compacted, n: {:?}",n);
         Ok(n)
     }
@@ -127,20 +244,80 @@ impl Dal {
         Ok(())
     }
 
+    /// Updates the bookmark if it has not been changed since it was read, i.e. the version matches
     #[instrument(level = "debug")]
-    pub fn update_bookmark(&mut self, bm: Bookmark) -> Result<Vec<Bookmark>> {
-        diesel::update(bookmarks.find(bm.id))
-            .set((
+    pub fn update_bookmark(&mut self, mut bm: Bookmark) -> Result<Vec<Bookmark>> {
+        let (id_, expected) = (bm.id, bm.version);
+        if bm.has_unloaded_embedding() {
+            // keeps the stored embedding instead of overwriting it with the placeholder
+            bm.embedding = self.get_embeddings(&[id_])?.remove(&id_);
+        }
+        // ids are renumbered by delete_and_compact, the uuid makes sure it is still the same bookmark
+        let updated: Vec<Bookmark> = diesel::update(
+            bookmarks
+                .find(id_)
+                .filter(version.eq(expected))
+                .filter(schema::bookmarks::uuid.eq(bm.uuid.clone())),
+        )
+        .set((
                 URL.eq(bm.URL),
                 metadata.eq(bm.metadata),
                 tags.eq(bm.tags),
                 desc.eq(bm.desc),
                 flags.eq(bm.flags),
                 embedding.eq(bm.embedding),
+                embedding_model.eq(bm.embedding_model),
+                embedding_dim.eq(bm.embedding_dim),
                 content_hash.eq(bm.content_hash),
+                language.eq(bm.language),
+                visibility.eq(bm.visibility),
+                schema::bookmarks::meta.eq(bm.meta),
+                last_update_ts.eq(clock::now_naive()),
+                version.eq(expected + 1),
             ))
             .get_results(&mut self.conn)
-            .with_context(|| format!("Failed to update bookmark with id {}", bm.id))
+            .with_context(|| format!("Failed to update bookmark with id {}", id_))?;
+        if updated.is_empty() {
+            if let Ok(current) = self.get_bookmark_by_id(id_) {
+                if current.uuid != bm.uuid {
+                    return Err(anyhow::anyhow!(
+                        "Conflict: bookmark {} has been deleted concurrently, its id belongs to another bookmark now, reload and retry",
+                        id_
+                    ));
+                }
+                return Err(anyhow::anyhow!(
+                    "Conflict: bookmark {} has been changed concurrently (version {}, expected {}), reload and retry",
+                    id_,
+                    current.version,
+                    expected
+                ));
+            }
+            return Err(anyhow::anyhow!(
+                "Conflict: bookmark {} was deleted concurrently, reload and retry",
+                id_
+            ));
+        }
+        Ok(updated)
+    }
+
+    /// Replaces content hashes without touching last_update_ts, the update trigger is suspended
+    #[instrument(level = "debug", skip(hashes))]
+    pub fn update_content_hashes(&mut self, hashes: &[(i32, Vec<u8>)]) -> Result<usize> {
+        self.transaction(|dal| {
+            dal.conn
+                .batch_execute("DROP TRIGGER IF EXISTS UpdateLastTime;")
+                .with_context(|| "Failed to suspend update trigger")?;
+            for (id_, hash) in hashes {
+                diesel::update(bookmarks.find(id_))
+                    .set(content_hash.eq(hash))
+                    .execute(&mut dal.conn)
+                    .with_context(|| format!("Failed to update content hash of bookmark {}", id_))?;
+            }
+            dal.conn
+                .batch_execute(UPDATE_LAST_TIME_TRIGGER)
+                .with_context(|| "Failed to restore update trigger")?;
+            Ok(hashes.len())
+        })
     }
 
     #[instrument(level = "debug")]
@@ -161,9 +338,17 @@ impl Dal {
                 tags: bm.tags.clone(),
                 desc: bm.desc.clone(),
                 flags: bm.flags,
-                last_update_ts: chrono::Utc::now().naive_utc(),
+                last_update_ts: clock::now_naive(),
                 embedding: new_bm.embedding.clone(),
                 content_hash: new_bm.content_hash.clone(),
+                uuid: bm.uuid.clone(),
+                language: new_bm.language.clone().or(bm.language.clone()),
+                embedding_model: new_bm.embedding_model.clone(),
+                embedding_dim: new_bm.embedding_dim,
+                version: bm.version,
+                visibility: bm.visibility.clone(),
+                created_ts: bm.created_ts,
+                meta: new_bm.meta.clone().or(bm.meta.clone()),
             }),
             Err(_) => self.insert_bookmark(new_bm),
         }
@@ -172,7 +357,7 @@ impl Dal {
     #[instrument(level = "debug")]
     pub fn get_bookmark_by_id(&mut self, id_: i32) -> Result<Bookmark> {
         sql_query(
-            "SELECT id, URL, metadata, tags, desc, flags, last_update_ts, embedding, content_hash FROM bookmarks \
+            "SELECT id, URL, metadata, tags, desc, flags, last_update_ts, embedding, content_hash, uuid, language, embedding_model, embedding_dim, version, visibility, created_ts, meta FROM bookmarks \
         where id = ?;",
         )
             .bind::<Integer, _>(id_)
@@ -183,6 +368,24 @@ impl Dal {
             })
     }
 
+    /// Bookmark by full uuid or unique uuid prefix
+    #[instrument(level = "debug")]
+    pub fn get_bookmark_by_uuid(&mut self, uuid_: &str) -> Result<Bookmark> {
+        let pattern = format!("{}%", uuid_.to_lowercase().replace(['%', '_'], ""));
+        let mut bms: Vec<Bookmark> = sql_query(
+            "SELECT id, URL, metadata, tags, desc, flags, last_update_ts, embedding, content_hash, uuid, language, embedding_model, embedding_dim, version, visibility, created_ts, meta FROM bookmarks \
+        where uuid LIKE ? LIMIT 2;",
+        )
+            .bind::<Text, _>(&pattern)
+            .get_results(&mut self.conn)
+            .with_context(|| format!("Database error while fetching bookmark {}", uuid_))?;
+        match bms.len() {
+            0 => Err(anyhow::anyhow!("Bookmark with uuid {} not found", uuid_)),
+            1 => Ok(bms.remove(0)),
+            _ => Err(anyhow::anyhow!("Ambiguous uuid prefix {}", uuid_)),
+        }
+    }
+
     // In dal.rs
     #[instrument(level = "debug")]
     pub fn get_bookmark_by_url(&mut self, url: &str) -> Result<Bookmark> {
@@ -190,7 +393,7 @@ impl Dal {
         let escaped_url = url.replace('\'', "''");
 
         sql_query(
-            "SELECT id, URL, metadata, tags, desc, flags, last_update_ts, embedding, content_hash
+            "SELECT id, URL, metadata, tags, desc, flags, last_update_ts, embedding, content_hash, uuid, language, embedding_model, embedding_dim, version, visibility, created_ts, meta
          FROM bookmarks
          WHERE URL = ?;",
         )
@@ -203,20 +406,66 @@ impl Dal {
     }
 
     #[instrument(level = "debug")]
+    /// Bookmarks matching the FTS query, all if empty. Embeddings are not loaded, a present one is an
+    /// empty placeholder (see `Bookmark::has_unloaded_embedding`), use `get_bookmarks_with_embeddings`.
     pub fn get_bookmarks(&mut self, query: &str) -> Result<Vec<Bookmark>> {
         if query.is_empty() {
             bookmarks
+                .select(lazy_columns())
                 .load::<Bookmark>(&mut self.conn)
                 .with_context(|| "Failed to load all bookmarks")
         } else {
-            let ids = self.get_bookmarks_fts(query)?;
+            let ids = if contains_cjk(query) {
+                self.get_bookmarks_trigram(query)?
+            } else {
+                self.get_bookmarks_fts(query)?
+            };
             bookmarks
+                .select(lazy_columns())
                 .filter(id.eq_any(ids))
                 .load::<Bookmark>(&mut self.conn)
                 .with_context(|| format!("Failed to load bookmarks matching query '{}'", query))
         }
     }
 
+    /// Like `get_bookmarks` with the embeddings, e.g. for semantic search and clustering
+    #[instrument(level = "debug")]
+    pub fn get_bookmarks_with_embeddings(&mut self, query: &str) -> Result<Vec<Bookmark>> {
+        let mut bms = self.get_bookmarks(query)?;
+        self.load_embeddings(&mut bms)?;
+        Ok(bms)
+    }
+
+    /// Replaces the embedding placeholders of the bookmarks by the stored embeddings
+    #[instrument(level = "debug", skip(bms))]
+    pub fn load_embeddings(&mut self, bms: &mut [Bookmark]) -> Result<()> {
+        let ids: Vec<i32> = bms.iter().filter(|bm| bm.has_unloaded_embedding()).map(|bm| bm.id).collect();
+        let mut embeddings = self.get_embeddings(&ids)?;
+        for bm in bms.iter_mut() {
+            if let Some(stored) = embeddings.remove(&bm.id) {
+                bm.embedding = Some(stored);
+            }
+        }
+        Ok(())
+    }
+
+    /// Embeddings of the bookmarks which have one, by id
+    #[instrument(level = "debug", skip(ids))]
+    pub fn get_embeddings(&mut self, ids: &[i32]) -> Result<HashMap<i32, Vec<u8>>> {
+        let mut result = HashMap::new();
+        // stays below the limit of bound parameters of sqlite
+        for chunk in ids.chunks(10_000) {
+            let rows: Vec<(i32, Vec<u8>)> = bookmarks
+                .filter(id.eq_any(chunk))
+                .filter(embedding.is_not_null())
+                .select((id, embedding.assume_not_null()))
+                .load(&mut self.conn)
+                .with_context(|| "Failed to load embeddings")?;
+            result.extend(rows);
+        }
+        Ok(result)
+    }
+
     #[instrument(level = "debug")]
     pub fn get_bookmarks_fts(&mut self, fts_query: &str) -> Result<Vec<i32>> {
         sql_query(
@@ -235,6 +484,62 @@ impl Dal {
         })
     }
 
+    /// FTS5 bm25 rank of the matching bookmarks, lower is better
+    #[instrument(level = "debug")]
+    pub fn get_fts_ranks(&mut self, fts_query: &str) -> Result<HashMap<i32, f64>> {
+        sql_query("SELECT id, rank FROM bookmarks_fts WHERE bookmarks_fts MATCH ?")
+            .bind::<Text, _>(fts_query)
+            .load::<RankResult>(&mut self.conn)
+            .map(|results| results.into_iter().map(|r| (r.id, r.rank)).collect())
+            .with_context(|| format!("Failed to rank full-text search with query '{}'", fts_query))
+    }
+
+    /// Substring search on the trigram index for text without word boundaries (CJK).
+    /// Terms are AND-combined, terms shorter than 3 characters fall back to LIKE.
+    #[instrument(level = "debug")]
+    pub fn get_bookmarks_trigram(&mut self, query: &str) -> Result<Vec<i32>> {
+        let (long_terms, short_terms): (Vec<&str>, Vec<&str>) = query
+            .split_whitespace()
+            .partition(|term| term.chars().count() >= 3);
+
+        let mut conditions = Vec::new();
+        if !long_terms.is_empty() {
+            conditions.push("bookmarks_fts_trigram MATCH ?".to_string());
+        }
+        for _ in &short_terms {
+            conditions.push(
+                r#"(URL LIKE ? OR metadata LIKE ? OR tags LIKE ? OR "desc" LIKE ?)"#.to_string(),
+            );
+        }
+        if conditions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut sql = sql_query(format!(
+            "SELECT rowid AS id FROM bookmarks_fts_trigram WHERE {};",
+            conditions.join(" AND ")
+        ))
+        .into_boxed();
+        if !long_terms.is_empty() {
+            let phrases = long_terms
+                .iter()
+                .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            sql = sql.bind::<Text, _>(phrases);
+        }
+        for term in &short_terms {
+            let pattern = format!("%{}%", term);
+            for _ in 0..4 {
+                sql = sql.bind::<Text, _>(pattern.clone());
+            }
+        }
+
+        sql.load::<IdResult>(&mut self.conn)
+            .map(|results| results.into_iter().map(|result| result.id).collect())
+            .with_context(|| format!("Failed to perform trigram search with query '{}'", query))
+    }
+
     #[instrument(level = "debug")]
     pub fn get_bookmarks_without_embedding(&mut self) -> Result<Vec<Bookmark>> {
         bookmarks
@@ -245,7 +550,7 @@ impl Dal {
 
     pub fn bm_exists(&mut self, url: &str) -> Result<bool> {
         sql_query(
-            "SELECT id, URL, metadata, tags, desc, flags, last_update_ts, embedding, content_hash FROM bookmarks \
+            "SELECT id, URL, metadata, tags, desc, flags, last_update_ts, embedding, content_hash, uuid, language, embedding_model, embedding_dim, version, visibility, created_ts, meta FROM bookmarks \
             where URL = ?;",
         )
             .bind::<Text, _>(url)
@@ -339,6 +644,81 @@ impl Dal {
         .with_context(|| format!("Failed to get {} oldest bookmarks", n))
     }
 
+    #[instrument(level = "debug")]
+    pub fn insert_registry(&mut self, registry: &Registry) -> Result<usize> {
+        diesel::insert_into(schema::registries::table)
+            .values(registry)
+            .execute(&mut self.conn)
+            .with_context(|| format!("Failed to add registry {}", registry.name))
+    }
+
+    pub fn get_registries(&mut self) -> Result<Vec<Registry>> {
+        schema::registries::table
+            .order(schema::registries::name)
+            .load::<Registry>(&mut self.conn)
+            .with_context(|| "Failed to get registries")
+    }
+
+    pub fn get_registry(&mut self, name: &str) -> Result<Registry> {
+        schema::registries::table
+            .find(name)
+            .first::<Registry>(&mut self.conn)
+            .map_err(|e| match e {
+                DieselError::NotFound => anyhow::anyhow!("Registry {} not found", name),
+                e => anyhow::anyhow!("Database error while fetching registry {}: {}", name, e),
+            })
+    }
+
+    #[instrument(level = "debug")]
+    pub fn delete_registry(&mut self, name: &str) -> Result<usize> {
+        diesel::delete(schema::registries::table.find(name))
+            .execute(&mut self.conn)
+            .with_context(|| format!("Failed to remove registry {}", name))
+    }
+
+    #[instrument(level = "debug")]
+    pub fn set_registry_synced(&mut self, name: &str) -> Result<usize> {
+        diesel::update(schema::registries::table.find(name))
+            .set(schema::registries::last_sync.eq(clock::now_naive()))
+            .execute(&mut self.conn)
+            .with_context(|| format!("Failed to update registry {}", name))
+    }
+
+    /// Inserts or replaces the readable text of a bookmark
+    pub fn upsert_readable(&mut self, readable: &Readable) -> Result<usize> {
+        diesel::insert_into(schema::readable::table)
+            .values(readable)
+            .on_conflict(schema::readable::uuid)
+            .do_update()
+            .set(readable)
+            .execute(&mut self.conn)
+            .with_context(|| format!("Failed to store readable text of bookmark {}", readable.uuid))
+    }
+
+    pub fn get_readable(&mut self, uuid_: &str) -> Result<Option<Readable>> {
+        schema::readable::table
+            .find(uuid_)
+            .first::<Readable>(&mut self.conn)
+            .optional()
+            .with_context(|| format!("Failed to get readable text of bookmark {}", uuid_))
+    }
+
+    /// Ids of the bookmarks whose readable text matches the FTS query, best match first
+    #[instrument(level = "debug")]
+    pub fn get_readable_fts(&mut self, fts_query: &str) -> Result<Vec<i32>> {
+        sql_query(
+            "SELECT b.id FROM readable_fts \
+            JOIN readable r ON r.rowid = readable_fts.rowid \
+            JOIN bookmarks b ON b.uuid = r.uuid \
+            WHERE readable_fts MATCH ? \
+            ORDER BY readable_fts.rank",
+        )
+        .bind::<Text, _>(fts_query)
+        .load::<IdResult>(&mut self.conn)
+        .map(|results| results.into_iter().map(|result| result.id).collect())
+        .with_context(|| format!("Failed to search readable texts with query '{}'", fts_query))
+    }
+
     #[instrument(level = "trace")]
     pub fn check_schema_migrations_exists(&mut self) -> Result<bool> {
         let query = "
@@ -368,6 +748,187 @@ impl Dal {
         trace!("Embedding ColumnCheck: {:?}", result);
         Ok(result.iter().any(|item| item.column_exists > 0))
     }
+
+    /// runs `PRAGMA integrity_check`, returns the reported problems (empty if ok)
+    #[instrument(level = "debug")]
+    pub fn integrity_check(&mut self) -> Result<Vec<String>> {
+        let result: Vec<IntegrityCheck> = sql_query("PRAGMA integrity_check;")
+            .load(&mut self.conn)
+            .with_context(|| "Failed to run integrity check")?;
+        Ok(result
+            .into_iter()
+            .map(|r| r.integrity_check)
+            .filter(|r| r != "ok")
+            .collect())
+    }
+
+    /// checks the internal consistency of the FTS index
+    #[instrument(level = "debug")]
+    pub fn fts_integrity_check(&mut self) -> Result<()> {
+        sql_query("INSERT INTO bookmarks_fts(bookmarks_fts, rank) VALUES('integrity-check', 0);")
+            .execute(&mut self.conn)
+            .with_context(|| "FTS index integrity check failed")?;
+        Ok(())
+    }
+
+    /// ids of bookmarks missing in the FTS index
+    #[instrument(level = "debug")]
+    pub fn get_fts_missing_ids(&mut self) -> Result<Vec<i32>> {
+        sql_query("SELECT id FROM bookmarks WHERE id NOT IN (SELECT id FROM bookmarks_fts_docsize);")
+            .load::<IdResult>(&mut self.conn)
+            .map(|results| results.into_iter().map(|result| result.id).collect())
+            .with_context(|| "Failed to get bookmarks missing in FTS index")
+    }
+
+    /// ids in the FTS index without corresponding bookmark
+    #[instrument(level = "debug")]
+    pub fn get_fts_stale_ids(&mut self) -> Result<Vec<i32>> {
+        sql_query("SELECT id FROM bookmarks_fts_docsize WHERE id NOT IN (SELECT id FROM bookmarks);")
+            .load::<IdResult>(&mut self.conn)
+            .map(|results| results.into_iter().map(|result| result.id).collect())
+            .with_context(|| "Failed to get stale FTS index entries")
+    }
+
+    /// recreates the FTS indexes from the bookmarks table with the columns maintained by the triggers
+    #[instrument(level = "debug")]
+    pub fn rebuild_fts(&mut self) -> Result<()> {
+        let query = r#"
+            INSERT INTO bookmarks_fts(bookmarks_fts) VALUES('delete-all');
+            INSERT INTO bookmarks_fts (rowid, URL, metadata, tags, "desc")
+            SELECT id, URL, metadata, tags, desc FROM bookmarks;
+            INSERT INTO bookmarks_fts_trigram(bookmarks_fts_trigram) VALUES('delete-all');
+            INSERT INTO bookmarks_fts_trigram (rowid, URL, metadata, tags, "desc")
+            SELECT id, URL, metadata, tags, desc FROM bookmarks;
+        "#;
+        self.transaction(|dal| {
+            dal.conn
+                .batch_execute(query)
+                .with_context(|| "Failed to rebuild FTS index")
+        })?;
+        debug!("Rebuilt FTS index");
+        Ok(())
+    }
+
+    /// writes a consistent copy of the database to a new file
+    #[instrument(level = "debug")]
+    pub fn backup_into(&mut self, path: &str) -> Result<()> {
+        sql_query("VACUUM INTO ?;")
+            .bind::<Text, _>(path)
+            .execute(&mut self.conn)
+            .with_context(|| format!("Failed to back up database to {}", path))?;
+        Ok(())
+    }
+
+    /// rebuilds the database file, releasing free pages, e.g. of cleared embeddings
+    #[instrument(level = "debug")]
+    pub fn vacuum(&mut self) -> Result<()> {
+        self.conn.batch_execute("VACUUM;").with_context(|| "Failed to vacuum database")
+    }
+
+    /// updates the statistics of the query planner
+    #[instrument(level = "debug")]
+    pub fn analyze(&mut self) -> Result<()> {
+        self.conn.batch_execute("ANALYZE;").with_context(|| "Failed to analyze database")
+    }
+
+    /// page size, number of pages and number of free pages of the database file
+    #[instrument(level = "debug")]
+    pub fn get_page_stats(&mut self) -> Result<PageStats> {
+        sql_query("SELECT page_size, page_count, freelist_count FROM pragma_page_size, pragma_page_count, pragma_freelist_count;")
+            .get_result::<PageStats>(&mut self.conn)
+            .with_context(|| "Failed to get page statistics")
+    }
+
+    /// bytes used by each table and index, largest first
+    #[instrument(level = "debug")]
+    pub fn get_object_sizes(&mut self) -> Result<Vec<ObjectSize>> {
+        sql_query(
+            "SELECT s.name AS name, COALESCE(m.type, 'table') AS kind, SUM(s.pgsize) AS size
+            FROM dbstat s LEFT JOIN sqlite_master m ON m.name = s.name
+            GROUP BY s.name ORDER BY size DESC, s.name;",
+        )
+        .load::<ObjectSize>(&mut self.conn)
+        .with_context(|| "Failed to get table sizes")
+    }
+
+    /// number of embeddings and their total bytes
+    #[instrument(level = "debug")]
+    pub fn get_embedding_size(&mut self) -> Result<(i64, i64)> {
+        let result: EmbeddingSize =
+            sql_query("SELECT COUNT(embedding) AS n, COALESCE(SUM(length(embedding)), 0) AS size FROM bookmarks;")
+                .get_result(&mut self.conn)
+                .with_context(|| "Failed to get embedding size")?;
+        Ok((result.n, result.size))
+    }
+
+    /// number of bookmarks with the tag, without loading them
+    #[instrument(level = "debug")]
+    pub fn count_tagged(&mut self, tag: &str) -> Result<i64> {
+        let result: TagCount = sql_query("SELECT COUNT(*) AS n FROM bookmarks WHERE instr(tags, ?) > 0;")
+            .bind::<Text, _>(format!(",{},", tag))
+            .get_result(&mut self.conn)
+            .with_context(|| format!("Failed to count bookmarks tagged {}", tag))?;
+        Ok(result.n)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DbInfo {
+    pub url: String,
+    pub sqlite_version: String,
+    pub journal_mode: String,
+    pub busy_timeout_ms: i64,
+}
+
+#[derive(QueryableByName, Debug)]
+struct ConnectionSettings {
+    #[diesel(sql_type = Text)]
+    sqlite_version: String,
+    #[diesel(sql_type = Text)]
+    journal_mode: String,
+    #[diesel(sql_type = BigInt)]
+    busy_timeout_ms: i64,
+}
+
+#[derive(QueryableByName, Debug, Clone, PartialEq)]
+pub struct PageStats {
+    #[diesel(sql_type = BigInt)]
+    pub page_size: i64,
+    #[diesel(sql_type = BigInt)]
+    pub page_count: i64,
+    #[diesel(sql_type = BigInt)]
+    pub freelist_count: i64,
+}
+
+#[derive(QueryableByName, Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectSize {
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    /// table or index
+    #[diesel(sql_type = Text)]
+    pub kind: String,
+    #[diesel(sql_type = BigInt)]
+    pub size: i64,
+}
+
+#[derive(QueryableByName, Debug)]
+struct EmbeddingSize {
+    #[diesel(sql_type = BigInt)]
+    n: i64,
+    #[diesel(sql_type = BigInt)]
+    size: i64,
+}
+
+#[derive(QueryableByName, Debug)]
+struct TagCount {
+    #[diesel(sql_type = BigInt)]
+    n: i64,
+}
+
+#[derive(QueryableByName, Debug)]
+struct IntegrityCheck {
+    #[diesel(sql_type = Text)]
+    integrity_check: String,
 }
 
 #[derive(QueryableByName, Debug)]
@@ -11,6 +11,14 @@ diesel::table! {
         last_update_ts -> Timestamp,
         embedding -> Nullable<Binary>,
         content_hash -> Nullable<Binary>,
+        uuid -> Text,
+        language -> Nullable<Text>,
+        embedding_model -> Nullable<Text>,
+        embedding_dim -> Nullable<Integer>,
+        version -> Integer,
+        visibility -> Text,
+        created_ts -> Nullable<Timestamp>,
+        meta -> Nullable<Text>,
     }
 }
 
@@ -25,3 +33,22 @@ diesel::table! {
         last_update_ts -> Timestamp,
     }
 }
+
+diesel::table! {
+    registries (name) {
+        name -> Text,
+        url -> Text,
+        last_sync -> Nullable<Timestamp>,
+        public_key -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    readable (uuid) {
+        uuid -> Text,
+        title -> Text,
+        byline -> Text,
+        text -> Text,
+        fetched_ts -> Timestamp,
+    }
+}
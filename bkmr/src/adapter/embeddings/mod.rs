@@ -1,7 +1,7 @@
 mod providers;
 mod utils;
 
-pub use providers::{DummyEmbedding, OpenAiEmbedding};
+pub use providers::{estimate_tokens, price_per_million_tokens, DummyEmbedding, OpenAiEmbedding};
 pub use utils::{cosine_similarity, deserialize_embedding, serialize_embedding};
 
 use anyhow::Result;
@@ -10,4 +10,14 @@ use anyhow::Result;
 pub trait Embedding: Send + Sync {
     /// Embeds text into a vector of floats
     fn embed(&self, text: &str) -> Result<Option<Vec<f32>>>;
+
+    /// Model name stored with the embeddings, None if no embeddings are produced
+    fn model(&self) -> Option<&str> {
+        None
+    }
+
+    /// One-line summary of the text, None if the provider cannot generate text
+    fn summarize(&self, _text: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
\ No newline at end of file
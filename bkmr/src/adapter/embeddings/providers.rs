@@ -1,8 +1,42 @@
 use std::env;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use anyhow::{anyhow, Result, Context as _};
+use reqwest::blocking::Response;
+use reqwest::StatusCode;
 use serde_derive::{Deserialize, Serialize};
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 use super::Embedding;
+use crate::environment::{OpenAiConfig, CONFIG};
+
+pub const DEFAULT_OPENAI_MODEL: &str = "text-embedding-ada-002";
+/// Chat model generating the one-line summaries of `digest --summaries`
+pub const SUMMARY_MODEL: &str = "gpt-4o-mini";
+const SUMMARY_PROMPT: &str = "Summarize the bookmark in one short sentence, without preamble.";
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const BUDGET_WINDOW: Duration = Duration::from_secs(60);
+const CHARS_PER_TOKEN: usize = 4;
+
+/// USD per million input tokens of the OpenAI embedding models
+const PRICES_PER_MILLION_TOKENS: &[(&str, f64)] = &[
+    ("text-embedding-ada-002", 0.10),
+    ("text-embedding-3-small", 0.02),
+    ("text-embedding-3-large", 0.13),
+];
+
+/// Rough token count of a text, OpenAI tokens average about four characters
+pub fn estimate_tokens(text: &str) -> u64 {
+    text.len().div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Price in USD per million tokens, None for models not in the pricing table
+pub fn price_per_million_tokens(model: &str) -> Option<f64> {
+    PRICES_PER_MILLION_TOKENS
+        .iter()
+        .find(|(m, _)| *m == model)
+        .map(|(_, price)| *price)
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct DummyEmbedding;
@@ -18,12 +52,61 @@ impl Embedding for DummyEmbedding {
 #[derive(Debug, Clone)]
 pub struct OpenAiEmbedding {
     url: String,
+    model: String,
+    policy: OpenAiConfig,
+    budget: Arc<Mutex<Budget>>,
+}
+
+/// Requests and estimated tokens spent in the current one minute window
+#[derive(Debug)]
+struct Budget {
+    window_start: Instant,
+    requests: u32,
+    tokens: u64,
+}
+
+impl Budget {
+    fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            requests: 0,
+            tokens: 0,
+        }
+    }
+
+    /// Blocks until the request fits into the per-minute budget
+    fn acquire(&mut self, policy: &OpenAiConfig, tokens: u64) {
+        if self.window_start.elapsed() >= BUDGET_WINDOW {
+            *self = Self::new();
+        }
+        let requests_exceeded = policy
+            .requests_per_minute
+            .is_some_and(|max| self.requests >= max);
+        let tokens_exceeded = policy
+            .tokens_per_minute
+            .is_some_and(|max| self.requests > 0 && self.tokens + tokens > max);
+        if requests_exceeded || tokens_exceeded {
+            let wait = BUDGET_WINDOW.saturating_sub(self.window_start.elapsed());
+            debug!("OpenAI budget exhausted, waiting {:?}", wait);
+            thread::sleep(wait);
+            *self = Self::new();
+        }
+        self.requests += 1;
+        self.tokens += tokens;
+    }
 }
 
 impl Default for OpenAiEmbedding {
+    /// OpenAI API with the configured `embedding_model`
     fn default() -> Self {
         Self {
             url: "https://api.openai.com".to_string(),
+            model: CONFIG
+                .embedding_model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string()),
+            policy: CONFIG.openai,
+            budget: Arc::new(Mutex::new(Budget::new())),
         }
     }
 }
@@ -44,23 +127,39 @@ struct EmbeddingData {
     embedding: Vec<f32>,
 }
 
+#[derive(Serialize, Deserialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    max_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
 impl Embedding for OpenAiEmbedding {
     #[instrument]
     fn embed(&self, text: &str) -> Result<Option<Vec<f32>>> {
         debug!("OpenAI embedding request for: {}", text);
-        let client = reqwest::blocking::Client::new();
-        let api_key = env::var("OPENAI_API_KEY").context("OPENAI_API_KEY not set")?;
-
         let request = EmbeddingRequest {
             input: text.to_string(),
-            model: "text-embedding-ada-002".to_string(),
+            model: self.model.clone(),
         };
-
-        let response = client
-            .post(format!("{}/v1/embeddings", self.url))
-            .header("Authorization", format!("Bearer {}", api_key))
-            .json(&request)
-            .send()?
+        let response = self
+            .post("/v1/embeddings", &request, estimate_tokens(text))?
             .json::<EmbeddingResponse>()
             .context("Failed to parse OpenAI response")?;
 
@@ -69,10 +168,220 @@ impl Embedding for OpenAiEmbedding {
             .ok_or_else(|| anyhow!("No embeddings in response"))
             .map(Some)
     }
+
+    fn model(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    #[instrument]
+    fn summarize(&self, text: &str) -> Result<Option<String>> {
+        let message = |role: &str, content: &str| ChatMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+        };
+        let request = ChatRequest {
+            model: SUMMARY_MODEL.to_string(),
+            messages: vec![message("system", SUMMARY_PROMPT), message("user", text)],
+            max_tokens: 60,
+        };
+        let response = self
+            .post("/v1/chat/completions", &request, estimate_tokens(text))?
+            .json::<ChatResponse>()
+            .context("Failed to parse OpenAI response")?;
+
+        let summary = response
+            .choices
+            .first()
+            .and_then(|choice| choice.message.content.lines().map(str::trim).find(|line| !line.is_empty()))
+            .ok_or_else(|| anyhow!("No summary in response"))?;
+        Ok(Some(summary.to_string()))
+    }
 }
 
 impl OpenAiEmbedding {
     pub fn new(url: String) -> Self {
-        Self { url }
+        Self {
+            url,
+            model: DEFAULT_OPENAI_MODEL.to_string(),
+            policy: OpenAiConfig::default(),
+            budget: Arc::new(Mutex::new(Budget::new())),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: OpenAiConfig) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Posts the request within the budget, retrying rate limits, server and connection errors
+    fn post(&self, path: &str, request: &impl serde::Serialize, tokens: u64) -> Result<Response> {
+        let client = reqwest::blocking::Client::new();
+        let api_key = env::var("OPENAI_API_KEY").context("OPENAI_API_KEY not set")?;
+
+        let mut attempt = 0;
+        loop {
+            self.budget
+                .lock()
+                .map_err(|e| anyhow!("OpenAI budget lock poisoned: {}", e))?
+                .acquire(&self.policy, tokens);
+            let result = client
+                .post(format!("{}{}", self.url, path))
+                .header("Authorization", format!("Bearer {}", api_key))
+                .json(request)
+                .send();
+
+            let (error, retry_after) = match result {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) if is_retryable(response.status()) => {
+                    let retry_after = retry_after(&response);
+                    (api_error(response), retry_after)
+                }
+                Ok(response) => return Err(api_error(response)),
+                Err(e) if e.is_timeout() || e.is_connect() => {
+                    (anyhow!("OpenAI request failed: {}", e), None)
+                }
+                Err(e) => return Err(anyhow!("OpenAI request failed: {}", e)),
+            };
+            if attempt >= self.policy.max_retries {
+                return Err(error.context(format!("Giving up after {} retries", attempt)));
+            }
+            let backoff = retry_after.unwrap_or_else(|| {
+                Duration::from_millis(self.policy.backoff_ms.saturating_mul(1 << attempt.min(16)))
+            });
+            attempt += 1;
+            warn!("{:#}, retry {}/{} in {:?}", error, attempt, self.policy.max_retries, backoff);
+            thread::sleep(backoff.min(MAX_BACKOFF));
+        }
+    }
+}
+
+/// Rate limits and server errors are worth retrying, other client errors are not
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Delay requested by the `Retry-After` header in seconds
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Error with the status and the message of the OpenAI error response
+fn api_error(response: Response) -> anyhow::Error {
+    let status = response.status();
+    let body = response.text().unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|json| json["error"]["message"].as_str().map(String::from))
+        .unwrap_or(body);
+    anyhow!("OpenAI API error {}: {}", status, message.trim())
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    fn policy(max_retries: u32) -> OpenAiConfig {
+        OpenAiConfig {
+            max_retries,
+            backoff_ms: 1,
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    fn test_retry_on_rate_limit() {
+        let mut server = mockito::Server::new();
+        env::set_var("OPENAI_API_KEY", "test_key");
+        let limited = server
+            .mock("POST", "/v1/embeddings")
+            .with_status(429)
+            .with_header("retry-after", "0")
+            .with_body(r#"{"error": {"message": "Rate limit reached"}}"#)
+            .expect(2)
+            .create();
+        let ok = server
+            .mock("POST", "/v1/embeddings")
+            .with_status(200)
+            .with_body(r#"{"data": [{"embedding": [0.1, 0.2]}]}"#)
+            .expect(1)
+            .create();
+
+        let embedder = OpenAiEmbedding::new(server.url()).with_policy(policy(3));
+        assert_eq!(embedder.embed("text").unwrap(), Some(vec![0.1, 0.2]));
+        limited.assert();
+        ok.assert();
+    }
+
+    #[rstest]
+    fn test_errors_are_surfaced() {
+        let mut server = mockito::Server::new();
+        env::set_var("OPENAI_API_KEY", "test_key");
+        server
+            .mock("POST", "/v1/embeddings")
+            .with_status(503)
+            .with_body("unavailable")
+            .expect(2)
+            .create();
+        let embedder = OpenAiEmbedding::new(server.url()).with_policy(policy(1));
+        let error = format!("{:#}", embedder.embed("text").unwrap_err());
+        assert!(error.contains("Giving up after 1 retries"), "{}", error);
+        assert!(error.contains("503"), "{}", error);
+
+
+        // client errors are not retried
+        let mut server = mockito::Server::new();
+        let unauthorized = server
+            .mock("POST", "/v1/embeddings")
+            .with_status(401)
+            .with_body(r#"{"error": {"message": "Incorrect API key"}}"#)
+            .expect(1)
+            .create();
+        let embedder = OpenAiEmbedding::new(server.url()).with_policy(policy(3));
+        let error = embedder.embed("text").unwrap_err().to_string();
+        assert_eq!(error, "OpenAI API error 401 Unauthorized: Incorrect API key");
+        unauthorized.assert();
+    }
+
+    #[rstest]
+    fn test_summarize() {
+        let mut server = mockito::Server::new();
+        env::set_var("OPENAI_API_KEY", "test_key");
+        let chat = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJsonString(format!(r#"{{"model": "{}"}}"#, SUMMARY_MODEL)))
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "\nA fast search engine.\n"}}]}"#)
+            .create();
+
+        let embedder = OpenAiEmbedding::new(server.url()).with_policy(policy(0));
+        assert_eq!(
+            embedder.summarize("tantivy -- full text search").unwrap().as_deref(),
+            Some("A fast search engine.")
+        );
+        chat.assert();
+        assert_eq!(DummyEmbedding.summarize("text").unwrap(), None);
+    }
+
+    #[rstest]
+    fn test_budget() {
+        let policy = OpenAiConfig {
+            requests_per_minute: Some(2),
+            ..Default::default()
+        };
+        let mut budget = Budget::new();
+        budget.acquire(&policy, 10);
+        budget.acquire(&policy, 10);
+        assert_eq!((budget.requests, budget.tokens), (2, 20));
+
+        // a new window starts after one minute
+        budget.window_start = Instant::now() - BUDGET_WINDOW;
+        budget.acquire(&policy, 5);
+        assert_eq!((budget.requests, budget.tokens), (1, 5));
     }
 }
\ No newline at end of file
@@ -2,10 +2,13 @@
 
 use crate::util::helper::extract_filename;
 use crate::model::bookmark::{Bookmark, BookmarkBuilder};
+use crate::service::facets::FacetCount;
+use crate::service::secrets::redact_bookmark;
 use anyhow::Context;
 use camino::Utf8Path;
 use chrono::NaiveDateTime;
 use serde_derive::Serialize;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io;
 use std::io::{BufRead, Write};
@@ -22,7 +25,7 @@ use std::io::{BufRead, Write};
 /// # Returns
 ///
 /// * `anyhow::Result<()>` - Returns `Ok(())` if the JSON string conforms to the expected format,
-/// otherwise returns an `Err` with a message indicating the invalid JSON format.
+///   otherwise returns an `Err` with a message indicating the invalid JSON format.
 ///
 /// # Errors
 ///
@@ -76,7 +79,7 @@ where
     Ok(bookmarks)
 }
 
-pub fn bms_to_json(bms: &Vec<Bookmark>) {
+pub fn bms_to_json(bms: &[Bookmark]) {
     let bms_view: Vec<BookmarkView> = bms.iter().map(BookmarkView::from).collect();
     let json =
         serde_json::to_string_pretty(&bms_view).expect("Failed to serialize bookmarks to JSON.");
@@ -86,6 +89,15 @@ pub fn bms_to_json(bms: &Vec<Bookmark>) {
     println!();
 }
 
+/// `search --json --facets` output, the facet counts next to the bookmarks
+pub fn bms_with_facets_to_json(bms: &[Bookmark], facets: &BTreeMap<String, Vec<FacetCount>>) {
+    let json = serde_json::json!({
+        "bookmarks": bms.iter().map(BookmarkView::from).collect::<Vec<_>>(),
+        "facets": facets,
+    });
+    println!("{}", serde_json::to_string_pretty(&json).expect("Failed to serialize bookmarks to JSON."));
+}
+
 #[derive(Serialize)]
 pub struct BookmarkView {
     pub id: i32,
@@ -96,10 +108,13 @@ pub struct BookmarkView {
     pub flags: i32,
     #[serde(with = "serde_with::chrono::NaiveDateTime")]
     pub last_update_ts: NaiveDateTime,
+    pub uuid: String,
 }
 
+/// `_secret_` bookmarks are redacted, views leave bkmr as `--json` output and hook payloads
 impl From<&Bookmark> for BookmarkView {
     fn from(bm: &Bookmark) -> Self {
+        let bm = &redact_bookmark(bm);
         BookmarkView {
             id: bm.id,
             URL: bm.URL.clone(),
@@ -108,6 +123,7 @@ impl From<&Bookmark> for BookmarkView {
             desc: bm.desc.clone(),
             flags: bm.flags,
             last_update_ts: bm.last_update_ts,
+            uuid: bm.uuid.clone(),
         }
     }
 }
@@ -1,5 +1,17 @@
 // src/cli/args.rs
 use clap::{Parser, Subcommand};
+
+use crate::model::bookmark::Visibility;
+use crate::service::batch::BatchOp;
+use crate::service::facets::Facet;
+use crate::service::bulk::BulkFormat;
+use crate::service::cheats::CheatSource;
+use crate::service::clipboard::CopyFormat;
+use crate::service::dir::Shell;
+use crate::service::merge::{MergeStrategy, OnExists};
+use crate::service::saved::SavedSource;
+use crate::service::tags::TagSort;
+use crate::service::timeline::Period;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -11,7 +23,7 @@ pub struct Cli {
 
     /// Sets a custom config file
     #[arg(short, long, value_name = "FILE")]
-    config: Option<PathBuf>,
+    pub config: Option<PathBuf>,
 
     /// Turn debugging information on
     #[arg(short, long, action = clap::ArgAction::Count)]
@@ -20,6 +32,22 @@ pub struct Cli {
     #[arg(long = "openai", help = "use OpenAI API to embed bookmarks")]
     pub openai: bool,
 
+    /// Use a database migrated by a newer bkmr anyway, which may fail or lose data of its new features
+    #[arg(long = "force")]
+    pub force: bool,
+
+    /// Also log to this file, overrides the [logging] config
+    #[arg(long = "log-file", value_name = "FILE", global = true)]
+    pub log_file: Option<PathBuf>,
+
+    /// Fail with exit code 3 instead of prompting, for scripts and cron jobs
+    #[arg(long = "no-input", global = true, env = "BKMR_NO_INPUT")]
+    pub no_input: bool,
+
+    /// Plain labeled output without colors and fzf screens, e.g. for screen readers
+    #[arg(long = "plain", global = true, env = "BKMR_PLAIN")]
+    pub plain: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -43,21 +71,35 @@ pub enum Commands {
 
         #[arg(
         short = 'T',
-        long = "Tags",
+        long = "ntags-all",
+        visible_alias = "Tags",
         help = "not match all, comma separated list"
         )]
         tags_all_not: Option<String>,
 
-        #[arg(short = 'n', long = "ntags", help = "match any, comma separated list")]
+        #[arg(short = 'n', long = "ntags", visible_alias = "tags-any", help = "match any, comma separated list")]
         tags_any: Option<String>,
 
         #[arg(
         short = 'N',
-        long = "Ntags",
+        long = "exclude-any",
+        visible_alias = "Ntags",
         help = "not match any, comma separated list"
         )]
         tags_any_not: Option<String>,
 
+        #[arg(long = "untagged", help = "only bookmarks without tags")]
+        untagged: bool,
+
+        #[arg(long = "language", help = "only bookmarks with this language, e.g. rust")]
+        language: Option<String>,
+
+        #[arg(long = "min-hits", help = "only bookmarks opened at least n times")]
+        min_hits: Option<i32>,
+
+        #[arg(long = "unused-since", value_name = "AGE", help = "only bookmarks not opened or modified for AGE, e.g. 30d, 6m, 1y")]
+        unused_since: Option<String>,
+
         #[arg(long = "prefix", help = "tags to prefix the tags option")]
         tags_prefix: Option<String>,
 
@@ -81,6 +123,20 @@ pub enum Commands {
 
         #[arg(short = 'l', long = "limit", help = "limit number of results")]
         limit: Option<i32>,
+
+        #[arg(long = "explain", help = "show score breakdown per result")]
+        explain: bool,
+
+        #[arg(long = "fulltext", help = "also match the readable text of the fetched pages")]
+        fulltext: bool,
+
+        #[arg(
+        long = "facets",
+        value_delimiter = ',',
+        conflicts_with = "is_fuzzy",
+        help = "also count the matches per value of the facets, before --limit, e.g. tags,domain,year"
+        )]
+        facets: Vec<Facet>,
     },
     /// Semantic Search with OpenAI
     SemSearch {
@@ -90,17 +146,57 @@ pub enum Commands {
         #[arg(short = 'l', long = "limit", help = "limit number of results")]
         limit: Option<i32>,
 
+        #[arg(short = 't', long = "tags", help = "only bookmarks with all tags")]
+        tags: Option<String>,
+
+        #[arg(long = "type", help = "only bookmarks of this type (system tag), e.g. sql, _snip_")]
+        bm_type: Option<String>,
+
+        #[arg(long = "threshold", help = "minimum similarity, e.g. 0.8")]
+        threshold: Option<f32>,
+
+        #[arg(long = "explain", help = "show score breakdown per result")]
+        explain: bool,
+
         #[arg(long = "np", help = "no prompt")]
         non_interactive: bool,
     },
     /// Open/launch bookmarks
     Open {
-        /// list of ids, separated by comma, no blanks
+        /// list of ids, uuids or titles (unique prefix), separated by comma, no blanks
         ids: String,
     },
+    /// All actions applicable to a bookmark to choose from: open, copy, show, edit, read, delete, ...
+    Actions {
+        /// id, uuid or title
+        id: String,
+    },
+    /// Copy a bookmark to the clipboard in an explicit format
+    Copy {
+        /// id, uuid or title
+        id: String,
+        #[arg(long = "as", value_enum, default_value_t = CopyFormat::Raw, help = "clipboard payload")]
+        format: CopyFormat,
+    },
+    /// Answer one JSON request (search, add, get) with JSON on stdout, for editor plugins
+    Rpc {
+        #[arg(long = "stdin-json", required = true, help = "read the request from stdin, e.g. {\"method\": \"get\", \"params\": {\"id\": 1}}")]
+        stdin_json: bool,
+    },
+    /// Pick a bookmark in a tmux popup and type its interpolated content into the current pane
+    TmuxPopup {
+        #[arg(short = 't', long = "tags", help = "only bookmarks with all of these tags, e.g. _snip_")]
+        tags: Option<String>,
+        #[arg(long = "pane", help = "pick in this terminal and type into the pane, e.g. #{pane_id} (default: open a popup for the current pane)")]
+        pane: Option<String>,
+    },
     /// Add a bookmark
     Add {
-        url: String,
+        #[arg(
+            required_unless_present_any = ["template", "from_clipboard"],
+            conflicts_with_all = ["template", "from_clipboard"]
+        )]
+        url: Option<String>,
         /// list of tags, separated by comma, no blanks in between
         tags: Option<String>,
         #[arg(long = "title", help = "title")]
@@ -111,39 +207,134 @@ pub enum Commands {
         no_web: bool,
         #[arg(short = 'e', long = "edit", help = "edit the bookmark while adding")]
         edit: bool,
+        #[arg(long = "template", help = "create from the template bookmark with this title (tag _template_)")]
+        template: Option<String>,
+        #[arg(long = "from-clipboard", conflicts_with = "template", help = "add the clipboard content: URL, markdown or snippet, opens the editor")]
+        from_clipboard: bool,
+        #[arg(long = "language", help = "language of the content, e.g. rust, sql (default: detected)")]
+        language: Option<String>,
+        #[arg(long = "inbox", conflicts_with_all = ["template", "from_clipboard", "edit"], help = "quick capture tagged _inbox_ without fetching URL data, see triage")]
+        inbox: bool,
+        #[arg(long = "on-exists", value_enum, default_value_t = OnExists::Ask, help = "when the URL is already bookmarked: merge title, description and tags into it, skip or error")]
+        on_exists: OnExists,
+        #[arg(long = "canonicalize", conflicts_with_all = ["no_web", "inbox", "template", "from_clipboard"], help = "offer the canonical URL of the page (link rel=canonical, og:url) instead, taken without asking with --no-input")]
+        canonicalize: bool,
+    },
+    /// Review the _inbox_ bookmarks one at a time: keep with tags, discard or skip
+    Triage {
+        #[arg(long = "no-web", help = "do not fetch URL data of untitled bookmarks")]
+        no_web: bool,
+    },
+    /// Review recent bookmarks one at a time: keep, tag or delete, progress is kept between sessions
+    Review {
+        #[arg(long = "since", value_name = "AGE", help = "bookmarks created within, e.g. 7d, 2w (default: 7d unless --untagged or --unopened)")]
+        since: Option<String>,
+        #[arg(long = "untagged", help = "only bookmarks without tags")]
+        untagged: bool,
+        #[arg(long = "unopened", help = "only bookmarks never opened")]
+        unopened: bool,
+        #[arg(long = "reset", help = "forget the reviewed bookmarks and start over")]
+        reset: bool,
+    },
+    /// Show the readable text of a bookmark: title, byline and main text of the page
+    Read {
+        /// id or uuid
+        id: String,
+        #[arg(long = "fetch", help = "fetch the page (again) and store its readable text")]
+        fetch: bool,
     },
     /// Delete bookmarks
     Delete {
         /// list of ids, separated by comma, no blanks
-        ids: String,
+        #[arg(required_unless_present_any = ["query", "tags"], conflicts_with_all = ["query", "tags"])]
+        ids: Option<String>,
+        #[arg(long = "query", help = "delete all bookmarks matching the FTS query")]
+        query: Option<String>,
+        #[arg(short = 't', long = "tags", help = "delete bookmarks with all tags, comma separated list")]
+        tags: Option<String>,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be deleted")]
+        dry_run: bool,
+        #[arg(long = "yes", help = "delete without typing the number of bookmarks to confirm")]
+        yes: bool,
     },
     /// Update bookmarks
     Update {
         /// list of ids, separated by comma, no blanks
-        ids: String,
-        #[arg(short = 't', long = "tags", help = "add tags to taglist")]
+        #[arg(required_unless_present = "query", conflicts_with = "query")]
+        ids: Option<String>,
+        #[arg(long = "query", help = "update all bookmarks matching the FTS query")]
+        query: Option<String>,
+        #[arg(short = 't', long = "tags", visible_alias = "add-tags", help = "add tags to taglist")]
         tags: Option<String>,
-        #[arg(short = 'n', long = "ntags", help = "remove tags from taglist")]
+        #[arg(short = 'n', long = "ntags", visible_alias = "remove-tags", help = "remove tags from taglist")]
         tags_not: Option<String>,
         #[arg(short = 'f', long = "force", help = "overwrite taglist with tags")]
         force: bool,
+        #[arg(long = "language", conflicts_with = "query", help = "set the language, empty string clears it")]
+        language: Option<String>,
+        #[arg(long = "visibility", value_enum, conflicts_with = "query", help = "set who may see the bookmarks outside of this database")]
+        visibility: Option<Visibility>,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+        #[arg(long = "allow-system-tags", help = "allow adding or removing system tags like _snip_, they select how bookmarks open")]
+        allow_system_tags: bool,
+    },
+    /// Fetch titles and descriptions of the web bookmarks again, or only clean up the stored titles
+    RefreshMetadata {
+        /// list of ids, separated by comma, no blanks (default: all)
+        ids: Option<String>,
+        #[arg(long = "normalize-titles", help = "apply the title rules to the stored titles, no fetching")]
+        normalize_titles: bool,
+        #[arg(long = "canonicalize", conflicts_with = "normalize_titles", help = "also replace URLs by the canonical URL of the page (link rel=canonical, og:url)")]
+        canonicalize: bool,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Rewrite http bookmarks to https where both respond identically: same status, final URL and title
+    UpgradeHttps {
+        /// list of ids, separated by comma, no blanks (default: all)
+        ids: Option<String>,
+        #[arg(short = 'd', long = "dry-run", help = "only report the outcome per URL")]
+        dry_run: bool,
     },
     /// Edit bookmarks
     Edit {
         /// Edit bookmarks, list of ids, separated by comma, no blanks
         ids: String,
     },
-    /// Show Bookmarks (list of ids, separated by comma, no blanks)
+    /// Show Bookmarks (list of ids, uuids or titles, separated by comma, no blanks)
     Show { ids: String },
     /// Opens n random URLs
     Surprise {
         #[arg(short = 'n', help = "number of URLs to open", default_value_t = 1)]
         n: i32,
+        /// only bookmarks with all of these tags, separated by comma, no blanks
+        #[arg(short = 't', long = "tags")]
+        tags: Option<String>,
+        #[arg(long = "unopened-only", help = "only bookmarks never opened")]
+        unopened_only: bool,
+        #[arg(long = "older-than", value_name = "AGE", help = "only bookmarks created before, e.g. 6m, 1y")]
+        older_than: Option<String>,
+        #[arg(long = "daily", help = "the same picks all day")]
+        daily: bool,
     },
     /// Tag for which related tags should be shown. No input: all tags are printed
+    #[command(args_conflicts_with_subcommands = true)]
     Tags {
+        #[command(subcommand)]
+        command: Option<TagsCommands>,
         /// Tag for which related tags should be shown. No input: all tags are shown
         tag: Option<String>,
+        #[arg(long = "names-only", conflicts_with = "cloud", help = "only the tags, without number of bookmarks")]
+        names_only: bool,
+        #[arg(long = "sort", value_enum, default_value_t = TagSort::Freq, help = "sort order")]
+        sort: TagSort,
+        #[arg(long = "cloud", help = "show tag cloud, emphasized by frequency")]
+        cloud: bool,
+        #[arg(long = "prefix", help = "only tags starting with prefix")]
+        prefix: Option<String>,
+        #[arg(long = "json", help = "output as json")]
+        is_json: bool,
     },
     /// Initialize bookmark database
     CreateDb {
@@ -155,20 +346,332 @@ pub enum Commands {
     Backfill {
         #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
         dry_run: bool,
+        #[arg(long = "migrate-embeddings", help = "also re-embed bookmarks embedded with another model (requires --openai)")]
+        migrate_embeddings: bool,
+        #[arg(short = 'y', long = "yes", help = "do not ask for confirmation of the estimated cost")]
+        yes: bool,
     },
     /// Load texts for semantic similarity search as bookmarks.
     /// The actual content of the file is not stored in the database, only the embeddings.
     LoadTexts {
         #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
         dry_run: bool,
+        #[arg(short = 'y', long = "yes", help = "do not ask for confirmation of the estimated cost")]
+        yes: bool,
         /// pathname to ndjson file
         path: String,
     },
-    #[command(hide = true)]
-    Xxx {
+    /// Check database integrity: FTS index, missing files, embeddings, tag format
+    Doctor {
+        #[arg(long = "fix", help = "repair fixable problems")]
+        fix: bool,
+    },
+    /// Check bookmark quality: missing titles and tags, long descriptions, http URLs, duplicate titles
+    Lint {
+        #[arg(long = "fix", help = "apply the safe fixes: https URLs and tags of the tag rules")]
+        fix: bool,
+        #[arg(long = "no-web", help = "do not check whether http URLs respond identically via https")]
+        no_web: bool,
+    },
+    /// Show version, config file and database, e.g. for bug reports
+    Info {
+        #[arg(long = "db", help = "also show sqlite version, journal mode, busy timeout and migrations")]
+        db: bool,
+    },
+    /// Counters for shell prompts and status bars, e.g. bookmarks in the inbox
+    Status {
+        #[arg(long = "porcelain", help = "one line of key=value pairs, e.g. inbox=3")]
+        porcelain: bool,
+        #[arg(long = "starship", conflicts_with = "porcelain", help = "prompt segment of the [status] template, empty if all counters are zero")]
+        starship: bool,
+    },
+    /// Extend the database schema for this bkmr version, required after upgrades
+    Migrate {
+        #[arg(long = "status", help = "show the schema version and pending migrations")]
+        status: bool,
+        #[arg(short = 'd', long = "dry-run", help = "apply the migrations to a temporary copy only")]
+        dry_run: bool,
+        #[arg(long = "backup-first", help = "copy the database to <db>.<timestamp>.bak first, instead of asking")]
+        backup_first: bool,
+    },
+    /// Reclaim space and inspect the size of the database
+    Maintenance {
+        #[command(subcommand)]
+        command: MaintenanceCommands,
+    },
+    /// Key-value annotations of a bookmark, `{meta.<key>}` in templates, `meta:<key>=<value>` in searches
+    Meta {
+        #[command(subcommand)]
+        command: MetaCommands,
+    },
+    /// Tag rules from the config file, applied when adding bookmarks
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommands,
+    },
+    /// Read-only snippet packs published by a team, synced as bookmarks tagged registry:<name>
+    Registry {
+        #[command(subcommand)]
+        command: RegistryCommands,
+    },
+    /// Export all bookmarks for bulk editing, re-import with `apply`
+    Export {
+        #[arg(long = "format", value_enum, default_value_t = BulkFormat::Toml, help = "output format")]
+        format: BulkFormat,
+        #[arg(long = "visibility", value_enum, help = "only bookmarks at least this visible (default: private, shared with --sign)")]
+        visibility: Option<Visibility>,
+        #[arg(long = "sign", help = "signed bundle, see [signing] config")]
+        sign: bool,
+    },
+    /// Apply an edited export: creates, updates and deletes bookmarks to match the file
+    Apply {
+        /// exported file (.toml or .json) or signed bundle
+        path: String,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Import a shared export: creates and updates bookmarks matched by uuid or URL, never deletes
+    Import {
+        /// exported file (.toml, .json or .org), signed bundle or export of linkding, Shiori or wallabag
+        path: String,
+        #[arg(long = "format", value_enum, help = "file format (default: by extension)")]
+        format: Option<BulkFormat>,
+        #[arg(long = "verify", help = "require a bundle signed by one of the [signing] trusted_keys")]
+        verify: bool,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Group the embedded bookmarks into topics by embedding similarity (k-means)
+    Clusters {
+        #[arg(short = 'k', long = "clusters", help = "number of clusters (default: sqrt(n/2))")]
+        k: Option<usize>,
+        #[arg(long = "titles", default_value_t = 3, help = "representative titles per cluster")]
+        titles: usize,
+        #[arg(long = "json", help = "output as json")]
+        is_json: bool,
+    },
+    /// Summary of the bookmarks added recently, grouped by tag, e.g. for mail or notes
+    Digest {
+        #[arg(long = "since", value_name = "AGE", default_value = "7d", help = "e.g. 1d, 7d, 1m")]
+        since: String,
+        #[arg(long = "email", conflicts_with = "markdown", help = "mail with subject header, e.g. | sendmail me@example.com")]
+        email: bool,
+        #[arg(long = "markdown", help = "markdown with a section per tag")]
+        markdown: bool,
+        #[arg(long = "summaries", help = "one-line summary per bookmark by the embedder (requires --openai)")]
+        summaries: bool,
+    },
+    /// Number of bookmarks created per month or week, or the bookmarks of one period
+    Timeline {
+        #[arg(long = "by", value_enum, default_value_t = Period::Month, help = "bucket size")]
+        by: Period,
+        #[arg(help = "show the bookmarks of this period, e.g. 2026-10 or 2026-W41")]
+        period: Option<String>,
+        #[arg(long = "json", help = "output as json")]
+        is_json: bool,
+    },
+    /// Delete bookmarks not opened or modified for a given time
+    Prune {
+        #[arg(long = "unused-since", value_name = "AGE", help = "e.g. 30d, 6m, 1y")]
+        unused_since: String,
+        #[arg(long = "max-hits", help = "only bookmarks opened at most n times")]
+        max_hits: Option<i32>,
+        /// list of tags protecting bookmarks from pruning, separated by comma, no blanks
+        #[arg(long = "keep-tags")]
+        keep_tags: Option<String>,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Apply a JSON plan written by --dry-run of update, apply, merge, prune, rules apply or capture
+    ApplyPlan {
+        /// plan file (.json)
+        path: String,
+        #[arg(short = 'y', long = "yes", help = "apply without confirmation")]
+        yes: bool,
+    },
+    /// Merge bookmarks from another bkmr database, tags of identical bookmarks are united
+    Merge {
+        /// pathname to other database file
+        path: String,
+        #[arg(long = "strategy", value_enum, default_value_t = MergeStrategy::Newest, help = "resolution of differing title/description")]
+        strategy: MergeStrategy,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Capture URLs from text on stdin or a file and add selected ones
+    Capture {
+        /// file to scan, default: stdin
+        path: Option<String>,
+        /// list of tags for all added bookmarks, separated by comma, no blanks in between
+        #[arg(short = 't', long = "tags")]
+        tags: Option<String>,
+        #[arg(short = 'a', long = "all", help = "add all new URLs without asking")]
+        all: bool,
+        #[arg(long = "no-web", help = "do not fetch URL data")]
+        no_web: bool,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Offer frequently used long commands of the shell history as _shell_ bookmarks
+    ImportHistory {
+        #[arg(long = "shell", value_enum, help = "default: from $SHELL")]
+        shell: Option<Shell>,
+        #[arg(long = "min-count", default_value_t = 3, help = "minimum number of uses")]
+        min_count: usize,
+        #[arg(long = "file", help = "history file, default: $HISTFILE or the shell's default")]
+        file: Option<String>,
+        #[arg(short = 'a', long = "all", help = "add all candidates without asking")]
+        all: bool,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Import starred GitHub repositories tagged with language and topics, re-runs only add new stars
+    ImportGithubStars {
+        #[arg(long = "user", required_unless_present = "token", help = "GitHub user name")]
+        user: Option<String>,
+        #[arg(long = "token", help = "API token, needed for private repos (default: $GITHUB_TOKEN)")]
+        token: Option<String>,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Import Hacker News favorites or saved Reddit items, subreddits become tags, re-runs only add new items
+    ImportSaved {
+        #[arg(value_enum)]
+        source: SavedSource,
+        #[arg(long = "user", help = "Hacker News or Reddit user name")]
+        user: String,
+        #[arg(long = "token", help = "Reddit OAuth token (default: $REDDIT_TOKEN)")]
+        token: Option<String>,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+    /// Cheat sheets from community sources as markdown snippets
+    Cheats {
+        #[command(subcommand)]
+        command: CheatsCommands,
+    },
+    /// Bookmark types defined by system tags
+    Types {
+        #[command(subcommand)]
+        command: TypesCommands,
+    },
+    /// Print shell function enabling cd into _dir_ bookmarks, e.g. eval "$(bkmr shell-init bash)"
+    ShellInit {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Apply one operation to many bookmarks within one transaction
+    Batch {
         /// list of ids, separated by comma, no blanks
-        ids: String,
-        #[arg(short = 't', long = "tags", help = "add tags to taglist")]
+        #[arg(required_unless_present = "tags")]
+        ids: Option<String>,
+        #[arg(short = 't', long = "tags", help = "only bookmarks with all tags, comma separated list")]
         tags: Option<String>,
+        #[arg(long = "op", value_enum, help = "operation on each bookmark")]
+        op: BatchOp,
+        #[arg(long = "value", help = "tags to add or remove, comma separated list")]
+        value: Option<String>,
+        #[arg(long = "allow-system-tags", help = "allow adding or removing system tags like _snip_, they select how bookmarks open")]
+        allow_system_tags: bool,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be changed")]
+        dry_run: bool,
+        #[arg(short = 'y', long = "yes", help = "do not ask for confirmation")]
+        yes: bool,
+    },
+}
+#[derive(Subcommand)]
+pub enum TypesCommands {
+    /// List builtin and configured types
+    List,
+}
+#[derive(Subcommand)]
+pub enum CheatsCommands {
+    /// Import the page of a command, tagged with its name, re-import replaces it
+    Import {
+        #[arg(value_enum)]
+        source: CheatSource,
+        /// e.g. tar, git commit
+        #[arg(required = true, num_args = 1..)]
+        command: Vec<String>,
+        #[arg(long = "platform", help = "linux, osx, windows, ... (default: current OS, then common)")]
+        platform: Option<String>,
+    },
+}
+#[derive(Subcommand)]
+pub enum RegistryCommands {
+    /// Add a registry and sync it
+    Add {
+        name: String,
+        /// git repository containing bkmr-pack.json, https URL or local path of the pack
+        url: String,
+        #[arg(long = "key", help = "base64 public key, the pack must be a bundle signed with it")]
+        key: Option<String>,
+    },
+    /// Remove a registry and its bookmarks
+    Remove { name: String },
+    /// List registries
+    List,
+    /// Pull the packs and update their bookmarks
+    Sync {
+        /// only this registry, default: all
+        name: Option<String>,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+}
+#[derive(Subcommand)]
+pub enum TagsCommands {
+    /// Normalize the tags of all bookmarks as configured in [tags], e.g. after changing the config
+    Normalize {
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
     },
-}
\ No newline at end of file
+}
+#[derive(Subcommand)]
+pub enum MaintenanceCommands {
+    /// Rebuild the database file, releasing free space, e.g. after clearing embeddings
+    Vacuum,
+    /// Update the statistics of the query planner
+    Analyze,
+    /// Show the size of the tables, indexes and embeddings
+    Size {
+        #[arg(long = "json", help = "output as JSON")]
+        is_json: bool,
+    },
+}
+#[derive(Subcommand)]
+pub enum MetaCommands {
+    /// Set the key to the value
+    Set {
+        /// id, uuid or title
+        id: String,
+        key: String,
+        value: String,
+    },
+    /// Show the value of the key, all annotations without key
+    Get {
+        /// id, uuid or title
+        id: String,
+        key: Option<String>,
+    },
+    /// Remove the key
+    Unset {
+        /// id, uuid or title
+        id: String,
+        key: String,
+    },
+}
+#[derive(Subcommand)]
+pub enum RulesCommands {
+    /// List configured tag rules
+    List,
+    /// Apply tag rules to existing bookmarks
+    Apply {
+        /// list of ids, separated by comma, no blanks
+        ids: Option<String>,
+        #[arg(long = "retroactive", help = "apply rules to all existing bookmarks")]
+        retroactive: bool,
+        #[arg(short = 'd', long = "dry-run", help = "only show what would be done")]
+        dry_run: bool,
+    },
+}
@@ -1,41 +1,89 @@
-use std::fs::create_dir_all;
-use std::io::Write;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, create_dir_all};
+use std::io::{self, Write};
 
 use crate::adapter::embeddings::{cosine_similarity, deserialize_embedding, OpenAiEmbedding};
-use crate::cli::args::{Cli, Commands};
+use crate::cli::args::{
+    CheatsCommands, Cli, Commands, MaintenanceCommands, MetaCommands, RegistryCommands, RulesCommands, TagsCommands, TypesCommands,
+};
 use crate::context::Context;
 use crate::service::process::DisplayField;
 use crate::{
     adapter::dal::Dal,
-    adapter::json::{bms_to_json, read_ndjson_file_and_create_bookmarks},
-    environment::CONFIG,
-    load_url_details,
+    adapter::json::{bms_to_json, bms_with_facets_to_json, read_ndjson_file_and_create_bookmarks},
+    environment::{config_path, CONFIG},
+    fetch_page, load_url_details, parse_url_details, retag,
     model::{
-        bms::Bookmarks,
-        bookmark::{BookmarkBuilder, BookmarkUpdater},
+        bms::{split_meta_filters, Bookmarks},
+        system_tag::{changed_system_tags, normalize_system_tag, SystemTags},
+        bookmark::{Bookmark, BookmarkBuilder, BookmarkUpdater, Visibility},
+        registry::Registry,
         tag::Tags,
     },
     service::{
         self,
-        embeddings::create_embeddings_for_non_bookmarks,
-        fzf::fzf_process,
+        batch::{self, BatchOp},
+        bulk::{self as bulk_ops, BulkBookmark, BulkFile, BulkFormat, Change},
+        cheats::{self, CheatSource},
+        digest::{self as digest_ops, DigestFormat},
+        capture::{extract_urls, fetch_titles, parse_selection, prompt_tty},
+        clipboard::{classify, copy_payload, read_clipboard, suggest_title, write_clipboard, ClipKind, CopyFormat},
+        cluster,
+        dir::{shell_init, Shell},
+        history,
+        https::{self, https_url, Outcome},
+        doctor::{diagnose, repair},
+        embeddings::{confirm_embedding_cost, create_embeddings_for_non_bookmarks},
+        facets::{self, Facet},
+        fzf::{fzf_process, fzf_select},
+        github,
+        bus::{self, BookmarkEvent},
+        canonical::parse_canonical_url,
+        journal,
+        language::fill_language,
+        lint::{self as lint_ops, Fix},
+        lookup,
+        maintenance::{self, format_bytes},
+        merge::{self, MergeStrategy, OnExists},
+        migrate,
+        palette::{self, PaletteAction},
+        plan::Plan,
         process::{
-            delete_bms, edit_bms, open_bm, show_bms, DisplayBookmark, ALL_FIELDS, DEFAULT_FIELDS,
+            delete_bms, do_touch, edit_bms, open_bm, show_bms, DisplayBookmark, ALL_FIELDS, DEFAULT_FIELDS,
         },
+        ranking::{rank, Score},
+        readability,
+        registry,
+        rpc,
+        review::{self as review_ops, ReviewAction, ReviewFilter, ReviewState},
+        rules::TagRules,
+        saved::{self, SavedSource},
+        secrets,
+        signing::{self, SigningKey},
+        snippet::vscode_snippets,
+        status::Status,
+        surprise::{self, SurpriseFilter},
+        tags::{render_cloud, select_tags, TagSort},
+        template::Template,
+        timeline::{self as timeline_ops, Period},
+        titles::TitleRules,
+        tmux,
+        triage::{self as triage_ops, TriageAction, TriageProgress},
     },
 };
 use anyhow::{anyhow, Context as _};
 use camino::Utf8Path;
 use crossterm::style::Stylize;
-use diesel::connection::SimpleConnection;
 use diesel::result::DatabaseErrorKind;
 use diesel::result::Error::DatabaseError;
-use diesel_migrations::MigrationHarness;
 use itertools::Itertools;
 use termcolor::{Color, ColorSpec, StandardStream, WriteColor};
-use tracing::{debug, info, instrument};
-use crate::adapter::dal::migration::{init_db, MIGRATIONS};
-use crate::util::helper::{confirm, ensure_int_vector};
+use tracing::instrument;
+use crate::adapter::dal::migration::init_db;
+use crate::util::clock;
+use crate::util::helper::{
+    confirm, contains_cjk, ensure_int_vector, input_allowed, interpolate_env, is_plain, new_uuid, parse_age, prompt,
+};
 
 // Type alias for commonly used Result type
 type Result<T> = anyhow::Result<T>;
@@ -49,6 +97,10 @@ pub fn execute_command(stderr: StandardStream, cli: Cli) -> anyhow::Result<()> {
             tags_all_not,
             tags_any,
             tags_any_not,
+            untagged,
+            language,
+            min_hits,
+            unused_since,
             tags_prefix,
             order_desc,
             order_asc,
@@ -56,6 +108,9 @@ pub fn execute_command(stderr: StandardStream, cli: Cli) -> anyhow::Result<()> {
             is_fuzzy,
             is_json,
             limit,
+            explain,
+            fulltext,
+            facets,
         }) => search_bookmarks(
             tags_prefix,
             tags_all,
@@ -64,20 +119,44 @@ pub fn execute_command(stderr: StandardStream, cli: Cli) -> anyhow::Result<()> {
             tags_all_not,
             tags_any_not,
             tags_exact,
+            untagged,
+            language,
+            min_hits,
+            unused_since,
             order_desc,
             order_asc,
             is_fuzzy,
             is_json,
             limit,
+            explain,
+            fulltext,
+            &facets,
             non_interactive,
             stderr,
         ),
         Some(Commands::SemSearch {
             query,
             limit,
+            tags,
+            bm_type,
+            threshold,
+            explain,
+            non_interactive,
+        }) => sem_search(
+            query,
+            limit,
+            tags,
+            bm_type,
+            threshold,
+            explain,
             non_interactive,
-        }) => sem_search(query, limit, non_interactive, stderr),
+            stderr,
+        ),
         Some(Commands::Open { ids }) => open_bookmarks(ids),
+        Some(Commands::Actions { id }) => bookmark_actions(id),
+        Some(Commands::Copy { id, format }) => copy_bookmark(id, format),
+        Some(Commands::Rpc { .. }) => rpc_request(),
+        Some(Commands::TmuxPopup { tags, pane }) => tmux_popup(tags, pane),
         Some(Commands::Add {
             url,
             tags,
@@ -85,38 +164,283 @@ pub fn execute_command(stderr: StandardStream, cli: Cli) -> anyhow::Result<()> {
             desc,
             no_web,
             edit,
-        }) => add_bookmark(url, tags, title, desc, no_web, edit),
-        Some(Commands::Delete { ids }) => delete_bookmarks(ids),
+            template,
+            from_clipboard,
+            language,
+            inbox,
+            on_exists,
+            canonicalize,
+        }) => match template {
+            Some(template) => {
+                add_bookmark_from_template(template, tags, title, desc, language, edit, on_exists)
+            }
+            None if from_clipboard => {
+                add_bookmark_from_clipboard(tags, title, desc, language, no_web, on_exists)
+            }
+            None if inbox => add_to_inbox(url.unwrap_or_default(), tags, title, desc),
+            None => add_bookmark(
+                url.unwrap_or_default(),
+                tags,
+                title,
+                desc,
+                language,
+                no_web,
+                edit,
+                on_exists,
+                canonicalize,
+            ),
+        },
+        Some(Commands::Triage { no_web }) => triage(no_web),
+        Some(Commands::Review {
+            since,
+            untagged,
+            unopened,
+            reset,
+        }) => review(since, untagged, unopened, reset),
+        Some(Commands::Read { id, fetch }) => read_bookmark(id, fetch),
+        Some(Commands::RefreshMetadata {
+            ids,
+            normalize_titles,
+            canonicalize,
+            dry_run,
+        }) => refresh_metadata(ids, normalize_titles, canonicalize, dry_run),
+        Some(Commands::UpgradeHttps { ids, dry_run }) => upgrade_https(ids, dry_run),
+        Some(Commands::Delete {
+            ids,
+            query,
+            tags,
+            dry_run,
+            yes,
+        }) => match ids {
+            Some(ids) => delete_bookmarks(ids),
+            None => delete_bookmarks_by_query(query, tags, dry_run, yes),
+        },
         Some(Commands::Update {
             ids,
+            query,
             tags,
             tags_not,
             force,
-        }) => update_bookmarks(force, tags, tags_not, ids),
+            language,
+            visibility,
+            dry_run,
+            allow_system_tags,
+        }) => match query {
+            Some(query) => {
+                update_bookmarks_by_query(query, force, tags, tags_not, dry_run, allow_system_tags)
+            }
+            None => update_bookmarks(
+                force,
+                tags,
+                tags_not,
+                language,
+                visibility,
+                ids.unwrap_or_default(),
+                allow_system_tags,
+            ),
+        },
         Some(Commands::Edit { ids }) => edit_bookmarks(ids),
         Some(Commands::Show { ids }) => show_bookmarks(ids),
-        Some(Commands::Tags { tag }) => show_tags(tag),
+        Some(Commands::Tags {
+            command: Some(TagsCommands::Normalize { dry_run }),
+            ..
+        }) => normalize_tags(dry_run),
+        Some(Commands::Tags {
+            command: None,
+            tag,
+            names_only,
+            sort,
+            cloud,
+            prefix,
+            is_json,
+        }) => show_tags(tag, names_only, sort, cloud, prefix, is_json),
         Some(Commands::CreateDb { path }) => create_db(path),
-        Some(Commands::Surprise { n }) => randomized(n),
-        Some(Commands::Backfill { dry_run }) => backfill_embeddings(dry_run),
-        Some(Commands::LoadTexts { dry_run, path }) => load_texts(dry_run, path),
-        Some(Commands::Xxx { ids, tags }) => {
-            eprintln!(
-                "ids: {:?}, tags: {:?}",
+        Some(Commands::Surprise {
+            n,
+            tags,
+            unopened_only,
+            older_than,
+            daily,
+        }) => randomized(n, tags, unopened_only, older_than, daily),
+        Some(Commands::Backfill {
+            dry_run,
+            migrate_embeddings,
+            yes,
+        }) => backfill_embeddings(dry_run, migrate_embeddings, yes),
+        Some(Commands::LoadTexts { dry_run, yes, path }) => load_texts(dry_run, yes, path),
+        Some(Commands::Doctor { fix }) => doctor(fix),
+        Some(Commands::Lint { fix, no_web }) => lint(fix, no_web),
+        Some(Commands::Info { db }) => show_info(db),
+        Some(Commands::Status { porcelain, starship }) => show_status(porcelain, starship),
+        Some(Commands::Migrate {
+            status,
+            dry_run,
+            backup_first,
+        }) => migrate_db(status, dry_run, backup_first),
+        Some(Commands::Export {
+            format,
+            visibility,
+            sign,
+        }) => export_bookmarks(format, visibility, sign),
+        Some(Commands::Apply { path, dry_run }) => apply_bulk_file(path, dry_run),
+        Some(Commands::Import {
+            path,
+            format,
+            verify,
+            dry_run,
+        }) => import_bulk_file(path, format, verify, dry_run),
+        Some(Commands::Clusters { k, titles, is_json }) => show_clusters(k, titles, is_json),
+        Some(Commands::Digest {
+            since,
+            email,
+            markdown,
+            summaries,
+        }) => digest(since, email, markdown, summaries),
+        Some(Commands::Timeline { by, period, is_json }) => show_timeline(by, period, is_json),
+        Some(Commands::Prune {
+            unused_since,
+            max_hits,
+            keep_tags,
+            dry_run,
+        }) => prune_bookmarks(unused_since, max_hits, keep_tags, dry_run),
+        Some(Commands::ApplyPlan { path, yes }) => apply_plan(path, yes),
+        Some(Commands::Merge {
+            path,
+            strategy,
+            dry_run,
+        }) => merge_db(path, strategy, dry_run),
+        Some(Commands::Registry { command }) => match command {
+            RegistryCommands::Add { name, url, key } => add_registry(name, url, key),
+            RegistryCommands::Remove { name } => remove_registry(name),
+            RegistryCommands::List => list_registries(),
+            RegistryCommands::Sync { name, dry_run } => sync_registries(name, dry_run),
+        },
+        Some(Commands::Maintenance { command }) => match command {
+            MaintenanceCommands::Vacuum => vacuum(),
+            MaintenanceCommands::Analyze => analyze(),
+            MaintenanceCommands::Size { is_json } => show_size(is_json),
+        },
+        Some(Commands::Meta { command }) => match command {
+            MetaCommands::Set { id, key, value } => set_meta(id, &key, Some(&value)),
+            MetaCommands::Get { id, key } => show_meta(id, key),
+            MetaCommands::Unset { id, key } => set_meta(id, &key, None),
+        },
+        Some(Commands::Rules { command }) => match command {
+            RulesCommands::List => list_rules(),
+            RulesCommands::Apply {
                 ids,
-                tags
-            );
+                retroactive,
+                dry_run,
+            } => apply_rules(ids, retroactive, dry_run),
+        },
+        Some(Commands::Capture {
+            path,
+            tags,
+            all,
+            no_web,
+            dry_run,
+        }) => capture_urls(path, tags, all, no_web, dry_run),
+        Some(Commands::ImportHistory {
+            shell,
+            min_count,
+            file,
+            all,
+            dry_run,
+        }) => import_history(shell, min_count, file, all, dry_run),
+        Some(Commands::ImportGithubStars {
+            user,
+            token,
+            dry_run,
+        }) => import_github_stars(user, token, dry_run),
+        Some(Commands::ImportSaved {
+            source,
+            user,
+            token,
+            dry_run,
+        }) => import_saved(source, user, token, dry_run),
+        Some(Commands::Cheats { command }) => match command {
+            CheatsCommands::Import {
+                source,
+                command,
+                platform,
+            } => import_cheats(source, command, platform),
+        },
+        Some(Commands::Types { command }) => match command {
+            TypesCommands::List => list_types(),
+        },
+        Some(Commands::ShellInit { shell }) => {
+            print!("{}", shell_init(shell));
             Ok(())
         }
+        Some(Commands::Batch {
+            ids,
+            tags,
+            op,
+            value,
+            allow_system_tags,
+            dry_run,
+            yes,
+        }) => batch_bookmarks(ids, tags, op, value, allow_system_tags, dry_run, yes),
         None => Ok(()),
     }
 }
 // Helper function to get and validate IDs
+const MIN_UUID_PREFIX: usize = 8;
+
+// Ids may be given as uuid or unique uuid prefix (min. 8 characters), or as title or title
+// prefix (ignoring case), ids do not survive compaction and differ between machines
 fn get_ids(ids: String) -> Result<Vec<i32>> {
-    ensure_int_vector(&ids.split(',').map(String::from).collect())
-        .ok_or_else(|| anyhow!("Invalid input, only numbers allowed"))
+    let parts: Vec<&str> = ids.split(',').collect();
+    if let Some(ids) = ensure_int_vector(&parts.iter().map(|p| p.to_string()).collect()) {
+        return Ok(ids);
+    }
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let bms = dal.get_bookmarks("")?;
+    // a title containing commas
+    let whole = lookup::find_by_title(&bms, &ids);
+    if parts.len() > 1 && whole.len() == 1 && whole[0].metadata.trim().eq_ignore_ascii_case(ids.trim()) {
+        return Ok(vec![whole[0].id]);
+    }
+
+    let mut resolved = Vec::new();
+    for part in parts {
+        if let Ok(id) = part.parse::<i32>() {
+            resolved.push(id);
+            continue;
+        }
+        let by_uuid = (part.len() >= MIN_UUID_PREFIX && lookup::is_uuid_like(part))
+            .then(|| dal.get_bookmark_by_uuid(part));
+        let titled = lookup::find_by_title(&bms, part);
+        let id = match (by_uuid, titled.len()) {
+            (Some(Ok(bm)), _) => bm.id,
+            (Some(Err(e)), 0) => return Err(e),
+            (None, 0) => return Err(anyhow!("No bookmark with id, uuid or title {:?}", part)),
+            (_, 1) => titled[0].id,
+            _ => select_bookmark(&titled, part)?,
+        };
+        resolved.push(id);
+    }
+    Ok(resolved)
 }
 
+/// Lets the user pick one of the bookmarks matching the title
+fn select_bookmark(candidates: &[&Bookmark], query: &str) -> Result<i32> {
+    eprintln!("{} bookmarks match {:?}:", candidates.len(), query);
+    let shown = &candidates[..candidates.len().min(lookup::MAX_CANDIDATES)];
+    for (i, bm) in shown.iter().enumerate() {
+        eprintln!("{:>3}. {} [{}]", i + 1, bm.metadata, bm.id);
+    }
+    let choice = prompt("Select: ");
+    choice
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=shown.len()).contains(n))
+        .map(|n| shown[n - 1].id)
+        .ok_or_else(|| anyhow!("Invalid selection {:?}", choice))
+}
+
+#[allow(clippy::too_many_arguments)]
 #[instrument]
 pub fn search_bookmarks(
     tags_prefix: Option<String>,
@@ -126,22 +450,37 @@ pub fn search_bookmarks(
     tags_all_not: Option<String>,
     tags_any_not: Option<String>,
     tags_exact: Option<String>,
+    untagged: bool,
+    language: Option<String>,
+    min_hits: Option<i32>,
+    unused_since: Option<String>,
     order_desc: bool,
     order_asc: bool,
     is_fuzzy: bool,
     is_json: bool,
     limit: Option<i32>,
+    explain: bool,
+    fulltext: bool,
+    facets: &[Facet],
     non_interactive: bool,
     mut stderr: StandardStream,
 ) -> Result<()> {
     let mut fields = DEFAULT_FIELDS.to_vec();
+    let searched_tags: Vec<String> = Tags::normalize_tag_string(tags_all.clone())
+        .into_iter()
+        .chain(Tags::normalize_tag_string(tags_any.clone()))
+        .collect();
 
     // Combine prefix tags with tags_all if present
     let tags_all = tags_prefix.map_or(tags_all.clone().unwrap_or_default(), |prefix| {
         tags_all.map_or(prefix.clone(), |all| format!("{},{}", all, prefix))
     });
 
-    let mut bms = Bookmarks::new(fts_query.unwrap_or_default());
+    let (fts_query, meta_filters) = split_meta_filters(&fts_query.unwrap_or_default());
+    let mut bms = Bookmarks::new(fts_query.clone());
+    if fulltext {
+        bms.add_readable_matches()?;
+    }
     bms.filter(
         Some(tags_all),
         tags_any,
@@ -149,40 +488,76 @@ pub fn search_bookmarks(
         tags_any_not,
         tags_exact,
     );
+    if untagged {
+        bms.filter_untagged();
+    }
+    if !meta_filters.is_empty() {
+        bms.filter_meta(&meta_filters);
+    }
+    if let Some(language) = language {
+        bms.filter_language(&language);
+        fields.push(DisplayField::Language);
+    }
+    if min_hits.is_some() || unused_since.is_some() {
+        let unused_since = unused_since
+            .map(|age| parse_age(&age).map(|age| clock::now_naive() - age))
+            .transpose()?;
+        bms.filter_usage(min_hits, unused_since);
+        fields.extend([DisplayField::Flags, DisplayField::LastUpdateTs]);
+    }
 
     // Sort bookmarks based on order flags
     match (order_desc, order_asc) {
         (true, false) => {
             bms.bms
-                .sort_by(|a, b| b.last_update_ts.cmp(&a.last_update_ts));
+                .sort_by_key(|bm| std::cmp::Reverse(bm.last_update_ts));
             fields.push(DisplayField::LastUpdateTs);
         }
         (false, true) => {
             bms.bms
-                .sort_by(|a, b| a.last_update_ts.cmp(&b.last_update_ts));
+                .sort_by_key(|bm| bm.last_update_ts);
             fields.push(DisplayField::LastUpdateTs);
         }
+        _ if !fts_query.is_empty() => rank(
+            &mut bms.bms,
+            &fts_ranks(&fts_query)?,
+            &searched_tags,
+            clock::now_naive(),
+            &CONFIG.ranking,
+        ),
         _ => bms.bms.sort_by_key(|bm| bm.metadata.to_lowercase()),
     }
 
+    let facet_counts = facets::facets(&bms.bms, facets);
+
     // Apply limit if specified
     if let Some(limit) = limit {
         bms.bms.truncate(limit as usize);
     }
 
-    // Handle different output modes
-    match (is_fuzzy, is_json) {
+    // Handle different output modes, plain output replaces the fzf screen by the numbered selection
+    match (is_fuzzy && !is_plain(), is_json) {
         (true, _) => {
             fzf_process(&bms.bms);
             return Ok(());
         }
         (_, true) => {
-            bms_to_json(&bms.bms);
+            if facets.is_empty() {
+                bms_to_json(&bms.bms);
+            } else {
+                bms_with_facets_to_json(&bms.bms, &facet_counts);
+            }
             return Ok(());
         }
         _ => {
             let d_bms: Vec<DisplayBookmark> = bms.bms.iter().map(DisplayBookmark::from).collect();
             show_bms(&d_bms, &fields);
+            for line in facets::facet_lines(&facet_counts) {
+                println!("{}", line);
+            }
+            if explain {
+                explain_scores(&bms.bms, &fts_query, &HashMap::new(), &searched_tags)?;
+            }
             eprintln!("Found {} bookmarks", bms.bms.len());
 
             if non_interactive {
@@ -213,16 +588,114 @@ pub fn open_bookmarks(ids: String) -> Result<()> {
     Ok(())
 }
 
+/// Lists the actions applicable to the bookmark and runs the chosen one
+#[instrument]
+pub fn bookmark_actions(id: String) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let id = *get_ids(id)?.first().ok_or_else(|| anyhow!("No bookmark id given"))?;
+    let bm = dal.get_bookmark_by_id(id)?;
+    let system_tags = SystemTags::new(&CONFIG.types)?;
+    let actions = palette::actions(&bm, dal.get_readable(&bm.uuid)?.is_some());
+
+    eprintln!("{} [{}]", bm.metadata, bm.id);
+    for (i, action) in actions.iter().enumerate() {
+        eprintln!("{:>3}. {}", i + 1, action.label(&bm, system_tags.for_bookmark(&bm)));
+    }
+    let choice = prompt("Action: ");
+    let action = choice
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| actions.get(i))
+        .ok_or_else(|| anyhow!("Invalid selection {:?}", choice))?;
+    match action {
+        PaletteAction::Open => open_bm(&bm),
+        PaletteAction::Copy => {
+            write_clipboard(&bm.URL)?;
+            eprintln!("Copied to the clipboard.");
+            Ok(())
+        }
+        PaletteAction::Show => show_bookmarks(id.to_string()),
+        PaletteAction::Edit => edit_bms(vec![1], vec![bm]),
+        PaletteAction::Read => read_bookmark(id.to_string(), false),
+        PaletteAction::FetchReadable => read_bookmark(id.to_string(), true),
+        PaletteAction::RefreshMetadata => refresh_metadata(Some(id.to_string()), false, false, false),
+        PaletteAction::Delete if confirm(&format!("Delete {}?", bm.URL)) => delete_bms(vec![1], vec![bm]),
+        PaletteAction::Delete => Err(anyhow!("Operation aborted by user")),
+    }
+}
+
+#[instrument]
+pub fn copy_bookmark(id: String, format: CopyFormat) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let id = *get_ids(id)?.first().ok_or_else(|| anyhow!("No bookmark id given"))?;
+    let payload = copy_payload(&dal.get_bookmark_by_id(id)?, format)?;
+    write_clipboard(&payload)?;
+    eprintln!("Copied to clipboard.");
+    Ok(())
+}
+
+/// Answers the JSON request on stdin with `{"result": ...}` or `{"error": "..."}` on one line
+#[instrument]
+pub fn rpc_request() -> Result<()> {
+    let content = io::read_to_string(io::stdin()).context("Failed to read stdin")?;
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let result = rpc::parse(&content)
+        .and_then(|request| rpc::handle(&mut dal, &TagRules::new(&CONFIG.rules)?, request));
+    match result {
+        Ok((value, added)) => {
+            if let Some(bm) = added {
+                bus::publish(BookmarkEvent::Added(bm));
+            }
+            println!("{}", serde_json::json!({ "result": value }));
+            Ok(())
+        }
+        Err(e) => {
+            println!("{}", serde_json::json!({ "error": format!("{:#}", e) }));
+            Err(e)
+        }
+    }
+}
+
+/// Opens the picker in a tmux popup, which runs this command again with the pane to type into
+#[instrument]
+pub fn tmux_popup(tags: Option<String>, pane: Option<String>) -> Result<()> {
+    let Some(pane) = pane else {
+        let pane = std::env::var("TMUX_PANE").map_err(|_| anyhow!("Not inside tmux, TMUX_PANE is not set"))?;
+        let exe = std::env::current_exe().context("Failed to find the bkmr executable")?;
+        let env = tmux::forwarded_env(std::env::vars());
+        return tmux::run(&tmux::popup_args(&exe.to_string_lossy(), &pane, tags.as_deref(), &env));
+    };
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let tags = Tags::normalize_tag_string(tags);
+    let bms: Vec<Bookmark> = dal
+        .get_bookmarks("")?
+        .into_iter()
+        .filter(|bm| Tags::match_all_tags(&tags, &bm.get_tags()))
+        .collect();
+    let Some(bm) = fzf_select(&bms) else {
+        return Ok(());
+    };
+    tmux::run(&tmux::send_keys_args(&pane, &interpolate_env(&bm.URL)?))?;
+    do_touch(&bm)
+}
+
+#[allow(clippy::too_many_arguments)]
 #[instrument]
 pub fn add_bookmark(
     url: String,
     tags: Option<String>,
     title: Option<String>,
     desc: Option<String>,
+    language: Option<String>,
     no_web: bool,
     edit: bool,
+    on_exists: OnExists,
+    canonicalize: bool,
 ) -> Result<()> {
     let mut dal = Dal::new(CONFIG.db_url.clone());
+    let mut url = url;
 
     // Check for unknown tags
     let unknown_tags = Bookmarks::new(String::new())
@@ -234,20 +707,312 @@ pub fn add_bookmark(
     }
 
     // Get web details if needed
-    let (web_title, web_desc, _) = if !no_web {
-        load_url_details(&url).unwrap_or_default()
-    } else {
-        Default::default()
+    let (web_title, web_desc, article) = match (!no_web).then(|| fetch_page(&url)) {
+        Some(Ok(page)) => {
+            let (title, desc, _) = parse_url_details(&url, &page)?;
+            if let Some(canonical) = parse_canonical_url(&url, &page).filter(|_| canonicalize) {
+                // without input the explicitly requested canonical URL is taken as is
+                if !input_allowed() || confirm(&format!("Use the canonical URL {} instead?", canonical)) {
+                    url = canonical;
+                }
+            }
+            (title, desc, readability::extract(&page))
+        }
+        _ => Default::default(),
     };
 
-    let mut bm = BookmarkBuilder::new()
+    let bm = BookmarkBuilder::new()
         .id(1)
         .URL(url.clone())
         .metadata(title.unwrap_or(web_title))
         .tags(Tags::create_normalized_tag_string(tags))
         .desc(desc.unwrap_or(web_desc))
         .flags(0)
+        .language(language.map(|l| l.trim().to_lowercase()))
+        .uuid(new_uuid())
+        .build();
+    let bm = insert_new_bookmark(&mut dal, bm, edit, on_exists)?;
+    if let Some(article) = article {
+        dal.upsert_readable(&article.into_readable(&bm.uuid))?;
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn read_bookmark(id: String, fetch: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let id = *get_ids(id)?.first().ok_or_else(|| anyhow!("No bookmark id given"))?;
+    let bm = dal.get_bookmark_by_id(id)?;
+    if fetch {
+        let page = fetch_page(&bm.URL).with_context(|| format!("Failed to fetch {}", bm.URL))?;
+        let article = readability::extract(&page)
+            .ok_or_else(|| anyhow!("No readable text in {} or archiving forbidden by robots meta tag", bm.URL))?;
+        dal.upsert_readable(&article.into_readable(&bm.uuid))?;
+    }
+    let readable = dal
+        .get_readable(&bm.uuid)?
+        .ok_or_else(|| anyhow!("No readable text of bookmark {}, use --fetch", id))?;
+    println!("{}", readable.title.bold());
+    if !readable.byline.is_empty() {
+        println!("{}", readable.byline.dim());
+    }
+    println!("\n{}", readable.text);
+    Ok(())
+}
+
+#[instrument]
+pub fn add_bookmark_from_template(
+    template: String,
+    tags: Option<String>,
+    title: Option<String>,
+    desc: Option<String>,
+    language: Option<String>,
+    edit: bool,
+    on_exists: OnExists,
+) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let template = Template::find(&dal.get_bookmarks("")?, &template)?;
+    let mut bm = template.instantiate(title.as_deref(), clock::today());
+
+    let tags = Tags::normalize_tag_string(tags);
+    let unknown_tags = Bookmarks::new(String::new())
+        .check_tags(tags.clone())
+        .context("Failed to check tags")?;
+    if !unknown_tags.is_empty() && !confirm(&format!("Unknown tags: {:?}, create?", unknown_tags)) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+    let mut all_tags = bm.get_tags();
+    all_tags.extend(tags);
+    bm.set_tags(all_tags);
+    if let Some(desc) = desc {
+        bm.desc = desc;
+    }
+    if let Some(language) = language {
+        bm.language = Some(language.trim().to_lowercase());
+    }
+    insert_new_bookmark(&mut dal, bm, edit, on_exists)?;
+    Ok(())
+}
+
+/// Adds the clipboard content, prefilled by its kind, and opens the editor for review
+#[instrument]
+pub fn add_bookmark_from_clipboard(
+    tags: Option<String>,
+    title: Option<String>,
+    desc: Option<String>,
+    language: Option<String>,
+    no_web: bool,
+    on_exists: OnExists,
+) -> Result<()> {
+    let text = read_clipboard()?;
+    let kind = classify(&text);
+    eprintln!("Clipboard content: {:?}", kind);
+    let (url, no_web) = match kind {
+        ClipKind::Url => (text.trim().to_string(), no_web),
+        _ => (text.trim_end().to_string(), true),
+    };
+    let title = title.or_else(|| suggest_title(&text, kind));
+    let tags = match kind.type_tag() {
+        Some(tag) => Some(format!("{},{}", tag, tags.unwrap_or_default())),
+        None => tags,
+    };
+    add_bookmark(url, tags, title, desc, language, no_web, true, on_exists, false)
+}
+
+/// Quick capture without fetching URL data or asking about unknown tags, reviewed with `triage`
+#[instrument]
+pub fn add_to_inbox(
+    url: String,
+    tags: Option<String>,
+    title: Option<String>,
+    desc: Option<String>,
+) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let mut tags = Tags::normalize_tag_string(tags);
+    tags.push(triage_ops::INBOX_TAG.to_string());
+    let mut bm = BookmarkBuilder::new()
+        .id(1)
+        .URL(url)
+        .metadata(title.unwrap_or_default())
+        .desc(desc.unwrap_or_default())
+        .flags(0)
         .build();
+    bm.set_tags(tags);
+    insert_new_bookmark(&mut dal, bm, false, OnExists::Error)?;
+    Ok(())
+}
+
+/// Presents the inbox bookmarks oldest first until the inbox is done or the user quits
+#[instrument]
+pub fn triage(no_web: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let mut skipped = HashSet::new();
+    let total = triage_ops::pending(&dal.get_bookmarks("")?, &skipped).len();
+    if total == 0 {
+        eprintln!("Inbox is empty.");
+        return Ok(());
+    }
+    let mut progress = TriageProgress::default();
+    'inbox: while let Some(bm) = triage_ops::pending(&dal.get_bookmarks("")?, &skipped).into_iter().next() {
+        let (web_title, web_desc) = if bm.metadata.is_empty() && !no_web {
+            load_url_details(&bm.URL)
+                .map(|(title, desc, _)| (title, desc))
+                .unwrap_or_default()
+        } else {
+            Default::default()
+        };
+        eprintln!("[{}/{}] {}", progress.done() + 1, total, bm.URL);
+        let title = if bm.metadata.is_empty() { &web_title } else { &bm.metadata };
+        if !title.is_empty() {
+            eprintln!("  {}", title.as_str().green());
+        }
+        let tags: Vec<String> = bm.get_tags().into_iter().filter(|tag| tag != triage_ops::INBOX_TAG).collect();
+        if !tags.is_empty() {
+            eprintln!("  {}", format!("[{}]", tags.join(",")).dim());
+        }
+        loop {
+            let action = match triage_ops::parse_action(&prompt("k [tags] | d | o | e | s | q > ")) {
+                Ok(action) => action,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+            };
+            match action {
+                TriageAction::Keep(tags) => {
+                    let mut kept = triage_ops::keep(&bm, tags, web_title, web_desc);
+                    kept.update();
+                    for bm in dal.update_bookmark(kept)? {
+                        bus::publish(BookmarkEvent::Updated(bm));
+                    }
+                    progress.kept += 1;
+                }
+                TriageAction::Discard => {
+                    delete_bms(vec![1], vec![bm])?;
+                    progress.discarded += 1;
+                }
+                TriageAction::Open => {
+                    open_bm(&bm)?;
+                    continue;
+                }
+                // presented again with the edited values
+                TriageAction::Edit => {
+                    edit_bms(vec![1], vec![bm])?;
+                    continue 'inbox;
+                }
+                TriageAction::Skip => {
+                    skipped.insert(bm.uuid.clone());
+                    progress.skipped += 1;
+                }
+                TriageAction::Quit => break 'inbox,
+            }
+            break;
+        }
+    }
+    let left = triage_ops::pending(&dal.get_bookmarks("")?, &HashSet::new()).len();
+    eprintln!("Triage: {}, {} left in inbox.", progress, left);
+    Ok(())
+}
+
+/// Presents the bookmarks of the filter oldest first, decisions are saved right away so that
+/// the next session continues where this one quit
+#[instrument]
+pub fn review(since: Option<String>, untagged: bool, unopened: bool, reset: bool) -> Result<()> {
+    let since = match since {
+        None if untagged || unopened => None,
+        since => Some(parse_age(since.as_deref().unwrap_or("7d"))?),
+    };
+    let filter = ReviewFilter {
+        created_after: since.map(|age| clock::now_naive() - age),
+        untagged,
+        unopened,
+    };
+    let path = ReviewState::file().ok_or_else(|| anyhow!("No directory for the review state"))?;
+    let mut state = if reset { ReviewState::default() } else { ReviewState::load(&path)? };
+    if reset {
+        state.save(&path)?;
+    }
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let mut skipped = HashSet::new();
+    let total = review_ops::pending(&dal.get_bookmarks("")?, &filter, &state, &skipped).len();
+    if total == 0 {
+        eprintln!("Nothing to review.");
+        return Ok(());
+    }
+    let (mut kept, mut tagged, mut deleted) = (0, 0, 0);
+    let mut done = 0;
+    'review: while let Some(bm) = review_ops::pending(&dal.get_bookmarks("")?, &filter, &state, &skipped)
+        .into_iter()
+        .next()
+    {
+        eprintln!("[{}/{}] {}", done + 1, total, bm.URL);
+        if !bm.metadata.is_empty() {
+            eprintln!("  {}", bm.metadata.as_str().green());
+        }
+        let tags = bm.get_tags();
+        if !tags.is_empty() {
+            eprintln!("  {}", format!("[{}]", tags.join(",")).dim());
+        }
+        loop {
+            let action = match review_ops::parse_action(&prompt("k | t tags | d | o | s | q > ")) {
+                Ok(action) => action,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+            };
+            match action {
+                ReviewAction::Keep => kept += 1,
+                ReviewAction::Tag(tags) => {
+                    let mut all_tags = bm.get_tags();
+                    all_tags.extend(tags);
+                    let mut bm = bm.clone();
+                    bm.set_tags(all_tags);
+                    bm.update();
+                    for bm in dal.update_bookmark(bm)? {
+                        bus::publish(BookmarkEvent::Updated(bm));
+                    }
+                    tagged += 1;
+                }
+                ReviewAction::Delete => {
+                    delete_bms(vec![1], vec![bm.clone()])?;
+                    deleted += 1;
+                }
+                ReviewAction::Open => {
+                    open_bm(&bm)?;
+                    continue;
+                }
+                ReviewAction::Skip => {
+                    skipped.insert(bm.uuid.clone());
+                    done += 1;
+                    continue 'review;
+                }
+                ReviewAction::Quit => break 'review,
+            }
+            break;
+        }
+        state.reviewed.insert(bm.uuid.clone());
+        state.save(&path)?;
+        done += 1;
+    }
+    let left = review_ops::pending(&dal.get_bookmarks("")?, &filter, &state, &HashSet::new()).len();
+    eprintln!(
+        "Review: kept {}, tagged {}, deleted {}, skipped {}, {} left.",
+        kept,
+        tagged,
+        deleted,
+        skipped.len(),
+        left
+    );
+    Ok(())
+}
+
+/// Applies tag rules, inserts the bookmark and runs the add hook.
+/// An existing bookmark of the URL is resolved by `on_exists`, returns the stored bookmark.
+fn insert_new_bookmark(dal: &mut Dal, mut bm: Bookmark, edit: bool, on_exists: OnExists) -> Result<Bookmark> {
+    TagRules::new(&CONFIG.rules)?.apply(&mut bm);
+    fill_language(&mut bm);
     bm.update();
 
     let result = dal.insert_bookmark(bm.convert_to_new_bookmark());
@@ -256,18 +1021,20 @@ pub fn add_bookmark(
             if edit {
                 edit_bms(vec![1], bms.clone()).context("Failed to edit bookmark")?;
             }
-            println!("Added bookmark: {}", bms[0].id);
-            show_bms(
-                &bms.iter().map(DisplayBookmark::from).collect::<Vec<_>>(),
-                &DEFAULT_FIELDS,
-            );
-            Ok(())
+            let added = bms
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("Failed to insert bookmark: {}", bm.URL))?;
+            bus::publish(BookmarkEvent::Added(added.clone()));
+            println!("Added bookmark: {}", added.id);
+            show_bms(&[DisplayBookmark::from(&added)], &DEFAULT_FIELDS);
+            Ok(added)
         }
         Err(e) => {
             if let Some(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) =
                 e.downcast_ref::<diesel::result::Error>()
             {
-                Err(anyhow!("Bookmark already exists: {}", url))
+                resolve_existing(dal, &bm, on_exists)
             } else {
                 Err(e)
             }
@@ -275,18 +1042,102 @@ pub fn add_bookmark(
     }
 }
 
+/// Merges the added bookmark into the existing one of its URL, keeps or refuses it
+fn resolve_existing(dal: &mut Dal, bm: &Bookmark, on_exists: OnExists) -> Result<Bookmark> {
+    let existing = dal.get_bookmark_by_url(&bm.URL)?;
+    let on_exists = match on_exists {
+        OnExists::Ask if input_allowed() => {
+            let answer = prompt(&format!(
+                "Bookmark already exists: {} [{}]. (m)erge, (s)kip or (a)bort? [a] ",
+                bm.URL, existing.id
+            ));
+            match answer.trim().to_lowercase().as_str() {
+                "m" | "merge" => OnExists::Merge,
+                "s" | "skip" => OnExists::Skip,
+                _ => OnExists::Error,
+            }
+        }
+        OnExists::Ask => OnExists::Error,
+        on_exists => on_exists,
+    };
+    match on_exists {
+        OnExists::Merge => {
+            let mut merged = merge::merge_duplicate(&existing, bm);
+            merged.update();
+            let updated = dal
+                .update_bookmark(merged)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("Failed to merge into bookmark {}", existing.id))?;
+            bus::publish(BookmarkEvent::Updated(updated.clone()));
+            println!("Merged into bookmark: {}", updated.id);
+            show_bms(&[DisplayBookmark::from(&updated)], &DEFAULT_FIELDS);
+            Ok(updated)
+        }
+        OnExists::Skip => {
+            println!("Skipped existing bookmark: {}", existing.id);
+            Ok(existing)
+        }
+        _ => Err(anyhow!("Bookmark already exists: {} [{}]", bm.URL, existing.id)),
+    }
+}
+
 #[instrument]
 pub fn delete_bookmarks(ids: String) -> Result<()> {
     let ids = get_ids(ids)?;
     delete_bms(ids, Bookmarks::new(String::new()).bms).context("Failed to delete bookmarks")
 }
 
+/// Deletes all bookmarks matching the query and tags within one transaction, they are written to
+/// the journal first
+#[instrument]
+pub fn delete_bookmarks_by_query(query: Option<String>, tags: Option<String>, dry_run: bool, yes: bool) -> Result<()> {
+    let tags = Tags::normalize_tag_string(tags);
+    let mut bms: Vec<Bookmark> = Bookmarks::new(query.clone().unwrap_or_default())
+        .bms
+        .into_iter()
+        .filter(|bm| Tags::match_all_tags(&tags, &bm.get_tags()))
+        .collect();
+    if bms.is_empty() {
+        eprintln!("No bookmarks match.");
+        return Ok(());
+    }
+    // highest id first because of id compaction
+    bms.sort_by_key(|bm| std::cmp::Reverse(bm.id));
+    let changes: Vec<Change> = bms.iter().cloned().map(Change::Delete).collect();
+    for change in changes.iter().rev() {
+        eprintln!("{}", change);
+    }
+    if dry_run {
+        println!("{}", Plan::from_changes(&changes).to_json()?);
+        eprintln!("Would delete {} bookmarks.", changes.len());
+        return Ok(());
+    }
+    if !yes {
+        let answer = prompt(&format!("Type {} to delete {} bookmarks: ", bms.len(), bms.len()));
+        if answer.trim() != bms.len().to_string() {
+            return Err(anyhow!("Operation aborted by user"));
+        }
+    }
+
+    let dir = journal::journal_dir().ok_or_else(|| anyhow!("No directory for the journal"))?;
+    let path = journal::record_deletion(&dir, &bms, clock::now_naive())?;
+    let n = bms.len();
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    bulk_ops::apply(&mut dal, changes).context("Failed to delete bookmarks")?;
+    eprintln!("Deleted {} bookmarks, restore them with: bkmr import {}", n, path.display());
+    Ok(())
+}
+
 #[instrument]
 pub fn update_bookmarks(
     force: bool,
     tags: Option<String>,
     tags_not: Option<String>,
+    language: Option<String>,
+    visibility: Option<Visibility>,
     ids: String,
+    allow_system_tags: bool,
 ) -> Result<()> {
     // Validate force update requirements
     if force && (tags.is_none() || tags_not.is_some()) {
@@ -294,52 +1145,289 @@ pub fn update_bookmarks(
     }
 
     let ids = get_ids(ids)?;
+    if tags.is_some() || tags_not.is_some() {
+        let add: HashSet<String> = Tags::normalize_tag_string(tags.clone()).into_iter().collect();
+        let remove: HashSet<String> = Tags::normalize_tag_string(tags_not.clone()).into_iter().collect();
+        let mut dal = Dal::new(CONFIG.db_url.clone());
+        let changes = ids
+            .iter()
+            .map(|id| {
+                let bm = dal.get_bookmark_by_id(*id)?;
+                let new_tags = retag(&bm, &add, &remove, force);
+                Ok((bm, new_tags))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        guard_system_tags(&changes, allow_system_tags)?;
+    }
+    if language.is_none() && visibility.is_none() {
+        let tags = Tags::normalize_tag_string(tags);
+        let tags_not = Tags::normalize_tag_string(tags_not);
+        let updated = crate::update_bookmarks(ids, tags, tags_not, force)
+            .context("Failed to update bookmarks")?;
+        updated
+            .into_iter()
+            .for_each(|bm| bus::publish(BookmarkEvent::Updated(bm)));
+        return Ok(());
+    }
+
+    let retag = tags.is_some() || tags_not.is_some();
     let tags = Tags::normalize_tag_string(tags);
     let tags_not = Tags::normalize_tag_string(tags_not);
-
-    crate::update_bookmarks(ids, tags, tags_not, force).context("Failed to update bookmarks")
-}
-
-#[instrument]
-pub fn edit_bookmarks(ids: String) -> Result<()> {
-    edit_bms(get_ids(ids)?, Bookmarks::new(String::new()).bms).context("Failed to edit bookmarks")
-}
-
-#[instrument]
-pub fn show_bookmarks(ids: String) -> Result<()> {
+    let language = language.map(|l| (!l.trim().is_empty()).then(|| l.trim().to_lowercase()));
     let mut dal = Dal::new(CONFIG.db_url.clone());
-    let mut bms = Vec::new();
+    let updated = dal
+        .transaction(|dal| {
+            let mut updated = Vec::new();
+            for id in ids {
+                if retag {
+                    crate::update_bm(id, &tags, &tags_not, dal, force)?;
+                }
+                let mut bm = dal.get_bookmark_by_id(id)?;
+                if let Some(language) = &language {
+                    bm.language = language.clone();
+                }
+                if let Some(visibility) = visibility {
+                    bm.visibility = visibility.to_string();
+                }
+                bm.update();
+                updated.extend(dal.update_bookmark(bm)?);
+            }
+            Ok(updated)
+        })
+        .context("Failed to update bookmarks")?;
+    updated
+        .into_iter()
+        .for_each(|bm| bus::publish(BookmarkEvent::Updated(bm)));
+    Ok(())
+}
 
-    for id in get_ids(ids)? {
-        if let Ok(bm) = dal.get_bookmark_by_id(id) {
-            bms.push(bm);
-        } else {
-            eprintln!("Bookmark with id {} not found", id);
+/// Generic tag updates must not change system tags by accident, they select how bookmarks open
+fn guard_system_tags(changes: &[(Bookmark, String)], allow_system_tags: bool) -> Result<()> {
+    if allow_system_tags {
+        return Ok(());
+    }
+    for (bm, new_tags) in changes {
+        let changed = changed_system_tags(&bm.get_tags(), &Tags::normalize_tag_string(Some(new_tags.clone())));
+        if !changed.is_empty() {
+            return Err(anyhow!(
+                "Bookmark {}: system tags {} would be added or removed, use --allow-system-tags to change them",
+                bm.id,
+                changed.join(",")
+            ));
         }
     }
-
-    show_bms(
-        &bms.iter().map(DisplayBookmark::from).collect::<Vec<_>>(),
-        &ALL_FIELDS,
-    );
     Ok(())
 }
 
+/// Applies tag changes to all bookmarks matching the query within one transaction
 #[instrument]
-pub fn show_tags(tag: Option<String>) -> Result<()> {
-    let mut dal = Dal::new(CONFIG.db_url.clone());
-    let tags = match tag {
-        Some(ref tag) => dal.get_related_tags(tag),
-        None => dal.get_all_tags(),
-    }?;
-
-    for tag in tags {
-        println!("{}: {}", tag.n, tag.tag);
+pub fn update_bookmarks_by_query(
+    query: String,
+    force: bool,
+    tags: Option<String>,
+    tags_not: Option<String>,
+    dry_run: bool,
+    allow_system_tags: bool,
+) -> Result<()> {
+    if force && (tags.is_none() || tags_not.is_some()) {
+        return Err(anyhow!("Force update requires tags but no ntags"));
+    }
+    if tags.is_none() && tags_not.is_none() {
+        return Err(anyhow!("Nothing to update, provide tags to add or remove"));
     }
-    Ok(())
-}
 
-#[instrument]
+    let tags: HashSet<String> = Tags::normalize_tag_string(tags).into_iter().collect();
+    let tags_not: HashSet<String> = Tags::normalize_tag_string(tags_not).into_iter().collect();
+
+    let changes: Vec<(Bookmark, String)> = Bookmarks::new(query.clone())
+        .bms
+        .into_iter()
+        .filter_map(|bm| {
+            let new_tags = retag(&bm, &tags, &tags_not, force);
+            (new_tags != bm.tags).then_some((bm, new_tags))
+        })
+        .collect();
+
+    if changes.is_empty() {
+        eprintln!("No bookmarks matching {:?} need changes.", query);
+        return Ok(());
+    }
+    guard_system_tags(&changes, allow_system_tags)?;
+    for (bm, new_tags) in &changes {
+        eprintln!("{}: {} -> {}", bm.id, bm.tags, new_tags);
+    }
+    if dry_run {
+        let plan_changes: Vec<Change> = changes
+            .iter()
+            .map(|(bm, new_tags)| Change::Update {
+                old: bm.clone(),
+                new: BulkBookmark {
+                    tags: Tags::normalize_tag_string(Some(new_tags.clone())),
+                    ..BulkBookmark::from(bm)
+                },
+            })
+            .collect();
+        println!("{}", Plan::from_changes(&plan_changes).to_json()?);
+        eprintln!("Would update {} bookmarks.", changes.len());
+        return Ok(());
+    }
+    if !confirm(&format!("Update {} bookmarks?", changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let updated = dal
+        .transaction(|dal| {
+            let mut updated = Vec::new();
+            for (bm, new_tags) in changes {
+                // flags count the opens, a retag leaves them as the plan shows
+                let mut bm = Bookmark { tags: new_tags, ..bm };
+                bm.update();
+                updated.extend(dal.update_bookmark(bm)?);
+            }
+            Ok(updated)
+        })
+        .context("Failed to update bookmarks")?;
+    eprintln!("Updated {} bookmarks.", updated.len());
+    updated
+        .into_iter()
+        .for_each(|bm| bus::publish(BookmarkEvent::Updated(bm)));
+    Ok(())
+}
+
+/// Applies the operation to the bookmarks with the ids and all tags within one transaction
+#[instrument]
+pub fn batch_bookmarks(
+    ids: Option<String>,
+    tags: Option<String>,
+    op: BatchOp,
+    value: Option<String>,
+    allow_system_tags: bool,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let value = Tags::normalize_tag_string(value);
+    if op.needs_tags() && value.is_empty() {
+        return Err(anyhow!("--op {} needs the tags as --value", op));
+    }
+    if op == BatchOp::SetEmbeddable && Context::read_global().model().is_none() {
+        return Err(anyhow!("--op {} requires an embedder, use --openai", op));
+    }
+    let ids = ids.map(get_ids).transpose()?.unwrap_or_default();
+    let tags = Tags::normalize_tag_string(tags);
+
+    let changes: Vec<(Bookmark, Bookmark)> = batch::select(Bookmarks::new(String::new()).bms, &ids, &tags)
+        .into_iter()
+        .filter_map(|bm| batch::apply(&bm, op, &value).map(|new| (bm, new)))
+        .collect();
+    if changes.is_empty() {
+        eprintln!("No bookmarks need changes.");
+        return Ok(());
+    }
+    let retagged: Vec<(Bookmark, String)> = changes.iter().map(|(old, new)| (old.clone(), new.tags.clone())).collect();
+    guard_system_tags(&retagged, allow_system_tags)?;
+    for (old, new) in &changes {
+        match op {
+            BatchOp::AddTag | BatchOp::RemoveTag => eprintln!("{}: {} -> {}", old.id, old.tags, new.tags),
+            _ => eprintln!("{}: {} {}", old.id, op, old.URL),
+        }
+    }
+    if op == BatchOp::SetEmbeddable {
+        // the estimated cost is confirmed instead
+        let contents: Vec<String> = changes.iter().map(|(_, new)| new.get_content()).collect();
+        confirm_embedding_cost(Context::read_global().model(), &contents, yes || dry_run)?;
+    } else if !dry_run && !yes && !confirm(&format!("Apply {} to {} bookmarks?", op, changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+    if dry_run {
+        let plan_changes: Vec<Change> = changes
+            .iter()
+            .map(|(old, new)| Change::Update {
+                old: old.clone(),
+                new: BulkBookmark::from(new),
+            })
+            .collect();
+        println!("{}", Plan::from_changes(&plan_changes).to_json()?);
+        eprintln!("Would change {} bookmarks.", changes.len());
+        return Ok(());
+    }
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let updated = dal
+        .transaction(|dal| {
+            let mut updated = Vec::new();
+            for (_, mut bm) in changes {
+                bm.update();
+                updated.extend(dal.update_bookmark(bm)?);
+            }
+            Ok(updated)
+        })
+        .context("Failed to update bookmarks")?;
+    eprintln!("Updated {} bookmarks.", updated.len());
+    updated
+        .into_iter()
+        .for_each(|bm| bus::publish(BookmarkEvent::Updated(bm)));
+    Ok(())
+}
+
+#[instrument]
+pub fn edit_bookmarks(ids: String) -> Result<()> {
+    edit_bms(get_ids(ids)?, Bookmarks::new(String::new()).bms).context("Failed to edit bookmarks")
+}
+
+#[instrument]
+pub fn show_bookmarks(ids: String) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let mut bms = Vec::new();
+
+    for id in get_ids(ids)? {
+        if let Ok(bm) = dal.get_bookmark_by_id(id) {
+            bms.push(bm);
+        } else {
+            eprintln!("Bookmark with id {} not found", id);
+        }
+    }
+
+    show_bms(
+        &bms.iter().map(DisplayBookmark::from).collect::<Vec<_>>(),
+        &ALL_FIELDS,
+    );
+    Ok(())
+}
+
+#[instrument]
+pub fn show_tags(
+    tag: Option<String>,
+    names_only: bool,
+    sort: TagSort,
+    cloud: bool,
+    prefix: Option<String>,
+    is_json: bool,
+) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let tags = match tag {
+        Some(ref tag) => dal.get_related_tags(tag),
+        None => dal.get_all_tags(),
+    }?;
+    let tags = select_tags(tags, prefix.as_deref(), sort);
+
+    if is_json {
+        println!("{}", serde_json::to_string_pretty(&tags)?);
+    } else if cloud && !is_plain() {
+        println!("{}", render_cloud(&tags));
+    } else {
+        for tag in tags {
+            if names_only {
+                println!("{}", tag.tag);
+            } else {
+                println!("{}: {}", tag.n, tag.tag);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
 pub fn create_db(path: String) -> Result<()> {
     let path = Utf8Path::new(&path);
     if path.exists() {
@@ -359,9 +1447,26 @@ pub fn create_db(path: String) -> Result<()> {
 }
 
 #[instrument]
-pub fn randomized(n: i32) -> Result<()> {
+pub fn randomized(
+    n: i32,
+    tags: Option<String>,
+    unopened_only: bool,
+    older_than: Option<String>,
+    daily: bool,
+) -> Result<()> {
+    let filter = SurpriseFilter {
+        tags: Tags::normalize_tag_string(tags),
+        unopened_only,
+        created_before: older_than
+            .map(|age| parse_age(&age).map(|age| clock::now_naive() - age))
+            .transpose()?,
+    };
+    let seed = daily.then(|| surprise::daily_seed(clock::now_naive().date()));
     let mut dal = Dal::new(CONFIG.db_url.clone());
-    let bms = dal.get_randomized_bookmarks(n)?;
+    let bms = surprise::pick(&dal.get_bookmarks("")?, n.max(0) as usize, &filter, seed);
+    if bms.is_empty() {
+        eprintln!("No bookmarks match.");
+    }
 
     for bm in &bms {
         open::that(&bm.URL).with_context(|| format!("Failed to open URL: {}", bm.URL))?;
@@ -369,62 +1474,90 @@ pub fn randomized(n: i32) -> Result<()> {
     Ok(())
 }
 
+/// Commands other than `migrate` and `info` require the schema of this bkmr version, `force` accepts
+/// a schema of a newer bkmr, `allow_pending` an outdated one
 #[instrument(level = "debug")]
-pub fn enable_embeddings_if_required() -> Result<()> {
+pub fn require_current_schema(force: bool, allow_pending: bool) -> Result<()> {
+    let status = migrate::status(&mut Dal::new(CONFIG.db_url.clone()))?;
+    if !force {
+        status.check_supported()?;
+    } else if !status.unknown.is_empty() {
+        eprintln!("Warning: database schema is newer than this bkmr supports, continuing because of --force");
+    }
+    if !status.pending.is_empty() && !allow_pending {
+        return Err(anyhow!(
+            "Database schema is outdated, {} migrations pending. Run 'bkmr migrate --backup-first' (see 'bkmr migrate --status')",
+            status.pending.len()
+        ));
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn migrate_db(status_only: bool, dry_run: bool, backup_first: bool) -> Result<()> {
     let mut dal = Dal::new(CONFIG.db_url.clone());
+    let status = migrate::status(&mut dal)?;
 
-    if dal.check_embedding_column_exists()? {
-        info!("Embedding column already exists, no action required.");
+    if status_only {
+        println!("Schema version: {}", status.version().unwrap_or("none"));
+        println!("Applied: {}", status.applied.len());
+        for version in &status.unknown {
+            println!("Unknown: {} (applied by a newer bkmr)", version);
+        }
+        for name in &status.pending {
+            println!("Pending: {}", name);
+        }
+        if status.legacy {
+            eprintln!("Database of bkmr v1, the migration table is created by 'bkmr migrate'.");
+        }
+        return Ok(());
+    }
+    if status.pending.is_empty() {
+        eprintln!("Database schema is up to date.");
         return Ok(());
     }
 
-    eprintln!("New 'bkmr' version requires an extension of the database schema.");
-    eprintln!("Two new columns will be added to the 'bookmarks' table:");
+    eprintln!("The following migrations will be applied:");
+    status.pending.iter().for_each(|name| eprintln!("  {}", name));
+    if dry_run {
+        let applied = migrate::dry_run(&mut dal)?;
+        eprintln!("Would apply {} migrations, verified on a copy of the database.", applied.len());
+        return Ok(());
+    }
 
-    if !confirm("Please backup up your DB before continue! Do you want to continue?") {
+    if backup_first {
+        let path = migrate::backup(&mut dal, &CONFIG.db_url)?;
+        eprintln!("Backed up database to {}", path);
+    } else if !confirm("Please backup up your DB before continue! Do you want to continue?") {
         return Err(anyhow!("Operation aborted by user"));
     }
-
-    // Create migrations table if it doesn't exist
-    if !dal.check_schema_migrations_exists()? {
-        const MIGRATION_TABLE_SQL: &str = r#"
-            BEGIN TRANSACTION;
-            CREATE TABLE IF NOT EXISTS __diesel_schema_migrations (
-                version VARCHAR(50) PRIMARY KEY NOT NULL,
-                run_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
-            );
-            INSERT INTO __diesel_schema_migrations (version, run_on)
-            VALUES ('20221229110455', '2023-12-23 09:27:06');
-            COMMIT;
-        "#;
-
-        info!("Creating migration table... {:?}", dal);
-        dal.conn
-            .batch_execute(MIGRATION_TABLE_SQL)
-            .context("Failed to create migrations table")?;
+    let (_, rehashed) = migrate::apply(&mut dal)?;
+    if rehashed > 0 {
+        eprintln!("Recomputed {} content hashes.", rehashed);
     }
-
-    let pending = dal
-        .conn
-        .pending_migrations(MIGRATIONS)
-        .map_err(|e| anyhow::anyhow!("Failed to get pending migrations: {}", e))?;
-
-    pending.iter().for_each(|m| {
-        debug!("Pending Migration: {}", m.name());
-    });
-
-    dal.conn
-        .run_pending_migrations(MIGRATIONS)
-        .map_err(|e| anyhow::anyhow!("Failed to run pending migrations: {}", e))?;
-
     eprintln!("{}", "Database schema has been extended.".blue());
     Ok(())
 }
 
 #[instrument]
-pub fn backfill_embeddings(dry_run: bool) -> Result<()> {
+pub fn backfill_embeddings(dry_run: bool, migrate_embeddings: bool, yes: bool) -> Result<()> {
     let mut dal = Dal::new(CONFIG.db_url.clone());
-    let bms = dal.get_bookmarks_without_embedding()?;
+    let mut bms = dal.get_bookmarks_without_embedding()?;
+    if migrate_embeddings {
+        let model = Context::read_global()
+            .model()
+            .map(String::from)
+            .ok_or_else(|| anyhow!("--migrate-embeddings requires --openai"))?;
+        let stale: Vec<Bookmark> = dal
+            .get_bookmarks("")?
+            .into_iter()
+            .filter(|bm| bm.embedding.is_some() && bm.embedding_model.as_deref() != Some(&model))
+            .collect();
+        eprintln!("{} bookmarks embedded with another model than {}", stale.len(), model);
+        bms.extend(stale);
+    }
+    let contents: Vec<String> = bms.iter().map(|bm| bm.get_content()).collect();
+    confirm_embedding_cost(Context::read_global().model(), &contents, yes || dry_run)?;
 
     for bm in &bms {
         println!("Updating: {:?}", bm.metadata);
@@ -438,9 +1571,12 @@ pub fn backfill_embeddings(dry_run: bool) -> Result<()> {
 }
 
 #[instrument]
-pub fn load_texts(dry_run: bool, path: String) -> Result<()> {
+pub fn load_texts(dry_run: bool, yes: bool, path: String) -> Result<()> {
+    // upper bound, unchanged texts are not embedded again
+    let bms = read_ndjson_file_and_create_bookmarks(&path)?;
+    let contents: Vec<String> = bms.iter().map(|bm| bm.get_content()).collect();
+    confirm_embedding_cost(Context::read_global().model(), &contents, yes || dry_run)?;
     if dry_run {
-        let bms = read_ndjson_file_and_create_bookmarks(&path)?;
         eprintln!("Would load {} texts for semantic search.", bms.len());
         Ok(())
     } else {
@@ -449,18 +1585,346 @@ pub fn load_texts(dry_run: bool, path: String) -> Result<()> {
 }
 
 #[instrument]
+pub fn doctor(fix: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let findings = diagnose(&mut dal)?;
+
+    if findings.is_empty() {
+        eprintln!("{}", "No problems found.".green());
+        return Ok(());
+    }
+    for finding in &findings {
+        println!("{}", finding);
+    }
+
+    let n_fixable = findings.iter().filter(|f| f.is_fixable()).count();
+    if fix {
+        let n = repair(&mut dal, &findings)?;
+        eprintln!("Fixed {} of {} problems.", n, findings.len());
+        if findings.iter().any(|f| f.check == service::doctor::Check::Embedding) {
+            eprintln!("Run 'bkmr backfill' to recreate cleared embeddings.");
+        }
+    } else {
+        eprintln!(
+            "Found {} problems, {} fixable with --fix.",
+            findings.len(),
+            n_fixable
+        );
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn lint(fix: bool, no_web: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let bms = dal.get_bookmarks("")?;
+    let rules = TagRules::new(&CONFIG.rules)?;
+    let https_available =
+        |url: &str| https_url(url).is_some_and(|https| matches!(https::check(url, &https), Outcome::Identical(_)));
+    let findings = lint_ops::lint(&bms, &rules, (!no_web).then_some(&https_available as &dyn Fn(&str) -> bool));
+    for finding in &findings {
+        println!("{}", finding);
+    }
+    eprintln!(
+        "Quality: {:.0}% of {} bookmarks without findings, {} findings.",
+        lint_ops::score(bms.len(), &findings),
+        bms.len(),
+        findings.len()
+    );
+    let fixable: Vec<_> = findings.iter().filter(|f| f.fix.is_some()).collect();
+    if !fix {
+        if !fixable.is_empty() {
+            eprintln!("{} fixable with --fix.", fixable.len());
+        }
+        return Ok(());
+    }
+
+    let updated = dal
+        .transaction(|dal| {
+            let mut updated = Vec::new();
+            for finding in &fixable {
+                let mut bm = dal.get_bookmark_by_id(finding.id)?;
+                match &finding.fix {
+                    Some(Fix::Url(url)) if dal.bm_exists(url)? => {
+                        eprintln!("{}: {} is bookmarked already, not fixed", bm.id, url);
+                        continue;
+                    }
+                    Some(Fix::Url(url)) => bm.URL = url.clone(),
+                    Some(Fix::Tags(tags)) => {
+                        let mut all_tags = bm.get_tags();
+                        all_tags.extend(tags.iter().cloned());
+                        bm.set_tags(all_tags);
+                    }
+                    None => continue,
+                }
+                bm.update();
+                updated.extend(dal.update_bookmark(bm)?);
+            }
+            Ok(updated)
+        })
+        .context("Failed to fix bookmarks")?;
+    eprintln!("Fixed {} of {} findings.", updated.len(), findings.len());
+    updated
+        .into_iter()
+        .for_each(|bm| bus::publish(BookmarkEvent::Updated(bm)));
+    Ok(())
+}
+
+#[instrument]
+pub fn show_status(porcelain: bool, starship: bool) -> Result<()> {
+    let status = Status::read(&mut Dal::new(CONFIG.db_url.clone()))?;
+    if starship {
+        let segment = status.render(&CONFIG.status.starship);
+        if !segment.is_empty() {
+            println!("{}", segment);
+        }
+    } else if porcelain {
+        println!("{}", status.porcelain());
+    } else {
+        status.lines().iter().for_each(|line| println!("{}", line));
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn show_info(db: bool) -> Result<()> {
+    println!("Version: {}", env!("CARGO_PKG_VERSION"));
+    println!("Config: {}", config_path());
+    println!("Database: {}", CONFIG.db_url);
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let status = migrate::status(&mut dal)?;
+    println!("Schema version: {}", status.version().unwrap_or("none"));
+    if db {
+        let info = dal.get_db_info()?;
+        println!("SQLite: {}", info.sqlite_version);
+        println!("Journal mode: {}", info.journal_mode);
+        println!("Busy timeout: {} ms", info.busy_timeout_ms);
+        println!(
+            "Migrations: {} applied, {} pending",
+            status.applied.len(),
+            status.pending.len()
+        );
+        for name in &status.pending {
+            println!("  pending: {}", name);
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn vacuum() -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let before = maintenance::size_report(&mut dal)?.file_size;
+    dal.vacuum()?;
+    let after = maintenance::size_report(&mut dal)?.file_size;
+    eprintln!(
+        "Vacuumed database: {} -> {}",
+        format_bytes(before),
+        format_bytes(after)
+    );
+    Ok(())
+}
+
+#[instrument]
+pub fn analyze() -> Result<()> {
+    Dal::new(CONFIG.db_url.clone()).analyze()?;
+    eprintln!("Updated query planner statistics.");
+    Ok(())
+}
+
+#[instrument]
+pub fn show_size(is_json: bool) -> Result<()> {
+    let report = maintenance::size_report(&mut Dal::new(CONFIG.db_url.clone()))?;
+    if is_json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for line in report.lines() {
+            println!("{}", line);
+        }
+        if report.free_size > 0 {
+            eprintln!("Run 'bkmr maintenance vacuum' to release the free space.");
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn set_meta(id: String, key: &str, value: Option<&str>) -> Result<()> {
+    let key = key.trim();
+    if key.is_empty() || key.contains(['=', ' ', '}']) {
+        return Err(anyhow!("Invalid key {:?}, it must not be empty or contain blanks, '=' or '}}'", key));
+    }
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let id = *get_ids(id)?.first().ok_or_else(|| anyhow!("No bookmark id given"))?;
+    let mut bm = dal.get_bookmark_by_id(id)?;
+    if value.is_none() && !bm.meta().contains_key(key) {
+        return Err(anyhow!("Bookmark {} has no key {:?}", id, key));
+    }
+    bm.set_meta(key, value);
+    for bm in dal.update_bookmark(bm)? {
+        bus::publish(BookmarkEvent::Updated(bm));
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn show_meta(id: String, key: Option<String>) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let id = *get_ids(id)?.first().ok_or_else(|| anyhow!("No bookmark id given"))?;
+    let meta = dal.get_bookmark_by_id(id)?.meta();
+    match key {
+        Some(key) => {
+            let value = meta
+                .get(&key)
+                .ok_or_else(|| anyhow!("Bookmark {} has no key {:?}", id, key))?;
+            println!("{}", value);
+        }
+        None => meta.iter().for_each(|(key, value)| println!("{}={}", key, value)),
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn list_rules() -> Result<()> {
+    let rules = TagRules::new(&CONFIG.rules)?;
+    if rules.rules.is_empty() {
+        eprintln!("No tag rules configured.");
+    }
+    for rule in &rules.rules {
+        println!("{}: {}", rule.key, rule.tags.join(","));
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn add_registry(name: String, url: String, key: Option<String>) -> Result<()> {
+    let registry = Registry::new(&name, &url, key);
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    dal.insert_registry(&registry)?;
+    eprintln!("Added registry {}.", registry.name);
+    sync_registries(Some(registry.name), false)
+}
+
+#[instrument]
+pub fn remove_registry(name: String) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let n = registry::remove(&mut dal, &name.to_lowercase())?;
+    eprintln!("Removed registry {} and {} bookmarks.", name, n);
+    Ok(())
+}
+
+pub fn list_registries() -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let registries = dal.get_registries()?;
+    if registries.is_empty() {
+        eprintln!("No registries configured.");
+    }
+    for registry in &registries {
+        let last_sync = registry
+            .last_sync
+            .map(|ts| ts.to_string())
+            .unwrap_or_else(|| "never".to_string());
+        println!("{}: {} (synced: {})", registry.name, registry.url, last_sync);
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn sync_registries(name: Option<String>, dry_run: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let registries = match name {
+        Some(name) => vec![dal.get_registry(&name.to_lowercase())?],
+        None => dal.get_registries()?,
+    };
+    for registry in &registries {
+        let changes = registry::sync(&mut dal, registry, dry_run)?;
+        for change in &changes {
+            eprintln!("{}", change);
+        }
+        if dry_run {
+            println!("{}", Plan::from_changes(&changes).to_json()?);
+            eprintln!("Would apply {} changes from {}.", changes.len(), registry.name);
+        } else {
+            eprintln!("Synced {}: {} changes.", registry.name, changes.len());
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn apply_rules(ids: Option<String>, retroactive: bool, dry_run: bool) -> Result<()> {
+    let rules = TagRules::new(&CONFIG.rules)?;
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+
+    let bms = match (ids, retroactive) {
+        (Some(ids), false) => get_ids(ids)?
+            .into_iter()
+            .map(|id| dal.get_bookmark_by_id(id))
+            .collect::<Result<Vec<_>>>()?,
+        (None, true) => dal.get_bookmarks("")?,
+        _ => return Err(anyhow!("Either ids or --retroactive required")),
+    };
+
+    let mut changes = Vec::new();
+    let mut updated = Vec::new();
+    let n = dal.transaction(|dal| {
+        let mut n = 0;
+        for mut bm in bms {
+            let old = bm.clone();
+            let added = rules.apply(&mut bm);
+            if added.is_empty() {
+                continue;
+            }
+            n += 1;
+            eprintln!("Tagging: {} [{}]: +{}", bm.metadata, bm.id, added.join(",+"));
+            if dry_run {
+                changes.push(Change::Update {
+                    old,
+                    new: BulkBookmark::from(&bm),
+                });
+            } else {
+                bm.update();
+                updated.extend(dal.update_bookmark(bm)?);
+            }
+        }
+        Ok(n)
+    })?;
+    updated
+        .into_iter()
+        .for_each(|bm| bus::publish(BookmarkEvent::Updated(bm)));
+    if dry_run {
+        println!("{}", Plan::from_changes(&changes).to_json()?);
+    }
+    eprintln!("Tagged {} bookmarks", n);
+    Ok(())
+}
+
+#[instrument]
+#[allow(clippy::too_many_arguments)]
 pub fn sem_search(
     query: String,
     limit: Option<i32>,
+    tags: Option<String>,
+    bm_type: Option<String>,
+    threshold: Option<f32>,
+    explain: bool,
     non_interactive: bool,
     mut stderr: StandardStream,
 ) -> Result<()> {
-    let bms = Bookmarks::new(String::new());
+    // similarity is only computed for the candidates passing the filters
+    let mut bms = Bookmarks::new(String::new());
+    let searched_tags: Vec<String> = Tags::normalize_tag_string(tags)
+        .into_iter()
+        .chain(bm_type.as_deref().map(normalize_system_tag))
+        .collect();
+    bms.filter(Some(searched_tags.join(",")), None, None, None, None);
+    bms.load_embeddings()?;
     let results = find_similar(&query, &bms)?;
     let limit = limit.unwrap_or(10) as usize;
 
     let filtered_results: Vec<_> = results
         .iter()
+        .filter(|(_, similarity)| threshold.is_none_or(|t| *similarity >= t))
         .filter_map(|(id, similarity)| {
             bms.bms.iter().find(|bm| bm.id == *id).map(|bm| {
                 let mut dbm = DisplayBookmark::from(bm);
@@ -478,6 +1942,11 @@ pub fn sem_search(
         .cloned()
         .collect();
     show_bms(&display_bookmarks, &DEFAULT_FIELDS);
+    if explain {
+        let similarities: HashMap<i32, f32> = results.iter().copied().collect();
+        let found: Vec<Bookmark> = filtered_results.iter().map(|(bm, _)| bm.clone()).collect();
+        explain_scores(&found, "", &similarities, &searched_tags)?;
+    }
 
     if non_interactive {
         let ids = filtered_results
@@ -501,6 +1970,37 @@ pub fn sem_search(
     Ok(())
 }
 
+/// bm25 ranks of the FTS query, empty for CJK queries which use the trigram index
+fn fts_ranks(fts_query: &str) -> Result<HashMap<i32, f64>> {
+    if fts_query.is_empty() || contains_cjk(fts_query) {
+        return Ok(HashMap::new());
+    }
+    Dal::new(CONFIG.db_url.clone()).get_fts_ranks(fts_query)
+}
+
+/// Prints the score breakdown of the results to stderr
+fn explain_scores(
+    bms: &[Bookmark],
+    fts_query: &str,
+    similarities: &HashMap<i32, f32>,
+    tags: &[String],
+) -> Result<()> {
+    let fts_ranks = fts_ranks(fts_query)?;
+    let now = clock::now_naive();
+    for bm in bms {
+        let score = Score::new(
+            bm,
+            fts_ranks.get(&bm.id).copied(),
+            similarities.get(&bm.id).copied(),
+            tags,
+            now,
+            &CONFIG.ranking,
+        );
+        eprintln!("{:>4}: {}", bm.id, score);
+    }
+    Ok(())
+}
+
 #[instrument]
 pub fn find_similar(query: &str, bms: &Bookmarks) -> Result<Vec<(i32, f32)>> {
     Context::update_global(Context::new(Box::new(OpenAiEmbedding::default())))?;
@@ -509,22 +2009,785 @@ pub fn find_similar(query: &str, bms: &Bookmarks) -> Result<Vec<(i32, f32)>> {
         .execute(query)?
         .ok_or_else(|| anyhow!("No embedding generated. OpenAI flag set?"))?;
 
+    let model = Context::read_global().model().map(String::from);
     let query_vector = ndarray::Array1::from(embedding);
     let mut results = Vec::with_capacity(bms.bms.len());
+    let mut incompatible = 0;
 
     for bm in &bms.bms {
         if let Some(embedding_data) = &bm.embedding {
+            // vectors of other models (or legacy ones of another dimension) are not comparable
+            if bm.embedding_model.is_some() && bm.embedding_model != model {
+                incompatible += 1;
+                continue;
+            }
             let bm_embedding = deserialize_embedding(embedding_data.clone())?;
+            if bm_embedding.len() != query_vector.len() {
+                incompatible += 1;
+                continue;
+            }
             let bm_vector = ndarray::Array1::from(bm_embedding);
             let similarity = cosine_similarity(&query_vector, &bm_vector);
             results.push((bm.id, similarity));
         }
     }
+    if incompatible > 0 {
+        eprintln!(
+            "Skipped {} bookmarks embedded with another model, run `bkmr --openai backfill --migrate-embeddings`",
+            incompatible
+        );
+    }
 
     results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
     Ok(results)
 }
 
+#[instrument]
+pub fn export_bookmarks(format: BulkFormat, visibility: Option<Visibility>, sign: bool) -> Result<()> {
+    let level = visibility.unwrap_or(if sign {
+        Visibility::Shared
+    } else {
+        Visibility::Private
+    });
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let bms: Vec<Bookmark> = dal
+        .get_bookmarks("")?
+        .into_iter()
+        .filter(|bm| bm.is_visible_at(level))
+        .map(|bm| secrets::redact_bookmark(&bm))
+        .collect();
+    if format == BulkFormat::Vscode {
+        if sign {
+            return Err(anyhow!("vscode exports cannot be signed"));
+        }
+        println!("{}", serde_json::to_string_pretty(&vscode_snippets(&bms))?);
+        return Ok(());
+    }
+    let file = BulkFile {
+        visibility: (level != Visibility::Private).then_some(level),
+        ..BulkFile::new(&bms)
+    };
+    let content = file.serialize(format)?;
+    if sign {
+        let key = SigningKey::load_or_create(&signing::key_path(&CONFIG.signing)?)?;
+        println!("{}", key.sign(content, format).to_json()?);
+        return Ok(());
+    }
+    print!("{}", content);
+    Ok(())
+}
+
+#[instrument]
+pub fn apply_bulk_file(path: String, dry_run: bool) -> Result<()> {
+    let content =
+        std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path))?;
+    let file = signing::open(&content, BulkFormat::from_path(&path), None)
+        .with_context(|| format!("Invalid bulk file {}", path))?;
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let changes = bulk_ops::diff(&dal.get_bookmarks("")?, &file)?;
+    if changes.is_empty() {
+        eprintln!("Nothing to do, bookmarks match {}.", path);
+        return Ok(());
+    }
+    for change in &changes {
+        eprintln!("{}", change);
+    }
+    if dry_run {
+        println!("{}", Plan::from_changes(&changes).to_json()?);
+        eprintln!("Would apply {} changes.", changes.len());
+        return Ok(());
+    }
+    if !confirm(&format!("Apply {} changes?", changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    let n = changes.len();
+    bulk_ops::apply(&mut dal, changes).context("Failed to apply bulk file")?;
+    eprintln!("Applied {} changes.", n);
+    Ok(())
+}
+
+#[instrument]
+pub fn normalize_tags(dry_run: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    // the tags of a bulk bookmark are normalized when read
+    let changes: Vec<Change> = dal
+        .get_bookmarks("")?
+        .into_iter()
+        .map(|bm| (BulkBookmark::from(&bm), bm))
+        .filter(|(new, bm)| Tags::create_normalized_tag_string(Some(new.tags.join(","))) != bm.tags)
+        .map(|(new, old)| Change::Update { old, new })
+        .collect();
+    if changes.is_empty() {
+        eprintln!("Nothing to do, tags are normalized.");
+        return Ok(());
+    }
+    for change in &changes {
+        eprintln!("{}", change);
+    }
+    if dry_run {
+        println!("{}", Plan::from_changes(&changes).to_json()?);
+        eprintln!("Would update {} bookmarks.", changes.len());
+        return Ok(());
+    }
+    if !confirm(&format!("Update {} bookmarks?", changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    let n = changes.len();
+    bulk_ops::apply(&mut dal, changes).context("Failed to normalize tags")?;
+    eprintln!("Updated {} bookmarks.", n);
+    Ok(())
+}
+
+#[instrument]
+pub fn refresh_metadata(ids: Option<String>, normalize_titles: bool, canonicalize: bool, dry_run: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let bms = match ids {
+        Some(ids) => get_ids(ids)?
+            .into_iter()
+            .map(|id| dal.get_bookmark_by_id(id))
+            .collect::<Result<Vec<_>>>()?,
+        None => dal.get_bookmarks("")?,
+    };
+    let rules = TitleRules::new(&CONFIG.titles)?;
+
+    let mut changes = Vec::new();
+    for bm in &bms {
+        let mut new = BulkBookmark::from(bm);
+        if normalize_titles {
+            new.title = rules.normalize(&bm.metadata, &bm.URL);
+        } else if bm.URL.starts_with("http://") || bm.URL.starts_with("https://") {
+            match fetch_page(&bm.URL).and_then(|page| Ok((parse_url_details(&bm.URL, &page)?, page))) {
+                // keeps the current values if the page has none
+                Ok(((title, desc, _), page)) => {
+                    if !title.is_empty() {
+                        new.title = title;
+                    }
+                    if !desc.is_empty() {
+                        new.desc = desc;
+                    }
+                    if let Some(canonical) = parse_canonical_url(&bm.URL, &page).filter(|_| canonicalize) {
+                        if dal.bm_exists(&canonical)? {
+                            eprintln!("{}: canonical URL {} is bookmarked already", bm.id, canonical);
+                        } else {
+                            new.url = canonical;
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to fetch {}: {:#}", bm.URL, e),
+            }
+        }
+        if new.title != bm.metadata || new.desc != bm.desc || new.url != bm.URL {
+            changes.push(Change::Update { old: bm.clone(), new });
+        }
+    }
+    if changes.is_empty() {
+        eprintln!("Nothing to do, metadata is up to date.");
+        return Ok(());
+    }
+    for change in &changes {
+        eprintln!("{}", change);
+    }
+    if dry_run {
+        println!("{}", Plan::from_changes(&changes).to_json()?);
+        eprintln!("Would update {} bookmarks.", changes.len());
+        return Ok(());
+    }
+    if !confirm(&format!("Update {} bookmarks?", changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    let n = changes.len();
+    bulk_ops::apply(&mut dal, changes).context("Failed to refresh metadata")?;
+    eprintln!("Updated {} bookmarks.", n);
+    Ok(())
+}
+
+/// Rewrites the http bookmarks whose https URL responds identically, reports the outcome per URL
+#[instrument]
+pub fn upgrade_https(ids: Option<String>, dry_run: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let bms = match ids {
+        Some(ids) => get_ids(ids)?
+            .into_iter()
+            .map(|id| dal.get_bookmark_by_id(id))
+            .collect::<Result<Vec<_>>>()?,
+        None => dal.get_bookmarks("")?,
+    };
+
+    let mut changes = Vec::new();
+    let mut checked = 0;
+    for bm in &bms {
+        let Some(https) = https_url(&bm.URL) else { continue };
+        checked += 1;
+        let outcome = if dal.bm_exists(&https)? {
+            Outcome::Exists(https.clone())
+        } else {
+            https::check(&bm.URL, &https)
+        };
+        eprintln!("{}: {}: {}", bm.id, bm.URL, outcome);
+        if let Outcome::Identical(url) = outcome {
+            let new = BulkBookmark {
+                url,
+                ..BulkBookmark::from(bm)
+            };
+            changes.push(Change::Update { old: bm.clone(), new });
+        }
+    }
+    if changes.is_empty() {
+        eprintln!("Nothing to do, {} http bookmarks checked.", checked);
+        return Ok(());
+    }
+    if dry_run {
+        println!("{}", Plan::from_changes(&changes).to_json()?);
+        eprintln!("Would upgrade {} of {} http bookmarks.", changes.len(), checked);
+        return Ok(());
+    }
+    if !confirm(&format!("Upgrade {} bookmarks to https?", changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    let n = changes.len();
+    bulk_ops::apply(&mut dal, changes).context("Failed to upgrade bookmarks")?;
+    eprintln!("Upgraded {} of {} http bookmarks.", n, checked);
+    Ok(())
+}
+
+#[instrument]
+pub fn import_bulk_file(
+    path: String,
+    format: Option<BulkFormat>,
+    verify: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let content =
+        std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path))?;
+    let trusted_keys = verify.then_some(CONFIG.signing.trusted_keys.as_slice());
+    let format = format.unwrap_or_else(|| BulkFormat::from_path(&path));
+    let file = signing::open(&content, format, trusted_keys)
+        .map_err(|e| anyhow!("Invalid import {}: {:#}", path, e))?;
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let changes = bulk_ops::merge_changes(&dal.get_bookmarks("")?, &file)?;
+    if changes.is_empty() {
+        eprintln!("Nothing to do, bookmarks are up to date with {}.", path);
+        return Ok(());
+    }
+    for change in &changes {
+        eprintln!("{}", change);
+    }
+    if dry_run {
+        println!("{}", Plan::from_changes(&changes).to_json()?);
+        eprintln!("Would apply {} changes.", changes.len());
+        return Ok(());
+    }
+    if !confirm(&format!("Apply {} changes?", changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    let n = changes.len();
+    bulk_ops::apply(&mut dal, changes).context("Failed to import")?;
+    eprintln!("Imported {} changes.", n);
+    Ok(())
+}
+
+#[instrument]
+pub fn show_clusters(k: Option<usize>, titles: usize, is_json: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let (clusters, skipped) = cluster::cluster(&dal.get_bookmarks_with_embeddings("")?, k, titles)?;
+    if skipped > 0 {
+        eprintln!("Skipped {} bookmarks without comparable embedding.", skipped);
+    }
+    if is_json {
+        println!("{}", serde_json::to_string_pretty(&clusters)?);
+        return Ok(());
+    }
+    for (i, cluster) in clusters.iter().enumerate() {
+        println!(
+            "{} {} {}",
+            format!("{}.", i + 1).yellow(),
+            cluster.label().green(),
+            format!("({} bookmarks)", cluster.size).dim()
+        );
+        if !cluster.candidate_tags.is_empty() {
+            println!("   candidate tags: {}", cluster.candidate_tags.join(", "));
+        }
+        for representative in &cluster.representatives {
+            println!("   {} [{}]", representative.title, representative.id);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn digest(since: String, email: bool, markdown: bool, summaries: bool) -> Result<()> {
+    let cutoff = clock::now_naive() - parse_age(&since)?;
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let added: Vec<Bookmark> = digest_ops::added_since(&dal.get_bookmarks("")?, cutoff)
+        .iter()
+        .map(secrets::redact_bookmark)
+        .collect();
+    let since = cutoff.format("%Y-%m-%d").to_string();
+    if added.is_empty() {
+        eprintln!("No bookmarks added since {}.", since);
+        return Ok(());
+    }
+
+    let mut by_id = HashMap::new();
+    if summaries {
+        let ctx = Context::read_global();
+        for bm in &added {
+            let readable = dal.get_readable(&bm.uuid)?;
+            let text = digest_ops::summary_input(bm, readable.as_ref());
+            let summary = ctx
+                .summarize(&text)
+                .with_context(|| format!("Failed to summarize bookmark {}", bm.id))?
+                .ok_or_else(|| anyhow!("Summaries need an embedder generating text, use --openai"))?;
+            by_id.insert(bm.id, summary);
+        }
+    }
+    let format = if email {
+        DigestFormat::Email
+    } else if markdown {
+        DigestFormat::Markdown
+    } else {
+        DigestFormat::Text
+    };
+    print!("{}", digest_ops::render(&added, &since, &by_id, format));
+    Ok(())
+}
+
+#[instrument]
+pub fn show_timeline(by: Period, period: Option<String>, is_json: bool) -> Result<()> {
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let bms = dal.get_bookmarks("")?;
+    if let Some(period) = period {
+        let key = by.parse_key(&period)?;
+        let selected = timeline_ops::drill_down(&bms, by, &key);
+        if is_json {
+            bms_to_json(&selected);
+        } else if selected.is_empty() {
+            eprintln!("No bookmarks created in {}.", key);
+        } else {
+            show_bms(
+                &selected.iter().map(DisplayBookmark::from).collect::<Vec<_>>(),
+                &DEFAULT_FIELDS,
+            );
+        }
+        return Ok(());
+    }
+
+    let buckets = timeline_ops::timeline(&bms, by);
+    if is_json {
+        println!("{}", serde_json::to_string_pretty(&buckets)?);
+    } else {
+        print!("{}", timeline_ops::render_table(&buckets));
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn prune_bookmarks(
+    unused_since: String,
+    max_hits: Option<i32>,
+    keep_tags: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let cutoff = clock::now_naive() - parse_age(&unused_since)?;
+    let keep_tags = Tags::normalize_tag_string(keep_tags);
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let mut stale: Vec<Bookmark> = dal
+        .get_bookmarks("")?
+        .into_iter()
+        .filter(|bm| bm.last_update_ts < cutoff)
+        .filter(|bm| max_hits.is_none_or(|n| bm.flags <= n))
+        .filter(|bm| !bm.get_tags().iter().any(|t| keep_tags.contains(t)))
+        .collect();
+    if stale.is_empty() {
+        eprintln!("No bookmarks unused since {}.", unused_since);
+        return Ok(());
+    }
+
+    // highest id first, deletion compacts the ids
+    stale.sort_by_key(|bm| std::cmp::Reverse(bm.id));
+    for bm in &stale {
+        eprintln!("- {}: {} (opened {}x, last {})", bm.id, bm.URL, bm.flags, bm.last_update_ts.date());
+    }
+    let changes: Vec<Change> = stale.iter().cloned().map(Change::Delete).collect();
+    if dry_run {
+        println!("{}", Plan::from_changes(&changes).to_json()?);
+        eprintln!("Would delete {} bookmarks.", changes.len());
+        return Ok(());
+    }
+    if !confirm(&format!("Delete {} bookmarks?", changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    bulk_ops::apply(&mut dal, changes).context("Failed to prune bookmarks")?;
+    eprintln!("Deleted {} bookmarks.", stale.len());
+    Ok(())
+}
+
+#[instrument]
+pub fn apply_plan(path: String, yes: bool) -> Result<()> {
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path))?;
+    let plan = Plan::parse(&content).with_context(|| format!("Invalid plan {}", path))?;
+    if plan.is_empty() {
+        eprintln!("Nothing to do, plan {} is empty.", path);
+        return Ok(());
+    }
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let changes = plan.to_changes(&dal.get_bookmarks("")?)?;
+    for change in &changes {
+        eprintln!("{}", change);
+    }
+    if !yes && !confirm(&format!("Apply {} changes?", changes.len())) {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    let n = changes.len();
+    bulk_ops::apply(&mut dal, changes).context("Failed to apply plan")?;
+    eprintln!("Applied {} changes.", n);
+    Ok(())
+}
+
+#[instrument]
+pub fn merge_db(path: String, strategy: MergeStrategy, dry_run: bool) -> Result<()> {
+    if !Utf8Path::new(&path).exists() {
+        return Err(anyhow!("Database does not exist at {:?}", path));
+    }
+    let others = merge::read_database(&path)?;
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let actions = merge::plan(&dal.get_bookmarks("")?, &others, strategy, |local, other| {
+        eprintln!("{}: {}", local.id, local.URL);
+        eprintln!("  local: {} | {}", local.metadata, local.desc);
+        eprintln!("  other: {} | {}", other.metadata, other.desc);
+        confirm("Take title and description from other?")
+    });
+    if actions.is_empty() {
+        eprintln!("Nothing to merge from {}.", path);
+        return Ok(());
+    }
+    for action in &actions {
+        eprintln!("{}", action);
+    }
+    if dry_run {
+        println!("{}", Plan::from_merge(&actions).to_json()?);
+        eprintln!("Would apply {} changes.", actions.len());
+        return Ok(());
+    }
+
+    let n = actions.len();
+    merge::apply(&mut dal, actions).context("Failed to merge bookmarks")?;
+    eprintln!("Merged {} changes from {}.", n, path);
+    Ok(())
+}
+
+#[instrument]
+pub fn capture_urls(
+    path: Option<String>,
+    tags: Option<String>,
+    all: bool,
+    no_web: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let text = match &path {
+        Some(path) => {
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?
+        }
+        None => io::read_to_string(io::stdin()).context("Failed to read stdin")?,
+    };
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let mut urls = Vec::new();
+    for url in extract_urls(&text) {
+        if !dal.bm_exists(&url)? {
+            urls.push(url);
+        }
+    }
+    if urls.is_empty() {
+        eprintln!("No new URLs found.");
+        return Ok(());
+    }
+
+    let candidates = if no_web {
+        urls.into_iter().map(|url| (url, String::new())).collect()
+    } else {
+        fetch_titles(&urls)
+    };
+    for (i, (url, title)) in candidates.iter().enumerate() {
+        eprintln!("{:>3}. {} {}", i + 1, url, title.as_str().dim());
+    }
+    if dry_run {
+        let tags = Tags::normalize_tag_string(tags);
+        let plan = Plan {
+            create: candidates
+                .iter()
+                .map(|(url, title)| BulkBookmark {
+                    url: url.clone(),
+                    title: title.clone(),
+                    tags: tags.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        println!("{}", plan.to_json()?);
+        eprintln!("Would offer {} new URLs for adding.", candidates.len());
+        return Ok(());
+    }
+
+    let selected = if all {
+        (0..candidates.len()).collect()
+    } else {
+        parse_selection(
+            &prompt_tty("Select URLs to add (e.g. 1,3-5, all, empty for none): ")?,
+            candidates.len(),
+        )?
+    };
+    let tags = Tags::normalize_tag_string(tags);
+    let unknown_tags = Bookmarks::new(String::new())
+        .check_tags(tags.clone())
+        .context("Failed to check tags")?;
+    if !selected.is_empty()
+        && !unknown_tags.is_empty()
+        && !confirm(&format!("Unknown tags: {:?}, create?", unknown_tags))
+    {
+        return Err(anyhow!("Operation aborted by user"));
+    }
+
+    for i in &selected {
+        let (url, title) = &candidates[*i];
+        let mut bm = BookmarkBuilder::new()
+            .id(1)
+            .URL(url.clone())
+            .metadata(title.clone())
+            .flags(0)
+            .build();
+        bm.set_tags(tags.clone());
+        insert_new_bookmark(&mut dal, bm, false, OnExists::Error)?;
+    }
+    eprintln!("Captured {} of {} new URLs.", selected.len(), candidates.len());
+    Ok(())
+}
+
+#[instrument]
+pub fn import_history(
+    shell: Option<Shell>,
+    min_count: usize,
+    file: Option<String>,
+    all: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let shell = shell.unwrap_or_else(history::detect_shell);
+    let path = match file {
+        Some(file) => file.into(),
+        None => history::history_path(shell)?,
+    };
+    // zsh stores non-ASCII characters metafied
+    let content = String::from_utf8_lossy(
+        &fs::read(&path).with_context(|| format!("Failed to read {:?}", path))?,
+    )
+    .into_owned();
+    let commands = history::parse_history(shell, &content);
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let mut candidates = Vec::new();
+    let mut with_secrets = 0;
+    for (command, count) in history::frequent_commands(&commands, min_count) {
+        if dal.bm_exists(&command)? {
+            continue;
+        }
+        if !secrets::SCANNER.scan(&command).is_empty() {
+            with_secrets += 1;
+            continue;
+        }
+        let mut tags = history::suggest_tags(&command);
+        tags.push("_shell_".to_string());
+        candidates.push((command, count, tags));
+    }
+    if with_secrets > 0 {
+        eprintln!("Skipped {} commands containing secrets.", with_secrets);
+    }
+    if candidates.is_empty() {
+        eprintln!("No new commands used at least {} times in {:?}.", min_count, path);
+        return Ok(());
+    }
+
+    for (i, (command, count, tags)) in candidates.iter().enumerate() {
+        eprintln!(
+            "{:>3}. {} {}",
+            i + 1,
+            command,
+            format!("({}x) [{}]", count, tags.join(",")).dim()
+        );
+    }
+    if dry_run {
+        let plan = Plan {
+            create: candidates
+                .iter()
+                .map(|(command, _, tags)| BulkBookmark {
+                    url: command.clone(),
+                    title: command.clone(),
+                    tags: tags.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        println!("{}", plan.to_json()?);
+        eprintln!("Would offer {} commands for adding.", candidates.len());
+        return Ok(());
+    }
+
+    let selected = if all {
+        (0..candidates.len()).collect()
+    } else {
+        parse_selection(
+            &prompt_tty("Select commands to add (e.g. 1,3-5, all, empty for none): ")?,
+            candidates.len(),
+        )?
+    };
+    for i in &selected {
+        let (command, _, tags) = &candidates[*i];
+        let mut bm = BookmarkBuilder::new()
+            .id(1)
+            .URL(command.clone())
+            .metadata(command.clone())
+            .flags(0)
+            .build();
+        bm.set_tags(tags.clone());
+        insert_new_bookmark(&mut dal, bm, false, OnExists::Error)?;
+    }
+    eprintln!("Added {} of {} commands.", selected.len(), candidates.len());
+    Ok(())
+}
+
+#[instrument(skip(token))]
+pub fn import_github_stars(user: Option<String>, token: Option<String>, dry_run: bool) -> Result<()> {
+    let token = token.or_else(|| std::env::var("GITHUB_TOKEN").ok());
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let stars = github::new_stars(
+        github::GITHUB_API_URL,
+        user.as_deref(),
+        token.as_deref(),
+        |url| dal.bm_exists(url),
+    )?;
+    if stars.is_empty() {
+        eprintln!("No new stars.");
+        return Ok(());
+    }
+    for star in &stars {
+        eprintln!("{} {}", star.html_url, format!("[{}]", star.tags().join(",")).dim());
+    }
+    if dry_run {
+        let plan = Plan {
+            create: stars
+                .iter()
+                .map(|star| BulkBookmark::from(&star.to_bookmark()))
+                .map(|bm| BulkBookmark { id: None, uuid: None, ..bm })
+                .collect(),
+            ..Default::default()
+        };
+        println!("{}", plan.to_json()?);
+        eprintln!("Would import {} new stars.", stars.len());
+        return Ok(());
+    }
+
+    // oldest first, so that the ids follow the starring order
+    for star in stars.iter().rev() {
+        insert_new_bookmark(&mut dal, star.to_bookmark(), false, OnExists::Error)?;
+    }
+    eprintln!("Imported {} new stars.", stars.len());
+    Ok(())
+}
+
+#[instrument(skip(token))]
+pub fn import_saved(source: SavedSource, user: String, token: Option<String>, dry_run: bool) -> Result<()> {
+    let (base_url, token) = match source {
+        SavedSource::Hn => (saved::HN_URL, None),
+        SavedSource::Reddit => (
+            saved::REDDIT_API_URL,
+            token.or_else(|| std::env::var("REDDIT_TOKEN").ok()),
+        ),
+    };
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let items = saved::new_items(source, base_url, &user, token.as_deref(), |url| dal.bm_exists(url))?;
+    if items.is_empty() {
+        eprintln!("No new saved items.");
+        return Ok(());
+    }
+    for item in &items {
+        eprintln!("{} {}", item.url, format!("[{}]", item.tags.join(",")).dim());
+    }
+    if dry_run {
+        let plan = Plan {
+            create: items
+                .iter()
+                .map(|item| BulkBookmark::from(&item.to_bookmark()))
+                .map(|bm| BulkBookmark { id: None, uuid: None, ..bm })
+                .collect(),
+            ..Default::default()
+        };
+        println!("{}", plan.to_json()?);
+        eprintln!("Would import {} new saved items.", items.len());
+        return Ok(());
+    }
+
+    // oldest first, so that the ids follow the saving order
+    for item in items.iter().rev() {
+        insert_new_bookmark(&mut dal, item.to_bookmark(), false, OnExists::Error)?;
+    }
+    eprintln!("Imported {} new saved items.", items.len());
+    Ok(())
+}
+
+#[instrument]
+pub fn import_cheats(source: CheatSource, command: Vec<String>, platform: Option<String>) -> Result<()> {
+    let CheatSource::Tldr = source;
+    let name = cheats::page_name(&command.join(" "));
+    let platforms = match &platform {
+        Some(platform) => vec![platform.as_str(), "common"],
+        None => cheats::default_platforms(),
+    };
+    let page = cheats::fetch_tldr(cheats::TLDR_BASE_URL, &name, &platforms)?;
+    let bm = cheats::tldr_bookmark(&name, &page);
+
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let Some(old) = cheats::find_imported(&dal.get_bookmarks("")?, &name).cloned() else {
+        insert_new_bookmark(&mut dal, bm, false, OnExists::Error)?;
+        return Ok(());
+    };
+    if old.URL == bm.URL {
+        eprintln!("tldr page of {} is up to date: {}", name, old.id);
+        return Ok(());
+    }
+    let mut updated = Bookmark {
+        URL: bm.URL,
+        desc: bm.desc,
+        ..old
+    };
+    updated.update();
+    for bm in dal.update_bookmark(updated)? {
+        eprintln!("Updated tldr page of {}: {}", name, bm.id);
+        bus::publish(BookmarkEvent::Updated(bm));
+    }
+    Ok(())
+}
+
+#[instrument]
+pub fn list_types() -> Result<()> {
+    let system_tags = SystemTags::new(&CONFIG.types)?;
+    for system_tag in system_tags.tags.values() {
+        println!("{}", system_tag);
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -564,7 +2827,8 @@ mod tests {
     fn test_find_similar_when_embed_null(temp_dir: Utf8PathBuf) -> Result<()> {
         // Given: v2 database with embeddings and OpenAI context
         fs::rename("../db/bkmr.v2.noembed.db", "../db/bkmr.db").expect("Failed to rename database");
-        let bms = Bookmarks::new("".to_string());
+        let mut bms = Bookmarks::new("".to_string());
+        bms.load_embeddings()?;
         Context::update_global(Context::new(Box::new(OpenAiEmbedding::default())))?;
 
         // When: find similar for "blub"
@@ -580,7 +2844,8 @@ mod tests {
     fn test_find_similar(temp_dir: Utf8PathBuf) -> Result<()> {
         // Given: Set up test environment
         fs::rename("../db/bkmr.v2.db", "../db/bkmr.db")?;
-        let bms = Bookmarks::new("".to_string());
+        let mut bms = Bookmarks::new("".to_string());
+        bms.load_embeddings()?;
 
         // Initialize CTX with proper error handling and verification
         Context::update_global(Context::new(Box::new(OpenAiEmbedding::default())))?;
@@ -621,7 +2886,7 @@ mod tests {
         Context::update_global(Context::new(Box::new(OpenAiEmbedding::default())))?;
         // Given: v2 database with embeddings
         // When:
-        sem_search("blub".to_string(), None, false, stderr)?;
+        sem_search("blub".to_string(), None, None, None, None, false, false, stderr)?;
         // Then: Expect the first three entries to be: blub, blub3, blub2
         Ok(())
     }
@@ -629,7 +2894,7 @@ mod tests {
     #[ignore = "interactive: opens browser link"]
     #[test]
     fn test_randomized() -> Result<()> {
-        randomized(1)?;
+        randomized(1, None, false, None, false)?;
         Ok(())
     }
 }
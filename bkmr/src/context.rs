@@ -1,6 +1,8 @@
 // bkmr/src/context.rs
 use crate::adapter::embeddings::{serialize_embedding, DummyEmbedding, Embedding};
+use crate::util::clock::{Clock, SystemClock};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use once_cell::sync::OnceCell;
 use std::fmt;
 use std::sync::RwLock;
@@ -9,25 +11,50 @@ pub static CTX: OnceCell<RwLock<Context>> = OnceCell::new();
 
 pub struct Context {
     embedder: Box<dyn Embedding>,
+    clock: Box<dyn Clock>,
 }
 
 impl fmt::Debug for Context {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Context")
             .field("embedder", &"Box<dyn Embedding>")
+            .field("clock", &"Box<dyn Clock>")
             .finish()
     }
 }
 
 impl Context {
     pub fn new(embedder: Box<dyn Embedding>) -> Self {
-        Self { embedder }
+        Self {
+            embedder,
+            clock: Box::new(SystemClock),
+        }
+    }
+
+    /// Replaces the system clock, e.g. with a `FixedClock` for deterministic tests
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Model of the embedder, None if it produces no embeddings
+    pub fn model(&self) -> Option<&str> {
+        self.embedder.model()
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
     }
 
     pub fn execute(&self, text: &str) -> Result<Option<Vec<f32>>> {
         self.embedder.embed(text)
     }
 
+    /// One-line summary by the embedder, None if it cannot generate text
+    pub fn summarize(&self, text: &str) -> Result<Option<String>> {
+        self.embedder.summarize(text)
+    }
+
     /// Gets embedding for text and serializes it to bytes
     pub fn get_embedding(&self, content: &str) -> Option<Vec<u8>> {
         match self.execute(content) {
@@ -1,12 +1,20 @@
 use clap::Parser;
 use once_cell::sync::{Lazy, OnceCell};
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::sync::RwLock;
-use std::{env, process};
+use std::{env, fs, process};
 use tracing::debug;
 
 // Default height for FZF window
 const DEFAULT_HEIGHT: &str = "50%";
+// Default location of the optional config file, override with BKMR_CONFIG
+const DEFAULT_CONFIG_PATH: &str = "~/.config/bkmr/config.toml";
+
+/// Path of the config file, which need not exist
+pub fn config_path() -> String {
+    env::var("BKMR_CONFIG").unwrap_or_else(|_| shellexpand::tilde(DEFAULT_CONFIG_PATH).to_string())
+}
 
 static SETTINGS: OnceCell<RwLock<Settings>> = OnceCell::new();
 
@@ -28,10 +36,361 @@ pub struct FzfEnvOpts {
     pub show_tags: bool,
 }
 
+/// Sections of the optional TOML config file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ConfigFile {
+    /// tag rules: pattern -> tags, e.g. `"^https://github.com" = ["dev", "github"]`
+    pub rules: BTreeMap<String, Vec<String>>,
+    pub hooks: Hooks,
+    /// custom system tags: `_name_` -> type definition
+    pub types: BTreeMap<String, TypeConfig>,
+    /// connection profiles of `_sql_` bookmarks
+    pub sql_profiles: BTreeMap<String, SqlProfile>,
+    /// editor command with `{file}` and `{line}` placeholders, default: `$EDITOR +<line> <file>`
+    pub editor: Option<String>,
+    /// custom open commands, first matching pattern wins
+    pub openers: Vec<OpenerConfig>,
+    /// weights of the search ranking
+    pub ranking: RankingConfig,
+    /// OpenAI embedding model, default: `text-embedding-ada-002`
+    pub embedding_model: Option<String>,
+    pub openai: OpenAiConfig,
+    pub logging: LoggingConfig,
+    pub signing: SigningConfig,
+    pub secrets: SecretsConfig,
+    pub fetch_policies: Vec<FetchPolicyConfig>,
+    pub titles: TitlesConfig,
+    pub tags: TagsConfig,
+    pub database: DatabaseConfig,
+    pub status: StatusConfig,
+}
+
+/// Cleanup of fetched titles, also applied by `refresh-metadata --normalize-titles`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TitlesConfig {
+    /// e.g. `&amp;` to `&`
+    pub decode_entities: bool,
+    pub collapse_whitespace: bool,
+    /// removes a trailing site name naming the host, e.g. ` | GitHub`
+    pub strip_site_name: bool,
+    /// regexes removed from titles, e.g. `^\(\d+\) ` for notification counts
+    pub strip_patterns: Vec<String>,
+}
+
+impl Default for TitlesConfig {
+    fn default() -> Self {
+        Self {
+            decode_entities: true,
+            collapse_whitespace: true,
+            strip_site_name: true,
+            strip_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Normalization of entered, imported and stored tags, also applied by `tags normalize`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TagsConfig {
+    pub lowercase: bool,
+    /// replaces blanks within a tag, e.g. `-` for `my-tag`, removed by default
+    pub space_replacement: String,
+    /// unicode canonical composition, e.g. `e` + combining acute to `é`
+    pub nfc: bool,
+}
+
+impl Default for TagsConfig {
+    fn default() -> Self {
+        Self {
+            lowercase: true,
+            space_replacement: String::new(),
+            nfc: true,
+        }
+    }
+}
+
+/// Settings of the sqlite connection, every command opens a single connection
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// how long to wait for concurrent writers before failing with "database is locked"
+    pub busy_timeout_ms: u64,
+    /// e.g. `wal` to keep reading while another process writes, unchanged by default
+    pub journal_mode: Option<String>,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            busy_timeout_ms: 5000,
+            journal_mode: None,
+        }
+    }
+}
+
+/// Output of `bkmr status`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StatusConfig {
+    /// segment of `--starship`, placeholders: `{inbox}`
+    pub starship: String,
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            starship: "🔖 {inbox}".to_string(),
+        }
+    }
+}
+
+/// Fetching of the URLs of a domain and its subdomains by metadata fetching, link checks and
+/// readable text: disabled for privacy, with headers (values interpolate `${VAR}`) or via a proxy
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FetchPolicyConfig {
+    pub domain: String,
+    pub fetch: bool,
+    pub headers: BTreeMap<String, String>,
+    pub proxy: Option<String>,
+}
+
+impl Default for FetchPolicyConfig {
+    fn default() -> Self {
+        Self {
+            domain: String::new(),
+            fetch: true,
+            headers: BTreeMap::new(),
+            proxy: None,
+        }
+    }
+}
+
+/// Secret patterns: warnings on add, redaction of `_secret_` bookmarks in output and exports
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// AWS keys, GitHub/OpenAI/Slack tokens, private keys and `password=...`
+    pub builtin: bool,
+    /// additional regexes
+    pub patterns: Vec<String>,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// Ed25519 keys of signed export bundles
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SigningConfig {
+    /// private key of `export --sign`, default: `<config dir>/bkmr/signing.key`, created on first use
+    pub key_file: Option<String>,
+    /// base64 public keys accepted by `import --verify`
+    pub trusted_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Log file in addition to stderr, rotated by size
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub file: bool,
+    /// default: `<state dir>/bkmr/bkmr.log`
+    pub path: Option<String>,
+    /// file log level: error, warn, info, debug or trace, raised by `-d`
+    pub level: String,
+    pub format: LogFormat,
+    pub max_size_kb: u64,
+    /// number of rotated files kept
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: false,
+            path: None,
+            level: "info".to_string(),
+            format: LogFormat::Text,
+            max_size_kb: 1024,
+            max_files: 3,
+        }
+    }
+}
+
+/// Retry policy and request budget of the OpenAI API
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OpenAiConfig {
+    /// retries on 429, 5xx and connection errors
+    pub max_retries: u32,
+    /// first retry delay, doubled per retry, a Retry-After header takes precedence
+    pub backoff_ms: u64,
+    pub requests_per_minute: Option<u32>,
+    /// estimated as 4 characters per token
+    pub tokens_per_minute: Option<u64>,
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            backoff_ms: 500,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+        }
+    }
+}
+
+/// Weights of the score components, results tagged `pinned` get `pin_boost` on top
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    pub fts: f64,
+    pub similarity: f64,
+    pub recency: f64,
+    pub hits: f64,
+    pub tags: f64,
+    pub pin_boost: f64,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            fts: 1.0,
+            similarity: 1.0,
+            recency: 0.3,
+            hits: 0.2,
+            tags: 1.0,
+            pin_boost: 5.0,
+        }
+    }
+}
+
+/// Open command for URIs matching a glob against host, file name or URI,
+/// and/or bookmarks with a tag, e.g. a browser profile for `work` bookmarks.
+/// `{url}` is replaced by the URI, appended if missing
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct OpenerConfig {
+    pub pattern: Option<String>,
+    pub tag: Option<String>,
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SqlDriver {
+    #[default]
+    Sqlite,
+    Postgres,
+    Mysql,
+}
+
+/// Database connection: sqlite file path or postgres/mysql URL
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SqlProfile {
+    pub driver: SqlDriver,
+    pub url: String,
+}
+
+/// Custom bookmark type, selected by its system tag
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TypeConfig {
+    pub description: Option<String>,
+    /// shell command run on open, placeholders (replaced shell quoted): {url}, {title}, {id}
+    pub action: Option<String>,
+    /// language of the bookmark content, e.g. `sql`
+    pub language: Option<String>,
+}
+
+/// Lifecycle hooks: shell command (JSON payload on stdin) or http(s) URL (JSON POST)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    pub on_add: Option<String>,
+    pub on_delete: Option<String>,
+    pub on_open: Option<String>,
+    /// hooks still running after this are killed (commands) or aborted (http)
+    pub timeout_secs: u64,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Self {
+            on_add: None,
+            on_delete: None,
+            on_open: None,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Reads the config file from BKMR_CONFIG or the default location.
+    /// A missing file results in an empty configuration.
+    pub fn load() -> Self {
+        let path = config_path();
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => {
+                debug!("No config file found at {:?}", path);
+                return Self::default();
+            }
+        };
+
+        match toml::from_str(&content) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!(
+                    "Error: Failed to parse config file: {:?}\nError: {}\nPlease check bkmr documentation.",
+                    path, e
+                );
+                process::exit(1);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Settings {
     pub db_url: String,
     pub fzf_opts: FzfEnvOpts,
+    pub rules: BTreeMap<String, Vec<String>>,
+    pub hooks: Hooks,
+    pub types: BTreeMap<String, TypeConfig>,
+    pub sql_profiles: BTreeMap<String, SqlProfile>,
+    pub editor: Option<String>,
+    pub openers: Vec<OpenerConfig>,
+    pub ranking: RankingConfig,
+    pub embedding_model: Option<String>,
+    pub openai: OpenAiConfig,
+    pub logging: LoggingConfig,
+    pub signing: SigningConfig,
+    pub secrets: SecretsConfig,
+    pub fetch_policies: Vec<FetchPolicyConfig>,
+    pub titles: TitlesConfig,
+    pub tags: TagsConfig,
+    pub database: DatabaseConfig,
+    pub status: StatusConfig,
 }
 
 impl Default for Settings {
@@ -76,7 +435,47 @@ impl Settings {
             })
             .unwrap_or_default();
 
-        let settings = Settings { db_url, fzf_opts };
+        let ConfigFile {
+            rules,
+            hooks,
+            types,
+            sql_profiles,
+            editor,
+            openers,
+            ranking,
+            embedding_model,
+            openai,
+            logging,
+            signing,
+            secrets,
+            fetch_policies,
+            titles,
+            tags,
+            database,
+            status,
+        } = ConfigFile::load();
+
+        let settings = Settings {
+            db_url,
+            fzf_opts,
+            rules,
+            hooks,
+            types,
+            sql_profiles,
+            editor,
+            openers,
+            ranking,
+            embedding_model,
+            openai,
+            logging,
+            signing,
+            secrets,
+            fetch_policies,
+            titles,
+            tags,
+            database,
+            status,
+        };
         debug!("Settings initialized: {:?}", settings);
         settings
     }
@@ -122,6 +521,7 @@ impl Default for FzfEnvOpts {
 mod test {
     use super::*;
     use rstest::*;
+    use serial_test::serial;
 
     #[rstest]
     fn test_config() {
@@ -219,4 +619,142 @@ mod test {
         env::remove_var("BKMR_DB_URL");
         env::remove_var("BKMR_FZF_OPTS");
     }
+
+    #[rstest]
+    #[serial]
+    fn test_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            r#"
+            editor = "code --goto {file}:{line}"
+            embedding_model = "text-embedding-3-small"
+
+            [rules]
+            "^https://github.com" = ["dev", "github"]
+            "domain:*.rust-lang.org" = ["rust"]
+
+            [hooks]
+            on_add = "cat >> /tmp/bkmr_hooks.log"
+            timeout_secs = 3
+
+            [types._sql_]
+            action = "sqlite3 {url}"
+            language = "sql"
+
+            [sql_profiles.prod]
+            driver = "postgres"
+            url = "postgresql://localhost/app"
+
+            [[openers]]
+            tag = "work"
+            command = "firefox -P work {url}"
+
+            [[openers]]
+            pattern = "*youtube.com"
+            command = "mpv {url}"
+
+            [[openers]]
+            pattern = "*.pdf"
+            command = "zathura"
+
+            [ranking]
+            recency = 0.5
+            pin_boost = 10
+
+            [openai]
+            max_retries = 3
+            requests_per_minute = 500
+
+            [logging]
+            file = true
+            format = "json"
+
+            [signing]
+            trusted_keys = ["aGVsbG8="]
+
+            [secrets]
+            patterns = ["acme-[0-9]{4}"]
+
+            [[fetch_policies]]
+            domain = "intranet.example.com"
+            fetch = false
+
+            [[fetch_policies]]
+            domain = "github.com"
+            headers = { Authorization = "Bearer ${GITHUB_TOKEN}" }
+            proxy = "http://proxy.example.com:3128"
+
+            [titles]
+            strip_site_name = false
+            strip_patterns = ['^\(\d+\) ']
+
+            [tags]
+            space_replacement = "-"
+
+            [database]
+            journal_mode = "wal"
+
+            [status]
+            starship = "{inbox} inbox"
+            "#,
+        )
+        .unwrap();
+
+        env::set_var("BKMR_CONFIG", path.to_str().unwrap());
+        let config = ConfigFile::load();
+        env::remove_var("BKMR_CONFIG");
+
+        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.rules["^https://github.com"], vec!["dev", "github"]);
+        assert_eq!(config.rules["domain:*.rust-lang.org"], vec!["rust"]);
+        assert_eq!(config.hooks.on_add.as_deref(), Some("cat >> /tmp/bkmr_hooks.log"));
+        assert!(config.hooks.on_delete.is_none());
+        assert_eq!(config.hooks.timeout_secs, 3);
+        assert_eq!(config.types["_sql_"].action.as_deref(), Some("sqlite3 {url}"));
+        assert_eq!(config.types["_sql_"].language.as_deref(), Some("sql"));
+        assert_eq!(config.sql_profiles["prod"].driver, SqlDriver::Postgres);
+        assert_eq!(config.editor.as_deref(), Some("code --goto {file}:{line}"));
+        assert_eq!(config.embedding_model.as_deref(), Some("text-embedding-3-small"));
+        assert_eq!(config.openers.len(), 3);
+        assert_eq!(config.openers[0].tag.as_deref(), Some("work"));
+        assert!(config.openers[0].pattern.is_none());
+        assert_eq!(config.openers[2].pattern.as_deref(), Some("*.pdf"));
+        assert_eq!(config.ranking.recency, 0.5);
+        assert_eq!(config.ranking.pin_boost, 10.0);
+        assert_eq!(config.ranking.fts, 1.0);
+        assert_eq!(config.openai.max_retries, 3);
+        assert_eq!(config.openai.requests_per_minute, Some(500));
+        assert_eq!(config.openai.backoff_ms, 500);
+        assert!(config.logging.file);
+        assert_eq!(config.logging.format, LogFormat::Json);
+        assert_eq!(config.logging.max_files, 3);
+        assert!(config.signing.key_file.is_none());
+        assert_eq!(config.signing.trusted_keys, vec!["aGVsbG8="]);
+        assert!(config.secrets.builtin);
+        assert_eq!(config.secrets.patterns, vec!["acme-[0-9]{4}"]);
+        assert!(!config.fetch_policies[0].fetch);
+        assert!(config.fetch_policies[1].fetch);
+        assert_eq!(config.fetch_policies[1].headers["Authorization"], "Bearer ${GITHUB_TOKEN}");
+        assert_eq!(config.fetch_policies[1].proxy.as_deref(), Some("http://proxy.example.com:3128"));
+        assert!(config.titles.decode_entities);
+        assert!(!config.titles.strip_site_name);
+        assert_eq!(config.titles.strip_patterns, vec![r"^\(\d+\) "]);
+        assert!(config.tags.lowercase);
+        assert_eq!(config.tags.space_replacement, "-");
+        assert_eq!(config.database.busy_timeout_ms, 5000);
+        assert_eq!(config.database.journal_mode.as_deref(), Some("wal"));
+        assert_eq!(config.status.starship, "{inbox} inbox");
+    }
+
+    #[rstest]
+    #[serial]
+    fn test_config_file_missing() {
+        env::set_var("BKMR_CONFIG", "/non/existent/config.toml");
+        let config = ConfigFile::load();
+        env::remove_var("BKMR_CONFIG");
+
+        assert!(config.rules.is_empty());
+    }
 }
@@ -14,8 +14,10 @@ use crate::environment::CONFIG;
 use crate::model::bookmark::Bookmark;
 use crate::model::bookmark::BookmarkUpdater;
 use crate::model::tag::Tags;
+use crate::service::fetch;
+use crate::service::titles::TitleRules;
 use itertools::Itertools;
-use reqwest::blocking::Client;
+use reqwest::Method;
 use select::document::Document;
 use select::predicate::{Attr, Name};
 use tracing::{debug, error};
@@ -29,13 +31,70 @@ pub mod adapter {
 pub mod model {
     pub mod bms;
     pub mod bookmark;
+    pub mod readable;
+    pub mod registry;
+    pub mod system_tag;
     pub mod tag;
 }
 
 pub mod service {
+    pub mod actions;
+    pub mod batch;
+    pub mod bulk;
+    pub mod bus;
+    pub mod canonical;
+    pub mod capture;
+    pub mod cheats;
+    pub mod clipboard;
+    pub mod cluster;
+    pub mod digest;
+    pub mod dir;
+    pub mod doctor;
+    pub mod editor;
     pub mod embeddings;
+    pub mod event;
+    pub mod facets;
+    pub mod fetch;
     pub mod fzf;
+    pub mod history;
+    pub mod github;
+    pub mod hooks;
+    pub mod http;
+    pub mod https;
+    pub mod interop;
+    pub mod journal;
+    pub mod language;
+    pub mod lint;
+    pub mod lookup;
+    pub mod mail;
+    pub mod maintenance;
+    pub mod merge;
+    pub mod migrate;
+    pub mod opener;
+    pub mod org;
+    pub mod palette;
+    pub mod plan;
     pub mod process;
+    pub mod ranking;
+    pub mod readability;
+    pub mod registry;
+    pub mod review;
+    pub mod rules;
+    pub mod rpc;
+    pub mod saved;
+    pub mod secrets;
+    pub mod signing;
+    pub mod snippet;
+    pub mod sql;
+    pub mod ssh;
+    pub mod status;
+    pub mod surprise;
+    pub mod tags;
+    pub mod template;
+    pub mod timeline;
+    pub mod titles;
+    pub mod tmux;
+    pub mod triage;
 }
 
 pub mod cli;
@@ -46,10 +105,17 @@ pub mod util;
 /// creates list of normalized tags from "tag1,t2,t3" string
 /// be aware of shell parsing rules, so no blanks or quotes
 pub fn load_url_details(url: &str) -> Result<(String, String, String)> {
-    let client = Client::new();
-    let body = client.get(url).send()?.text()?;
+    parse_url_details(url, &fetch_page(url)?)
+}
+
+/// Body of the page, fetched by the fetch policy of its domain
+pub fn fetch_page(url: &str) -> Result<String> {
+    Ok(fetch::request(Method::GET, url, None)?.send()?.text()?)
+}
 
-    let document = Document::from(body.as_str());
+/// Title, description and keywords of a fetched page, the title cleaned up by the title rules
+pub fn parse_url_details(url: &str, body: &str) -> Result<(String, String, String)> {
+    let document = Document::from(body);
     // let document = Document::from(body.to_string());
 
     let title = document
@@ -57,6 +123,7 @@ pub fn load_url_details(url: &str) -> Result<(String, String, String)> {
         .next()
         .map(|n| n.text().trim().to_owned())
         .unwrap_or_default();
+    let title = TitleRules::new(&CONFIG.titles)?.normalize(&title, url);
 
     let description = document
         .find(Attr("name", "description"))
@@ -80,26 +147,45 @@ pub fn update_bookmarks(
     tags: Vec<String>,
     tags_not: Vec<String>,
     force: bool,
-) -> Result<()> {
+) -> Result<Vec<Bookmark>> {
     // let mut bms = Bookmarks::new("".to_string());
     let mut dal = Dal::new(CONFIG.db_url.clone());
-    for id in ids {
-        update_bm(id, &tags, &tags_not, &mut dal, force).map_err(|e| {
-            // Adjust the error handling here as needed
-            // If 'e' needs to be used or logged, do it here. If necessary, clone 'e'.
-            // Example: log::error!("Error updating bookmark: {}", e);
-            // Assuming 'e' implements the 'Error' trait and can be converted/cloned
-            error!("Error updating bookmark {}: {}", id, e);
-            e
-        })?;
-    }
-    Ok(())
+    // all or nothing
+    dal.transaction(|dal| {
+        let mut updated = Vec::new();
+        for id in ids {
+            updated.extend(update_bm(id, &tags, &tags_not, dal, force).map_err(|e| {
+                error!("Error updating bookmark {}: {}", id, e);
+                e
+            })?);
+        }
+        Ok(updated)
+    })
+}
+
+/// Normalized tag string of bm after adding tags and removing tags_not, force replaces all tags
+pub fn retag(
+    bm: &Bookmark,
+    tags: &HashSet<String>,
+    tags_not: &HashSet<String>,
+    force: bool,
+) -> String {
+    let new_tags = if force {
+        tags.clone()
+    } else {
+        let mut new_tags = Tags::normalize_tag_string(Some(bm.tags.clone()))
+            .into_iter()
+            .collect::<HashSet<String>>();
+        new_tags.extend(tags.iter().cloned());
+        new_tags.difference(tags_not).cloned().collect()
+    };
+    format!(",{},", new_tags.iter().sorted().join(","))
 }
 
 pub fn update_bm(
     id: i32,
-    tags: &Vec<String>,
-    tags_not: &Vec<String>,
+    tags: &[String],
+    tags_not: &[String],
     dal: &mut Dal,
     force: bool,
 ) -> Result<Vec<Bookmark>> {
@@ -108,25 +194,11 @@ pub fn update_bm(
     debug!("id {}, tags {:?}, tags_not {:?}", id, tags, tags_not);
 
     let bm = dal.get_bookmark_by_id(id)?;
-
-    let new_tags = if force {
-        tags
-    } else {
-        let mut new_tags = Tags::normalize_tag_string(Some(bm.tags.clone()))
-            .into_iter()
-            .collect::<HashSet<String>>();
-        new_tags.extend(tags);
-        new_tags
-            .difference(&tags_not)
-            .map(|s| s.to_string())
-            .collect()
-    };
-
-    let bm_tags: Vec<String> = new_tags.iter().sorted().cloned().collect();
+    let bm_tags = retag(&bm, &tags, &tags_not, force);
     debug!("bm_tags {:?}", bm_tags);
 
     let mut bm_updated = Bookmark {
-        tags: format!(",{},", bm_tags.join(",")),
+        tags: bm_tags,
         flags: bm.flags + 1,
         ..bm
     };
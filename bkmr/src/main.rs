@@ -1,12 +1,15 @@
 // bkmr/src/main.rs
 
-use std::sync::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
 use bkmr::{
     cli::{args::Cli, commands},
     adapter::embeddings::{DummyEmbedding, OpenAiEmbedding},
 };
 use bkmr::context::{Context, CTX};
-use bkmr::environment::CONFIG;
+use bkmr::environment::{LogFormat, LoggingConfig, CONFIG};
+use bkmr::util::logging::{default_log_path, JsonFormat, RotatingFile};
+use bkmr::util::helper::{is_plain, set_no_input, set_plain};
 use clap::Parser;
 use crossterm::style::Stylize;
 use termcolor::{ColorChoice, StandardStream};
@@ -15,6 +18,7 @@ use tracing_subscriber::{
     filter::{filter_fn, LevelFilter},
     fmt::{self, format::FmtSpan},
     prelude::*,
+    Layer, Registry,
 };
 use bkmr::cli::args::Commands;
 
@@ -22,21 +26,34 @@ use bkmr::cli::args::Commands;
 fn main() {
     // let stdout = StandardStream::stdout(ColorChoice::Always);
     // use stderr as human output in order to make stdout output passable to downstream processes
-    let stderr = StandardStream::stderr(ColorChoice::Always);
-
     let cli = Cli::parse();
+    let stderr = StandardStream::stderr(if cli.plain { ColorChoice::Never } else { ColorChoice::Always });
+
+    // must happen before CONFIG is accessed for the first time
+    if let Some(config) = &cli.config {
+        std::env::set_var("BKMR_CONFIG", config);
+    }
 
-    setup_logging(cli.debug);
+    set_plain(cli.plain);
+    setup_logging(cli.debug, &CONFIG.logging, cli.log_file.as_deref());
+    set_no_input(cli.no_input);
 
-    if let Some(Commands::CreateDb { .. }) = &cli.command {
-        // Skip the path.exists check and create database with correct schema
+    if let Some(Commands::CreateDb { .. } | Commands::ShellInit { .. }) = &cli.command {
+        // Skip the path.exists check: create-db creates the database, shell-init needs none
     } else {
         let path = std::path::Path::new(&CONFIG.db_url);
         if !path.exists() {
             eprintln!("Error: db_url path does not exist: {:?}", CONFIG.db_url);
             std::process::exit(1);
         }
-        commands::enable_embeddings_if_required().expect("Failed to enable embeddings");
+        // migrations are applied explicitly by `bkmr migrate`, info shows the schema in any case
+        if !matches!(&cli.command, Some(Commands::Info { .. } | Commands::Migrate { status: true, .. })) {
+            let allow_pending = matches!(&cli.command, Some(Commands::Migrate { .. }));
+            if let Err(e) = commands::require_current_schema(cli.force, allow_pending) {
+                eprintln!("{}", format!("Error: {}", e).red());
+                std::process::exit(1);
+            }
+        }
     }
 
     let context = if cli.openai {
@@ -57,7 +74,8 @@ fn main() {
     }
 }
 
-fn setup_logging(verbosity: u8) {
+/// stderr at the `-d` level, plus the log file if configured or given by `--log-file`
+fn setup_logging(verbosity: u8, logging: &LoggingConfig, log_file: Option<&Path>) {
     debug!("INIT: Attempting logger init from main.rs");
 
     let filter = match verbosity {
@@ -82,15 +100,21 @@ fn setup_logging(verbosity: u8) {
     // Create a subscriber with formatted output directed to stderr
     let fmt_layer = fmt::layer()
         .with_writer(std::io::stderr) // Set writer first
+        .with_ansi(!is_plain())
         .with_target(true)
         .with_thread_names(false)
         .with_span_events(FmtSpan::ENTER)
         .with_span_events(FmtSpan::CLOSE);
 
     // Apply filters to the layer
-    let filtered_layer = fmt_layer.with_filter(filter).with_filter(module_filter);
+    let filtered_layer = fmt_layer.with_filter(filter).with_filter(module_filter.clone());
 
-    tracing_subscriber::registry().with(filtered_layer).init();
+    let file_layer = log_file_layer(filter, logging, log_file).map(|layer| layer.with_filter(module_filter));
+
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(filtered_layer)
+        .init();
 
     // Log initial debug level
     match filter {
@@ -101,6 +125,39 @@ fn setup_logging(verbosity: u8) {
     }
 }
 
+fn log_file_layer(
+    filter: LevelFilter,
+    logging: &LoggingConfig,
+    log_file: Option<&Path>,
+) -> Option<Box<dyn Layer<Registry> + Send + Sync>> {
+    let path = match log_file {
+        Some(path) => path.to_path_buf(),
+        None if logging.file => match &logging.path {
+            Some(path) => PathBuf::from(shellexpand::tilde(path).to_string()),
+            None => default_log_path()?,
+        },
+        None => return None,
+    };
+    let file = match RotatingFile::open(&path, logging.max_size_kb * 1024, logging.max_files) {
+        Ok(file) => Mutex::new(file),
+        Err(e) => {
+            eprintln!("Warning: Cannot open log file {:?}: {}", path, e);
+            return None;
+        }
+    };
+    let level = logging.level.parse::<LevelFilter>().unwrap_or_else(|_| {
+        eprintln!("Warning: Invalid log level {:?}, using info", logging.level);
+        LevelFilter::INFO
+    });
+
+    let layer = fmt::layer().with_writer(file).with_ansi(false).with_target(true);
+    let layer = match logging.format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.event_format(JsonFormat).boxed(),
+    };
+    Some(layer.with_filter(filter.max(level)).boxed())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,7 +168,7 @@ mod tests {
 
     #[ctor::ctor]
     fn init() {
-        setup_logging(2); // Set maximum debug level for tests
+        setup_logging(2, &LoggingConfig::default(), None); // Set maximum debug level for tests
     }
 
     #[fixture]
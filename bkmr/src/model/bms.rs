@@ -5,6 +5,7 @@ use crate::environment::CONFIG;
 use crate::model::bookmark::Bookmark;
 use crate::model::tag::Tags;
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use tracing::debug;
 
 #[allow(dead_code)]
@@ -28,6 +29,25 @@ impl Bookmarks {
             dal,
         }
     }
+    /// Embeddings of the bookmarks, not loaded by `new`
+    pub fn load_embeddings(&mut self) -> Result<()> {
+        self.dal.load_embeddings(&mut self.bms)
+    }
+    /// Adds the bookmarks whose readable text matches the query
+    pub fn add_readable_matches(&mut self) -> Result<()> {
+        if self.fts_query.is_empty() {
+            return Ok(());
+        }
+        let known: HashSet<i32> = self.bms.iter().map(|bm| bm.id).collect();
+        for id in self.dal.get_readable_fts(&self.fts_query)? {
+            if !known.contains(&id) {
+                let bm = self.dal.get_bookmark_by_id(id)?;
+                self.bms.push(bm);
+            }
+        }
+        Ok(())
+    }
+
     pub fn check_tags(&mut self, tags: Vec<String>) -> Result<Vec<String>> {
         let all_tags: HashSet<String> = HashSet::from_iter(self.dal.get_all_tags_as_vec()?);
         let tags = HashSet::from_iter(tags.into_iter().filter(|s| !s.is_empty()));
@@ -107,5 +127,63 @@ impl Bookmarks {
         }
         debug!("{:?}", self.bms);
     }
+    /// Keeps only bookmarks without any tags
+    pub fn filter_untagged(&mut self) {
+        self.bms.retain(|bm| bm.get_tags().is_empty());
+        debug!("{:?}", self.bms);
+    }
+    pub fn filter_language(&mut self, language: &str) {
+        self.bms
+            .retain(|bm| bm.language.as_deref().is_some_and(|l| l.eq_ignore_ascii_case(language)));
+        debug!("{:?}", self.bms);
+    }
+    /// Keeps bookmarks annotated with all the key-value pairs
+    pub fn filter_meta(&mut self, filters: &[(String, String)]) {
+        self.bms.retain(|bm| {
+            let meta = bm.meta();
+            filters.iter().all(|(key, value)| meta.get(key) == Some(value))
+        });
+        debug!("{:?}", self.bms);
+    }
+    /// Keeps bookmarks opened at least min_hits times and/or not used (opened or modified) since
+    pub fn filter_usage(&mut self, min_hits: Option<i32>, unused_since: Option<NaiveDateTime>) {
+        self.bms.retain(|bm| {
+            min_hits.is_none_or(|n| bm.flags >= n)
+                && unused_since.is_none_or(|ts| bm.last_update_ts < ts)
+        });
+        debug!("{:?}", self.bms);
+    }
 }
 
+
+/// Splits `meta:<key>=<value>` terms off a full text query, they are no FTS syntax
+pub fn split_meta_filters(fts_query: &str) -> (String, Vec<(String, String)>) {
+    let mut filters = Vec::new();
+    let mut terms = Vec::new();
+    for term in fts_query.split_whitespace() {
+        match term.strip_prefix("meta:").and_then(|kv| kv.split_once('=')) {
+            Some((key, value)) if !key.is_empty() => filters.push((key.to_string(), value.to_string())),
+            _ => terms.push(term),
+        }
+    }
+    (terms.join(" "), filters)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("rust meta:project=alpha", "rust", vec![("project", "alpha")])]
+    #[case("meta:a=1 meta:b=", "", vec![("a", "1"), ("b", "")])]
+    #[case("rust AND web", "rust AND web", vec![])]
+    #[case("meta:=x metadata:y", "meta:=x metadata:y", vec![])]
+    fn test_split_meta_filters(#[case] query: &str, #[case] expected_query: &str, #[case] expected: Vec<(&str, &str)>) {
+        let (query, filters) = split_meta_filters(query);
+        assert_eq!(query, expected_query);
+        let expected: Vec<(String, String)> = expected.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(filters, expected);
+    }
+}
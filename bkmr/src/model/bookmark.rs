@@ -1,16 +1,21 @@
 #![allow(non_snake_case)]
 
-use chrono::{NaiveDateTime, Utc};
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
-use diesel::sql_types::Integer;
+use diesel::sql_types::{Double, Integer};
 use diesel::sql_types::Text;
-use serde::Serialize;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::str::FromStr;
 use tracing::debug;
-use crate::util::helper::calc_content_hash;
+use crate::util::clock;
+use crate::util::helper::{calc_content_hash, content_hash_matches, new_uuid};
 use crate::model::tag::Tags;
 
 use crate::adapter::dal::schema::bookmarks;
+use crate::adapter::embeddings::deserialize_embedding;
 use crate::context::Context;
 // ORM mappings
 
@@ -20,7 +25,15 @@ pub struct IdResult {
     pub id: i32,
 }
 
-#[derive(QueryableByName, Debug, PartialOrd, PartialEq)]
+#[derive(QueryableByName)]
+pub struct RankResult {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Double)]
+    pub rank: f64,
+}
+
+#[derive(QueryableByName, Debug, Clone, PartialOrd, PartialEq, Serialize)]
 pub struct TagsFrequency {
     #[diesel(sql_type = Integer)]
     pub n: i32,
@@ -28,6 +41,37 @@ pub struct TagsFrequency {
     pub tag: String,
 }
 
+/// Who may see a bookmark outside of the local database, ordered from private to public
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Private,
+    /// e.g. a team via signed bundles and registries
+    Shared,
+    Public,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Visibility::Private => "private",
+            Visibility::Shared => "shared",
+            Visibility::Public => "public",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        <Self as ValueEnum>::from_str(s.trim(), true)
+            .map_err(|_| anyhow::anyhow!("Invalid visibility {:?}, expected private, shared or public", s))
+    }
+}
+
 pub trait BookmarkUpdater {
     fn update(&mut self);
 }
@@ -46,13 +90,27 @@ pub struct Bookmark {
     // pub last_update_ts: DateTime<Utc>,
     pub embedding: Option<Vec<u8>>,
     pub content_hash: Option<Vec<u8>>,
+    /// stable reference, preserved by export, import and merge
+    pub uuid: String,
+    /// programming or natural language of the content, e.g. `rust`, `en`
+    pub language: Option<String>,
+    /// embedder model and vector dimension of the embedding
+    pub embedding_model: Option<String>,
+    pub embedding_dim: Option<i32>,
+    /// incremented by every update, an update of an outdated version fails
+    pub version: i32,
+    /// private, shared or public, see `Bookmark::visibility`
+    pub visibility: String,
+    pub created_ts: Option<NaiveDateTime>,
+    /// key-value annotations as JSON object, see `Bookmark::meta`
+    pub meta: Option<String>,
 }
 
 impl fmt::Display for Bookmark {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "id: {}, URL: {}, metadata: {}, tags: {}, desc: {}, flags: {}, last_update_ts: {}, embedding: {}, content_hash: {}",
+            "id: {}, URL: {}, metadata: {}, tags: {}, desc: {}, flags: {}, last_update_ts: {}, embedding: {}, content_hash: {}, uuid: {}, language: {}",
             self.id,
             self.URL,
             self.metadata,
@@ -63,7 +121,9 @@ impl fmt::Display for Bookmark {
             self.embedding.as_ref()
                 .map_or(String::from("None"), |v| format!("{:X?}", &v.iter().take(3).collect::<Vec<&u8>>())), // Truncate and hex format
             self.content_hash.as_ref()
-                .map_or(String::from("None"), |v| format!("{:X?}", &v.iter().take(3).collect::<Vec<&u8>>())) // Truncate and hex format
+                .map_or(String::from("None"), |v| format!("{:X?}", &v.iter().take(3).collect::<Vec<&u8>>())), // Truncate and hex format
+            self.uuid,
+            self.language.as_deref().unwrap_or("None")
         )
     }
 }
@@ -72,6 +132,38 @@ impl Bookmark {
     pub fn get_tags(&self) -> Vec<String> {
         Tags::normalize_tag_string(Some(self.tags.clone()))
     }
+    /// The embedding is present but not loaded, see `Dal::get_bookmarks`
+    pub fn has_unloaded_embedding(&self) -> bool {
+        self.embedding.as_ref().is_some_and(|e| e.is_empty())
+    }
+    /// Unknown values are treated as private
+    pub fn visibility(&self) -> Visibility {
+        self.visibility.parse().unwrap_or_default()
+    }
+    /// Whether the bookmark may leave the local database at the given level
+    pub fn is_visible_at(&self, level: Visibility) -> bool {
+        self.visibility() >= level
+    }
+    /// Key-value annotations, none if the column does not hold a JSON object of strings
+    pub fn meta(&self) -> BTreeMap<String, String> {
+        self.meta
+            .as_deref()
+            .and_then(|meta| serde_json::from_str(meta).ok())
+            .unwrap_or_default()
+    }
+    /// Sets the key, None removes it
+    pub fn set_meta(&mut self, key: &str, value: Option<&str>) {
+        let mut meta = self.meta();
+        match value {
+            Some(value) => meta.insert(key.to_string(), value.to_string()),
+            None => meta.remove(key),
+        };
+        self.replace_meta(&meta);
+    }
+    /// Without annotations the column is NULL
+    pub fn replace_meta(&mut self, meta: &BTreeMap<String, String>) {
+        self.meta = (!meta.is_empty()).then(|| serde_json::to_string(meta).unwrap_or_default());
+    }
     pub fn set_tags(&mut self, tags: Vec<String>) {
         self.tags = format!(",{},", Tags::clean_tags(tags).join(","));
         debug!("{:?}", self);
@@ -89,7 +181,10 @@ impl Bookmark {
         format!("{}{} -- {}{}", tags_str, self.metadata, self.desc, tags_str)
     }
     pub fn has_content_changed(&self) -> bool {
-        self.content_hash != Some(calc_content_hash(self.get_content().as_str()))
+        !self
+            .content_hash
+            .as_ref()
+            .is_some_and(|hash| content_hash_matches(hash, self.get_content().as_str()))
     }
 
     // /// Update the embedding and content_hash fields
@@ -106,31 +201,56 @@ impl Bookmark {
     //     self.content_hash = Some(calc_content_hash(self.get_content().as_str()));
     // }
     pub fn convert_to_new_bookmark(&self) -> NewBookmark {
+        // an unloaded embedding must not be stored as empty blob, it is recreated by backfill
+        let unloaded = self.has_unloaded_embedding();
         NewBookmark {
             URL: self.URL.clone(),
             metadata: self.metadata.clone(),
-            tags: self.tags.clone(),
+            // imported tags are normalized like entered ones
+            tags: Tags::create_normalized_tag_string(Some(self.tags.clone())),
             desc: self.desc.clone(),
             flags: self.flags,
-            embedding: self.embedding.clone(),
+            last_update_ts: clock::now_naive(),
+            embedding: self.embedding.clone().filter(|_| !unloaded),
             content_hash: self.content_hash.clone(),
+            uuid: if self.uuid.is_empty() {
+                new_uuid()
+            } else {
+                self.uuid.clone()
+            },
+            language: self.language.clone(),
+            embedding_model: self.embedding_model.clone().filter(|_| !unloaded),
+            embedding_dim: self.embedding_dim.filter(|_| !unloaded),
+            visibility: self.visibility().to_string(),
+            created_ts: self.created_ts.or_else(|| Some(clock::now_naive())),
+            meta: self.meta.clone(),
         }
     }
 }
 
 impl BookmarkUpdater for Bookmark {
     fn update(&mut self) {
-        if !self.has_content_changed() && self.embedding.is_some() {
+        let ctx = Context::read_global();
+        // embeddings of another model are not comparable and get replaced
+        let model_changed = ctx
+            .model()
+            .is_some_and(|model| self.embedding_model.as_deref() != Some(model));
+        if !self.has_content_changed() && self.embedding.is_some() && !model_changed {
             // If content hasn't changed and an embedding exists, log and return early.
             debug!("Embedding exists and is up-to-date");
+            self.content_hash = Some(calc_content_hash(self.get_content().as_str())); // upgrade legacy hash
             return;
         }
 
         // Assuming `CTX` is a globally accessible context that can produce embeddings.
         // And `calc_content_hash` is a function that calculates the hash of the bookmark content.
-        let embedding = Context::read_global()
-            .get_embedding(self.get_content().as_str());
+        let embedding = ctx.get_embedding(self.get_content().as_str());
 
+        self.embedding_dim = embedding
+            .as_ref()
+            .and_then(|bytes| deserialize_embedding(bytes.clone()).ok())
+            .map(|vector| vector.len() as i32);
+        self.embedding_model = embedding.as_ref().and(ctx.model().map(String::from));
         self.embedding = embedding;
         self.content_hash = Some(calc_content_hash(self.get_content().as_str()));
     }
@@ -154,6 +274,14 @@ impl fmt::Debug for Bookmark {
                 "content_hash",
                 &self.content_hash.as_ref().map(|v| LastEntries(v)),
             )
+            .field("uuid", &self.uuid)
+            .field("language", &self.language)
+            .field("embedding_model", &self.embedding_model)
+            .field("embedding_dim", &self.embedding_dim)
+            .field("version", &self.version)
+            .field("visibility", &self.visibility)
+            .field("created_ts", &self.created_ts)
+            .field("meta", &self.meta)
             .finish()
     }
 }
@@ -176,8 +304,16 @@ pub struct NewBookmark {
     pub tags: String,
     pub desc: String,
     pub flags: i32,
+    pub last_update_ts: NaiveDateTime,
     pub embedding: Option<Vec<u8>>,
     pub content_hash: Option<Vec<u8>>,
+    pub uuid: String,
+    pub language: Option<String>,
+    pub embedding_model: Option<String>,
+    pub embedding_dim: Option<i32>,
+    pub visibility: String,
+    pub created_ts: Option<NaiveDateTime>,
+    pub meta: Option<String>,
 }
 
 #[derive(Default, Debug, PartialOrd, PartialEq)]
@@ -191,6 +327,9 @@ pub struct BookmarkBuilder {
     last_update_ts: NaiveDateTime,
     embedding: Option<Vec<u8>>,
     content_hash: Option<Vec<u8>>,
+    uuid: String,
+    language: Option<String>,
+    visibility: Visibility,
 }
 
 impl BookmarkBuilder {
@@ -227,6 +366,18 @@ impl BookmarkBuilder {
         self.embedding = embedding;
         self
     }
+    pub fn uuid(mut self, uuid: String) -> Self {
+        self.uuid = uuid;
+        self
+    }
+    pub fn language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+    pub fn visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
 
     pub fn build(self) -> Bookmark {
         let mut bm = Bookmark {
@@ -236,9 +387,17 @@ impl BookmarkBuilder {
             tags: self.tags,
             desc: self.desc,
             flags: self.flags,
-            last_update_ts: Utc::now().naive_utc(),
+            last_update_ts: clock::now_naive(),
             embedding: self.embedding,
             content_hash: None,
+            uuid: self.uuid,
+            language: self.language,
+            embedding_model: None,
+            embedding_dim: None,
+            version: 0,
+            visibility: self.visibility.to_string(),
+            created_ts: None,
+            meta: None,
         };
         bm.content_hash = Some(calc_content_hash(bm.get_content().as_str()));
         bm
@@ -251,7 +410,7 @@ mod test {
     use rstest::*;
 
     use crate::util::helper::calc_content_hash;
-    use crate::model::bookmark::Bookmark;
+    use crate::model::bookmark::{Bookmark, BookmarkBuilder, BookmarkUpdater, Visibility};
 
     #[fixture]
     fn bm() -> Bookmark {
@@ -282,10 +441,26 @@ mod test {
     #[rstest]
     fn test_get_content_hash(bm: Bookmark) {
         let expected_content = ",aaa,xxx,metadata -- desc,aaa,xxx,";
-        let expected_hash = md5::compute(expected_content).0.to_vec();
+        let mut expected_hash = vec![2];
+        expected_hash.extend(md5::compute(expected_content).0);
         assert_eq!(calc_content_hash(bm.get_content().as_str()), expected_hash);
     }
 
+    #[rstest]
+    fn test_content_hash_ignores_whitespace(mut bm: Bookmark) {
+        bm.desc = "first line\nsecond line".to_string();
+        bm.content_hash = Some(calc_content_hash(bm.get_content().as_str()));
+        bm.desc = "first  line\r\n\n  second line".to_string();
+        assert!(!bm.has_content_changed());
+        bm.desc = "firstline second line".to_string();
+        assert!(bm.has_content_changed());
+
+        // legacy md5 of the raw content is still accepted
+        bm.desc = "desc".to_string();
+        bm.content_hash = Some(md5::compute(bm.get_content()).0.to_vec());
+        assert!(!bm.has_content_changed());
+    }
+
     #[rstest]
     fn test_has_content_changed(mut bm: Bookmark) {
         // Case 1: Content hasn't changed
@@ -321,6 +496,38 @@ mod test {
         assert_eq!(bm.id, 0);
     }
 
+    #[rstest]
+    fn test_visibility() {
+        let bm = BookmarkBuilder::new().build();
+        assert_eq!(bm.visibility, "private");
+        assert!(bm.is_visible_at(Visibility::Private));
+        assert!(!bm.is_visible_at(Visibility::Shared));
+
+        let bm = BookmarkBuilder::new().visibility(Visibility::Shared).build();
+        assert!(bm.is_visible_at(Visibility::Shared));
+        assert!(!bm.is_visible_at(Visibility::Public));
+
+        assert_eq!(" Public".parse::<Visibility>().unwrap(), Visibility::Public);
+        assert!("team".parse::<Visibility>().is_err());
+        let unknown = Bookmark {
+            visibility: "team".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(unknown.visibility(), Visibility::Private);
+    }
+
+    #[rstest]
+    fn test_meta(mut bm: Bookmark) {
+        assert!(bm.meta().is_empty());
+        bm.set_meta("project", Some("alpha"));
+        bm.set_meta("owner", Some("tw"));
+        assert_eq!(bm.meta.as_deref(), Some(r#"{"owner":"tw","project":"alpha"}"#));
+        assert_eq!(bm.meta().get("project").map(String::as_str), Some("alpha"));
+        bm.set_meta("owner", None);
+        bm.set_meta("project", None);
+        assert_eq!(bm.meta, None);
+    }
+
     #[rstest]
     fn test_debug_output_empty_fields() {
         let bookmark = Bookmark {
@@ -333,6 +540,14 @@ mod test {
             last_update_ts: DateTime::from_timestamp(60, 0).unwrap().naive_utc(),
             embedding: None,
             content_hash: None,
+            uuid: String::new(),
+            language: None,
+            embedding_model: None,
+            embedding_dim: None,
+            version: 0,
+            visibility: "private".to_string(),
+            created_ts: None,
+            meta: None,
         };
 
         let debug_str = format!("{:?}", bookmark);
@@ -365,4 +580,35 @@ mod test {
         assert!(debug_str.contains("embedding: Some([5, 6, 7, 8, 9, 10, 11, 12, 13, 14])"));
         assert!(debug_str.contains("content_hash: Some([21, 22, 23, 24, 25, 26, 27, 28, 29, 30])"));
     }
+
+    struct ModelEmbedding;
+    impl crate::adapter::embeddings::Embedding for ModelEmbedding {
+        fn embed(&self, _text: &str) -> anyhow::Result<Option<Vec<f32>>> {
+            Ok(Some(vec![0.1, 0.2, 0.3]))
+        }
+        fn model(&self) -> Option<&str> {
+            Some("model-b")
+        }
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    fn test_update_replaces_embedding_of_other_model(mut bm: Bookmark) -> anyhow::Result<()> {
+        use crate::adapter::embeddings::DummyEmbedding;
+        use crate::context::Context as Ctx;
+
+        bm.embedding = Some(vec![1, 2, 3]);
+        bm.embedding_model = Some("model-a".to_string());
+        Ctx::update_global(Ctx::new(Box::new(ModelEmbedding)))?;
+        bm.update();
+        Ctx::update_global(Ctx::new(Box::new(DummyEmbedding)))?;
+
+        assert_eq!(bm.embedding_model.as_deref(), Some("model-b"));
+        assert_eq!(bm.embedding_dim, Some(3));
+
+        // without embedder the existing embedding is kept
+        bm.update();
+        assert_eq!(bm.embedding_model.as_deref(), Some("model-b"));
+        Ok(())
+    }
 }
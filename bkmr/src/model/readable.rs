@@ -0,0 +1,16 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::adapter::dal::schema::readable;
+
+/// Main text of a fetched page, stored apart from the bookmark
+#[derive(Queryable, Insertable, AsChangeset, Debug, Clone, PartialEq)]
+#[diesel(table_name = readable)]
+pub struct Readable {
+    /// uuid of the bookmark
+    pub uuid: String,
+    pub title: String,
+    pub byline: String,
+    pub text: String,
+    pub fetched_ts: NaiveDateTime,
+}
@@ -0,0 +1,47 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::adapter::dal::schema::registries;
+
+/// Prefix of the tag namespace marking bookmarks synced from a registry
+pub const REGISTRY_TAG_PREFIX: &str = "registry:";
+
+/// Snippet pack published by a team, synced read-only into the local database
+#[derive(Queryable, Insertable, Debug, Clone, PartialEq)]
+#[diesel(table_name = registries)]
+pub struct Registry {
+    pub name: String,
+    pub url: String,
+    pub last_sync: Option<NaiveDateTime>,
+    /// base64 signing key, packs must be bundles signed with it
+    pub public_key: Option<String>,
+}
+
+impl Registry {
+    pub fn new(name: &str, url: &str, public_key: Option<String>) -> Self {
+        Self {
+            name: name.to_lowercase(),
+            url: url.to_string(),
+            last_sync: None,
+            public_key,
+        }
+    }
+
+    pub fn tag(&self) -> String {
+        format!("{}{}", REGISTRY_TAG_PREFIX, self.name)
+    }
+
+    /// Cloned with git, otherwise fetched via https or read from a local path
+    pub fn is_git(&self) -> bool {
+        self.url.starts_with("git@")
+            || self.url.starts_with("git://")
+            || self.url.starts_with("ssh://")
+            || self.url.trim_end_matches('/').ends_with(".git")
+    }
+
+    /// Packs of remote registries without a public key are not verified, local paths are trusted
+    pub fn is_trusted(&self) -> bool {
+        self.public_key.is_some()
+            || !(self.is_git() || self.url.starts_with("https://") || self.url.starts_with("http://"))
+    }
+}
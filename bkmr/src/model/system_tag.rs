@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+use crate::environment::TypeConfig;
+use crate::model::bookmark::Bookmark;
+use crate::util::helper::shell_quote;
+
+/// Bookmark type, selected by a system tag of the form `_name_`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SystemTag {
+    pub tag: String,
+    pub description: String,
+    /// shell command run on open instead of the default opener
+    pub action: Option<String>,
+    pub language: Option<String>,
+    pub builtin: bool,
+}
+
+impl SystemTag {
+    fn builtin(tag: &str, description: &str) -> Self {
+        Self {
+            tag: tag.to_string(),
+            description: description.to_string(),
+            builtin: true,
+            ..Default::default()
+        }
+    }
+
+    /// Action command with placeholders {url}, {title} and {id} replaced by the shell quoted values,
+    /// in one pass so that values containing placeholders are not substituted again
+    pub fn action_for(&self, bm: &Bookmark) -> Option<String> {
+        self.action.as_ref().map(|action| {
+            let id = bm.id.to_string();
+            let placeholders = [("{url}", bm.URL.as_str()), ("{title}", bm.metadata.as_str()), ("{id}", id.as_str())];
+            let mut command = String::new();
+            let mut rest = action.as_str();
+            while let Some(start) = rest.find('{') {
+                command.push_str(&rest[..start]);
+                rest = &rest[start..];
+                match placeholders.iter().find(|(p, _)| rest.starts_with(p)) {
+                    Some((p, value)) => {
+                        command.push_str(&shell_quote(value));
+                        rest = &rest[p.len()..];
+                    }
+                    None => {
+                        command.push('{');
+                        rest = &rest[1..];
+                    }
+                }
+            }
+            command.push_str(rest);
+            command
+        })
+    }
+}
+
+impl fmt::Display for SystemTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<12} {:<8} {}",
+            self.tag,
+            if self.builtin { "builtin" } else { "custom" },
+            self.description
+        )?;
+        if let Some(language) = &self.language {
+            write!(f, " [{}]", language)?;
+        }
+        if let Some(action) = &self.action {
+            write!(f, " -> {}", action)?;
+        }
+        Ok(())
+    }
+}
+
+/// true for tags of the form `_name_`
+pub fn is_system_tag(tag: &str) -> bool {
+    tag.len() > 2 && tag.starts_with('_') && tag.ends_with('_')
+}
+
+/// System tags added or removed by changing the tags from old to new, sorted
+pub fn changed_system_tags(old: &[String], new: &[String]) -> Vec<String> {
+    let mut changed: Vec<String> = old
+        .iter()
+        .filter(|t| !new.contains(t))
+        .chain(new.iter().filter(|t| !old.contains(t)))
+        .filter(|t| is_system_tag(t))
+        .cloned()
+        .collect();
+    changed.sort();
+    changed
+}
+
+/// `sql` and `_sql_` both denote the system tag `_sql_`
+pub fn normalize_system_tag(name: &str) -> String {
+    if is_system_tag(name) {
+        name.to_lowercase()
+    } else {
+        format!("_{}_", name.trim_matches('_').to_lowercase())
+    }
+}
+
+/// Builtin types whose open runs a command or sends a request
+const EXECUTABLE_TAGS: &[&str] = &["_http_", "_shell_", "_sql_", "_ssh_"];
+
+fn builtin_system_tags() -> Vec<SystemTag> {
+    vec![
+        SystemTag::builtin("_imported_", "imported with load-texts"),
+        SystemTag::builtin("_edit_", "file, opened in the editor, path:<line> supported"),
+        SystemTag::builtin("_event_", "ICS event template, opened in the calendar"),
+        SystemTag::builtin("_http_", "HTTP request template, sent on open"),
+        SystemTag::builtin("_inbox_", "quick capture of add --inbox, waiting for triage"),
+        SystemTag {
+            language: Some("sql".to_string()),
+            ..SystemTag::builtin("_sql_", "parameterized SQL query, run on open")
+        },
+        SystemTag::builtin("_dir_", "directory, cd with shell-init function or file manager"),
+        SystemTag::builtin("_mail_", "email template, opened in the mail client"),
+        SystemTag::builtin("_md_", "markdown text, printed and copied to the clipboard on open"),
+        SystemTag::builtin("_snip_", "code snippet, printed and copied to the clipboard on open"),
+        SystemTag::builtin("_ssh_", "remote command '<[user@]host> <command>', run on open"),
+        SystemTag::builtin("_template_", "scaffold for add --template <title>, {date} and {title} are replaced"),
+    ]
+}
+
+/// Builtin system tags extended and overridden by the `[types]` config section
+#[derive(Debug, Clone, Default)]
+pub struct SystemTags {
+    pub tags: BTreeMap<String, SystemTag>,
+}
+
+impl SystemTags {
+    pub fn new(types: &BTreeMap<String, TypeConfig>) -> Result<Self> {
+        let mut tags: BTreeMap<String, SystemTag> = builtin_system_tags()
+            .into_iter()
+            .map(|t| (t.tag.clone(), t))
+            .collect();
+
+        for (name, config) in types {
+            let tag = normalize_system_tag(name);
+            if !tag[1..tag.len() - 1].chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(anyhow!("Invalid type name {:?}, use letters and digits only", name));
+            }
+            let entry = tags.entry(tag.clone()).or_insert_with(|| SystemTag {
+                tag,
+                ..Default::default()
+            });
+            if let Some(description) = &config.description {
+                entry.description = description.clone();
+            }
+            entry.action = config.action.clone().or(entry.action.take());
+            entry.language = config.language.clone().or(entry.language.take());
+        }
+        Ok(Self { tags })
+    }
+
+    pub fn get(&self, tag: &str) -> Option<&SystemTag> {
+        self.tags.get(tag)
+    }
+
+    /// Opening a bookmark with the tag runs something: builtin executable types and types with an action
+    pub fn is_executable(&self, tag: &str) -> bool {
+        EXECUTABLE_TAGS.contains(&tag) || self.get(tag).is_some_and(|t| t.action.is_some())
+    }
+
+    /// First known system tag of the bookmark
+    pub fn for_bookmark(&self, bm: &Bookmark) -> Option<&SystemTag> {
+        bm.get_tags()
+            .iter()
+            .filter(|t| is_system_tag(t))
+            .find_map(|t| self.get(t))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    #[fixture]
+    fn system_tags() -> SystemTags {
+        let mut types = BTreeMap::new();
+        types.insert(
+            "report".to_string(),
+            TypeConfig {
+                description: Some("SQL report".to_string()),
+                action: Some("sqlite3 db.sqlite {url}".to_string()),
+                language: Some("sql".to_string()),
+            },
+        );
+        types.insert(
+            "_imported_".to_string(),
+            TypeConfig {
+                action: Some("less {url}".to_string()),
+                ..Default::default()
+            },
+        );
+        SystemTags::new(&types).unwrap()
+    }
+
+    #[rstest]
+    #[case("_sql_", true)]
+    #[case("_", false)]
+    #[case("__", false)]
+    #[case("sql", false)]
+    #[case("_sql", false)]
+    fn test_is_system_tag(#[case] tag: &str, #[case] expected: bool) {
+        assert_eq!(is_system_tag(tag), expected);
+    }
+
+    #[rstest]
+    #[case(vec!["_snip_", "rust"], vec!["rust", "cli"], vec!["_snip_"])]
+    #[case(vec!["rust"], vec!["_md_", "rust"], vec!["_md_"])]
+    #[case(vec!["_snip_", "rust"], vec!["_snip_"], vec![])]
+    fn test_changed_system_tags(#[case] old: Vec<&str>, #[case] new: Vec<&str>, #[case] expected: Vec<&str>) {
+        let tags = |v: Vec<&str>| v.into_iter().map(String::from).collect::<Vec<_>>();
+        assert_eq!(changed_system_tags(&tags(old), &tags(new)), tags(expected));
+    }
+
+    #[rstest]
+    #[case("sql", "_sql_")]
+    #[case("_SQL_", "_sql_")]
+    #[case("_sql", "_sql_")]
+    fn test_normalize_system_tag(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(normalize_system_tag(name), expected);
+    }
+
+    #[rstest]
+    fn test_system_tags(system_tags: SystemTags) {
+        let report = system_tags.get("_report_").unwrap();
+        assert!(!report.builtin);
+        assert_eq!(report.language.as_deref(), Some("sql"));
+
+        // config overrides builtin action but keeps the description
+        let imported = system_tags.get("_imported_").unwrap();
+        assert!(imported.builtin);
+        assert_eq!(imported.action.as_deref(), Some("less {url}"));
+        assert_eq!(imported.description, "imported with load-texts");
+    }
+
+    #[rstest]
+    fn test_is_executable(system_tags: SystemTags) {
+        assert!(system_tags.is_executable("_ssh_"));
+        assert!(system_tags.is_executable("_report_"));
+        assert!(system_tags.is_executable("_imported_"));
+        assert!(!system_tags.is_executable("_snip_"));
+        assert!(!system_tags.is_executable("rust"));
+    }
+
+    #[rstest]
+    fn test_invalid_type_name() {
+        let mut types = BTreeMap::new();
+        types.insert("my type".to_string(), TypeConfig::default());
+        assert!(SystemTags::new(&types).is_err());
+    }
+
+    #[rstest]
+    fn test_for_bookmark(system_tags: SystemTags) {
+        let bm = BookmarkBuilder::new()
+            .id(3)
+            .URL("select 1".to_string())
+            .tags(",_report_,db,".to_string())
+            .build();
+        let report = system_tags.for_bookmark(&bm).unwrap();
+        assert_eq!(report.tag, "_report_");
+        assert_eq!(
+            report.action_for(&bm).as_deref(),
+            Some("sqlite3 db.sqlite 'select 1'")
+        );
+
+        let bm = BookmarkBuilder::new().tags(",db,_unknown_,".to_string()).build();
+        assert!(system_tags.for_bookmark(&bm).is_none());
+    }
+
+    #[rstest]
+    fn test_action_for_quotes_values() {
+        let log = SystemTag {
+            action: Some("notify {title} {id} && tail -f {url} {unknown}".to_string()),
+            ..Default::default()
+        };
+        let bm = BookmarkBuilder::new()
+            .id(7)
+            .URL("/var/log/{title}.log".to_string())
+            .metadata(r#""; rm -rf ~ #"#.to_string())
+            .build();
+        assert_eq!(
+            log.action_for(&bm).as_deref(),
+            Some(r#"notify '"; rm -rf ~ #' '7' && tail -f '/var/log/{title}.log' {unknown}"#)
+        );
+        let quote = BookmarkBuilder::new().metadata("it's".to_string()).build();
+        let title = SystemTag {
+            action: Some("echo {title}".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(title.action_for(&quote).as_deref(), Some(r"echo 'it'\''s'"));
+    }
+}
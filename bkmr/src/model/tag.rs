@@ -1,6 +1,9 @@
+use icu_normalizer::ComposingNormalizer;
 use std::collections::HashSet;
 use tracing::debug;
 
+use crate::environment::{TagsConfig, CONFIG};
+
 #[derive(Debug, PartialOrd, PartialEq, Clone, Default)]
 pub struct Tags {
     tag: String,
@@ -19,22 +22,34 @@ impl Tags {
     pub fn normalize_tag_string(tag_str: Option<String>) -> Vec<String> {
         match tag_str {
             Some(s) => {
-                let _tags = s
-                    .replace(' ', "")
-                    .split(',')
-                    .map(|s| s.trim().to_lowercase().to_owned())
-                    .collect::<Vec<_>>();
+                let _tags = s.split(',').map(|s| s.to_owned()).collect::<Vec<_>>();
                 Self::clean_tags(_tags)
             }
             None => Vec::new(),
         }
     }
 
+    /// normalizes a single tag as configured, e.g. `My Tag` to `my-tag` with space replacement `-`
+    pub fn normalize_tag(tag: &str, config: &TagsConfig) -> String {
+        let mut tag = tag.split_whitespace().collect::<Vec<_>>().join(&config.space_replacement);
+        if config.nfc {
+            tag = ComposingNormalizer::new_nfc().normalize(&tag);
+        }
+        if config.lowercase {
+            tag = tag.to_lowercase();
+        }
+        tag
+    }
+
     pub fn clean_tags(tags: Vec<String>) -> Vec<String> {
+        Self::clean_tags_with(tags, &CONFIG.tags)
+    }
+
+    pub fn clean_tags_with(tags: Vec<String>, config: &TagsConfig) -> Vec<String> {
         let mut _tags: Vec<String> = tags
             .iter()
             .flat_map(|s| s.split(','))
-            .map(|s| s.trim().to_lowercase().to_owned())
+            .map(|s| Self::normalize_tag(s, config))
             .filter(|s| !s.is_empty())
             .collect();
         _tags.sort();
@@ -64,20 +79,20 @@ impl Tags {
         tags.join(new_delimiter)
     }
 
-    pub fn match_exact_tags(tags: &Vec<String>, bm_tags: &Vec<String>) -> bool {
+    pub fn match_exact_tags(tags: &[String], bm_tags: &[String]) -> bool {
         let set1: HashSet<String> = tags.iter().map(|s| s.to_string()).collect();
         let set2: HashSet<String> = bm_tags.iter().map(|s| s.to_string()).collect();
         set1 == set2
     }
 
-    pub fn match_all_tags(tags: &Vec<String>, bm_tags: &Vec<String>) -> bool {
+    pub fn match_all_tags(tags: &[String], bm_tags: &[String]) -> bool {
         let set1: HashSet<_> = tags.iter().collect();
         let set2: HashSet<_> = bm_tags.iter().collect();
         let intersect = set1.intersection(&set2).collect::<HashSet<_>>();
         intersect == set1.iter().collect()
     }
 
-    pub fn match_any_tags(tags: &Vec<String>, bm_tags: &Vec<String>) -> bool {
+    pub fn match_any_tags(tags: &[String], bm_tags: &[String]) -> bool {
         let set1: HashSet<_> = tags.iter().collect();
         let set2: HashSet<_> = bm_tags.iter().collect();
         let intersect = set1.intersection(&set2).collect::<HashSet<_>>();
@@ -87,6 +102,7 @@ impl Tags {
 
 #[cfg(test)]
 mod test {
+    use crate::environment::TagsConfig;
     use crate::model::tag::Tags;
     use rstest::*;
     use tracing::debug;
@@ -129,6 +145,29 @@ mod test {
         assert_eq!(Tags::clean_tags(input), expected)
     }
 
+    #[rstest]
+    #[case("My  Tag", "", "mytag")]
+    #[case(" My  Tag ", "-", "my-tag")]
+    #[case("Cafe\u{301}", "", "café")]
+    fn test_normalize_tag(#[case] tag: &str, #[case] space_replacement: &str, #[case] expected: &str) {
+        let config = TagsConfig {
+            space_replacement: space_replacement.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(Tags::normalize_tag(tag, &config), expected);
+    }
+
+    #[rstest]
+    fn test_clean_tags_with() {
+        let config = TagsConfig {
+            lowercase: false,
+            space_replacement: "_".to_string(),
+            nfc: false,
+        };
+        let tags = vec!["New York,ny".to_string(), "New  York".to_string()];
+        assert_eq!(Tags::clean_tags_with(tags, &config), vec!["New_York", "ny"]);
+    }
+
     #[rstest]
     #[case(Some("tag1,tag2".to_string()), String::from(",tag1,tag2,"))]
     #[case(Some("tag2,tag1".to_string()), String::from(",tag1,tag2,"))]
@@ -148,8 +187,8 @@ mod test {
         #[case] bm_tags: &Vec<&str>,
         #[case] expected: bool,
     ) {
-        let tags = &tags.iter().map(|s| s.to_string()).collect();
-        let bm_tags = &bm_tags.iter().map(|s| s.to_string()).collect();
+        let tags: &Vec<String> = &tags.iter().map(|s| s.to_string()).collect();
+        let bm_tags: &Vec<String> = &bm_tags.iter().map(|s| s.to_string()).collect();
         debug!("{:?}, {:?} {:?}", tags, bm_tags, expected);
         assert_eq!(Tags::match_exact_tags(tags, bm_tags), expected)
     }
@@ -164,8 +203,8 @@ mod test {
         #[case] bm_tags: &Vec<&str>,
         #[case] expected: bool,
     ) {
-        let tags = &tags.iter().map(|s| s.to_string()).collect();
-        let bm_tags = &bm_tags.iter().map(|s| s.to_string()).collect();
+        let tags: &Vec<String> = &tags.iter().map(|s| s.to_string()).collect();
+        let bm_tags: &Vec<String> = &bm_tags.iter().map(|s| s.to_string()).collect();
         debug!("{:?}, {:?} {:?}", tags, bm_tags, expected);
         assert_eq!(Tags::match_all_tags(tags, bm_tags), expected)
     }
@@ -180,8 +219,8 @@ mod test {
         #[case] bm_tags: &Vec<&str>,
         #[case] expected: bool,
     ) {
-        let tags = &tags.iter().map(|s| s.to_string()).collect();
-        let bm_tags = &bm_tags.iter().map(|s| s.to_string()).collect();
+        let tags: &Vec<String> = &tags.iter().map(|s| s.to_string()).collect();
+        let bm_tags: &Vec<String> = &bm_tags.iter().map(|s| s.to_string()).collect();
         debug!("{:?}, {:?} {:?}", tags, bm_tags, expected);
         assert_eq!(Tags::match_any_tags(tags, bm_tags), expected)
     }
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use tracing::debug;
+
+use crate::model::bookmark::Bookmark;
+use crate::service::dir::open_dir;
+use crate::service::editor::open_in_editor;
+use crate::service::event::open_event;
+use crate::service::http::execute_request;
+use crate::service::mail::open_mail;
+use crate::service::snippet::open_snippet;
+use crate::service::sql::execute_query;
+use crate::service::ssh::execute_ssh;
+
+/// Opens bookmarks of builtin types with their specific action.
+/// Returns false if the type has no builtin action and the default opener applies.
+pub fn open_builtin(tag: &str, bm: &Bookmark) -> Result<bool> {
+    debug!("Builtin action for {:?}: {:?}", tag, bm.id);
+    match tag {
+        "_dir_" => open_dir(&bm.URL)?,
+        "_edit_" => open_in_editor(&bm.URL)?,
+        "_event_" => open_event(&bm.URL)?,
+        "_http_" => execute_request(&bm.URL)?,
+        "_mail_" => open_mail(&bm.URL)?,
+        "_md_" => open_snippet(&bm.URL)?,
+        "_snip_" => open_snippet(&bm.URL)?,
+        "_sql_" => execute_query(&bm.URL)?,
+        "_ssh_" => execute_ssh(&bm.URL)?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use clap::ValueEnum;
+
+use crate::model::bookmark::Bookmark;
+use crate::model::tag::Tags;
+use crate::retag;
+
+/// Operation of `bkmr batch` on each selected bookmark
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum BatchOp {
+    /// Add the tags given with --value
+    AddTag,
+    /// Remove the tags given with --value
+    RemoveTag,
+    /// Create the embedding again with the current embedder
+    SetEmbeddable,
+    /// Count an access and update the timestamp, as opening does
+    Touch,
+}
+
+impl BatchOp {
+    pub fn needs_tags(&self) -> bool {
+        matches!(self, BatchOp::AddTag | BatchOp::RemoveTag)
+    }
+}
+
+impl fmt::Display for BatchOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default();
+        write!(f, "{}", name)
+    }
+}
+
+/// Bookmarks with one of the ids, all if none are given, restricted to bookmarks with all tags
+pub fn select(bms: Vec<Bookmark>, ids: &[i32], tags: &[String]) -> Vec<Bookmark> {
+    let ids: HashSet<i32> = ids.iter().copied().collect();
+    bms.into_iter()
+        .filter(|bm| ids.is_empty() || ids.contains(&bm.id))
+        .filter(|bm| Tags::match_all_tags(tags, &bm.get_tags()))
+        .collect()
+}
+
+/// Bookmark after the operation, None if it does not change.
+/// The embedding is created when the bookmark is updated, its content hash is cleared to force it.
+pub fn apply(bm: &Bookmark, op: BatchOp, tags: &[String]) -> Option<Bookmark> {
+    let tags: HashSet<String> = tags.iter().cloned().collect();
+    match op {
+        BatchOp::AddTag => {
+            let new_tags = retag(bm, &tags, &HashSet::new(), false);
+            (new_tags != bm.tags).then(|| Bookmark { tags: new_tags, ..bm.clone() })
+        }
+        BatchOp::RemoveTag => {
+            let new_tags = retag(bm, &HashSet::new(), &tags, false);
+            (new_tags != bm.tags).then(|| Bookmark { tags: new_tags, ..bm.clone() })
+        }
+        BatchOp::SetEmbeddable => Some(Bookmark {
+            content_hash: None,
+            ..bm.clone()
+        }),
+        BatchOp::Touch => Some(Bookmark {
+            flags: bm.flags + 1,
+            ..bm.clone()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn bm(id: i32, tags: &str) -> Bookmark {
+        let bm = BookmarkBuilder::new()
+            .id(id)
+            .URL(format!("https://example.com/{}", id))
+            .tags(tags.to_string())
+            .flags(0)
+            .build();
+        Bookmark {
+            content_hash: Some(vec![1]),
+            ..bm
+        }
+    }
+
+    #[rstest]
+    #[case(vec![], vec![], vec![1, 2, 3])]
+    #[case(vec![1, 3], vec![], vec![1, 3])]
+    #[case(vec![], vec!["rust"], vec![1, 2])]
+    #[case(vec![2, 3], vec!["rust"], vec![2])]
+    fn test_select(#[case] ids: Vec<i32>, #[case] tags: Vec<&str>, #[case] expected: Vec<i32>) {
+        let bms = vec![bm(1, ",rust,"), bm(2, ",rust,todo,"), bm(3, ",go,")];
+        let tags: Vec<String> = tags.into_iter().map(String::from).collect();
+        let selected: Vec<i32> = select(bms, &ids, &tags).iter().map(|bm| bm.id).collect();
+        assert_eq!(selected, expected);
+    }
+
+    #[rstest]
+    fn test_apply() {
+        let todo = vec!["todo".to_string()];
+        assert_eq!(apply(&bm(1, ",rust,"), BatchOp::AddTag, &todo).unwrap().tags, ",rust,todo,");
+        assert!(apply(&bm(1, ",rust,todo,"), BatchOp::AddTag, &todo).is_none());
+        assert_eq!(apply(&bm(1, ",rust,todo,"), BatchOp::RemoveTag, &todo).unwrap().tags, ",rust,");
+        assert!(apply(&bm(1, ",rust,"), BatchOp::RemoveTag, &todo).is_none());
+        assert_eq!(apply(&bm(1, ",rust,"), BatchOp::SetEmbeddable, &[]).unwrap().content_hash, None);
+        assert_eq!(apply(&bm(1, ",rust,"), BatchOp::Touch, &[]).unwrap().flags, 1);
+    }
+
+    #[rstest]
+    fn test_display() {
+        assert_eq!(BatchOp::SetEmbeddable.to_string(), "set-embeddable");
+    }
+}
@@ -0,0 +1,646 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::adapter::dal::Dal;
+use crate::model::bookmark::{Bookmark, BookmarkBuilder, BookmarkUpdater, Visibility};
+use crate::model::tag::Tags;
+use crate::service::bus::{self, BookmarkEvent};
+use crate::service::interop;
+use crate::service::org;
+use crate::service::secrets::REDACTED;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum BulkFormat {
+    Toml,
+    Json,
+    /// VS Code snippets of the `_snip_` bookmarks, export only
+    Vscode,
+    /// linkding REST API json
+    Linkding,
+    /// Shiori API json
+    Shiori,
+    /// wallabag API or export json
+    Wallabag,
+    /// org-mode headlines with links or source blocks, e.g. Emacs link collections
+    Org,
+}
+
+impl BulkFormat {
+    /// json for `.json` files, org for `.org` files, toml otherwise
+    pub fn from_path(path: &str) -> Self {
+        let path = path.to_lowercase();
+        if path.ends_with(".json") {
+            BulkFormat::Json
+        } else if path.ends_with(".org") {
+            BulkFormat::Org
+        } else {
+            BulkFormat::Toml
+        }
+    }
+}
+
+/// Editable representation of a bookmark, entries without id or known uuid are created
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BulkBookmark {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    pub url: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub desc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// kept if missing, private for new bookmarks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    /// key-value annotations, kept if missing, empty removes them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<BTreeMap<String, String>>,
+    /// creation time of new bookmarks, now if missing, existing bookmarks keep theirs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<NaiveDateTime>,
+}
+
+impl From<&Bookmark> for BulkBookmark {
+    fn from(bm: &Bookmark) -> Self {
+        Self {
+            id: Some(bm.id),
+            uuid: Some(bm.uuid.clone()),
+            url: bm.URL.clone(),
+            title: bm.metadata.clone(),
+            tags: bm.get_tags(),
+            desc: bm.desc.clone(),
+            language: bm.language.clone(),
+            visibility: Some(bm.visibility()),
+            meta: Some(bm.meta()).filter(|meta| !meta.is_empty()),
+            created_ts: bm.created_ts,
+        }
+    }
+}
+
+impl BulkBookmark {
+    /// Keeps the current values of fields redacted by the export
+    fn unredact(&self, bm: &Bookmark) -> Self {
+        let keep = |new: &String, old: &String| {
+            if new.contains(REDACTED) {
+                old.clone()
+            } else {
+                new.clone()
+            }
+        };
+        Self {
+            url: keep(&self.url, &bm.URL),
+            title: keep(&self.title, &bm.metadata),
+            desc: keep(&self.desc, &bm.desc),
+            ..self.clone()
+        }
+    }
+
+    /// Keeps the current title, description and language if missing in the entry
+    fn keep_missing(&self, bm: &Bookmark) -> Self {
+        let keep = |new: &String, old: &String| {
+            if new.is_empty() {
+                old.clone()
+            } else {
+                new.clone()
+            }
+        };
+        Self {
+            title: keep(&self.title, &bm.metadata),
+            desc: keep(&self.desc, &bm.desc),
+            language: self.language.clone().or_else(|| bm.language.clone()),
+            ..self.clone()
+        }
+    }
+
+    fn tag_string(&self) -> String {
+        Tags::create_normalized_tag_string(Some(self.tags.join(",")))
+    }
+
+    fn differs(&self, bm: &Bookmark) -> bool {
+        self.url != bm.URL
+            || self.title != bm.metadata
+            || self.desc != bm.desc
+            || self.tag_string() != bm.tags
+            || self.language != bm.language
+            || self.visibility.is_some_and(|v| v != bm.visibility())
+            || self.meta.as_ref().is_some_and(|meta| *meta != bm.meta())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BulkFile {
+    /// set by exports of the bookmarks of at least this visibility, the others are not deleted by `apply`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    pub bookmarks: Vec<BulkBookmark>,
+}
+
+impl BulkFile {
+    pub fn new(bms: &[Bookmark]) -> Self {
+        Self {
+            visibility: None,
+            bookmarks: bms.iter().map(BulkBookmark::from).collect(),
+        }
+    }
+
+    pub fn serialize(&self, format: BulkFormat) -> Result<String> {
+        match format {
+            BulkFormat::Toml => toml::to_string(self).context("Failed to serialize to toml"),
+            BulkFormat::Json => {
+                serde_json::to_string_pretty(self).context("Failed to serialize to json")
+            }
+            BulkFormat::Vscode => Err(anyhow!("vscode format is only supported by export")),
+            BulkFormat::Org => Ok(org::serialize(self)),
+            _ => interop::serialize(self, format),
+        }
+    }
+
+    pub fn parse(content: &str, format: BulkFormat) -> Result<Self> {
+        match format {
+            BulkFormat::Toml => toml::from_str(content).context("Failed to parse toml"),
+            BulkFormat::Json => serde_json::from_str(content).context("Failed to parse json"),
+            BulkFormat::Vscode => Err(anyhow!("vscode format is only supported by export")),
+            BulkFormat::Org => org::parse(content),
+            _ => interop::parse(content, format),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Create(BulkBookmark),
+    Update { old: Bookmark, new: BulkBookmark },
+    Delete(Bookmark),
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Create(new) => write!(f, "+ {} [{}]", new.url, new.tag_string()),
+            Change::Update { old, new } => {
+                write!(f, "~ {}:", old.id)?;
+                if old.URL != new.url {
+                    write!(f, " url {:?} -> {:?}", old.URL, new.url)?;
+                }
+                if old.metadata != new.title {
+                    write!(f, " title {:?} -> {:?}", old.metadata, new.title)?;
+                }
+                if old.desc != new.desc {
+                    write!(f, " desc changed")?;
+                }
+                if old.tags != new.tag_string() {
+                    write!(f, " tags {} -> {}", old.tags, new.tag_string())?;
+                }
+                if old.language != new.language {
+                    write!(f, " language {:?} -> {:?}", old.language, new.language)?;
+                }
+                if let Some(visibility) = new.visibility.filter(|v| *v != old.visibility()) {
+                    write!(f, " visibility {} -> {}", old.visibility(), visibility)?;
+                }
+                Ok(())
+            }
+            Change::Delete(old) => write!(f, "- {}: {}", old.id, old.URL),
+        }
+    }
+}
+
+/// Changes required to turn the current bookmarks into the bulk file
+/// Entries are matched by id, without id by uuid (files from other machines).
+/// Fields still containing `[REDACTED]` keep their current value.
+/// Bookmarks missing in the file are deleted, unless the file is an export without them (`visibility`).
+pub fn diff(current: &[Bookmark], file: &BulkFile) -> Result<Vec<Change>> {
+    let by_uuid: BTreeMap<&str, i32> = current
+        .iter()
+        .filter(|bm| !bm.uuid.is_empty())
+        .map(|bm| (bm.uuid.as_str(), bm.id))
+        .collect();
+    let current: BTreeMap<i32, &Bookmark> = current.iter().map(|bm| (bm.id, bm)).collect();
+    let mut seen = HashSet::new();
+    let mut changes = Vec::new();
+
+    for entry in &file.bookmarks {
+        // ids change by compaction, the uuid decides whenever the entry has one
+        let id = match entry.uuid.as_deref() {
+            Some(uuid) => {
+                let id = by_uuid.get(uuid).copied();
+                if let Some(entry_id) = entry.id.filter(|i| Some(*i) != id && current.contains_key(i)) {
+                    return Err(anyhow!(
+                        "Stale file: id {} belongs to another bookmark than uuid {}, export again",
+                        entry_id,
+                        uuid
+                    ));
+                }
+                id
+            }
+            None => entry.id,
+        };
+        match id {
+            None => changes.push(Change::Create(entry.clone())),
+            Some(id) => {
+                if !seen.insert(id) {
+                    return Err(anyhow!("Duplicate id {} in bulk file", id));
+                }
+                let old = current
+                    .get(&id)
+                    .ok_or_else(|| anyhow!("Unknown id {} in bulk file", id))?;
+                let entry = entry.unredact(old);
+                if entry.differs(old) {
+                    changes.push(Change::Update {
+                        old: (*old).clone(),
+                        new: entry,
+                    });
+                }
+            }
+        }
+    }
+    for (id, bm) in current.iter().rev() {
+        let exported = file.visibility.is_none_or(|level| bm.is_visible_at(level));
+        if !seen.contains(id) && exported {
+            changes.push(Change::Delete((*bm).clone()));
+        }
+    }
+    debug!("{:?}", changes);
+    Ok(changes)
+}
+
+/// Changes merging the file into the current bookmarks: entries are matched by uuid or URL,
+/// ids in the file are ignored and nothing is deleted (files shared by others or exported
+/// by other bookmark managers). Missing titles, descriptions and languages are kept.
+pub fn merge_changes(current: &[Bookmark], file: &BulkFile) -> Result<Vec<Change>> {
+    let entries = file
+        .bookmarks
+        .iter()
+        .map(|entry| {
+            match current
+                .iter()
+                .find(|bm| entry.uuid.as_deref() == Some(bm.uuid.as_str()) || entry.url == bm.URL)
+            {
+                Some(bm) => BulkBookmark {
+                    id: Some(bm.id),
+                    ..entry.keep_missing(bm)
+                },
+                None => BulkBookmark {
+                    id: None,
+                    ..entry.clone()
+                },
+            }
+        })
+        .collect();
+    Ok(diff(current, &BulkFile { visibility: None, bookmarks: entries })?
+        .into_iter()
+        .filter(|change| !matches!(change, Change::Delete(_)))
+        .collect())
+}
+
+/// Applies the changes in one transaction: updates, deletes (highest id first because of
+/// id compaction) and finally creates. The events are published after the commit,
+/// returns the stored bookmarks.
+#[instrument(level = "debug", skip(changes))]
+pub fn apply(dal: &mut Dal, changes: Vec<Change>) -> Result<Vec<Bookmark>> {
+    let events = dal.transaction(|dal| apply_changes(dal, &changes))?;
+    Ok(bus::publish_all(events))
+}
+
+/// Applies the changes within the transaction of the caller, returns the events to publish
+/// once it has been committed
+pub fn apply_changes(dal: &mut Dal, changes: &[Change]) -> Result<Vec<BookmarkEvent>> {
+    let mut events = Vec::new();
+    let mut updated = Vec::new();
+    for change in changes {
+        if let Change::Update { old, new } = change {
+            let mut bm = Bookmark {
+                URL: new.url.clone(),
+                metadata: new.title.clone(),
+                desc: new.desc.clone(),
+                tags: new.tag_string(),
+                language: new.language.clone(),
+                visibility: new
+                    .visibility
+                    .map_or(old.visibility.clone(), |v| v.to_string()),
+                ..old.clone()
+            };
+            if let Some(meta) = &new.meta {
+                bm.replace_meta(meta);
+            }
+            bm.update();
+            updated.extend(dal.update_bookmark(bm)?.into_iter().map(|bm| bm.uuid));
+        }
+    }
+    for change in changes {
+        if let Change::Delete(old) = change {
+            dal.delete_and_compact(old.id)?;
+            events.push(BookmarkEvent::Deleted(old.clone()));
+        }
+    }
+    // the deletes may have moved the updated bookmarks to lower ids
+    for uuid in updated {
+        events.push(BookmarkEvent::Updated(dal.get_bookmark_by_uuid(&uuid)?));
+    }
+    for change in changes {
+        if let Change::Create(new) = change {
+            let mut bm = BookmarkBuilder::new()
+                .URL(new.url.clone())
+                .metadata(new.title.clone())
+                .tags(new.tag_string())
+                .desc(new.desc.clone())
+                .flags(0)
+                .uuid(new.uuid.clone().unwrap_or_default())
+                .language(new.language.clone())
+                .visibility(new.visibility.unwrap_or_default())
+                .build();
+            bm.replace_meta(&new.meta.clone().unwrap_or_default());
+            bm.created_ts = new.created_ts;
+            bm.update();
+            let added = dal.insert_bookmark(bm.convert_to_new_bookmark())?;
+            events.extend(added.into_iter().map(BookmarkEvent::Added));
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use rstest::*;
+
+    use super::*;
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    fn test_roundtrip_toml() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let file = BulkFile::new(&dal.get_bookmarks("")?);
+
+        let parsed = BulkFile::parse(&file.serialize(BulkFormat::Toml)?, BulkFormat::Toml)?;
+        assert_eq!(parsed, file);
+        assert!(diff(&dal.get_bookmarks("")?, &parsed)?.is_empty());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_roundtrip_meta() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let mut bm = dal.get_bookmark_by_id(1)?;
+        bm.set_meta("project", Some("alpha"));
+        dal.update_bookmark(bm)?;
+        let current = dal.get_bookmarks("")?;
+
+        let file = BulkFile::parse(&BulkFile::new(&current).serialize(BulkFormat::Toml)?, BulkFormat::Toml)?;
+        assert_eq!(file.bookmarks[0].meta, Some(BTreeMap::from([("project".to_string(), "alpha".to_string())])));
+        assert!(diff(&current, &file)?.is_empty());
+
+        // missing meta keeps the annotations, entries created from the file get theirs
+        let mut file = file;
+        file.bookmarks[0].meta = None;
+        let mut created = file.bookmarks[0].clone();
+        created.id = None;
+        created.uuid = None;
+        created.url = "https://created.example".to_string();
+        created.meta = Some(BTreeMap::from([("owner".to_string(), "tw".to_string())]));
+        file.bookmarks.push(created);
+        apply(&mut dal, diff(&current, &file)?)?;
+        assert_eq!(dal.get_bookmark_by_id(1)?.meta().get("project").map(String::as_str), Some("alpha"));
+        assert_eq!(dal.get_bookmark_by_url("https://created.example")?.meta().len(), 1);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_apply_keeps_created_ts() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let created = NaiveDateTime::parse_from_str("2019-05-06 07:08:09", "%Y-%m-%d %H:%M:%S")?;
+        let mut file = BulkFile::new(&dal.get_bookmarks("")?);
+        file.bookmarks.push(BulkBookmark {
+            url: "https://old.example".to_string(),
+            created_ts: Some(created),
+            ..Default::default()
+        });
+        file.bookmarks.push(BulkBookmark {
+            url: "https://new.example".to_string(),
+            ..Default::default()
+        });
+
+        let file = BulkFile::parse(&file.serialize(BulkFormat::Toml)?, BulkFormat::Toml)?;
+        let changes = diff(&dal.get_bookmarks("")?, &file)?;
+        apply(&mut dal, changes)?;
+        assert_eq!(dal.get_bookmark_by_url("https://old.example")?.created_ts, Some(created));
+        assert!(dal.get_bookmark_by_url("https://new.example")?.created_ts > Some(created));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_diff_errors() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let current = dal.get_bookmarks("")?;
+        let entry = BulkBookmark {
+            id: Some(1),
+            ..Default::default()
+        };
+
+        let duplicate = BulkFile {
+            visibility: None,
+            bookmarks: vec![entry.clone(), entry],
+        };
+        assert!(diff(&current, &duplicate).is_err());
+
+        let unknown = BulkFile {
+            visibility: None,
+            bookmarks: vec![BulkBookmark {
+                id: Some(999),
+                ..Default::default()
+            }],
+        };
+        assert!(diff(&current, &unknown).is_err());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_diff_matches_uuid_without_id() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let current = dal.get_bookmarks("")?;
+        let mut file = BulkFile::new(&current);
+        file.bookmarks[2].id = None;
+        file.bookmarks[2].title = "from other machine".to_string();
+
+        let changes = diff(&current, &file)?;
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], Change::Update { old, .. } if old.id == current[2].id));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_diff_after_compaction() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let mut file = BulkFile::new(&dal.get_bookmarks("")?);
+        file.bookmarks[4].title = "changed".to_string();
+        let moved = file.bookmarks[4].uuid.clone();
+
+        // ids after the deleted bookmark shift down, a new bookmark takes the last id
+        dal.delete_and_compact(2)?;
+        let bm = BookmarkBuilder::new()
+            .URL("https://new.example".to_string())
+            .build();
+        dal.insert_bookmark(bm.convert_to_new_bookmark())?;
+
+        assert!(diff(&dal.get_bookmarks("")?, &file).unwrap_err().to_string().starts_with("Stale file"));
+
+        // without ids the uuids still match the right bookmarks
+        file.bookmarks.iter_mut().for_each(|b| b.id = None);
+        let current = dal.get_bookmarks("")?;
+        let changes = diff(&current, &file)?;
+        assert!(changes.iter().any(|c| matches!(c, Change::Update { old, new } if Some(&old.uuid) == moved.as_ref() && new.title == "changed")));
+        assert!(changes.iter().any(|c| matches!(c, Change::Delete(old) if old.URL == "https://new.example")));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_diff_and_apply() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let mut file = BulkFile::new(&dal.get_bookmarks("")?);
+        file.bookmarks[0].tags = vec!["changed".to_string()];
+        file.bookmarks[0].visibility = Some(Visibility::Shared);
+        file.bookmarks.retain(|b| b.id != Some(3) && b.id != Some(5));
+        file.bookmarks.push(BulkBookmark {
+            url: "https://new.example".to_string(),
+            title: "new".to_string(),
+            tags: vec!["new".to_string()],
+            ..Default::default()
+        });
+
+        let changes = diff(&dal.get_bookmarks("")?, &file)?;
+        let ids = |c: &Change| match c {
+            Change::Create(_) => None,
+            Change::Update { old, .. } | Change::Delete(old) => Some(old.id),
+        };
+        assert_eq!(
+            changes.iter().map(ids).collect::<Vec<_>>(),
+            vec![Some(1), None, Some(5), Some(3)]
+        );
+
+        apply(&mut dal, changes)?;
+        let bms = dal.get_bookmarks("")?;
+        assert_eq!(bms.len(), 10);
+        assert_eq!(dal.get_bookmark_by_id(1)?.tags, ",changed,");
+        assert_eq!(dal.get_bookmark_by_id(1)?.visibility(), Visibility::Shared);
+        assert_eq!(dal.get_bookmark_by_url("https://new.example")?.visibility, "private");
+        assert!(dal.bm_exists("https://new.example")?);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_apply_publishes_events() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = events.clone();
+        bus::BUS.subscribe(move |event| {
+            if event.bookmark().URL.starts_with("https://published.example") {
+                seen.lock().unwrap().push(event.clone());
+            }
+        });
+        let mut first = dal.get_bookmark_by_id(1)?;
+        first.URL = "https://published.example/updated".to_string();
+        dal.update_bookmark(first)?;
+
+        let mut file = BulkFile::new(&dal.get_bookmarks("")?);
+        file.bookmarks[0].title = "updated".to_string();
+        file.bookmarks.retain(|b| b.id != Some(2));
+        file.bookmarks.push(BulkBookmark {
+            url: "https://published.example/added".to_string(),
+            ..Default::default()
+        });
+        let changes = diff(&dal.get_bookmarks("")?, &file)?;
+        let stored = apply(&mut dal, changes)?;
+
+        assert_eq!(stored.len(), 2);
+        let events = events.lock().unwrap();
+        assert!(matches!(&events[..], [BookmarkEvent::Updated(updated), BookmarkEvent::Added(added)]
+            if updated.metadata == "updated" && added.URL.ends_with("added")));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_diff_keeps_redacted_fields() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let current = dal.get_bookmarks("")?;
+        let mut file = BulkFile::new(&current);
+        file.bookmarks[0].url = REDACTED.to_string();
+        file.bookmarks[0].desc = format!("token={}", REDACTED);
+        assert!(diff(&current, &file)?.is_empty());
+
+        file.bookmarks[0].title = "changed".to_string();
+        let changes = diff(&current, &file)?;
+        assert!(matches!(&changes[0], Change::Update { old, new } if new.url == old.URL && new.desc == old.desc));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_diff_filtered_export_keeps_others() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let mut current = dal.get_bookmarks("")?;
+        current[0].visibility = Visibility::Shared.to_string();
+        current[1].visibility = Visibility::Shared.to_string();
+        let shared: Vec<Bookmark> = current.iter().take(2).cloned().collect();
+        let export = BulkFile {
+            visibility: Some(Visibility::Shared),
+            bookmarks: shared[..1].iter().map(BulkBookmark::from).collect(),
+        };
+        let parsed = BulkFile::parse(&export.serialize(BulkFormat::Toml)?, BulkFormat::Toml)?;
+        assert_eq!(parsed, export);
+
+        // only the shared bookmark missing in the export is deleted, the private ones are kept
+        let changes = diff(&current, &parsed)?;
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], Change::Delete(bm) if bm.id == current[1].id));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_merge_changes() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let current = dal.get_bookmarks("")?;
+        let mut shared = BulkBookmark::from(&current[1]);
+        shared.id = Some(1);
+        shared.uuid = None;
+        shared.title = "shared".to_string();
+        let file = BulkFile {
+            visibility: None,
+            bookmarks: vec![
+                shared,
+                BulkBookmark {
+                    id: Some(2),
+                    url: "https://new.example".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        // matched by URL, the foreign ids are ignored and missing bookmarks are kept
+        let changes = merge_changes(&current, &file)?;
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(&changes[0], Change::Update { old, .. } if old.id == current[1].id));
+        assert!(matches!(&changes[1], Change::Create(new) if new.url == "https://new.example"));
+
+        // entries of other bookmark managers without description or language
+        let foreign = BulkFile {
+            visibility: None,
+            bookmarks: vec![BulkBookmark {
+                desc: String::new(),
+                language: None,
+                ..BulkBookmark::from(&current[1])
+            }],
+        };
+        assert!(merge_changes(&current, &foreign)?.is_empty());
+        Ok(())
+    }
+}
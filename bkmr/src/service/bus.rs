@@ -0,0 +1,110 @@
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use tracing::debug;
+
+use crate::model::bookmark::Bookmark;
+use crate::service::{hooks, secrets};
+
+/// Domain events, published by the services after the change has been committed
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookmarkEvent {
+    Added(Bookmark),
+    Updated(Bookmark),
+    Deleted(Bookmark),
+    Opened(Bookmark),
+}
+
+impl BookmarkEvent {
+    pub fn bookmark(&self) -> &Bookmark {
+        match self {
+            BookmarkEvent::Added(bm)
+            | BookmarkEvent::Updated(bm)
+            | BookmarkEvent::Deleted(bm)
+            | BookmarkEvent::Opened(bm) => bm,
+        }
+    }
+}
+
+pub type Subscriber = Box<dyn Fn(&BookmarkEvent) + Send + Sync>;
+
+/// In-process publish/subscribe, subscribers are called synchronously in order of subscription
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: RwLock<Vec<Subscriber>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self, subscriber: impl Fn(&BookmarkEvent) + Send + Sync + 'static) {
+        self.subscribers
+            .write()
+            .expect("Failed to acquire event bus write lock")
+            .push(Box::new(subscriber));
+    }
+
+    pub fn publish(&self, event: &BookmarkEvent) {
+        debug!("Publishing {:?}", event);
+        for subscriber in self
+            .subscribers
+            .read()
+            .expect("Failed to acquire event bus read lock")
+            .iter()
+        {
+            subscriber(event);
+        }
+    }
+}
+
+/// Global bus, the configured hooks and the secret scanner are subscribed by default
+pub static BUS: Lazy<EventBus> = Lazy::new(|| {
+    let bus = EventBus::default();
+    bus.subscribe(hooks::on_event);
+    bus.subscribe(secrets::on_event);
+    bus
+});
+
+pub fn publish(event: BookmarkEvent) {
+    BUS.publish(&event);
+}
+
+/// Publishes the events of a committed unit of work, returns the added and updated bookmarks
+pub fn publish_all(events: Vec<BookmarkEvent>) -> Vec<Bookmark> {
+    events
+        .into_iter()
+        .filter_map(|event| {
+            publish(event.clone());
+            match event {
+                BookmarkEvent::Added(bm) | BookmarkEvent::Updated(bm) => Some(bm),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    #[rstest]
+    fn test_publish_reaches_all_subscribers() {
+        let bus = EventBus::default();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        for name in ["first", "second"] {
+            let received = Arc::clone(&received);
+            bus.subscribe(move |event| {
+                received
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", name, event.bookmark().id))
+            });
+        }
+
+        bus.publish(&BookmarkEvent::Added(BookmarkBuilder::new().id(7).build()));
+        assert_eq!(*received.lock().unwrap(), vec!["first 7", "second 7"]);
+    }
+}
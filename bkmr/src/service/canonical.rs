@@ -0,0 +1,56 @@
+use reqwest::Url;
+use select::document::Document;
+use select::predicate::{Attr, Name, Predicate};
+use tracing::debug;
+
+/// URL the page names as its canonical one: `<link rel="canonical">`, otherwise `og:url`.
+/// Relative URLs are resolved against the page, None if it is the page URL itself or not http(s).
+pub fn parse_canonical_url(url: &str, body: &str) -> Option<String> {
+    let document = Document::from(body);
+    let canonical = document
+        .find(Name("link").and(Attr("rel", "canonical")))
+        .find_map(|node| node.attr("href"))
+        .or_else(|| {
+            document
+                .find(Name("meta").and(Attr("property", "og:url")))
+                .find_map(|node| node.attr("content"))
+        })?
+        .trim();
+    let resolved = Url::parse(url).ok()?.join(canonical).ok()?;
+    if !matches!(resolved.scheme(), "http" | "https") {
+        return None;
+    }
+    let resolved = resolved.to_string();
+    debug!("Canonical URL of {}: {}", url, resolved);
+    let page = Url::parse(url).ok()?.to_string();
+    (resolved != page).then_some(resolved)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(
+        r#"<html><head><link rel="canonical" href="https://example.com/article"></head></html>"#,
+        Some("https://example.com/article")
+    )]
+    #[case(
+        r#"<html><head><meta property="og:url" content="https://example.com/og"></head></html>"#,
+        Some("https://example.com/og")
+    )]
+    #[case(
+        r#"<link rel="canonical" href="https://example.com/link"><meta property="og:url" content="https://example.com/og">"#,
+        Some("https://example.com/link")
+    )]
+    #[case(r#"<link rel="canonical" href="/article">"#, Some("https://example.com/article"))]
+    #[case(r#"<link rel="canonical" href="https://example.com/article?utm_source=x">"#, None)]
+    #[case(r#"<link rel="canonical" href="javascript:void(0)">"#, None)]
+    #[case("<html><head><title>No canonical</title></head></html>", None)]
+    fn test_parse_canonical_url(#[case] body: &str, #[case] expected: Option<&str>) {
+        let url = "https://example.com/article?utm_source=x";
+        assert_eq!(parse_canonical_url(url, body).as_deref(), expected);
+    }
+}
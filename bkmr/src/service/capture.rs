@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::thread;
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+use regex::Regex;
+use tracing::debug;
+
+use crate::load_url_details;
+use crate::util::helper::require_input;
+
+/// Unique http(s) URLs of the text in order of appearance, trailing punctuation removed
+pub fn extract_urls(text: &str) -> Vec<String> {
+    let url_re = Regex::new(r#"https?://[^\s<>"'`]+"#).unwrap();
+    url_re
+        .find_iter(text)
+        .map(|m| {
+            let mut url = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            // closing bracket is part of the URL only if it has an opening one, e.g. wikipedia
+            while let Some(stripped) = url.strip_suffix([')', ']', '>']) {
+                if url.matches('(').count() >= url.matches(')').count()
+                    && url.matches('[').count() >= url.matches(']').count()
+                {
+                    break;
+                }
+                url = stripped;
+            }
+            url.to_string()
+        })
+        .unique()
+        .collect()
+}
+
+/// Fetches the titles concurrently, empty title if the URL cannot be loaded
+pub fn fetch_titles(urls: &[String]) -> Vec<(String, String)> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = urls
+            .iter()
+            .map(|url| scope.spawn(move || load_url_details(url).map(|(title, _, _)| title)))
+            .collect();
+        urls.iter()
+            .zip(handles)
+            .map(|(url, handle)| {
+                let title = handle.join().ok().and_then(|r| r.ok()).unwrap_or_default();
+                debug!("{}: {:?}", url, title);
+                (url.clone(), title)
+            })
+            .collect()
+    })
+}
+
+/// Parses a selection like `1,3-5` or `all` of n items into zero based indices
+pub fn parse_selection(input: &str, n: usize) -> Result<Vec<usize>> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("all") || input.eq_ignore_ascii_case("a") {
+        return Ok((0..n).collect());
+    }
+    let mut selected = Vec::new();
+    for part in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        let parse = |s: &str| -> Result<usize> {
+            s.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|i| (1..=n).contains(i))
+                .ok_or_else(|| anyhow!("Invalid selection {:?}, use numbers 1-{}", part, n))
+        };
+        selected.extend(parse(start)? - 1..parse(end)?);
+    }
+    Ok(selected.into_iter().unique().collect())
+}
+
+/// Reads the answer from the terminal, since stdin may carry the captured text
+pub fn prompt_tty(prompt: &str) -> Result<String> {
+    require_input(prompt);
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+    let tty = File::open("/dev/tty").context("No terminal for selection, use --all")?;
+    let mut answer = String::new();
+    BufReader::new(tty).read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_extract_urls() {
+        let text = "see https://example.com/a. And (https://example.com/b) or\n\
+                    <https://en.wikipedia.org/wiki/Rust_(programming_language)>, again https://example.com/a";
+        assert_eq!(
+            extract_urls(text),
+            vec![
+                "https://example.com/a",
+                "https://example.com/b",
+                "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("all", vec![0, 1, 2, 3])]
+    #[case("", vec![])]
+    #[case("1, 3-4,1", vec![0, 2, 3])]
+    fn test_parse_selection(#[case] input: &str, #[case] expected: Vec<usize>) {
+        assert_eq!(parse_selection(input, 4).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("0")]
+    #[case("5")]
+    #[case("x")]
+    fn test_parse_selection_invalid(#[case] input: &str) {
+        assert!(parse_selection(input, 4).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use reqwest::StatusCode;
+use tracing::{debug, instrument};
+
+use crate::model::bookmark::{Bookmark, BookmarkBuilder};
+
+pub const TLDR_BASE_URL: &str = "https://raw.githubusercontent.com/tldr-pages/tldr/main/pages";
+/// Tag of all imported tldr pages, in addition to the command name and `_snip_`
+pub const TLDR_TAG: &str = "tldr";
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CheatSource {
+    /// https://tldr.sh community pages
+    Tldr,
+}
+
+/// Page names are lowercase with dashes: `git commit` -> `git-commit`
+pub fn page_name(command: &str) -> String {
+    command
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// tldr lookup order: pages of the current OS before the common ones
+pub fn default_platforms() -> Vec<&'static str> {
+    let os = if cfg!(target_os = "macos") {
+        "osx"
+    } else if cfg!(target_os = "windows") {
+        "windows"
+    } else {
+        "linux"
+    };
+    vec![os, "common"]
+}
+
+/// Markdown of the first platform having the page
+#[instrument(level = "debug")]
+pub fn fetch_tldr(base_url: &str, name: &str, platforms: &[&str]) -> Result<String> {
+    let client = reqwest::blocking::Client::new();
+    for platform in platforms {
+        let url = format!("{}/{}/{}.md", base_url.trim_end_matches('/'), platform, name);
+        let response = client
+            .get(&url)
+            .send()
+            .with_context(|| format!("Failed to fetch {}", url))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            debug!("No page {}", url);
+            continue;
+        }
+        return response
+            .error_for_status()
+            .and_then(|response| response.text())
+            .with_context(|| format!("Failed to fetch {}", url));
+    }
+    Err(anyhow!("No tldr page for {} ({})", name, platforms.join(", ")))
+}
+
+/// Description of a tldr page: the `>` lines without the "More information" link
+pub fn tldr_description(page: &str) -> String {
+    page.lines()
+        .filter_map(|line| line.strip_prefix("> "))
+        .filter(|line| !line.starts_with("More information"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Markdown snippet bookmark of the page, tagged with the command name
+pub fn tldr_bookmark(name: &str, page: &str) -> Bookmark {
+    let mut bm = BookmarkBuilder::new()
+        .id(1)
+        .URL(page.trim().to_string())
+        .metadata(format!("tldr {}", name))
+        .desc(tldr_description(page))
+        .flags(0)
+        .language(Some("markdown".to_string()))
+        .build();
+    bm.set_tags(vec![
+        "_snip_".to_string(),
+        TLDR_TAG.to_string(),
+        name.to_string(),
+    ]);
+    bm
+}
+
+/// Previously imported page of the command, replaced on re-import
+pub fn find_imported<'a>(bms: &'a [Bookmark], name: &str) -> Option<&'a Bookmark> {
+    let title = format!("tldr {}", name);
+    bms.iter().find(|bm| {
+        bm.metadata == title && bm.get_tags().iter().any(|tag| tag == TLDR_TAG)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use rstest::*;
+
+    use super::*;
+
+    const PAGE: &str = indoc! {"
+        # tar
+
+        > Archiving utility.
+        > Often combined with a compression method, such as gzip or bzip2.
+        > More information: <https://www.gnu.org/software/tar>.
+
+        - [c]reate an archive and write it to a [f]ile:
+
+        `tar cf {{path/to/target.tar}} {{path/to/file1 path/to/file2 ...}}`
+    "};
+
+    #[rstest]
+    fn test_page_name() {
+        assert_eq!(page_name("Git  commit"), "git-commit");
+        assert_eq!(page_name("tar"), "tar");
+    }
+
+    #[rstest]
+    fn test_tldr_bookmark() {
+        let bm = tldr_bookmark("tar", PAGE);
+        assert_eq!(bm.metadata, "tldr tar");
+        assert_eq!(
+            bm.desc,
+            "Archiving utility. Often combined with a compression method, such as gzip or bzip2."
+        );
+        assert_eq!(bm.tags, ",_snip_,tar,tldr,");
+        assert!(bm.URL.starts_with("# tar"));
+        let bms = vec![bm];
+        assert_eq!(find_imported(&bms, "tar"), Some(&bms[0]));
+        assert_eq!(find_imported(&bms, "git"), None);
+    }
+
+    #[rstest]
+    fn test_fetch_tldr_falls_back_to_common() -> Result<()> {
+        let mut server = mockito::Server::new();
+        let linux = server.mock("GET", "/linux/tar.md").with_status(404).create();
+        let common = server
+            .mock("GET", "/common/tar.md")
+            .with_body(PAGE)
+            .create();
+
+        assert_eq!(fetch_tldr(&server.url(), "tar", &["linux", "common"])?, PAGE);
+        linux.assert();
+        common.assert();
+
+        server.mock("GET", "/common/nope.md").with_status(404).create();
+        assert!(fetch_tldr(&server.url(), "nope", &["common"]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::{anyhow, Context, Result};
+use arboard::Clipboard;
+use clap::ValueEnum;
+use regex::Regex;
+
+use crate::model::bookmark::Bookmark;
+use crate::util::helper::interpolate_env;
+
+/// Kind of clipboard content, decides how a new bookmark is prefilled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipKind {
+    /// single http(s) URL, title and description are fetched from the web
+    Url,
+    /// markdown text, title from the first heading
+    Markdown,
+    /// anything else, title from the first line
+    Snippet,
+}
+
+impl ClipKind {
+    /// System tag of the bookmark type, URLs are plain bookmarks
+    pub fn type_tag(&self) -> Option<&'static str> {
+        match self {
+            ClipKind::Url => None,
+            ClipKind::Markdown => Some("_md_"),
+            ClipKind::Snippet => Some("_snip_"),
+        }
+    }
+}
+
+pub fn classify(text: &str) -> ClipKind {
+    let text = text.trim();
+    let url_re = Regex::new(r"^https?://\S+$").unwrap();
+    let markdown_re = Regex::new(r"(?m)^(#{1,6} \S|[-*] \[[ x]\] |```)|\[[^\]]+\]\([^)]+\)").unwrap();
+
+    if url_re.is_match(text) {
+        ClipKind::Url
+    } else if markdown_re.is_match(text) {
+        ClipKind::Markdown
+    } else {
+        ClipKind::Snippet
+    }
+}
+
+/// Title proposal: first markdown heading or first non-empty line, None for URLs
+pub fn suggest_title(text: &str, kind: ClipKind) -> Option<String> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    match kind {
+        ClipKind::Url => None,
+        ClipKind::Markdown => text
+            .lines()
+            .find(|l| l.starts_with('#'))
+            .map(|l| l.trim_start_matches('#').trim().to_string())
+            .or_else(|| lines.next().map(String::from)),
+        ClipKind::Snippet => lines.next().map(String::from),
+    }
+}
+
+pub fn read_clipboard() -> Result<String> {
+    let text = Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .context("Failed to read clipboard")?;
+    if text.trim().is_empty() {
+        return Err(anyhow!("Clipboard is empty"));
+    }
+    Ok(text)
+}
+
+pub fn write_clipboard(text: &str) -> Result<()> {
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .context("Failed to write clipboard")
+}
+
+/// Clipboard payload of `bkmr copy`
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum CopyFormat {
+    /// URL or snippet content as stored
+    #[default]
+    Raw,
+    /// with `$VAR` and `${VAR}` replaced by environment variables
+    Interpolated,
+    /// `[title](url)`
+    MdLink,
+    /// `[[url][title]]`
+    OrgLink,
+    /// `<a href="url">title</a>`
+    HtmlLink,
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Payload of the bookmark in the format, links fall back to the URL as title
+pub fn copy_payload(bm: &Bookmark, format: CopyFormat) -> Result<String> {
+    let title = if bm.metadata.trim().is_empty() { bm.URL.trim() } else { bm.metadata.trim() };
+    let payload = match format {
+        CopyFormat::Raw => bm.URL.clone(),
+        CopyFormat::Interpolated => interpolate_env(&bm.URL)?,
+        CopyFormat::MdLink => format!(
+            "[{}]({})",
+            title.replace('[', "\\[").replace(']', "\\]"),
+            bm.URL.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+        ),
+        // org descriptions cannot contain brackets
+        CopyFormat::OrgLink => format!(
+            "[[{}][{}]]",
+            bm.URL.replace('[', "%5B").replace(']', "%5D"),
+            title.replace('[', "{").replace(']', "}")
+        ),
+        CopyFormat::HtmlLink => format!("<a href=\"{}\">{}</a>", escape_html(&bm.URL), escape_html(title)),
+    };
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    #[rstest]
+    #[case(CopyFormat::Raw, "https://example.com/a (b)")]
+    #[case(CopyFormat::MdLink, "[Rust \\[book\\] & more](https://example.com/a%20%28b%29)")]
+    #[case(CopyFormat::OrgLink, "[[https://example.com/a (b)][Rust {book} & more]]")]
+    #[case(CopyFormat::HtmlLink, "<a href=\"https://example.com/a (b)\">Rust [book] &amp; more</a>")]
+    fn test_copy_payload(#[case] format: CopyFormat, #[case] expected: &str) {
+        let bm = BookmarkBuilder::new()
+            .URL("https://example.com/a (b)".to_string())
+            .metadata("Rust [book] & more".to_string())
+            .build();
+        assert_eq!(copy_payload(&bm, format).unwrap(), expected);
+    }
+
+    #[rstest]
+    fn test_copy_payload_interpolated() {
+        std::env::set_var("BKMR_TEST_COPY_HOST", "db.example.com");
+        let bm = BookmarkBuilder::new().URL("psql -h ${BKMR_TEST_COPY_HOST}".to_string()).build();
+        assert_eq!(copy_payload(&bm, CopyFormat::Interpolated).unwrap(), "psql -h db.example.com");
+        assert_eq!(copy_payload(&bm, CopyFormat::MdLink).unwrap(), "[psql -h ${BKMR_TEST_COPY_HOST}](psql%20-h%20${BKMR_TEST_COPY_HOST})");
+        std::env::remove_var("BKMR_TEST_COPY_HOST");
+    }
+
+    #[rstest]
+    #[case(ClipKind::Url, None)]
+    #[case(ClipKind::Markdown, Some("_md_"))]
+    #[case(ClipKind::Snippet, Some("_snip_"))]
+    fn test_type_tag(#[case] kind: ClipKind, #[case] expected: Option<&str>) {
+        assert_eq!(kind.type_tag(), expected);
+        // builtin types
+        let builtin = crate::model::system_tag::SystemTags::new(&Default::default()).unwrap();
+        assert!(expected.is_none_or(|tag| builtin.get(tag).is_some()));
+    }
+
+    #[rstest]
+    #[case("  https://example.com/a?b=1\n", ClipKind::Url)]
+    #[case("https://example.com and more", ClipKind::Snippet)]
+    #[case("# Notes\n\nsome text", ClipKind::Markdown)]
+    #[case("see [docs](https://docs.rs)", ClipKind::Markdown)]
+    #[case("- [ ] todo", ClipKind::Markdown)]
+    #[case("fn main() {\n    println!(\"#hi\");\n}", ClipKind::Snippet)]
+    fn test_classify(#[case] text: &str, #[case] expected: ClipKind) {
+        assert_eq!(classify(text), expected);
+    }
+
+    #[rstest]
+    #[case("https://example.com", ClipKind::Url, None)]
+    #[case("intro\n## Setup\ntext", ClipKind::Markdown, Some("Setup"))]
+    #[case("\n  ls -la | sort\n", ClipKind::Snippet, Some("ls -la | sort"))]
+    fn test_suggest_title(#[case] text: &str, #[case] kind: ClipKind, #[case] expected: Option<&str>) {
+        assert_eq!(suggest_title(text, kind).as_deref(), expected);
+    }
+}
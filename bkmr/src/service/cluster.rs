@@ -0,0 +1,279 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use ndarray::Array1;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use tracing::debug;
+
+use crate::adapter::embeddings::deserialize_embedding;
+use crate::model::bookmark::Bookmark;
+use crate::model::system_tag::is_system_tag;
+
+/// Fixed seed, so that repeated runs print the same clusters
+const SEED: u64 = 42;
+const MAX_ITERATIONS: usize = 100;
+const LABEL_TAGS: usize = 3;
+const MIN_WORD_LENGTH: usize = 4;
+
+const STOPWORDS: [&str; 24] = [
+    "about", "after", "also", "away", "been", "best", "from", "have", "here", "into", "more",
+    "most", "only", "other", "over", "some", "than", "that", "their", "there", "this", "what",
+    "when", "with",
+];
+
+/// Bookmark closest to the cluster centre
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Representative {
+    pub id: i32,
+    pub title: String,
+    pub similarity: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cluster {
+    pub size: usize,
+    /// most frequent regular tags and their counts
+    pub tags: Vec<(String, usize)>,
+    /// frequent title words of the members which are no tags yet
+    pub candidate_tags: Vec<String>,
+    pub representatives: Vec<Representative>,
+    pub ids: Vec<i32>,
+}
+
+impl Cluster {
+    pub fn label(&self) -> String {
+        if self.tags.is_empty() {
+            "(untagged)".to_string()
+        } else {
+            self.tags.iter().map(|(tag, _)| tag.as_str()).join(", ")
+        }
+    }
+}
+
+/// Default number of clusters for n bookmarks
+pub fn default_k(n: usize) -> usize {
+    (((n as f64) / 2.0).sqrt().round() as usize).clamp(1, n.max(1))
+}
+
+fn normalized(vector: Vec<f32>) -> Array1<f32> {
+    let vector = Array1::from(vector);
+    let norm = vector.dot(&vector).sqrt();
+    if norm == 0.0 {
+        vector
+    } else {
+        vector / norm
+    }
+}
+
+/// Spherical k-means on unit vectors with k-means++ initialization, the cluster index per vector
+pub fn kmeans(vectors: &[Array1<f32>], k: usize, seed: u64) -> Vec<usize> {
+    if vectors.is_empty() || k == 0 {
+        return Vec::new();
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut centroids = vec![vectors[rng.gen_range(0..vectors.len())].clone()];
+    while centroids.len() < k.min(vectors.len()) {
+        // the farther from the chosen centroids, the likelier the next one
+        let weights: Vec<f32> = vectors
+            .iter()
+            .map(|v| {
+                let best = centroids.iter().map(|c| c.dot(v)).fold(f32::MIN, f32::max);
+                (1.0 - best).max(0.0).powi(2)
+            })
+            .collect();
+        let next = match WeightedIndex::new(&weights) {
+            Ok(distribution) => distribution.sample(&mut rng),
+            // all vectors coincide with centroids
+            Err(_) => rng.gen_range(0..vectors.len()),
+        };
+        centroids.push(vectors[next].clone());
+    }
+
+    let nearest = |v: &Array1<f32>, centroids: &[Array1<f32>]| {
+        centroids
+            .iter()
+            .map(|c| c.dot(v))
+            .enumerate()
+            .fold((0, f32::MIN), |best, (i, s)| if s > best.1 { (i, s) } else { best })
+            .0
+    };
+    let mut assignments: Vec<usize> = vectors.iter().map(|v| nearest(v, &centroids)).collect();
+    for iteration in 0..MAX_ITERATIONS {
+        for (i, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&Array1<f32>> = vectors
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, a)| **a == i)
+                .map(|(v, _)| v)
+                .collect();
+            // empty clusters keep their centroid
+            if let Some(first) = members.first() {
+                let sum = members[1..].iter().fold((*first).clone(), |sum, v| sum + *v);
+                *centroid = normalized(sum.to_vec());
+            }
+        }
+        let next: Vec<usize> = vectors.iter().map(|v| nearest(v, &centroids)).collect();
+        if next == assignments {
+            debug!("converged after {} iterations", iteration);
+            break;
+        }
+        assignments = next;
+    }
+    assignments
+}
+
+fn top_tags(members: &[&Bookmark]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for tag in members.iter().flat_map(|bm| bm.get_tags()).filter(|t| !is_system_tag(t)) {
+        *counts.entry(tag).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .sorted_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)))
+        .take(LABEL_TAGS)
+        .collect()
+}
+
+/// Title words used by at least two members, not yet a tag of any member
+fn candidate_tags(members: &[&Bookmark]) -> Vec<String> {
+    let existing: HashSet<String> = members.iter().flat_map(|bm| bm.get_tags()).collect();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for bm in members {
+        let words: HashSet<String> = bm
+            .metadata
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .filter(|w| w.chars().count() >= MIN_WORD_LENGTH && !w.chars().all(|c| c.is_numeric()))
+            .filter(|w| !STOPWORDS.contains(&w.as_str()) && !existing.contains(w))
+            .collect();
+        for word in words {
+            *counts.entry(word).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count >= 2)
+        .sorted_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)))
+        .take(LABEL_TAGS)
+        .map(|(word, _)| word)
+        .collect()
+}
+
+/// Clusters of the embedded bookmarks, largest first, and the number of bookmarks skipped
+/// because they have no embedding or one of another dimension than the majority
+pub fn cluster(bms: &[Bookmark], k: Option<usize>, n_representatives: usize) -> Result<(Vec<Cluster>, usize)> {
+    let mut embedded: Vec<(&Bookmark, Vec<f32>)> = Vec::new();
+    for bm in bms {
+        if let Some(bytes) = &bm.embedding {
+            embedded.push((bm, deserialize_embedding(bytes.clone())?));
+        }
+    }
+    let dim = embedded.iter().map(|(_, v)| v.len()).counts().into_iter().max_by_key(|(_, n)| *n);
+    let dim = dim.map(|(dim, _)| dim).ok_or_else(|| anyhow!("No embedded bookmarks, run backfill --openai first"))?;
+    embedded.retain(|(_, v)| v.len() == dim);
+    let skipped = bms.len() - embedded.len();
+
+    let k = k.unwrap_or_else(|| default_k(embedded.len()));
+    if k == 0 || k > embedded.len() {
+        return Err(anyhow!("Cannot build {} clusters of {} embedded bookmarks", k, embedded.len()));
+    }
+    let (members, vectors): (Vec<&Bookmark>, Vec<Array1<f32>>) =
+        embedded.into_iter().map(|(bm, v)| (bm, normalized(v))).unzip();
+    let assignments = kmeans(&vectors, k, SEED);
+
+    let mut clusters: Vec<Cluster> = (0..k)
+        .filter_map(|i| {
+            let indices: Vec<usize> = (0..members.len()).filter(|j| assignments[*j] == i).collect();
+            if indices.is_empty() {
+                return None;
+            }
+            let group: Vec<&Bookmark> = indices.iter().map(|j| members[*j]).collect();
+            let sum = indices.iter().fold(Array1::zeros(dim), |sum, j| sum + &vectors[*j]);
+            let centre = normalized(sum.to_vec());
+            let representatives = indices
+                .iter()
+                .map(|j| Representative {
+                    id: members[*j].id,
+                    title: members[*j].metadata.clone(),
+                    similarity: centre.dot(&vectors[*j]),
+                })
+                .sorted_by(|a, b| b.similarity.total_cmp(&a.similarity))
+                .take(n_representatives)
+                .collect();
+            Some(Cluster {
+                size: group.len(),
+                tags: top_tags(&group),
+                candidate_tags: candidate_tags(&group),
+                representatives,
+                ids: group.iter().map(|bm| bm.id).collect(),
+            })
+        })
+        .collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.size));
+    Ok((clusters, skipped))
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::adapter::embeddings::serialize_embedding;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn bm(id: i32, title: &str, tags: &str, embedding: Option<Vec<f32>>) -> Bookmark {
+        BookmarkBuilder::new()
+            .id(id)
+            .URL(format!("https://example.com/{}", id))
+            .metadata(title.to_string())
+            .tags(tags.to_string())
+            .embedding(embedding.map(|v| serialize_embedding(v).unwrap()))
+            .build()
+    }
+
+    fn bms() -> Vec<Bookmark> {
+        vec![
+            bm(1, "Tokio runtime guide", ",rust,", Some(vec![1.0, 0.1, 0.0])),
+            bm(2, "Async runtime internals", ",rust,", Some(vec![0.9, 0.2, 0.0])),
+            bm(3, "Rust runtime comparison", "", Some(vec![1.0, 0.0, 0.1])),
+            bm(4, "Sourdough bread", ",baking,", Some(vec![0.0, 0.1, 1.0])),
+            bm(5, "Bread hydration", ",baking,", Some(vec![0.1, 0.0, 0.9])),
+            bm(6, "Not embedded", ",rust,", None),
+            bm(7, "Other model", ",rust,", Some(vec![1.0, 0.0])),
+        ]
+    }
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(2, 1)]
+    #[case(50, 5)]
+    #[case(1000, 22)]
+    fn test_default_k(#[case] n: usize, #[case] expected: usize) {
+        assert_eq!(default_k(n), expected);
+    }
+
+    #[rstest]
+    fn test_cluster() -> Result<()> {
+        let (clusters, skipped) = cluster(&bms(), Some(2), 2)?;
+        assert_eq!(skipped, 2);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].ids, vec![1, 2, 3]);
+        assert_eq!(clusters[0].label(), "rust");
+        assert_eq!(clusters[0].tags, vec![("rust".to_string(), 2)]);
+        assert_eq!(clusters[0].candidate_tags, vec!["runtime"]);
+        assert_eq!(clusters[0].representatives.len(), 2);
+        assert_eq!(clusters[1].ids, vec![4, 5]);
+        assert_eq!(clusters[1].candidate_tags, vec!["bread"]);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_cluster_errors() {
+        assert!(cluster(&bms()[5..6], None, 3).is_err());
+        assert!(cluster(&bms(), Some(10), 3).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDateTime;
+
+use crate::model::bookmark::Bookmark;
+use crate::model::readable::Readable;
+use crate::model::system_tag::is_system_tag;
+
+/// Group of bookmarks without a regular tag
+pub const UNTAGGED: &str = "untagged";
+/// Characters of the readable text sent for a summary, bounds the cost per bookmark
+const MAX_SUMMARY_TEXT: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DigestFormat {
+    #[default]
+    Text,
+    Markdown,
+    /// plain text mail with subject header, e.g. for `| sendmail me@example.com`
+    Email,
+}
+
+/// Bookmarks created since the cutoff, oldest first
+pub fn added_since(bms: &[Bookmark], cutoff: NaiveDateTime) -> Vec<Bookmark> {
+    let mut added: Vec<Bookmark> = bms
+        .iter()
+        .filter(|bm| bm.created_ts.is_some_and(|ts| ts >= cutoff))
+        .cloned()
+        .collect();
+    added.sort_by_key(|bm| (bm.created_ts, bm.id));
+    added
+}
+
+/// Bookmarks by regular tag, listed under each of their tags, largest groups first
+pub fn group_by_tag(bms: &[Bookmark]) -> Vec<(String, Vec<&Bookmark>)> {
+    let mut groups: BTreeMap<String, Vec<&Bookmark>> = BTreeMap::new();
+    for bm in bms {
+        let tags: Vec<String> = bm.get_tags().into_iter().filter(|tag| !is_system_tag(tag)).collect();
+        if tags.is_empty() {
+            groups.entry(UNTAGGED.to_string()).or_default().push(bm);
+        }
+        for tag in tags {
+            groups.entry(tag).or_default().push(bm);
+        }
+    }
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+    groups
+}
+
+/// Text to summarize, with the beginning of the readable text of the page if fetched
+pub fn summary_input(bm: &Bookmark, readable: Option<&Readable>) -> String {
+    let mut text = format!("{}\n{}\n{}", bm.metadata, bm.desc, bm.URL);
+    if let Some(readable) = readable {
+        text.push_str("\n\n");
+        text.extend(readable.text.chars().take(MAX_SUMMARY_TEXT));
+    }
+    text
+}
+
+/// Title of the digest entry, snippets and other bookmarks without title show the first URL line
+fn title(bm: &Bookmark) -> &str {
+    if bm.metadata.trim().is_empty() {
+        bm.URL.lines().next().unwrap_or_default()
+    } else {
+        bm.metadata.trim()
+    }
+}
+
+/// Digest of the added bookmarks, summaries by bookmark id are added below the titles
+pub fn render(
+    bms: &[Bookmark],
+    since: &str,
+    summaries: &HashMap<i32, String>,
+    format: DigestFormat,
+) -> String {
+    let heading = format!("bkmr digest: {} bookmarks added since {}", bms.len(), since);
+    let url = |bm: &Bookmark| bm.URL.lines().next().unwrap_or_default().to_string();
+    let mut out = String::new();
+    match format {
+        DigestFormat::Markdown => {
+            out.push_str(&format!("# {}\n", heading));
+            for (tag, group) in group_by_tag(bms) {
+                out.push_str(&format!("\n## {}\n\n", tag));
+                for bm in group {
+                    out.push_str(&format!("- [{}]({})", title(bm), url(bm)));
+                    if let Some(summary) = summaries.get(&bm.id) {
+                        out.push_str(&format!(": {}", summary));
+                    }
+                    out.push('\n');
+                }
+            }
+        }
+        DigestFormat::Text | DigestFormat::Email => {
+            if format == DigestFormat::Email {
+                out.push_str(&format!(
+                    "Subject: {}\nMIME-Version: 1.0\nContent-Type: text/plain; charset=utf-8\n\n",
+                    heading
+                ));
+            }
+            out.push_str(&format!("{}\n", heading));
+            for (tag, group) in group_by_tag(bms) {
+                out.push_str(&format!("\n{} ({})\n", tag, group.len()));
+                for bm in group {
+                    out.push_str(&format!("  {} [{}]\n    {}\n", title(bm), bm.id, url(bm)));
+                    if let Some(summary) = summaries.get(&bm.id) {
+                        out.push_str(&format!("    {}\n", summary));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn day(d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap().and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    fn bm(id: i32, title: &str, tags: &str, created: u32) -> Bookmark {
+        Bookmark {
+            created_ts: Some(day(created)),
+            ..BookmarkBuilder::new()
+                .id(id)
+                .URL(format!("https://example.com/{}", id))
+                .metadata(title.to_string())
+                .tags(tags.to_string())
+                .build()
+        }
+    }
+
+    fn bms() -> Vec<Bookmark> {
+        vec![
+            bm(1, "Old", ",rust,", 1),
+            bm(3, "Tokio", ",async,rust,", 9),
+            bm(2, "Serde", ",rust,", 8),
+            bm(4, "", ",_snip_,", 10),
+        ]
+    }
+
+    #[rstest]
+    fn test_added_since() {
+        let ids: Vec<i32> = added_since(&bms(), day(7)).iter().map(|bm| bm.id).collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+    }
+
+    #[rstest]
+    fn test_group_by_tag() {
+        let added = added_since(&bms(), day(7));
+        let groups: Vec<(String, Vec<i32>)> = group_by_tag(&added)
+            .into_iter()
+            .map(|(tag, group)| (tag, group.iter().map(|bm| bm.id).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("rust".to_string(), vec![2, 3]),
+                ("async".to_string(), vec![3]),
+                ("untagged".to_string(), vec![4]),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_summary_input() {
+        let bm = bm(1, "Tokio", ",rust,", 1);
+        assert_eq!(summary_input(&bm, None), "Tokio\n\nhttps://example.com/1");
+        let readable = Readable {
+            uuid: bm.uuid.clone(),
+            title: "Tokio".to_string(),
+            byline: String::new(),
+            text: "x".repeat(MAX_SUMMARY_TEXT + 10),
+            fetched_ts: day(1),
+        };
+        assert_eq!(
+            summary_input(&bm, Some(&readable)),
+            format!("Tokio\n\nhttps://example.com/1\n\n{}", "x".repeat(MAX_SUMMARY_TEXT))
+        );
+    }
+
+    #[rstest]
+    fn test_render_markdown() {
+        let added = added_since(&bms(), day(9));
+        let summaries = HashMap::from([(3, "Async runtime.".to_string())]);
+        assert_eq!(
+            render(&added, "2026-10-09", &summaries, DigestFormat::Markdown),
+            "# bkmr digest: 2 bookmarks added since 2026-10-09\n\n\
+             ## async\n\n- [Tokio](https://example.com/3): Async runtime.\n\n\
+             ## rust\n\n- [Tokio](https://example.com/3): Async runtime.\n\n\
+             ## untagged\n\n- [https://example.com/4](https://example.com/4)\n"
+        );
+    }
+
+    #[rstest]
+    fn test_render_email() {
+        let added = added_since(&bms(), day(10));
+        let mail = render(&added, "2026-10-10", &HashMap::new(), DigestFormat::Email);
+        assert!(mail.starts_with("Subject: bkmr digest: 1 bookmarks added since 2026-10-10\n"));
+        assert!(mail.contains("\n\nbkmr digest"));
+        assert!(mail.contains("untagged (1)\n  https://example.com/4 [4]\n"));
+    }
+}
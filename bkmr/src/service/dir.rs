@@ -0,0 +1,106 @@
+use std::fs;
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use indoc::indoc;
+use tracing::debug;
+
+use crate::util::helper::abspath;
+
+/// Set by the shell function of `bkmr shell-init`, receives the directory to cd into
+pub const CD_FILE_ENV: &str = "BKMR_CD_FILE";
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Shell function wrapping bkmr: opening a `_dir_` bookmark changes the directory of the shell
+pub fn shell_init(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash | Shell::Zsh => indoc! {r#"
+            bkmr() {
+                local cd_file
+                cd_file="$(mktemp)"
+                BKMR_CD_FILE="$cd_file" command bkmr "$@"
+                local status=$?
+                if [ -s "$cd_file" ]; then
+                    cd "$(cat "$cd_file")" || status=$?
+                fi
+                rm -f "$cd_file"
+                return $status
+            }
+        "#},
+        Shell::Fish => indoc! {r#"
+            function bkmr
+                set -l cd_file (mktemp)
+                BKMR_CD_FILE=$cd_file command bkmr $argv
+                set -l status_code $status
+                if test -s $cd_file
+                    cd (cat $cd_file)
+                end
+                rm -f $cd_file
+                return $status_code
+            end
+        "#},
+    }
+}
+
+/// Hands the directory to the shell function if present, otherwise opens the file manager
+pub fn open_dir(uri: &str) -> Result<()> {
+    let dir = abspath(uri).ok_or_else(|| anyhow!("Directory not found: {}", uri))?;
+    if !fs::metadata(&dir)?.is_dir() {
+        return Err(anyhow!("Not a directory: {}", dir));
+    }
+    match std::env::var(CD_FILE_ENV) {
+        Ok(cd_file) => {
+            debug!("cd {:?} via {:?}", dir, cd_file);
+            fs::write(&cd_file, &dir).with_context(|| format!("Failed to write {}", cd_file))
+        }
+        Err(_) => open::that(&dir).with_context(|| format!("Error OS opening {}", dir)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+    use serial_test::serial;
+
+    use super::*;
+
+    #[rstest]
+    #[serial]
+    fn test_open_dir_writes_cd_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let cd_file = dir.path().join("cd");
+        std::env::set_var(CD_FILE_ENV, &cd_file);
+        let result = open_dir(dir.path().to_str().unwrap());
+        std::env::remove_var(CD_FILE_ENV);
+
+        result.unwrap();
+        let target = fs::read_to_string(cd_file).unwrap();
+        assert_eq!(
+            fs::canonicalize(target).unwrap(),
+            fs::canonicalize(dir.path()).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[serial]
+    fn test_open_dir_rejects_files() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::env::set_var(CD_FILE_ENV, "/dev/null");
+        let result = open_dir(file.path().to_str().unwrap());
+        std::env::remove_var(CD_FILE_ENV);
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    #[case(Shell::Bash)]
+    #[case(Shell::Fish)]
+    fn test_shell_init(#[case] shell: Shell) {
+        assert!(shell_init(shell).contains("BKMR_CD_FILE"));
+    }
+}
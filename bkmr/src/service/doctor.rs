@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Result;
+use tracing::{debug, instrument};
+
+use crate::adapter::dal::Dal;
+use crate::adapter::embeddings::deserialize_embedding;
+use crate::model::bookmark::Bookmark;
+use crate::model::tag::Tags;
+use crate::util::helper::abspath;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Check {
+    Integrity,
+    FtsIndex,
+    MissingFile,
+    Embedding,
+    Tags,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Check::Integrity => "integrity",
+            Check::FtsIndex => "fts",
+            Check::MissingFile => "file",
+            Check::Embedding => "embedding",
+            Check::Tags => "tags",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Finding {
+    pub check: Check,
+    pub id: Option<i32>,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(check: Check, id: Option<i32>, message: String) -> Self {
+        Self { check, id, message }
+    }
+
+    pub fn is_fixable(&self) -> bool {
+        matches!(self.check, Check::FtsIndex | Check::Embedding | Check::Tags)
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.id {
+            Some(id) => write!(f, "[{}] {}: {}", self.check, id, self.message),
+            None => write!(f, "[{}] {}", self.check, self.message),
+        }
+    }
+}
+
+/// URIs which refer to local files, e.g. `/a/b`, `~/a`, `$HOME/a`, `./a`
+fn is_file_uri(uri: &str) -> bool {
+    uri.starts_with(['/', '~', '$', '.'])
+}
+
+/// Runs all checks against the database
+#[instrument(level = "debug")]
+pub fn diagnose(dal: &mut Dal) -> Result<Vec<Finding>> {
+    let mut findings: Vec<Finding> = dal
+        .integrity_check()?
+        .into_iter()
+        .map(|problem| Finding::new(Check::Integrity, None, problem))
+        .collect();
+
+    if let Err(e) = dal.fts_integrity_check() {
+        findings.push(Finding::new(Check::FtsIndex, None, format!("{:#}", e)));
+    }
+    for id in dal.get_fts_missing_ids()? {
+        findings.push(Finding::new(Check::FtsIndex, Some(id), "not in FTS index".to_string()));
+    }
+    for id in dal.get_fts_stale_ids()? {
+        findings.push(Finding::new(Check::FtsIndex, Some(id), "stale FTS index entry".to_string()));
+    }
+
+    let bms = dal.get_bookmarks_with_embeddings("")?;
+    findings.extend(check_files(&bms));
+    findings.extend(check_embeddings(&bms));
+    findings.extend(check_tags(&bms));
+
+    debug!("{:?}", findings);
+    Ok(findings)
+}
+
+fn check_files(bms: &[Bookmark]) -> Vec<Finding> {
+    bms.iter()
+        .filter(|bm| is_file_uri(&bm.URL) && abspath(&bm.URL).is_none())
+        .map(|bm| Finding::new(Check::MissingFile, Some(bm.id), format!("file not found: {}", bm.URL)))
+        .collect()
+}
+
+/// Embeddings which cannot be deserialized or deviate from the most common dimension
+fn check_embeddings(bms: &[Bookmark]) -> Vec<Finding> {
+    let dimensions: Vec<(i32, Option<usize>)> = bms
+        .iter()
+        .filter_map(|bm| {
+            bm.embedding
+                .as_ref()
+                .map(|e| (bm.id, deserialize_embedding(e.clone()).ok().map(|v| v.len())))
+        })
+        .collect();
+
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for dim in dimensions.iter().filter_map(|(_, dim)| *dim) {
+        *counts.entry(dim).or_default() += 1;
+    }
+    let expected = counts.into_iter().max_by_key(|(_, n)| *n).map(|(dim, _)| dim);
+
+    dimensions
+        .into_iter()
+        .filter_map(|(id, dim)| match (dim, expected) {
+            (None, _) => Some(Finding::new(
+                Check::Embedding,
+                Some(id),
+                "embedding cannot be deserialized".to_string(),
+            )),
+            (Some(dim), Some(expected)) if dim != expected => Some(Finding::new(
+                Check::Embedding,
+                Some(id),
+                format!("embedding dimension {} instead of {}", dim, expected),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+fn check_tags(bms: &[Bookmark]) -> Vec<Finding> {
+    bms.iter()
+        .filter_map(|bm| {
+            let normalized = Tags::create_normalized_tag_string(Some(bm.tags.clone()));
+            (bm.tags != normalized).then(|| {
+                Finding::new(
+                    Check::Tags,
+                    Some(bm.id),
+                    format!("malformed tags {:?}, expected {:?}", bm.tags, normalized),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Repairs the fixable findings, returns number of fixed findings
+#[instrument(level = "debug", skip(findings))]
+pub fn repair(dal: &mut Dal, findings: &[Finding]) -> Result<usize> {
+    let fixable: Vec<&Finding> = findings.iter().filter(|f| f.is_fixable()).collect();
+
+    dal.transaction(|dal| {
+        if fixable.iter().any(|f| f.check == Check::FtsIndex) {
+            dal.rebuild_fts()?;
+        }
+
+        for finding in &fixable {
+            let Some(id) = finding.id else { continue };
+            match finding.check {
+                Check::Embedding => {
+                    // cleared embeddings are recreated by `backfill`
+                    let bm = dal.get_bookmark_by_id(id)?;
+                    dal.update_bookmark(Bookmark {
+                        embedding: None,
+                        content_hash: None,
+                        ..bm
+                    })?;
+                }
+                Check::Tags => {
+                    let bm = dal.get_bookmark_by_id(id)?;
+                    let tags = Tags::create_normalized_tag_string(Some(bm.tags.clone()));
+                    dal.update_bookmark(Bookmark { tags, ..bm })?;
+                }
+                _ => {}
+            }
+        }
+        Ok(fixable.len())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use diesel::sql_query;
+    use diesel::RunQueryDsl;
+    use rstest::*;
+
+    use super::*;
+    use crate::adapter::embeddings::serialize_embedding;
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    #[case("/a/b.md", true)]
+    #[case("~/a", true)]
+    #[case("$HOME/a", true)]
+    #[case("./a", true)]
+    #[case("https://www.google.com", false)]
+    #[case("shell::vim", false)]
+    fn test_is_file_uri(#[case] uri: &str, #[case] expected: bool) {
+        assert_eq!(is_file_uri(uri), expected);
+    }
+
+    #[rstest]
+    fn test_check_embeddings() {
+        let bm = |id, embedding| Bookmark {
+            id,
+            embedding,
+            ..Default::default()
+        };
+        let bms = vec![
+            bm(1, Some(serialize_embedding(vec![0.1, 0.2]).unwrap())),
+            bm(2, Some(serialize_embedding(vec![0.3, 0.4]).unwrap())),
+            bm(3, Some(serialize_embedding(vec![0.1, 0.2, 0.3]).unwrap())),
+            bm(4, Some(vec![1])),
+            bm(5, None),
+        ];
+
+        let ids: Vec<_> = check_embeddings(&bms).into_iter().map(|f| f.id).collect();
+        assert_eq!(ids, vec![Some(3), Some(4)]);
+    }
+
+    #[rstest]
+    fn test_diagnose_and_repair_tags() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        sql_query("UPDATE bookmarks SET tags = 'Bbb,aaa' WHERE id = 3;").execute(&mut dal.conn)?;
+
+        let findings = diagnose(&mut dal)?;
+        let tags: Vec<_> = findings.iter().filter(|f| f.check == Check::Tags).collect();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].id, Some(3));
+
+        repair(&mut dal, &findings)?;
+        assert_eq!(dal.get_bookmark_by_id(3)?.tags, ",aaa,bbb,");
+        assert!(diagnose(&mut dal)?.iter().all(|f| f.check != Check::Tags));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_diagnose_and_repair_fts() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        sql_query("DROP TRIGGER bookmarks_ai;").execute(&mut dal.conn)?;
+        sql_query("INSERT INTO bookmarks (URL, metadata) VALUES ('https://unindexed', 'unindexed');")
+            .execute(&mut dal.conn)?;
+
+        let findings = diagnose(&mut dal)?;
+        assert!(findings
+            .iter()
+            .any(|f| f.check == Check::FtsIndex && f.id == Some(12)));
+        assert!(dal.get_bookmarks("unindexed")?.is_empty());
+
+        repair(&mut dal, &findings)?;
+        assert!(diagnose(&mut dal)?.iter().all(|f| f.check != Check::FtsIndex));
+        assert_eq!(dal.get_bookmarks("unindexed")?.len(), 1);
+        Ok(())
+    }
+}
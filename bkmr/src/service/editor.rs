@@ -0,0 +1,100 @@
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use tracing::debug;
+
+use crate::environment::CONFIG;
+use crate::util::helper::abspath;
+
+/// Splits `path/to/file.rs:123` into path and line number
+pub fn split_line_suffix(uri: &str) -> (&str, Option<u32>) {
+    let line_re = Regex::new(r":(\d+)$").unwrap();
+    match line_re.captures(uri) {
+        Some(caps) => (&uri[..uri.len() - caps[0].len()], caps[1].parse().ok()),
+        None => (uri, None),
+    }
+}
+
+/// Editor invocation: the configured template with `{file}` and `{line}` placeholders,
+/// otherwise `$EDITOR +<line> <file>`
+pub fn editor_command(
+    template: Option<&str>,
+    editor: &str,
+    file: &str,
+    line: Option<u32>,
+) -> Vec<String> {
+    match template {
+        Some(template) => template
+            .split_whitespace()
+            .map(|arg| {
+                arg.replace("{file}", file)
+                    .replace("{line}", &line.unwrap_or(1).to_string())
+            })
+            .collect(),
+        None => {
+            let mut args: Vec<String> = editor.split_whitespace().map(String::from).collect();
+            if let Some(line) = line {
+                args.push(format!("+{}", line));
+            }
+            args.push(file.to_string());
+            args
+        }
+    }
+}
+
+/// Opens the file in the editor at the line given by a `:<line>` suffix
+pub fn open_in_editor(uri: &str) -> Result<()> {
+    let (path, line) = split_line_suffix(uri);
+    let file = abspath(path).ok_or_else(|| anyhow!("File not found: {}", path))?;
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
+    let args = editor_command(CONFIG.editor.as_deref(), &editor, &file, line);
+    debug!("Editor command {:?}", args);
+
+    let (program, args) = args
+        .split_first()
+        .ok_or_else(|| anyhow!("Empty editor command"))?;
+    Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| {
+            format!(
+                "Error opening {} with [{}], check your EDITOR variable or editor config.",
+                file, program
+            )
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("src/main.rs:123", "src/main.rs", Some(123))]
+    #[case("src/main.rs", "src/main.rs", None)]
+    #[case("c:/x", "c:/x", None)]
+    fn test_split_line_suffix(
+        #[case] uri: &str,
+        #[case] path: &str,
+        #[case] line: Option<u32>,
+    ) {
+        assert_eq!(split_line_suffix(uri), (path, line));
+    }
+
+    #[rstest]
+    #[case(None, "nvim", Some(12), vec!["nvim", "+12", "/a.rs"])]
+    #[case(None, "code --wait", None, vec!["code", "--wait", "/a.rs"])]
+    #[case(Some("code --goto {file}:{line}"), "vim", Some(12), vec!["code", "--goto", "/a.rs:12"])]
+    #[case(Some("hx {file}:{line}"), "vim", None, vec!["hx", "/a.rs:1"])]
+    fn test_editor_command(
+        #[case] template: Option<&str>,
+        #[case] editor: &str,
+        #[case] line: Option<u32>,
+        #[case] expected: Vec<&str>,
+    ) {
+        assert_eq!(editor_command(template, editor, "/a.rs", line), expected);
+    }
+}
@@ -1,19 +1,83 @@
 use crate::adapter::dal::Dal;
 use crate::adapter::json::read_ndjson_file_and_create_bookmarks;
-use crate::util::helper::calc_content_hash;
-use crate::model::bookmark::BookmarkUpdater;
-use anyhow::Context;
+use crate::service::language::fill_language;
+use crate::util::helper::{calc_content_hash, content_hash_matches, is_legacy_content_hash};
+use crate::model::bookmark::{Bookmark, BookmarkUpdater};
+use crate::service::rules::TagRules;
+use crate::adapter::embeddings::{estimate_tokens, price_per_million_tokens};
+use crate::util::helper::confirm;
+use anyhow::{anyhow, Context};
 use camino::Utf8Path;
+use std::fmt;
 use tracing::debug;
 
+/// Estimated tokens and cost of embedding a batch of texts
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    pub model: String,
+    pub texts: usize,
+    pub tokens: u64,
+    /// None if the model is not in the pricing table
+    pub usd: Option<f64>,
+}
+
+impl CostEstimate {
+    pub fn new<S: AsRef<str>>(model: &str, texts: &[S]) -> Self {
+        let tokens = texts.iter().map(|t| estimate_tokens(t.as_ref())).sum();
+        Self {
+            model: model.to_string(),
+            texts: texts.len(),
+            tokens,
+            usd: price_per_million_tokens(model).map(|price| tokens as f64 * price / 1e6),
+        }
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} texts, ~{} tokens with {}", self.texts, self.tokens, self.model)?;
+        match self.usd {
+            Some(usd) => write!(f, ", ~${:.4}", usd),
+            None => write!(f, ", price unknown"),
+        }
+    }
+}
+
+/// Prints the cost estimate and asks for confirmation unless `yes`.
+/// Does nothing without an embedding model, i.e. when OpenAI is not used.
+pub fn confirm_embedding_cost<S: AsRef<str>>(
+    model: Option<&str>,
+    texts: &[S],
+    yes: bool,
+) -> anyhow::Result<()> {
+    let Some(model) = model else {
+        return Ok(());
+    };
+    let estimate = CostEstimate::new(model, texts);
+    eprintln!("Embedding {}", estimate);
+    if yes || estimate.texts == 0 || confirm("Proceed?") {
+        Ok(())
+    } else {
+        Err(anyhow!("Operation aborted by user"))
+    }
+}
+
 pub fn create_embeddings_for_non_bookmarks<P>(file_path: P) -> anyhow::Result<()>
 where
     P: AsRef<Utf8Path> + std::fmt::Display,
 {
     // 1. read_ndjson_file_and_create_bookmarks
     let bms = read_ndjson_file_and_create_bookmarks(file_path)?;
+    let rules = TagRules::new(&crate::CONFIG.rules)?;
     let mut dal = Dal::new(crate::CONFIG.db_url.clone());
+    // an interrupted import leaves the database unchanged
+    dal.transaction(|dal| import_texts(dal, bms, &rules))
+}
+
+fn import_texts(dal: &mut Dal, bms: Vec<Bookmark>, rules: &TagRules) -> anyhow::Result<()> {
     for mut bm in bms {
+        rules.apply(&mut bm);
+        fill_language(&mut bm); // before the raw content in desc is dropped
         debug!("Processing bookmark: {:?}", bm.convert_to_new_bookmark());
 
         match dal.get_bookmark_by_url(&bm.URL) {
@@ -22,8 +86,10 @@ where
                 bm.id = existing_bm.id; // make sure we have the correct id
                 let new_hash = calc_content_hash(bm.get_content().as_str());
 
-                if existing_bm.content_hash.is_some()
-                    && existing_bm.content_hash != Some(new_hash.clone())
+                if existing_bm
+                    .content_hash
+                    .as_ref()
+                    .is_some_and(|hash| !content_hash_matches(hash, bm.get_content().as_str()))
                 {
                     debug!(
                         "Hashes differ, updating...: {:?} {:?}",
@@ -44,6 +110,9 @@ where
                 } else {
                     // hashes are the same
                     eprintln!("No change for: {:?}", bm.URL);
+                    if existing_bm.content_hash.as_deref().is_some_and(is_legacy_content_hash) {
+                        dal.update_content_hashes(&[(existing_bm.id, new_hash)])?;
+                    }
                 }
             }
             Err(e) => {
@@ -61,3 +130,67 @@ where
     }
     Ok(())
 }
+
+/// Replaces legacy content hashes by the current ones where the content is unchanged.
+/// Stale legacy hashes are kept, so these bookmarks still get re-embedded.
+pub fn upgrade_content_hashes(dal: &mut Dal) -> anyhow::Result<usize> {
+    let hashes: Vec<(i32, Vec<u8>)> = dal
+        .get_bookmarks("")?
+        .iter()
+        .filter_map(|bm| {
+            let hash = bm.content_hash.as_deref()?;
+            let content = bm.get_content();
+            (is_legacy_content_hash(hash) && content_hash_matches(hash, &content))
+                .then(|| (bm.id, calc_content_hash(&content)))
+        })
+        .collect();
+    debug!("Upgrading {} content hashes", hashes.len());
+    dal.update_content_hashes(&hashes)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    #[case("text-embedding-3-small", Some(0.02))]
+    #[case("unknown-model", None)]
+    fn test_cost_estimate(#[case] model: &str, #[case] usd: Option<f64>) {
+        let texts = vec!["a".repeat(2_000_000), "b".repeat(2_000_000)];
+        let estimate = CostEstimate::new(model, &texts);
+        assert_eq!(estimate.texts, 2);
+        assert_eq!(estimate.tokens, 1_000_000);
+        assert_eq!(estimate.usd, usd);
+        assert!(estimate.to_string().starts_with("2 texts, ~1000000 tokens with "));
+    }
+
+    #[rstest]
+    fn test_confirm_embedding_cost_skipped() {
+        assert!(confirm_embedding_cost(None, &["text"], false).is_ok());
+        assert!(confirm_embedding_cost(Some("text-embedding-3-small"), &["text"], true).is_ok());
+    }
+
+    #[rstest]
+    fn test_upgrade_content_hashes() -> anyhow::Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let mut bms = dal.get_bookmarks("")?;
+        let (current, stale) = (bms.remove(0), bms.remove(0));
+        let legacy = |bm: &Bookmark| md5::compute(bm.get_content()).0.to_vec();
+        dal.update_content_hashes(&[
+            (current.id, legacy(&current)),
+            (stale.id, md5::compute("changed").0.to_vec()),
+        ])?;
+
+        assert_eq!(upgrade_content_hashes(&mut dal)?, 1);
+        let upgraded = dal.get_bookmark_by_id(current.id)?;
+        assert_eq!(upgraded.content_hash, Some(calc_content_hash(&current.get_content())));
+        assert_eq!(upgraded.last_update_ts, current.last_update_ts);
+        let kept = dal.get_bookmark_by_id(stale.id)?;
+        assert!(kept.content_hash.as_deref().is_some_and(is_legacy_content_hash));
+        assert_eq!(upgrade_content_hashes(&mut dal)?, 0);
+        Ok(())
+    }
+}
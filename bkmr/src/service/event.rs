@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration as StdDuration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use regex::{Captures, Regex};
+use tracing::debug;
+
+use crate::util::clock;
+
+/// Renders the ICS template of an `_event_` bookmark. Placeholders:
+/// - `{date}`, `{date+N}`, `{date-N}`: today +/- N days as `YYYYMMDD`
+/// - `{now}`: current UTC time as `YYYYMMDDTHHMMSSZ`
+/// - `{uid}`: unique event id
+///
+/// A bare VEVENT is wrapped into a VCALENDAR, line endings are converted to CRLF.
+pub fn render_event(template: &str, today: NaiveDate, now: DateTime<Utc>) -> String {
+    let date_re = Regex::new(r"\{date(?:([+-])(\d+))?\}").unwrap();
+    let rendered = date_re.replace_all(template.trim(), |caps: &Captures| {
+        let days: i64 = caps.get(2).map_or(0, |d| d.as_str().parse().unwrap_or(0));
+        let days = if caps.get(1).map(|s| s.as_str()) == Some("-") {
+            -days
+        } else {
+            days
+        };
+        (today + Duration::days(days)).format("%Y%m%d").to_string()
+    });
+    let rendered = rendered
+        .replace("{now}", &now.format("%Y%m%dT%H%M%SZ").to_string())
+        .replace(
+            "{uid}",
+            &format!("{}-{}@bkmr", now.timestamp_micros(), std::process::id()),
+        );
+
+    let calendar = if rendered.starts_with("BEGIN:VCALENDAR") {
+        rendered
+    } else {
+        format!(
+            "BEGIN:VCALENDAR\nVERSION:2.0\nPRODID:-//bkmr//EN\n{}\nEND:VCALENDAR",
+            rendered
+        )
+    };
+    calendar.lines().map(|l| format!("{}\r\n", l.trim_end())).collect()
+}
+
+/// Event files older than this have been imported by the calendar and are removed
+const MAX_EVENT_AGE: StdDuration = StdDuration::from_secs(24 * 60 * 60);
+
+/// Opened events are written here, calendar apps may import them long after open returns
+pub fn event_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("bkmr")
+        .join("events")
+}
+
+/// Removes the .ics files last modified before cutoff
+pub fn prune_events(dir: &Path, cutoff: SystemTime) -> Result<usize> {
+    let mut n = 0;
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {:?}", dir))? {
+        let path = entry?.path();
+        let modified = fs::metadata(&path).and_then(|m| m.modified())?;
+        if path.extension().is_some_and(|ext| ext == "ics") && modified < cutoff {
+            fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+            n += 1;
+        }
+    }
+    debug!("Pruned {} event files", n);
+    Ok(n)
+}
+
+/// Writes the rendered event to an .ics file in the event directory and opens it with the
+/// OS calendar handler, files of former runs are pruned
+pub fn open_event(template: &str) -> Result<()> {
+    let ics = render_event(template, clock::today(), clock::now());
+    let dir = event_dir();
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    if let Err(e) = prune_events(&dir, SystemTime::now() - MAX_EVENT_AGE) {
+        debug!("Failed to prune event files: {:?}", e);
+    }
+    let path = dir.join(format!("event-{}.ics", Utc::now().timestamp_micros()));
+    fs::write(&path, ics).with_context(|| format!("Failed to write {:?}", path))?;
+    debug!("Opening event {:?}", path);
+    open::that(&path).with_context(|| format!("Error OS opening {:?}", path))
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_render_event() {
+        let today = NaiveDate::from_ymd_opt(2024, 12, 30).unwrap();
+        let now = DateTime::from_timestamp(1_735_560_000, 0).unwrap();
+        let template = "BEGIN:VEVENT\nUID:{uid}\nDTSTAMP:{now}\nDTSTART;VALUE=DATE:{date+3}\nDTEND;VALUE=DATE:{date-1}\nSUMMARY:Review {date}\nEND:VEVENT\n";
+
+        let ics = render_event(template, today, now);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTAMP:20241230T120000Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250102\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20241229\r\n"));
+        assert!(ics.contains("SUMMARY:Review 20241230\r\n"));
+        assert!(!ics.contains("{uid}"));
+    }
+
+    #[rstest]
+    fn test_render_event_keeps_calendar() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let ics = render_event("BEGIN:VCALENDAR\nEND:VCALENDAR", today, Utc::now());
+        assert_eq!(ics, "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n");
+    }
+
+    #[rstest]
+    fn test_prune_events() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("event-1.ics"), "")?;
+        fs::write(dir.path().join("notes.txt"), "")?;
+
+        assert_eq!(prune_events(dir.path(), SystemTime::now() - StdDuration::from_secs(3600))?, 0);
+        assert_eq!(prune_events(dir.path(), SystemTime::now() + StdDuration::from_secs(60))?, 1);
+        assert!(!dir.path().join("event-1.ics").exists());
+        assert!(dir.path().join("notes.txt").exists());
+        Ok(())
+    }
+}
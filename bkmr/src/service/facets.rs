@@ -0,0 +1,125 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use chrono::Datelike;
+use clap::ValueEnum;
+use reqwest::Url;
+use serde::Serialize;
+
+use crate::model::bookmark::Bookmark;
+
+/// Bookmark property counted by `search --facets`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Facet {
+    Tags,
+    /// host of web URLs without `www.`
+    Domain,
+    /// of creation, last update for bookmarks from before creation times were recorded
+    Year,
+}
+
+impl fmt::Display for Facet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_possible_value().expect("no skipped values").get_name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+impl Facet {
+    fn values(&self, bm: &Bookmark) -> Vec<String> {
+        match self {
+            Facet::Tags => bm.get_tags(),
+            Facet::Domain => Url::parse(&bm.URL)
+                .ok()
+                .filter(|url| matches!(url.scheme(), "http" | "https"))
+                .and_then(|url| url.host_str().map(|host| host.trim_start_matches("www.").to_string()))
+                .into_iter()
+                .collect(),
+            Facet::Year => vec![bm.created_ts.unwrap_or(bm.last_update_ts).year().to_string()],
+        }
+    }
+
+    /// Counts per value, most frequent first
+    pub fn count(&self, bms: &[Bookmark]) -> Vec<FacetCount> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for value in bms.iter().flat_map(|bm| self.values(bm)) {
+            *counts.entry(value).or_default() += 1;
+        }
+        let mut counts: Vec<FacetCount> = counts
+            .into_iter()
+            .map(|(value, count)| FacetCount { value, count })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        counts
+    }
+}
+
+/// Counts of the requested facets by facet name
+pub fn facets(bms: &[Bookmark], facets: &[Facet]) -> BTreeMap<String, Vec<FacetCount>> {
+    facets
+        .iter()
+        .map(|facet| (facet.to_string(), facet.count(bms)))
+        .collect()
+}
+
+/// One line per facet value, aligned
+pub fn facet_lines(facets: &BTreeMap<String, Vec<FacetCount>>) -> Vec<String> {
+    let width = facets
+        .values()
+        .flatten()
+        .map(|c| c.value.chars().count())
+        .max()
+        .unwrap_or(0);
+    facets
+        .iter()
+        .flat_map(|(facet, counts)| {
+            counts
+                .iter()
+                .map(move |c| format!("{:<6} {:<width$} {:>5}", facet, c.value, c.count, width = width))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn bm(url: &str, tags: &str, year: i32) -> Bookmark {
+        let bm = BookmarkBuilder::new().URL(url.to_string()).tags(tags.to_string()).build();
+        Bookmark {
+            created_ts: NaiveDate::from_ymd_opt(year, 1, 1).unwrap().and_hms_opt(0, 0, 0),
+            ..bm
+        }
+    }
+
+    fn counts(counts: &[(&str, usize)]) -> Vec<FacetCount> {
+        counts
+            .iter()
+            .map(|(value, count)| FacetCount { value: value.to_string(), count: *count })
+            .collect()
+    }
+
+    #[rstest]
+    fn test_facets() {
+        let bms = vec![
+            bm("https://www.github.com/sysid/bkmr", ",rust,cli,", 2024),
+            bm("https://github.com/rust-lang/rust", ",rust,", 2025),
+            bm("https://docs.rs/diesel", ",rust,db,", 2025),
+            bm("ls -la", ",_snip_,", 2025),
+        ];
+        let facets = facets(&bms, &[Facet::Tags, Facet::Domain, Facet::Year]);
+        assert_eq!(facets["tags"], counts(&[("rust", 3), ("_snip_", 1), ("cli", 1), ("db", 1)]));
+        assert_eq!(facets["domain"], counts(&[("github.com", 2), ("docs.rs", 1)]));
+        assert_eq!(facets["year"], counts(&[("2025", 3), ("2024", 1)]));
+        assert_eq!(facet_lines(&facets)[0], "domain github.com     2");
+    }
+}
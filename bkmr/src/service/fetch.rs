@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, Proxy, Url};
+use tracing::debug;
+
+use crate::environment::{FetchPolicyConfig, CONFIG};
+use crate::util::helper::interpolate_env;
+
+/// How URLs of a domain and its subdomains are fetched
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchPolicy {
+    pub domain: String,
+    pub fetch: bool,
+    pub headers: Vec<(String, String)>,
+    pub proxy: Option<String>,
+}
+
+impl FetchPolicy {
+    pub fn new(config: &FetchPolicyConfig) -> Result<Self> {
+        let domain = config.domain.trim().trim_start_matches("*.").trim_matches('.').to_lowercase();
+        if domain.is_empty() {
+            return Err(anyhow!("Fetch policy needs a domain"));
+        }
+        if let Some(proxy) = &config.proxy {
+            Proxy::all(proxy).with_context(|| format!("Invalid proxy {:?} of fetch policy {}", proxy, domain))?;
+        }
+        Ok(Self {
+            domain,
+            fetch: config.fetch,
+            headers: config.headers.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            proxy: config.proxy.clone(),
+        })
+    }
+
+    pub fn matches_host(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        host == self.domain || host.ends_with(&format!(".{}", self.domain))
+    }
+}
+
+/// Policies of the configured domains, the most specific domain wins
+#[derive(Debug, Clone, Default)]
+pub struct FetchPolicies {
+    pub policies: Vec<FetchPolicy>,
+}
+
+impl FetchPolicies {
+    pub fn new(configs: &[FetchPolicyConfig]) -> Result<Self> {
+        let policies = configs.iter().map(FetchPolicy::new).collect::<Result<Vec<_>>>()?;
+        Ok(Self { policies })
+    }
+
+    pub fn find(&self, url: &str) -> Option<&FetchPolicy> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        self.policies
+            .iter()
+            .filter(|policy| policy.matches_host(host))
+            .max_by_key(|policy| policy.domain.len())
+    }
+
+    /// Request honoring the policy of the URL, an error if the domain must not be fetched
+    pub fn request(&self, method: Method, url: &str, timeout: Option<Duration>) -> Result<RequestBuilder> {
+        let mut builder = Client::builder();
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(policy) = self.find(url) {
+            debug!("{} fetched by policy {:?}", url, policy);
+            if !policy.fetch {
+                return Err(anyhow!("Fetching {} is disabled by the fetch policy of {}", url, policy.domain));
+            }
+            if let Some(proxy) = &policy.proxy {
+                builder = builder.proxy(Proxy::all(proxy)?);
+            }
+            let mut headers = HeaderMap::new();
+            for (name, value) in &policy.headers {
+                let value = interpolate_env(value)?;
+                headers.insert(
+                    HeaderName::from_bytes(name.as_bytes())
+                        .with_context(|| format!("Invalid header name {:?}", name))?,
+                    HeaderValue::from_str(&value).with_context(|| format!("Invalid value of header {}", name))?,
+                );
+            }
+            builder = builder.default_headers(headers);
+        }
+        Ok(builder.build()?.request(method, url))
+    }
+}
+
+/// Request by the configured fetch policies
+pub fn request(method: Method, url: &str, timeout: Option<Duration>) -> Result<RequestBuilder> {
+    FetchPolicies::new(&CONFIG.fetch_policies)?.request(method, url, timeout)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use rstest::*;
+
+    use super::*;
+
+    fn config(domain: &str, fetch: bool) -> FetchPolicyConfig {
+        FetchPolicyConfig {
+            domain: domain.to_string(),
+            fetch,
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case("https://intranet.example.com/wiki", Some("intranet.example.com"))]
+    #[case("https://docs.example.com", Some("example.com"))]
+    #[case("https://EXAMPLE.com", Some("example.com"))]
+    #[case("https://notexample.com", None)]
+    #[case("no url", None)]
+    fn test_find(#[case] url: &str, #[case] expected: Option<&str>) -> Result<()> {
+        let policies = FetchPolicies::new(&[config("*.example.com", true), config("intranet.example.com", false)])?;
+        assert_eq!(policies.find(url).map(|p| p.domain.as_str()), expected);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_invalid_policies() {
+        assert!(FetchPolicy::new(&config(" ", true)).is_err());
+        let config = FetchPolicyConfig {
+            proxy: Some("::invalid".to_string()),
+            ..config("example.com", true)
+        };
+        assert!(FetchPolicy::new(&config).is_err());
+    }
+
+    #[rstest]
+    fn test_request() -> Result<()> {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/page")
+            .match_header("authorization", "Bearer secret")
+            .with_body("ok")
+            .create();
+        let host = Url::parse(&server.url())?.host_str().unwrap_or_default().to_string();
+        let policies = FetchPolicies::new(&[FetchPolicyConfig {
+            headers: BTreeMap::from([("Authorization".to_string(), "Bearer secret".to_string())]),
+            ..config(&host, true)
+        }])?;
+        let url = format!("{}/page", server.url());
+        assert_eq!(policies.request(Method::GET, &url, None)?.send()?.text()?, "ok");
+        mock.assert();
+
+        let policies = FetchPolicies::new(&[config(&host, false)])?;
+        assert!(policies.request(Method::GET, &url, None).is_err());
+        Ok(())
+    }
+}
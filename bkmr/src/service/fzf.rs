@@ -19,9 +19,10 @@ use crate::environment::{FzfEnvOpts, CONFIG};
 use crate::model::bookmark::Bookmark;
 use crate::model::tag::Tags;
 use crate::service::process::{delete_bms, edit_bms, open_bms};
+use crate::util::helper::{is_plain, prompt, require_input};
 
 impl SkimItem for Bookmark {
-    fn text(&self) -> Cow<str> {
+    fn text(&self) -> Cow<'_, str> {
         let FzfEnvOpts { show_tags, .. } = &CONFIG.fzf_opts;
 
         let _text = match show_tags {
@@ -105,7 +106,8 @@ impl SkimItem for Bookmark {
     }
 }
 
-pub fn fzf_process(bms: &Vec<Bookmark>) {
+pub fn fzf_process(bms: &[Bookmark]) {
+    require_input("fzf selection");
     let FzfEnvOpts {
         reverse, height, ..
     } = &CONFIG.fzf_opts;
@@ -131,82 +133,106 @@ pub fn fzf_process(bms: &Vec<Bookmark>) {
     drop(tx_item); // so that skim could know when to stop waiting for more items.
 
     let mut stdout = std::io::stdout();
-    Skim::run_with(&options, Some(rx_item)).map(|out| match out.final_key {
-        Key::Ctrl('e') => {
-            let filtered = filter_bms(out);
-            // id selection not necessary since all bms are filtered, just open all bms
-            let ids = (1..=filtered.len()).map(|i| i as i32).collect();
-            debug!(
-                "{:?}, {:?}",
-                ids,
-                filtered
-            );
-            edit_bms(ids, filtered).unwrap_or_else(|e| {
-                debug!("{}", e);
-            });
-            // clear screen
-            // let mut stdout = std::io::stdout();
-            execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
-        }
-        Key::Ctrl('o') => {
-            let filtered = filter_bms(out);
-            // id selection not necessary since all bms are filtered, just open all bms
-            let ids: Vec<i32> = (1..=filtered.len()).map(|i| i as i32).collect();
-            debug!(
-                "{:?}, {:?}",
-                ids,
-                filtered
-            );
-            // Change this part to copy the bookmark URLs to the clipboard using the arboard crate
-            let mut clipboard = Clipboard::new().unwrap();
-            // TODO: do_touch required here
-            let urls = filtered.iter().map(|bm| &bm.URL).join("\n");
-            clipboard.set_text(urls).unwrap_or_else(|e| {
-                debug!("{}", e);
-            });
-            println!("Copied URLs to clipboard");
-            // let mut stdout = std::io::stdout();
-            execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
-        }
-        Key::Ctrl('d') => {
-            let filtered = filter_bms(out);
-            // id selection not necessary since all bms are filtered, just open all bms
-            let ids: Vec<i32> = (1..=filtered.len()).map(|i| i as i32).collect();
-            debug!(
-                "{:?}, {:?}",
-                ids,
-                filtered
-            );
-            // Delete the bookmarks
-            delete_bms(ids.clone(), filtered.clone()).unwrap_or_else(|e| {
-                debug!("{}", e);
-            });
-            println!("Deleted Bookmark: {:?}", filtered[0].URL);
-            // let mut stdout = std::io::stdout();
-            execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
-        }
-        Key::Enter => {
-            let filtered = filter_bms(out);
-            // id selection not necessary since all bms are filtered, just open all bms
-            let ids: Vec<i32> = (1..=filtered.len()).map(|i| i as i32).collect();
-            debug!(
-                "{:?}, {:?}",
-                ids,
-                filtered
-            );
-            open_bms(ids, filtered).unwrap_or_else(|e| {
-                debug!("{}", e);
-            });
-            // let mut stdout = std::io::stdout();
-            execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
-        }
-        Key::ESC => {
-            debug!("Esc");
-            // let mut stdout = std::io::stdout();
-            execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
+    if let Some(out) = Skim::run_with(&options, Some(rx_item)) {
+        match out.final_key {
+            Key::Ctrl('e') => {
+                let filtered = filter_bms(out);
+                // id selection not necessary since all bms are filtered, just open all bms
+                let ids = (1..=filtered.len()).map(|i| i as i32).collect();
+                debug!("{:?}, {:?}", ids, filtered);
+                edit_bms(ids, filtered).unwrap_or_else(|e| {
+                    debug!("{}", e);
+                });
+                // clear screen
+                // let mut stdout = std::io::stdout();
+                execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
+            }
+            Key::Ctrl('o') => {
+                let filtered = filter_bms(out);
+                // id selection not necessary since all bms are filtered, just open all bms
+                let ids: Vec<i32> = (1..=filtered.len()).map(|i| i as i32).collect();
+                debug!("{:?}, {:?}", ids, filtered);
+                // Change this part to copy the bookmark URLs to the clipboard using the arboard crate
+                let mut clipboard = Clipboard::new().unwrap();
+                // TODO: do_touch required here
+                let urls = filtered.iter().map(|bm| &bm.URL).join("\n");
+                clipboard.set_text(urls).unwrap_or_else(|e| {
+                    debug!("{}", e);
+                });
+                println!("Copied URLs to clipboard");
+                // let mut stdout = std::io::stdout();
+                execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
+            }
+            Key::Ctrl('d') => {
+                let filtered = filter_bms(out);
+                // id selection not necessary since all bms are filtered, just open all bms
+                let ids: Vec<i32> = (1..=filtered.len()).map(|i| i as i32).collect();
+                debug!("{:?}, {:?}", ids, filtered);
+                // Delete the bookmarks
+                delete_bms(ids.clone(), filtered.clone()).unwrap_or_else(|e| {
+                    debug!("{}", e);
+                });
+                println!("Deleted Bookmark: {:?}", filtered[0].URL);
+                // let mut stdout = std::io::stdout();
+                execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
+            }
+            Key::Enter => {
+                let filtered = filter_bms(out);
+                // id selection not necessary since all bms are filtered, just open all bms
+                let ids: Vec<i32> = (1..=filtered.len()).map(|i| i as i32).collect();
+                debug!("{:?}, {:?}", ids, filtered);
+                open_bms(ids, filtered).unwrap_or_else(|e| {
+                    debug!("{}", e);
+                });
+                // let mut stdout = std::io::stdout();
+                execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
+            }
+            Key::ESC => {
+                debug!("Esc");
+                // let mut stdout = std::io::stdout();
+                execute!(stdout, Clear(ClearType::FromCursorDown)).unwrap();
+            }
+            _ => (),
         }
-        _ => (),
-    });
+    }
+}
+
+/// Numbered list and a line prompt instead of the fzf screen, for `--plain`
+fn plain_select(bms: &[Bookmark]) -> Option<Bookmark> {
+    for (i, bm) in bms.iter().enumerate() {
+        eprintln!("{}. {} [{}]", i + 1, bm.metadata, bm.id);
+    }
+    let answer = prompt(&format!("Bookmark number 1 to {}, empty to cancel: ", bms.len()));
+    let n: usize = answer.trim().parse().ok()?;
+    bms.get(n.checked_sub(1)?).cloned()
+}
+
+/// Single bookmark chosen with Enter, None if aborted
+pub fn fzf_select(bms: &[Bookmark]) -> Option<Bookmark> {
+    require_input("fzf selection");
+    if is_plain() {
+        return plain_select(bms);
+    }
+    let FzfEnvOpts {
+        reverse, height, ..
+    } = &CONFIG.fzf_opts;
+
+    let options = SkimOptionsBuilder::default()
+        .reverse(reverse.to_owned())
+        .height(height.to_string())
+        .build()
+        .unwrap();
+
+    let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
+    for bm in bms {
+        tx_item.send(Arc::new(bm.clone())).unwrap();
+    }
+    drop(tx_item);
+
+    let out = Skim::run_with(&options, Some(rx_item)).filter(|out| out.final_key == Key::Enter)?;
+    out.selected_items
+        .first()
+        .and_then(|item| (**item).as_any().downcast_ref::<Bookmark>().cloned())
 }
 
 fn filter_bms(out: SkimOutput) -> Vec<Bookmark> {
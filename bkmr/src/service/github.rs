@@ -0,0 +1,176 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use tracing::{debug, instrument};
+
+use crate::model::bookmark::{Bookmark, BookmarkBuilder};
+
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+/// Tag of all imported stars, in addition to language and topics
+pub const GITHUB_TAG: &str = "github";
+const PER_PAGE: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct GitHubStar {
+    pub html_url: String,
+    pub full_name: String,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+}
+
+/// Tag of a GitHub language, e.g. `Jupyter Notebook` -> `jupyter-notebook`
+fn language_tag(language: &str) -> String {
+    language.trim().to_lowercase().replace(' ', "-")
+}
+
+impl GitHubStar {
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = vec![GITHUB_TAG.to_string()];
+        tags.extend(self.language.as_deref().map(language_tag));
+        tags.extend(self.topics.iter().map(|topic| topic.to_lowercase()));
+        tags
+    }
+
+    pub fn to_bookmark(&self) -> Bookmark {
+        let mut bm = BookmarkBuilder::new()
+            .id(1)
+            .URL(self.html_url.clone())
+            .metadata(self.full_name.clone())
+            .desc(self.description.clone().unwrap_or_default())
+            .flags(0)
+            .language(self.language.as_deref().map(language_tag))
+            .build();
+        bm.set_tags(self.tags());
+        bm
+    }
+}
+
+/// One page of stars, newest first: of the user, or of the token's owner including private repos
+#[instrument(level = "debug", skip(token))]
+pub fn fetch_stars_page(
+    base_url: &str,
+    user: Option<&str>,
+    token: Option<&str>,
+    page: usize,
+) -> Result<Vec<GitHubStar>> {
+    let path = match user {
+        Some(user) => format!("/users/{}/starred", user),
+        None if token.is_some() => "/user/starred".to_string(),
+        None => return Err(anyhow!("A user or a token is required")),
+    };
+    let url = format!("{}{}", base_url.trim_end_matches('/'), path);
+    let mut request = reqwest::blocking::Client::new()
+        .get(&url)
+        .query(&[
+            ("per_page", PER_PAGE.to_string()),
+            ("page", page.to_string()),
+            ("sort", "created".to_string()),
+            ("direction", "desc".to_string()),
+        ])
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "bkmr");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    request
+        .send()
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to fetch {}", url))?
+        .json()
+        .with_context(|| format!("Invalid response of {}", url))
+}
+
+/// Stars not known yet. Pages are fetched until a page has no new star,
+/// so re-runs only fetch the stars added since the last import.
+pub fn new_stars(
+    base_url: &str,
+    user: Option<&str>,
+    token: Option<&str>,
+    mut is_known: impl FnMut(&str) -> Result<bool>,
+) -> Result<Vec<GitHubStar>> {
+    let mut stars = Vec::new();
+    for page in 1.. {
+        let fetched = fetch_stars_page(base_url, user, token, page)?;
+        let n = fetched.len();
+        let mut new = Vec::new();
+        for star in fetched {
+            if !is_known(&star.html_url)? {
+                new.push(star);
+            }
+        }
+        debug!("page {}: {} stars, {} new", page, n, new.len());
+        let done = new.is_empty() || n < PER_PAGE;
+        stars.extend(new);
+        if done {
+            break;
+        }
+    }
+    Ok(stars)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+    use serde_json::json;
+
+    use super::*;
+
+    fn star(name: &str) -> serde_json::Value {
+        json!({
+            "html_url": format!("https://github.com/{}", name),
+            "full_name": name,
+            "description": "A tool",
+            "language": "Jupyter Notebook",
+            "topics": ["CLI", "search"],
+            "stargazers_count": 42,
+        })
+    }
+
+    #[rstest]
+    fn test_to_bookmark() -> Result<()> {
+        let star: GitHubStar = serde_json::from_value(star("sysid/bkmr"))?;
+        let bm = star.to_bookmark();
+        assert_eq!(bm.URL, "https://github.com/sysid/bkmr");
+        assert_eq!(bm.metadata, "sysid/bkmr");
+        assert_eq!(bm.desc, "A tool");
+        assert_eq!(bm.tags, ",cli,github,jupyter-notebook,search,");
+        assert_eq!(bm.language.as_deref(), Some("jupyter-notebook"));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_new_stars_stops_at_known() -> Result<()> {
+        let mut server = mockito::Server::new();
+        let page1: Vec<_> = (0..PER_PAGE).map(|i| star(&format!("a/{}", i))).collect();
+        let first = server
+            .mock("GET", "/users/sysid/starred")
+            .match_query(mockito::Matcher::UrlEncoded("page".into(), "1".into()))
+            .match_header("authorization", "Bearer secret")
+            .with_body(serde_json::to_string(&page1)?)
+            .create();
+        let second = server
+            .mock("GET", "/users/sysid/starred")
+            .match_query(mockito::Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_body(serde_json::to_string(&[star("b/new"), star("b/known")])?)
+            .create();
+
+        let stars = new_stars(&server.url(), Some("sysid"), Some("secret"), |url| {
+            Ok(url.ends_with("known"))
+        })?;
+        assert_eq!(stars.len(), PER_PAGE + 1);
+        assert_eq!(stars[PER_PAGE].full_name, "b/new");
+        first.assert();
+        second.assert();
+
+        // second run: the first page is completely known
+        let stars = new_stars(&server.url(), Some("sysid"), Some("secret"), |_| Ok(true))?;
+        assert!(stars.is_empty());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_user_or_token_required() {
+        assert!(fetch_stars_page("http://localhost:1", None, None, 1).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+
+use crate::service::dir::Shell;
+
+/// Commands shorter than this are not worth a snippet
+pub const MIN_COMMAND_LENGTH: usize = 20;
+
+/// Wrappers skipped when suggesting the program name as tag
+const WRAPPERS: [&str; 6] = ["sudo", "env", "time", "nohup", "exec", "command"];
+
+/// Shell of `$SHELL`, bash if unknown
+pub fn detect_shell() -> Shell {
+    match env::var("SHELL").unwrap_or_default().rsplit('/').next() {
+        Some("zsh") => Shell::Zsh,
+        Some("fish") => Shell::Fish,
+        _ => Shell::Bash,
+    }
+}
+
+/// `$HISTFILE` for bash and zsh, otherwise the default history file of the shell
+pub fn history_path(shell: Shell) -> Result<PathBuf> {
+    if let (Shell::Bash | Shell::Zsh, Ok(path)) = (shell, env::var("HISTFILE")) {
+        return Ok(PathBuf::from(path));
+    }
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("No home directory"))?;
+    Ok(match shell {
+        Shell::Bash => home.join(".bash_history"),
+        Shell::Zsh => home.join(".zsh_history"),
+        Shell::Fish => home.join(".local/share/fish/fish_history"),
+    })
+}
+
+/// Commands of a history file in order, multi-line commands joined
+pub fn parse_history(shell: Shell, content: &str) -> Vec<String> {
+    let mut commands: Vec<String> = Vec::new();
+    let mut continued = false;
+    for line in content.lines() {
+        let command = match shell {
+            // `#1700000000` timestamps with HISTTIMEFORMAT
+            Shell::Bash if line.starts_with('#') && line[1..].parse::<u64>().is_ok() => continue,
+            Shell::Bash => line,
+            // extended history: `: 1700000000:0;command`
+            Shell::Zsh if !continued && line.starts_with(": ") => {
+                line.split_once(';').map_or(line, |(_, command)| command)
+            }
+            Shell::Zsh => line,
+            Shell::Fish => match line.strip_prefix("- cmd: ") {
+                Some(command) => command,
+                None => continue,
+            },
+        };
+        if continued {
+            if let Some(last) = commands.last_mut() {
+                last.push('\n');
+                last.push_str(command);
+            }
+        } else {
+            commands.push(command.to_string());
+        }
+        continued = shell != Shell::Fish && command.ends_with('\\');
+    }
+    commands
+        .into_iter()
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+/// Long commands used at least min_count times, most frequent first
+pub fn frequent_commands(commands: &[String], min_count: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for command in commands {
+        if command.chars().count() >= MIN_COMMAND_LENGTH {
+            *counts.entry(command.as_str()).or_default() += 1;
+        }
+    }
+    let mut frequent: Vec<(String, usize)> = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= min_count)
+        .map(|(command, count)| (command.to_string(), count))
+        .collect();
+    frequent.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    debug!("{} frequent commands", frequent.len());
+    frequent
+}
+
+/// Program name, e.g. `docker` for `sudo docker compose up -d`
+pub fn suggest_tags(command: &str) -> Vec<String> {
+    command
+        .split_whitespace()
+        .find(|word| !WRAPPERS.contains(word) && !word.contains('='))
+        .map(|program| program.rsplit('/').next().unwrap_or(program).to_lowercase())
+        .filter(|program| program.chars().all(|c| c.is_alphanumeric() || "-_.".contains(c)))
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Shell::Bash, "#1700000000\nls -la\ngit log --oneline --graph \\\n  --all\n", vec!["ls -la", "git log --oneline --graph \\\n  --all"])]
+    #[case(Shell::Zsh, ": 1700000000:0;ls -la\n: 1700000001:0;echo a;b\n", vec!["ls -la", "echo a;b"])]
+    #[case(Shell::Fish, "- cmd: ls -la\n  when: 1700000000\n- cmd: cd /tmp\n  when: 1700000001\n  paths:\n    - /tmp\n", vec!["ls -la", "cd /tmp"])]
+    fn test_parse_history(#[case] shell: Shell, #[case] content: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(parse_history(shell, content), expected);
+    }
+
+    #[rstest]
+    fn test_frequent_commands() {
+        let commands: Vec<String> = [
+            "docker compose up -d --build",
+            "ls",
+            "ls",
+            "ls",
+            "kubectl get pods -n production",
+            "docker compose up -d --build",
+            "kubectl get pods -n production",
+            "docker compose up -d --build",
+        ]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+
+        assert_eq!(
+            frequent_commands(&commands, 2),
+            vec![
+                ("docker compose up -d --build".to_string(), 3),
+                ("kubectl get pods -n production".to_string(), 2),
+            ]
+        );
+        assert_eq!(frequent_commands(&commands, 3).len(), 1);
+    }
+
+    #[rstest]
+    #[case("sudo docker compose up -d", vec!["docker"])]
+    #[case("RUST_LOG=debug cargo test --workspace", vec!["cargo"])]
+    #[case("/usr/local/bin/terraform plan", vec!["terraform"])]
+    #[case("./run.sh --fast", vec!["run.sh"])]
+    #[case("$(which python) -m http.server", vec![])]
+    fn test_suggest_tags(#[case] command: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(suggest_tags(command), expected);
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use tracing::{debug, instrument};
+
+use crate::adapter::json::BookmarkView;
+use crate::environment::{Hooks, CONFIG};
+use crate::model::bookmark::Bookmark;
+use crate::service::bus::BookmarkEvent;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookEvent {
+    Add,
+    Delete,
+    Open,
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookEvent::Add => write!(f, "on_add"),
+            HookEvent::Delete => write!(f, "on_delete"),
+            HookEvent::Open => write!(f, "on_open"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct HookPayload {
+    pub event: HookEvent,
+    pub bookmark: BookmarkView,
+}
+
+impl HookPayload {
+    pub fn new(event: HookEvent, bm: &Bookmark) -> Self {
+        Self {
+            event,
+            bookmark: BookmarkView::from(bm),
+        }
+    }
+}
+
+fn hook_for(hooks: &Hooks, event: HookEvent) -> Option<&String> {
+    match event {
+        HookEvent::Add => hooks.on_add.as_ref(),
+        HookEvent::Delete => hooks.on_delete.as_ref(),
+        HookEvent::Open => hooks.on_open.as_ref(),
+    }
+}
+
+/// Runs the configured hook for the event.
+/// Hook failures are reported but never abort the triggering operation.
+pub fn run_hook(event: HookEvent, bm: &Bookmark) {
+    if let Some(hook) = hook_for(&CONFIG.hooks, event) {
+        let timeout = Duration::from_secs(CONFIG.hooks.timeout_secs);
+        if let Err(e) = execute_hook(hook, &HookPayload::new(event, bm), timeout) {
+            eprintln!("Warning: {} hook failed: {:?}", event, e);
+        }
+    }
+}
+
+/// Event bus subscriber running the hook of the event, updates have no hook
+pub fn on_event(event: &BookmarkEvent) {
+    let hook_event = match event {
+        BookmarkEvent::Added(_) => HookEvent::Add,
+        BookmarkEvent::Deleted(_) => HookEvent::Delete,
+        BookmarkEvent::Opened(_) => HookEvent::Open,
+        BookmarkEvent::Updated(_) => return,
+    };
+    run_hook(hook_event, event.bookmark());
+}
+
+/// http(s) hooks receive the payload as JSON POST, everything else is run as shell command
+/// with the payload on stdin. Commands running longer than the timeout are killed.
+#[instrument(skip(payload))]
+pub fn execute_hook(hook: &str, payload: &HookPayload, timeout: Duration) -> Result<()> {
+    let json = serde_json::to_string(payload).context("Failed to serialize hook payload")?;
+
+    if hook.starts_with("http://") || hook.starts_with("https://") {
+        debug!("POST hook payload to {:?}", hook);
+        let response = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()?
+            .post(hook)
+            .header("Content-Type", "application/json")
+            .body(json)
+            .send()
+            .with_context(|| format!("Failed to POST to {}", hook))?;
+        if !response.status().is_success() {
+            return Err(anyhow!("{} responded with {}", hook, response.status()));
+        }
+    } else {
+        debug!("Running hook command {:?}", hook);
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(hook)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to run {}", hook))?;
+        let Some(mut stdin) = child.stdin.take() else {
+            kill(&mut child);
+            return Err(anyhow!("Failed to open stdin of {}", hook));
+        };
+        // a hook not reading its input must not block bkmr once the pipe buffer is full
+        let writer = thread::spawn(move || match stdin.write_all(json.as_bytes()) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                debug!("Hook ignored its input");
+                Ok(())
+            }
+            result => result,
+        });
+        let status = match wait_with_timeout(&mut child, hook, timeout) {
+            Ok(status) => status,
+            Err(e) => {
+                kill(&mut child);
+                return Err(e);
+            }
+        };
+        // still blocked if the hook left a background process holding the pipe
+        if writer.is_finished() {
+            writer
+                .join()
+                .map_err(|_| anyhow!("Failed to write payload to {}", hook))?
+                .with_context(|| format!("Failed to write payload to {}", hook))?;
+        }
+        if !status.success() {
+            return Err(anyhow!("{} exited with {}", hook, status));
+        }
+    }
+    Ok(())
+}
+
+fn wait_with_timeout(child: &mut Child, hook: &str, timeout: Duration) -> Result<ExitStatus> {
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if started.elapsed() >= timeout {
+            return Err(anyhow!("{} timed out after {:?}", hook, timeout));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn kill(child: &mut Child) {
+    if let Err(e) = child.kill().and_then(|_| child.wait()) {
+        debug!("Failed to kill hook: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[fixture]
+    fn payload() -> HookPayload {
+        let bm = BookmarkBuilder::new()
+            .id(1)
+            .URL("https://www.rust-lang.org".to_string())
+            .metadata("Rust".to_string())
+            .tags(",rust,".to_string())
+            .build();
+        HookPayload::new(HookEvent::Add, &bm)
+    }
+
+    #[rstest]
+    fn test_payload(payload: HookPayload) {
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&payload).unwrap()).unwrap();
+        assert_eq!(json["event"], "add");
+        assert_eq!(json["bookmark"]["URL"], "https://www.rust-lang.org");
+        assert_eq!(json["bookmark"]["tags"], ",rust,");
+    }
+
+    #[rstest]
+    fn test_execute_hook_command(payload: HookPayload) {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("payload.json");
+
+        execute_hook(&format!("cat > {}", out.display()), &payload, TIMEOUT).unwrap();
+
+        let content = std::fs::read_to_string(out).unwrap();
+        assert!(content.contains(r#""event":"add""#));
+    }
+
+    #[rstest]
+    fn test_execute_hook_command_failure(payload: HookPayload) {
+        assert!(execute_hook("exit 1", &payload, TIMEOUT).is_err());
+    }
+
+    #[rstest]
+    fn test_execute_hook_command_timeout(payload: HookPayload) {
+        let started = Instant::now();
+
+        let result = execute_hook("sleep 10", &payload, Duration::from_millis(200));
+
+        assert!(result.unwrap_err().to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[rstest]
+    fn test_execute_hook_command_ignoring_large_payload(mut payload: HookPayload) {
+        payload.bookmark.desc = "x".repeat(100 * 1024);
+        let started = Instant::now();
+
+        let result = execute_hook("sleep 10", &payload, Duration::from_millis(200));
+
+        assert!(result.unwrap_err().to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        // input ignored by a hook exiting successfully is no failure
+        execute_hook("exit 0", &payload, TIMEOUT).unwrap();
+    }
+
+    #[rstest]
+    fn test_execute_hook_http(payload: HookPayload) {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/hook")
+            .match_header("content-type", "application/json")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"event": "add"}"#.to_string(),
+            ))
+            .with_status(200)
+            .create();
+
+        execute_hook(&format!("{}/hook", server.url()), &payload, TIMEOUT).unwrap();
+        mock.assert();
+    }
+
+    #[rstest]
+    fn test_execute_hook_http_failure(payload: HookPayload) {
+        let mut server = mockito::Server::new();
+        server.mock("POST", "/hook").with_status(500).create();
+
+        assert!(execute_hook(&format!("{}/hook", server.url()), &payload, TIMEOUT).is_err());
+    }
+}
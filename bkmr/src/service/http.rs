@@ -0,0 +1,167 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::blocking::Client;
+use reqwest::Method;
+use tracing::{debug, instrument};
+
+use crate::util::helper::interpolate_env;
+
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// HTTP request parsed from a `_http_` bookmark:
+///
+/// ```text
+/// POST https://api.example.com/items
+/// Content-Type: application/json
+/// Authorization: Bearer ${API_TOKEN}
+///
+/// {"name": "bkmr"}
+/// ```
+/// The method defaults to GET, headers follow the request line until the first blank line,
+/// the rest is the body. Environment variables are interpolated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    pub fn parse(template: &str) -> Result<Self> {
+        let template = interpolate_env(template)?;
+        let mut lines = template.trim_start().lines();
+
+        let request_line = lines.next().ok_or_else(|| anyhow!("Empty HTTP request"))?;
+        let (method, url) = match request_line.trim().split_once(char::is_whitespace) {
+            Some((method, url)) => (
+                Method::from_str(&method.to_uppercase())
+                    .with_context(|| format!("Invalid HTTP method {:?}", method))?,
+                url.trim().to_string(),
+            ),
+            None => (Method::GET, request_line.trim().to_string()),
+        };
+
+        let mut headers = Vec::new();
+        for line in lines.by_ref() {
+            if line.trim().is_empty() {
+                break;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid header line {:?}", line))?;
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+
+        let body = lines.collect::<Vec<_>>().join("\n");
+        let body = (!body.trim().is_empty()).then_some(body);
+
+        Ok(Self {
+            method,
+            url,
+            headers,
+            body,
+        })
+    }
+
+    /// Sends the request, returns status line and response body
+    #[instrument(level = "debug")]
+    pub fn send(&self) -> Result<(String, String)> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()?;
+        let mut request = client.request(self.method.clone(), &self.url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = &self.body {
+            request = request.body(body.clone());
+        }
+
+        let response = request
+            .send()
+            .with_context(|| format!("Failed to send {} {}", self.method, self.url))?;
+        let status = format!("{:?} {}", response.version(), response.status());
+        let text = response.text().context("Failed to read response body")?;
+        debug!("{}: {} bytes", status, text.len());
+        Ok((status, text))
+    }
+}
+
+/// JSON bodies are pretty-printed, everything else is returned as is
+pub fn pretty_body(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| serde_json::to_string_pretty(&json).ok())
+        .unwrap_or_else(|| body.to_string())
+}
+
+/// Executes the request template, prints the status to stderr and the body to stdout
+pub fn execute_request(template: &str) -> Result<()> {
+    let request = HttpRequest::parse(template)?;
+    let (status, body) = request.send()?;
+    eprintln!("{} {} -> {}", request.method, request.url, status);
+    println!("{}", pretty_body(&body));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_parse_get() {
+        let request = HttpRequest::parse("https://example.com/a").unwrap();
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.url, "https://example.com/a");
+        assert!(request.headers.is_empty());
+        assert!(request.body.is_none());
+    }
+
+    #[rstest]
+    fn test_parse_post() {
+        std::env::set_var("BKMR_TEST_HTTP_TOKEN", "secret");
+        let template = "post https://example.com/items\nContent-Type: application/json\nAuthorization: Bearer ${BKMR_TEST_HTTP_TOKEN}\n\n{\"name\": \"bkmr\"}\n";
+
+        let request = HttpRequest::parse(template).unwrap();
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(
+            request.headers,
+            vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("Authorization".to_string(), "Bearer secret".to_string()),
+            ]
+        );
+        assert_eq!(request.body.as_deref(), Some("{\"name\": \"bkmr\"}"));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("GET https://example.com\nno header")]
+    #[case("GET https://example.com/${BKMR_TEST_UNDEFINED_VAR}")]
+    fn test_parse_invalid(#[case] template: &str) {
+        assert!(HttpRequest::parse(template).is_err());
+    }
+
+    #[rstest]
+    fn test_send() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("PUT", "/items/1")
+            .match_header("x-api-key", "abc")
+            .match_body("payload")
+            .with_status(201)
+            .with_body(r#"{"id":1}"#)
+            .create();
+
+        let template = format!("PUT {}/items/1\nX-Api-Key: abc\n\npayload", server.url());
+        let (status, body) = HttpRequest::parse(&template).unwrap().send().unwrap();
+        mock.assert();
+        assert!(status.contains("201"));
+        assert_eq!(pretty_body(&body), "{\n  \"id\": 1\n}");
+    }
+}
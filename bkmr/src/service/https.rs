@@ -0,0 +1,127 @@
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::Result;
+use reqwest::Method;
+use tracing::debug;
+
+use crate::parse_url_details;
+use crate::service::fetch;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What `upgrade-https` compares of the responses of both schemes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    pub status: u16,
+    /// after redirects
+    pub final_url: String,
+    pub title: String,
+}
+
+impl Probe {
+    pub fn fetch(url: &str) -> Result<Probe> {
+        let response = fetch::request(Method::GET, url, Some(TIMEOUT))?.send()?;
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+        let (title, _, _) = parse_url_details(url, &response.text()?)?;
+        debug!("{}: {} {} {:?}", url, status, final_url, title);
+        Ok(Probe { status, final_url, title })
+    }
+}
+
+/// Result of the upgrade check of one URL
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// https responds like http, the bookmark can be rewritten
+    Identical(String),
+    Differs(String),
+    HttpsFailed(String),
+    /// nothing to compare with
+    HttpFailed(String),
+    /// the https URL is another bookmark already
+    Exists(String),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Identical(url) => write!(f, "upgradable to {}", url),
+            Outcome::Differs(reason) => write!(f, "https differs: {}", reason),
+            Outcome::HttpsFailed(e) => write!(f, "https failed: {}", e),
+            Outcome::HttpFailed(e) => write!(f, "http failed: {}", e),
+            Outcome::Exists(url) => write!(f, "{} is bookmarked already", url),
+        }
+    }
+}
+
+/// https variant of an http URL
+pub fn https_url(url: &str) -> Option<String> {
+    url.strip_prefix("http://").map(|rest| format!("https://{}", rest))
+}
+
+fn without_scheme(url: &str) -> &str {
+    url.split_once("://").map(|(_, rest)| rest).unwrap_or(url).trim_end_matches('/')
+}
+
+/// Both schemes respond identically if they end up at the same URL, apart from the scheme,
+/// with the same status and title
+pub fn compare(https_url: &str, http: &Probe, https: &Probe) -> Outcome {
+    if http.status != https.status {
+        return Outcome::Differs(format!("status {} instead of {}", https.status, http.status));
+    }
+    if without_scheme(&http.final_url) != without_scheme(&https.final_url) {
+        return Outcome::Differs(format!("ends at {} instead of {}", https.final_url, http.final_url));
+    }
+    if http.title != https.title {
+        return Outcome::Differs(format!("title {:?} instead of {:?}", https.title, http.title));
+    }
+    Outcome::Identical(https_url.to_string())
+}
+
+/// Fetches both schemes of the http URL and compares them
+pub fn check(http_url: &str, https_url: &str) -> Outcome {
+    let http = match Probe::fetch(http_url) {
+        Ok(probe) => probe,
+        Err(e) => return Outcome::HttpFailed(format!("{:#}", e)),
+    };
+    match Probe::fetch(https_url) {
+        Ok(https) => compare(https_url, &http, &https),
+        Err(e) => Outcome::HttpsFailed(format!("{:#}", e)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    fn probe(status: u16, final_url: &str, title: &str) -> Probe {
+        Probe {
+            status,
+            final_url: final_url.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    #[rstest]
+    #[case("http://a.example/x", Some("https://a.example/x"))]
+    #[case("https://a.example", None)]
+    #[case("ls -la", None)]
+    fn test_https_url(#[case] url: &str, #[case] expected: Option<&str>) {
+        assert_eq!(https_url(url).as_deref(), expected);
+    }
+
+    #[rstest]
+    #[case(probe(200, "http://a.example/", "A"), probe(200, "https://a.example/", "A"), true)]
+    // http redirecting to https itself
+    #[case(probe(200, "https://a.example/x", "A"), probe(200, "https://a.example/x", "A"), true)]
+    #[case(probe(200, "http://a.example/", "A"), probe(404, "https://a.example/", "Not Found"), false)]
+    #[case(probe(200, "http://a.example/", "A"), probe(200, "https://other.example/", "A"), false)]
+    #[case(probe(200, "http://a.example/", "A"), probe(200, "https://a.example/", "Default page"), false)]
+    fn test_compare(#[case] http: Probe, #[case] https: Probe, #[case] identical: bool) {
+        let outcome = compare("https://a.example", &http, &https);
+        assert_eq!(matches!(outcome, Outcome::Identical(_)), identical, "{}", outcome);
+    }
+}
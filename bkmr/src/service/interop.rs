@@ -0,0 +1,303 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::debug;
+
+use crate::model::bookmark::Visibility;
+use crate::service::bulk::{BulkBookmark, BulkFile, BulkFormat};
+
+/// linkding bookmark of the REST API (`/api/bookmarks/`)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LinkdingBookmark {
+    url: String,
+    title: String,
+    description: String,
+    tag_names: Vec<String>,
+    shared: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_added: Option<String>,
+}
+
+/// API list response or plain list of bookmarks
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LinkdingFile {
+    Page { results: Vec<LinkdingBookmark> },
+    List(Vec<LinkdingBookmark>),
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ShioriTag {
+    name: String,
+}
+
+/// Shiori bookmark of the API (`/api/bookmarks`)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ShioriBookmark {
+    url: String,
+    title: String,
+    excerpt: String,
+    tags: Vec<ShioriTag>,
+    /// 1 if public
+    public: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ShioriFile {
+    Page { bookmarks: Vec<ShioriBookmark> },
+    List(Vec<ShioriBookmark>),
+}
+
+/// Tags are objects in API responses and plain labels in wallabag exports
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum WallabagTag {
+    Label(String),
+    Object { label: String },
+}
+
+/// wallabag entry of the API (`/api/entries`) or of a wallabag JSON export
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default)]
+struct WallabagEntry {
+    url: String,
+    title: String,
+    tags: Vec<WallabagTag>,
+    is_public: bool,
+    created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WallabagEmbedded {
+    items: Vec<WallabagEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum WallabagFile {
+    Page {
+        #[serde(rename = "_embedded")]
+        embedded: WallabagEmbedded,
+    },
+    List(Vec<WallabagEntry>),
+}
+
+/// Tags of other managers may contain blanks, bkmr tags cannot
+fn tags(names: impl IntoIterator<Item = String>) -> Vec<String> {
+    names
+        .into_iter()
+        .map(|name| name.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Public or shared bookmarks are shared, the others keep their visibility
+fn visibility(public: bool) -> Option<Visibility> {
+    public.then_some(Visibility::Shared)
+}
+
+fn is_public(bm: &BulkBookmark) -> bool {
+    bm.visibility.is_some_and(|v| v >= Visibility::Shared)
+}
+
+/// UTC time of RFC 3339 timestamps (linkding) or of `+0200` offsets (wallabag)
+fn created_ts(ts: Option<&str>) -> Option<NaiveDateTime> {
+    let ts = ts?;
+    DateTime::parse_from_rfc3339(ts)
+        .or_else(|_| DateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%z"))
+        .map(|ts| ts.naive_utc())
+        .map_err(|e| debug!("Ignoring creation time {:?}: {}", ts, e))
+        .ok()
+}
+
+fn rfc3339(ts: Option<NaiveDateTime>) -> Option<String> {
+    ts.map(|ts| ts.and_utc().to_rfc3339())
+}
+
+fn bulk_bookmark(url: String, title: String, desc: String, tags: Vec<String>, public: bool) -> BulkBookmark {
+    BulkBookmark {
+        url,
+        title,
+        desc,
+        tags,
+        visibility: visibility(public),
+        ..Default::default()
+    }
+}
+
+/// Bookmarks of a file of another bookmark manager, without ids, uuids and languages
+pub fn parse(content: &str, format: BulkFormat) -> Result<BulkFile> {
+    let bookmarks: Vec<BulkBookmark> = match format {
+        BulkFormat::Linkding => {
+            let file: LinkdingFile = serde_json::from_str(content).context("Failed to parse linkding json")?;
+            let (LinkdingFile::Page { results } | LinkdingFile::List(results)) = file;
+            results
+                .into_iter()
+                .map(|bm| BulkBookmark {
+                    created_ts: created_ts(bm.date_added.as_deref()),
+                    ..bulk_bookmark(bm.url, bm.title, bm.description, tags(bm.tag_names), bm.shared)
+                })
+                .collect()
+        }
+        BulkFormat::Shiori => {
+            let file: ShioriFile = serde_json::from_str(content).context("Failed to parse shiori json")?;
+            let (ShioriFile::Page { bookmarks } | ShioriFile::List(bookmarks)) = file;
+            bookmarks
+                .into_iter()
+                .map(|bm| {
+                    let names = bm.tags.into_iter().map(|tag| tag.name);
+                    bulk_bookmark(bm.url, bm.title, bm.excerpt, tags(names), bm.public == 1)
+                })
+                .collect()
+        }
+        BulkFormat::Wallabag => {
+            let file: WallabagFile = serde_json::from_str(content).context("Failed to parse wallabag json")?;
+            let entries = match file {
+                WallabagFile::Page { embedded } => embedded.items,
+                WallabagFile::List(entries) => entries,
+            };
+            entries
+                .into_iter()
+                .map(|entry| {
+                    let names = entry.tags.into_iter().map(|tag| match tag {
+                        WallabagTag::Label(label) | WallabagTag::Object { label } => label,
+                    });
+                    BulkBookmark {
+                        created_ts: created_ts(entry.created_at.as_deref()),
+                        ..bulk_bookmark(entry.url, entry.title, String::new(), tags(names), entry.is_public)
+                    }
+                })
+                .collect()
+        }
+        _ => return Err(anyhow!("{:?} is not a format of another bookmark manager", format)),
+    };
+    debug!("{} bookmarks", bookmarks.len());
+    Ok(BulkFile { visibility: None, bookmarks })
+}
+
+/// File in the shape the other bookmark manager reads or returns from its API
+pub fn serialize(file: &BulkFile, format: BulkFormat) -> Result<String> {
+    let value = match format {
+        BulkFormat::Linkding => {
+            let results: Vec<LinkdingBookmark> = file
+                .bookmarks
+                .iter()
+                .map(|bm| LinkdingBookmark {
+                    url: bm.url.clone(),
+                    title: bm.title.clone(),
+                    description: bm.desc.clone(),
+                    tag_names: bm.tags.clone(),
+                    shared: is_public(bm),
+                    date_added: rfc3339(bm.created_ts),
+                })
+                .collect();
+            json!({"count": results.len(), "next": null, "previous": null, "results": results})
+        }
+        BulkFormat::Shiori => {
+            let bookmarks: Vec<ShioriBookmark> = file
+                .bookmarks
+                .iter()
+                .map(|bm| ShioriBookmark {
+                    url: bm.url.clone(),
+                    title: bm.title.clone(),
+                    excerpt: bm.desc.clone(),
+                    tags: bm.tags.iter().map(|name| ShioriTag { name: name.clone() }).collect(),
+                    public: is_public(bm) as i32,
+                })
+                .collect();
+            json!({"page": 1, "maxPage": 1, "bookmarks": bookmarks})
+        }
+        // wallabag's own export format, which its "wallabag v2" importer reads
+        BulkFormat::Wallabag => json!(file
+            .bookmarks
+            .iter()
+            .map(|bm| json!({
+                "url": bm.url,
+                "title": bm.title,
+                "tags": bm.tags,
+                "is_archived": 0,
+                "is_starred": 0,
+                "is_public": is_public(bm),
+                "created_at": rfc3339(bm.created_ts),
+            }))
+            .collect::<Vec<_>>()),
+        _ => return Err(anyhow!("{:?} is not a format of another bookmark manager", format)),
+    };
+    serde_json::to_string_pretty(&value).with_context(|| format!("Failed to serialize to {:?}", format))
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    fn file() -> BulkFile {
+        BulkFile {
+            visibility: None,
+            bookmarks: vec![
+                BulkBookmark {
+                    url: "https://www.rust-lang.org".to_string(),
+                    title: "Rust".to_string(),
+                    desc: "A language".to_string(),
+                    tags: vec!["lang".to_string(), "rust".to_string()],
+                    visibility: Some(Visibility::Public),
+                    created_ts: NaiveDateTime::parse_from_str("2021-03-04 05:06:07", "%Y-%m-%d %H:%M:%S").ok(),
+                    ..Default::default()
+                },
+                BulkBookmark {
+                    url: "https://example.com".to_string(),
+                    title: "Example".to_string(),
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    #[rstest]
+    #[case(BulkFormat::Linkding)]
+    #[case(BulkFormat::Shiori)]
+    fn test_roundtrip(#[case] format: BulkFormat) -> Result<()> {
+        let parsed = parse(&serialize(&file(), format)?, format)?;
+        assert_eq!(parsed.bookmarks[0].visibility, Some(Visibility::Shared));
+        assert_eq!(parsed.bookmarks[0].desc, "A language");
+        assert_eq!(parsed.bookmarks[0].tags, vec!["lang", "rust"]);
+        assert_eq!(parsed.bookmarks[1].url, "https://example.com");
+        assert_eq!(parsed.bookmarks[1].visibility, None);
+        if format == BulkFormat::Linkding {
+            assert_eq!(parsed.bookmarks[0].created_ts, file().bookmarks[0].created_ts);
+            assert_eq!(parsed.bookmarks[1].created_ts, None);
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_roundtrip_wallabag() -> Result<()> {
+        let parsed = parse(&serialize(&file(), BulkFormat::Wallabag)?, BulkFormat::Wallabag)?;
+        assert_eq!(parsed.bookmarks[0].title, "Rust");
+        assert_eq!(parsed.bookmarks[0].tags, vec!["lang", "rust"]);
+        assert_eq!(parsed.bookmarks[0].desc, "");
+        assert_eq!(parsed.bookmarks[0].created_ts, file().bookmarks[0].created_ts);
+        assert_eq!(parsed.bookmarks.len(), 2);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(BulkFormat::Linkding, r#"[{"url": "https://a.example", "title": "A", "tag_names": ["Web Dev"], "notes": "x"}]"#)]
+    #[case(BulkFormat::Shiori, r#"{"bookmarks": [{"id": 1, "url": "https://a.example", "title": "A", "tags": [{"id": 3, "name": "Web Dev"}]}], "maxPage": 1}"#)]
+    #[case(BulkFormat::Wallabag, r#"{"page": 1, "_embedded": {"items": [{"url": "https://a.example", "title": "A", "tags": [{"id": 1, "label": "Web Dev", "slug": "web-dev"}]}]}}"#)]
+    #[case(BulkFormat::Wallabag, r#"[{"url": "https://a.example", "title": "A", "tags": ["Web Dev"], "content": "<p>..</p>", "created_at": "2016-08-21T12:37:09+0200"}]"#)]
+    fn test_parse_api_responses(#[case] format: BulkFormat, #[case] content: &str) -> Result<()> {
+        let file = parse(content, format)?;
+        assert_eq!(file.bookmarks.len(), 1);
+        assert_eq!(file.bookmarks[0].url, "https://a.example");
+        assert_eq!(file.bookmarks[0].title, "A");
+        assert_eq!(file.bookmarks[0].tags, vec!["web-dev"]);
+        Ok(())
+    }
+}
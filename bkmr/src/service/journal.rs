@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use tracing::debug;
+
+use crate::model::bookmark::Bookmark;
+use crate::service::bulk::{BulkFile, BulkFormat};
+
+/// Deleted bookmarks are kept here as bulk JSON files
+pub fn journal_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("bkmr").join("journal"))
+}
+
+/// Writes the bookmarks about to be deleted, they are restored by `bkmr import <file>`
+pub fn record_deletion(dir: &Path, bms: &[Bookmark], now: NaiveDateTime) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create journal directory {:?}", dir))?;
+    let path = dir.join(format!("{}-delete.json", now.format("%Y%m%d-%H%M%S%.3f")));
+    fs::write(&path, BulkFile::new(bms).serialize(BulkFormat::Json)?)
+        .with_context(|| format!("Failed to write journal {:?}", path))?;
+    debug!("Journal {:?}", path);
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    fn test_record_deletion() -> Result<()> {
+        let (dir, mut dal) = setup_temp_db()?;
+        let bms = dal.get_bookmarks("")?;
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 10, 14).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let path = record_deletion(&dir.path().join("journal"), &bms[..2], now)?;
+        assert!(path.ends_with("20261014-120000.000-delete.json"));
+
+        let file = BulkFile::parse(&fs::read_to_string(&path)?, BulkFormat::Json)?;
+        assert_eq!(file.bookmarks.len(), 2);
+        assert_eq!(file.bookmarks[0].uuid.as_deref(), Some(bms[0].uuid.as_str()));
+        Ok(())
+    }
+}
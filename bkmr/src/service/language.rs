@@ -0,0 +1,151 @@
+use regex::Regex;
+use tracing::debug;
+
+use crate::model::bookmark::Bookmark;
+
+/// Minimum score of a programming language heuristic
+const MIN_CODE_SCORE: usize = 2;
+/// Minimum number of stopwords to decide on a natural language
+const MIN_STOPWORDS: usize = 3;
+
+/// (language, patterns): each matching pattern scores one point
+const CODE_PATTERNS: &[(&str, &[&str])] = &[
+    ("rust", &[r"\bfn \w+\(", r"\blet mut\b", r"\bimpl\b", r"\w+::\w+", r"\bpub (fn|struct|enum)\b", r"println!\("]),
+    ("python", &[r"(?m)^\s*def \w+\(.*\):", r"(?m)^\s*(from \w+ )?import \w+", r"\bself\.", r"\belif\b", r"(?m)^\s*class \w+.*:$"]),
+    ("bash", &[r"^#!/(usr/)?bin/(env )?(ba|z)?sh", r"\bfi\b", r"\$\{?\w+", r"\becho\b", r"\bthen\b", r"(?m)^\s*export \w+="]),
+    ("sql", &[r"(?i)\bselect\b.+\bfrom\b", r"(?i)\bwhere\b", r"(?i)\b(insert into|update \w+ set|delete from)\b", r"(?i)\b(inner|left) join\b", r"(?i)\bgroup by\b"]),
+    ("javascript", &[r"\bconst \w+ =", r"=>", r"\bfunction\b", r"console\.log\(", r"\b(let|var) \w+ ="]),
+    ("go", &[r"(?m)^package \w+", r"\bfunc \w+\(", r":=", r"fmt\.\w+\("]),
+];
+
+/// (language, stopwords) for notes
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "is", "of", "to", "in", "that", "with", "for", "this"]),
+    ("de", &["der", "die", "das", "und", "ist", "nicht", "mit", "ein", "eine", "auf"]),
+    ("fr", &["le", "la", "les", "et", "est", "des", "une", "pour", "dans", "pas"]),
+    ("es", &["el", "los", "las", "y", "es", "una", "para", "con", "por", "del"]),
+];
+
+/// Programming language of a snippet by keyword heuristics
+pub fn detect_code(text: &str) -> Option<&'static str> {
+    CODE_PATTERNS
+        .iter()
+        .map(|(lang, patterns)| {
+            let score = patterns
+                .iter()
+                .filter(|p| Regex::new(p).unwrap().is_match(text))
+                .count();
+            (*lang, score)
+        })
+        .filter(|(_, score)| *score >= MIN_CODE_SCORE)
+        .max_by_key(|(_, score)| *score)
+        .map(|(lang, _)| lang)
+}
+
+/// Natural language of a note: script for CJK/cyrillic, stopwords otherwise
+pub fn detect_natural(text: &str) -> Option<&'static str> {
+    let count = |range: &[(char, char)]| {
+        text.chars()
+            .filter(|c| range.iter().any(|(lo, hi)| (lo..=hi).contains(&c)))
+            .count()
+    };
+    let scripts: [(&str, &[(char, char)]); 4] = [
+        ("ja", &[('\u{3040}', '\u{30ff}')]),
+        ("ko", &[('\u{ac00}', '\u{d7af}')]),
+        ("zh", &[('\u{4e00}', '\u{9fff}')]),
+        ("ru", &[('\u{0400}', '\u{04ff}')]),
+    ];
+    // kana decide for japanese before the han characters shared with chinese
+    if let Some((lang, _)) = scripts.iter().find(|(_, range)| count(range) >= 2) {
+        return Some(lang);
+    }
+
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    STOPWORDS
+        .iter()
+        .map(|(lang, stopwords)| {
+            let n = words.iter().filter(|w| stopwords.contains(&w.as_str())).count();
+            (*lang, n)
+        })
+        .filter(|(_, n)| *n >= MIN_STOPWORDS)
+        .max_by_key(|(_, n)| *n)
+        .map(|(lang, _)| lang)
+}
+
+/// Language of the bookmark content: code or natural language,
+/// None for plain URLs and paths, which are described by their title
+pub fn detect_language(bm: &Bookmark) -> Option<&'static str> {
+    let content = bm.URL.trim();
+    let is_reference = !content.contains(char::is_whitespace)
+        && (content.contains("://") || content.starts_with(['/', '~', '.']));
+    let text = if is_reference { bm.desc.as_str() } else { content };
+    detect_code(text).or_else(|| detect_natural(text))
+}
+
+/// Sets the detected language unless the bookmark already has one, returns the language
+pub fn fill_language(bm: &mut Bookmark) -> Option<&'static str> {
+    if bm.language.is_some() {
+        return None;
+    }
+    let lang = detect_language(bm)?;
+    debug!("Detected language {:?} for {:?}", lang, bm.URL);
+    bm.language = Some(lang.to_string());
+    Some(lang)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    #[rstest]
+    #[case("fn main() {\n    let mut v = Vec::new();\n    println!(\"{:?}\", v);\n}", Some("rust"))]
+    #[case("def add(a, b):\n    return a + b\n\nimport os", Some("python"))]
+    #[case("#!/bin/bash\nif [ -z \"$HOME\" ]; then\n  echo missing\nfi", Some("bash"))]
+    #[case("SELECT id, name FROM users WHERE age > 3 GROUP BY name", Some("sql"))]
+    #[case("const add = (a, b) => a + b;\nconsole.log(add(1, 2));", Some("javascript"))]
+    #[case("package main\n\nfunc main() {\n\tx := 1\n\tfmt.Println(x)\n}", Some("go"))]
+    #[case("just some words", None)]
+    fn test_detect_code(#[case] text: &str, #[case] expected: Option<&str>) {
+        assert_eq!(detect_code(text), expected);
+    }
+
+    #[rstest]
+    #[case("This is a note about the setup of the server and the network", Some("en"))]
+    #[case("Das ist eine Notiz, die nicht mit der Hand geschrieben ist", Some("de"))]
+    #[case("数据库索引的设计", Some("zh"))]
+    #[case("データベースの設計", Some("ja"))]
+    #[case("Привет мир", Some("ru"))]
+    #[case("k8s cluster", None)]
+    fn test_detect_natural(#[case] text: &str, #[case] expected: Option<&str>) {
+        assert_eq!(detect_natural(text), expected);
+    }
+
+    #[rstest]
+    fn test_fill_language() {
+        let mut bm = BookmarkBuilder::new()
+            .URL("SELECT * FROM t WHERE id = 1".to_string())
+            .tags(",db,".to_string())
+            .build();
+        assert_eq!(fill_language(&mut bm), Some("sql"));
+        assert_eq!(bm.language.as_deref(), Some("sql"));
+        assert_eq!(bm.tags, ",db,");
+
+        // an explicit language is kept
+        bm.language = Some("sqlite".to_string());
+        assert_eq!(fill_language(&mut bm), None);
+        assert_eq!(bm.language.as_deref(), Some("sqlite"));
+
+        let mut bm = BookmarkBuilder::new()
+            .URL("https://example.com".to_string())
+            .metadata("the title".to_string())
+            .build();
+        assert_eq!(fill_language(&mut bm), None);
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use tracing::debug;
+
+use crate::model::bookmark::Bookmark;
+use crate::model::system_tag::is_system_tag;
+use crate::service::https::https_url;
+use crate::service::rules::TagRules;
+
+/// Descriptions longer than this (in characters) are flagged
+pub const MAX_DESC_LEN: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    MissingTitle,
+    NoTags,
+    LongDescription,
+    InsecureUrl,
+    DuplicateTitle,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Rule::MissingTitle => "title",
+            Rule::NoTags => "tags",
+            Rule::LongDescription => "desc",
+            Rule::InsecureUrl => "http",
+            Rule::DuplicateTitle => "duplicate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Change applied by `lint --fix`, only for findings where it cannot lose information
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    Url(String),
+    /// tags of the tag rules
+    Tags(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub rule: Rule,
+    pub id: i32,
+    pub message: String,
+    pub suggestion: String,
+    pub fix: Option<Fix>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}, {}", self.rule, self.id, self.message, self.suggestion)?;
+        if self.fix.is_some() {
+            write!(f, " (--fix)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Findings of all rules ordered by bookmark, `https_available` is asked whether an http URL
+/// can be switched to https, None skips the rule
+pub fn lint(bms: &[Bookmark], rules: &TagRules, https_available: Option<&dyn Fn(&str) -> bool>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for bm in bms {
+        if bm.metadata.trim().is_empty() {
+            let suggestion = if bm.URL.starts_with("http") {
+                format!("fetch it with: bkmr refresh-metadata {}", bm.id)
+            } else {
+                format!("add one with: bkmr edit {}", bm.id)
+            };
+            findings.push(Finding {
+                rule: Rule::MissingTitle,
+                id: bm.id,
+                message: "no title".to_string(),
+                suggestion,
+                fix: None,
+            });
+        }
+        if bm.get_tags().iter().all(|tag| is_system_tag(tag)) {
+            let fix = Some(rules.missing_tags(bm)).filter(|tags| !tags.is_empty());
+            let suggestion = match &fix {
+                Some(tags) => format!("tag rules add {}", tags.join(",")),
+                None => format!("add some with: bkmr update {} -t <tags>", bm.id),
+            };
+            findings.push(Finding {
+                rule: Rule::NoTags,
+                id: bm.id,
+                message: "no tags".to_string(),
+                suggestion,
+                fix: fix.map(Fix::Tags),
+            });
+        }
+        let desc_len = bm.desc.chars().count();
+        if desc_len > MAX_DESC_LEN {
+            findings.push(Finding {
+                rule: Rule::LongDescription,
+                id: bm.id,
+                message: format!("description of {} characters", desc_len),
+                suggestion: format!("shorten it to {} with: bkmr edit {}", MAX_DESC_LEN, bm.id),
+                fix: None,
+            });
+        }
+        if let (Some(https), Some(available)) = (https_url(&bm.URL), https_available) {
+            if available(&bm.URL) {
+                findings.push(Finding {
+                    rule: Rule::InsecureUrl,
+                    id: bm.id,
+                    message: "insecure http URL".to_string(),
+                    suggestion: format!("use {}", https),
+                    fix: Some(Fix::Url(https)),
+                });
+            }
+        }
+    }
+    findings.extend(duplicate_titles(bms));
+    findings.sort_by_key(|f| f.id);
+    debug!("{} findings", findings.len());
+    findings
+}
+
+/// Bookmarks sharing a title with another one, ignoring case
+fn duplicate_titles(bms: &[Bookmark]) -> Vec<Finding> {
+    let mut by_title: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+    for bm in bms.iter().filter(|bm| !bm.metadata.trim().is_empty()) {
+        by_title.entry(bm.metadata.trim().to_lowercase()).or_default().push(bm.id);
+    }
+    by_title
+        .into_values()
+        .filter(|ids| ids.len() > 1)
+        .flat_map(|ids| {
+            ids.iter()
+                .map(|&id| {
+                    let others: Vec<String> = ids.iter().filter(|&&other| other != id).map(|o| o.to_string()).collect();
+                    Finding {
+                        rule: Rule::DuplicateTitle,
+                        id,
+                        message: format!("same title as {}", others.join(", ")),
+                        suggestion: "retitle or delete one".to_string(),
+                        fix: None,
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Percentage of the bookmarks without findings
+pub fn score(n_bms: usize, findings: &[Finding]) -> f64 {
+    if n_bms == 0 {
+        return 100.0;
+    }
+    let mut flagged: Vec<i32> = findings.iter().map(|f| f.id).collect();
+    flagged.dedup();
+    100.0 * (n_bms - flagged.len()) as f64 / n_bms as f64
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn bm(id: i32, url: &str, title: &str, tags: &str) -> Bookmark {
+        BookmarkBuilder::new()
+            .id(id)
+            .URL(url.to_string())
+            .metadata(title.to_string())
+            .tags(tags.to_string())
+            .build()
+    }
+
+    fn rules() -> TagRules {
+        TagRules::new(&BTreeMap::from([("^https://github.com".to_string(), vec!["dev".to_string()])])).unwrap()
+    }
+
+    #[rstest]
+    fn test_lint() {
+        let long = Bookmark {
+            desc: "x".repeat(MAX_DESC_LEN + 1),
+            ..bm(3, "https://c.example", "C", ",web,")
+        };
+        let bms = vec![
+            bm(1, "http://a.example", "", ",web,"),
+            bm(2, "https://github.com/sysid/bkmr", "Dup", ",_snip_,"),
+            long,
+            bm(4, "http://b.example", "dup", ",web,"),
+            bm(5, "https://d.example", "D", ",,"),
+        ];
+        let available = |url: &str| url == "http://a.example";
+        let findings = lint(&bms, &rules(), Some(&available));
+        let found: Vec<(i32, Rule)> = findings.iter().map(|f| (f.id, f.rule)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, Rule::MissingTitle),
+                (1, Rule::InsecureUrl),
+                (2, Rule::NoTags),
+                (2, Rule::DuplicateTitle),
+                (3, Rule::LongDescription),
+                (4, Rule::DuplicateTitle),
+                (5, Rule::NoTags),
+            ]
+        );
+        assert_eq!(findings[1].fix, Some(Fix::Url("https://a.example".to_string())));
+        assert_eq!(findings[2].fix, Some(Fix::Tags(vec!["dev".to_string()])));
+        assert_eq!(findings[5].message, "same title as 2");
+        assert!(findings[6].fix.is_none());
+        assert_eq!(score(bms.len(), &findings), 0.0);
+
+        let offline: Vec<Rule> = lint(&bms, &rules(), None).iter().map(|f| f.rule).collect();
+        assert!(!offline.contains(&Rule::InsecureUrl));
+    }
+
+    #[rstest]
+    fn test_score() {
+        let bms = vec![bm(1, "https://a.example", "A", ",web,"), bm(2, "https://b.example", "", ",web,")];
+        assert_eq!(score(bms.len(), &lint(&bms, &rules(), None)), 50.0);
+        assert_eq!(score(0, &[]), 100.0);
+    }
+}
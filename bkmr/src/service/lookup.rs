@@ -0,0 +1,71 @@
+use crate::model::bookmark::Bookmark;
+
+/// Most candidates offered for disambiguation
+pub const MAX_CANDIDATES: usize = 20;
+
+/// Could be a uuid prefix, e.g. `0b3e9c1a` or `0b3e9c1a-5f2d`
+pub fn is_uuid_like(part: &str) -> bool {
+    part.chars().all(|c| c.is_ascii_hexdigit() || c == '-') && part.chars().any(|c| c.is_ascii_hexdigit())
+}
+
+/// Bookmarks titled like the query, ignoring case: exact matches, otherwise prefix matches, by id
+pub fn find_by_title<'a>(bms: &'a [Bookmark], query: &str) -> Vec<&'a Bookmark> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut exact: Vec<&Bookmark> = bms.iter().filter(|bm| bm.metadata.trim().to_lowercase() == query).collect();
+    if exact.is_empty() {
+        exact = bms
+            .iter()
+            .filter(|bm| bm.metadata.trim().to_lowercase().starts_with(&query))
+            .collect();
+    }
+    exact.sort_by_key(|bm| bm.id);
+    exact
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn bms() -> Vec<Bookmark> {
+        ["My Snippet", "My Snippet Extended", "my snippet", "Other"]
+            .iter()
+            .enumerate()
+            .map(|(i, title)| {
+                BookmarkBuilder::new()
+                    .id(i as i32 + 1)
+                    .URL(format!("https://example.com/{}", i))
+                    .metadata(title.to_string())
+                    .build()
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case("My Snippet", vec![1, 3])]
+    #[case("my snippet ext", vec![2])]
+    #[case("oth", vec![4])]
+    #[case("My", vec![1, 2, 3])]
+    #[case("none", vec![])]
+    #[case(" ", vec![])]
+    fn test_find_by_title(#[case] query: &str, #[case] expected: Vec<i32>) {
+        let bms = bms();
+        let ids: Vec<i32> = find_by_title(&bms, query).iter().map(|bm| bm.id).collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[rstest]
+    #[case("0b3e9c1a", true)]
+    #[case("0b3e9c1a-5f2d", true)]
+    #[case("deadbeef", true)]
+    #[case("My Snippet", false)]
+    #[case("--------", false)]
+    fn test_is_uuid_like(#[case] part: &str, #[case] expected: bool) {
+        assert_eq!(is_uuid_like(part), expected);
+    }
+}
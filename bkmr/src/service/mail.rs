@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Context, Result};
+use tracing::debug;
+
+use crate::util::helper::interpolate_env;
+
+/// Email template of a `_mail_` bookmark, environment variables are interpolated:
+///
+/// ```text
+/// To: team@example.com
+/// Cc: boss@example.com
+/// Subject: Status week ${WEEK}
+///
+/// Hi all,
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MailTemplate {
+    pub to: String,
+    pub cc: Option<String>,
+    pub bcc: Option<String>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+}
+
+impl MailTemplate {
+    pub fn parse(content: &str) -> Result<Self> {
+        let content = interpolate_env(content.trim_start())?;
+        let mut mail = MailTemplate::default();
+        let mut lines = content.lines();
+
+        for line in lines.by_ref() {
+            if line.trim().is_empty() {
+                break;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid header line {:?}", line))?;
+            let value = value.trim().to_string();
+            match name.trim().to_lowercase().as_str() {
+                "to" => mail.to = value,
+                "cc" => mail.cc = Some(value),
+                "bcc" => mail.bcc = Some(value),
+                "subject" => mail.subject = Some(value),
+                other => return Err(anyhow!("Unknown mail header {:?}", other)),
+            }
+        }
+        let body = lines.collect::<Vec<_>>().join("\n");
+        mail.body = (!body.trim().is_empty()).then_some(body);
+        Ok(mail)
+    }
+
+    /// RFC 6068 mailto URL
+    pub fn to_mailto(&self) -> String {
+        let to = self
+            .to
+            .split(',')
+            .map(|address| percent_encode(address.trim()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let fields = [
+            ("cc", &self.cc),
+            ("bcc", &self.bcc),
+            ("subject", &self.subject),
+            ("body", &self.body),
+        ];
+        let query = fields
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .as_ref()
+                    .map(|v| format!("{}={}", name, percent_encode(&v.replace('\n', "\r\n"))))
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+
+        if query.is_empty() {
+            format!("mailto:{}", to)
+        } else {
+            format!("mailto:{}?{}", to, query)
+        }
+    }
+}
+
+/// Percent-encodes everything except unreserved characters and `@`
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'@' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Opens the default mail client with the prefilled mail
+pub fn open_mail(content: &str) -> Result<()> {
+    let mailto = MailTemplate::parse(content)?.to_mailto();
+    debug!("{:?}", mailto);
+    open::that(&mailto).with_context(|| format!("Error OS opening {}", mailto))
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_to_mailto() {
+        std::env::set_var("BKMR_TEST_MAIL_WEEK", "42");
+        let content = "To: a@example.com, b@example.com\nSubject: Status week ${BKMR_TEST_MAIL_WEEK}\n\nHi all,\nall good & green";
+
+        let mail = MailTemplate::parse(content).unwrap();
+        assert_eq!(mail.subject.as_deref(), Some("Status week 42"));
+        assert_eq!(
+            mail.to_mailto(),
+            "mailto:a@example.com,b@example.com?subject=Status%20week%2042&body=Hi%20all%2C%0D%0Aall%20good%20%26%20green"
+        );
+    }
+
+    #[rstest]
+    fn test_to_mailto_recipient_only() {
+        let mail = MailTemplate::parse("To: a@example.com").unwrap();
+        assert_eq!(mail.to_mailto(), "mailto:a@example.com");
+    }
+
+    #[rstest]
+    #[case("From: a@example.com")]
+    #[case("To a@example.com")]
+    fn test_parse_invalid(#[case] content: &str) {
+        assert!(MailTemplate::parse(content).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::adapter::dal::{Dal, ObjectSize};
+
+/// Where the space of the database file goes, `bkmr maintenance size`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SizeReport {
+    pub file_size: i64,
+    /// free pages, released by `vacuum`
+    pub free_size: i64,
+    pub n_embeddings: i64,
+    pub embedding_size: i64,
+    pub objects: Vec<ObjectSize>,
+}
+
+pub fn size_report(dal: &mut Dal) -> Result<SizeReport> {
+    let pages = dal.get_page_stats()?;
+    let (n_embeddings, embedding_size) = dal.get_embedding_size()?;
+    Ok(SizeReport {
+        file_size: pages.page_size * pages.page_count,
+        free_size: pages.page_size * pages.freelist_count,
+        n_embeddings,
+        embedding_size,
+        objects: dal.get_object_sizes()?,
+    })
+}
+
+/// Binary units with one decimal, e.g. `1.5 MiB`
+pub fn format_bytes(n: i64) -> String {
+    let units = ["KiB", "MiB", "GiB", "TiB"];
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+    let mut size = n as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, units[unit])
+}
+
+impl SizeReport {
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Total: {}", format_bytes(self.file_size)),
+            format!("Free: {}", format_bytes(self.free_size)),
+            format!(
+                "Embeddings: {} in {} bookmarks",
+                format_bytes(self.embedding_size),
+                self.n_embeddings
+            ),
+        ];
+        let width = self.objects.iter().map(|o| o.name.len()).max().unwrap_or(0);
+        for object in &self.objects {
+            lines.push(format!(
+                "{:5} {:width$} {:>10}",
+                object.kind,
+                object.name,
+                format_bytes(object.size)
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    #[case(0, "0 B")]
+    #[case(1023, "1023 B")]
+    #[case(1536, "1.5 KiB")]
+    #[case(5 * 1024 * 1024, "5.0 MiB")]
+    fn test_format_bytes(#[case] n: i64, #[case] expected: &str) {
+        assert_eq!(format_bytes(n), expected);
+    }
+
+    #[rstest]
+    fn test_size_report() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let report = size_report(&mut dal)?;
+        assert!(report.file_size > 0);
+        let total: i64 = report.objects.iter().map(|o| o.size).sum();
+        assert!(total <= report.file_size);
+        assert!(report.lines()[0].starts_with("Total: "));
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use tracing::{debug, instrument};
+
+use crate::adapter::dal::Dal;
+use crate::model::bookmark::{Bookmark, BookmarkUpdater};
+use crate::service::bus::{self, BookmarkEvent};
+use crate::service::migrate;
+
+/// Bookmarks with embeddings of another database, older schemas are migrated on a copy
+#[instrument(level = "debug")]
+pub fn read_database(path: &str) -> Result<Vec<Bookmark>> {
+    let (_dir, mut dal) = migrate::migrated_copy(path)?;
+    dal.get_bookmarks_with_embeddings("")
+        .with_context(|| format!("Failed to read bookmarks from {}", path))
+}
+
+/// How to resolve differing title/description of the same bookmark, tags are always merged
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum MergeStrategy {
+    /// take the more recently updated version
+    Newest,
+    /// keep the local version
+    Skip,
+    /// ask for every conflict
+    Interactive,
+}
+
+/// What to do when an added URL is already bookmarked
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum OnExists {
+    /// ask, fails like error without input
+    #[default]
+    Ask,
+    /// merge into the existing bookmark
+    Merge,
+    /// keep the existing bookmark unchanged
+    Skip,
+    /// fail with the existing id
+    Error,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeAction {
+    Add(Bookmark),
+    Update { old: Bookmark, new: Bookmark },
+}
+
+impl fmt::Display for MergeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeAction::Add(bm) => write!(f, "+ {} [{}]", bm.URL, bm.tags),
+            MergeAction::Update { old, new } => {
+                write!(f, "~ {}: {}", old.id, old.URL)?;
+                if old.tags != new.tags {
+                    write!(f, " tags {} -> {}", old.tags, new.tags)?;
+                }
+                if old.metadata != new.metadata || old.desc != new.desc {
+                    write!(f, " title/desc from other")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Merges other into local: tags are united, conflicting title/description resolved by strategy.
+/// `ask` decides interactive conflicts, returns true to take the other version.
+fn merge_one(
+    local: &Bookmark,
+    other: &Bookmark,
+    strategy: MergeStrategy,
+    ask: &mut impl FnMut(&Bookmark, &Bookmark) -> bool,
+) -> Bookmark {
+    let mut merged = local.clone();
+    let mut tags = local.get_tags();
+    tags.extend(other.get_tags());
+    merged.set_tags(tags);
+    if merged.language.is_none() {
+        merged.language = other.language.clone();
+    }
+    // the more restrictive visibility wins
+    merged.visibility = local.visibility().min(other.visibility()).to_string();
+
+    if local.metadata != other.metadata || local.desc != other.desc {
+        let take_other = match strategy {
+            MergeStrategy::Newest => other.last_update_ts > local.last_update_ts,
+            MergeStrategy::Skip => false,
+            MergeStrategy::Interactive => ask(local, other),
+        };
+        if take_other {
+            merged.metadata = other.metadata.clone();
+            merged.desc = other.desc.clone();
+        }
+    }
+    merged
+}
+
+/// Merges an added duplicate into the existing bookmark: tags are united,
+/// a given title/description replaces the existing one and the open count is bumped
+pub fn merge_duplicate(existing: &Bookmark, added: &Bookmark) -> Bookmark {
+    let mut merged = existing.clone();
+    let mut tags = existing.get_tags();
+    tags.extend(added.get_tags());
+    merged.set_tags(tags);
+    if !added.metadata.trim().is_empty() {
+        merged.metadata = added.metadata.clone();
+    }
+    if !added.desc.trim().is_empty() {
+        merged.desc = added.desc.clone();
+    }
+    if merged.language.is_none() {
+        merged.language = added.language.clone();
+    }
+    merged.flags += 1;
+    merged
+}
+
+/// Actions required to merge the other bookmarks into the local ones.
+/// Bookmarks are identical if uuid, URL or content hash match.
+pub fn plan(
+    local: &[Bookmark],
+    others: &[Bookmark],
+    strategy: MergeStrategy,
+    mut ask: impl FnMut(&Bookmark, &Bookmark) -> bool,
+) -> Vec<MergeAction> {
+    let by_uuid: HashMap<&str, usize> = local
+        .iter()
+        .enumerate()
+        .filter(|(_, bm)| !bm.uuid.is_empty())
+        .map(|(i, bm)| (bm.uuid.as_str(), i))
+        .collect();
+    let by_url: HashMap<&str, usize> = local
+        .iter()
+        .enumerate()
+        .map(|(i, bm)| (bm.URL.as_str(), i))
+        .collect();
+    let by_hash: HashMap<&Vec<u8>, usize> = local
+        .iter()
+        .enumerate()
+        .filter_map(|(i, bm)| bm.content_hash.as_ref().map(|h| (h, i)))
+        .collect();
+
+    let mut merged = local.to_vec();
+    let mut added: Vec<Bookmark> = Vec::new();
+    for other in others {
+        let idx = by_uuid
+            .get(other.uuid.as_str())
+            .or_else(|| by_url.get(other.URL.as_str()))
+            .copied()
+            .or_else(|| {
+                other
+                    .content_hash
+                    .as_ref()
+                    .and_then(|h| by_hash.get(h).copied())
+            });
+        match idx {
+            Some(i) => merged[i] = merge_one(&merged[i], other, strategy, &mut ask),
+            None if added.iter().all(|bm| bm.URL != other.URL) => added.push(other.clone()),
+            None => {}
+        }
+    }
+
+    let mut actions: Vec<MergeAction> = local
+        .iter()
+        .zip(merged)
+        .filter(|(old, new)| *old != new)
+        .map(|(old, new)| MergeAction::Update {
+            old: old.clone(),
+            new,
+        })
+        .collect();
+    actions.extend(added.into_iter().map(MergeAction::Add));
+    debug!("{:?}", actions);
+    actions
+}
+
+/// Executes the merge actions within one transaction, the events are published after the
+/// commit, returns the stored bookmarks
+#[instrument(level = "debug", skip(actions))]
+pub fn apply(dal: &mut Dal, actions: Vec<MergeAction>) -> Result<Vec<Bookmark>> {
+    let events = dal.transaction(|dal| {
+        let mut events = Vec::new();
+        for action in actions {
+            match action {
+                MergeAction::Add(bm) => {
+                    let added = dal.insert_bookmark(bm.convert_to_new_bookmark())?;
+                    events.extend(added.into_iter().map(BookmarkEvent::Added));
+                }
+                MergeAction::Update { mut new, .. } => {
+                    new.update();
+                    let updated = dal.update_bookmark(new)?;
+                    events.extend(updated.into_iter().map(BookmarkEvent::Updated));
+                }
+            }
+        }
+        Ok(events)
+    })?;
+    Ok(bus::publish_all(events))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::DateTime;
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+    use crate::util::testing::setup_temp_db;
+
+    fn bm(id: i32, url: &str, title: &str, tags: &str, ts: i64) -> Bookmark {
+        let mut bm = BookmarkBuilder::new()
+            .id(id)
+            .URL(url.to_string())
+            .metadata(title.to_string())
+            .tags(tags.to_string())
+            .build();
+        bm.last_update_ts = DateTime::from_timestamp(ts, 0).unwrap().naive_utc();
+        bm
+    }
+
+    #[rstest]
+    #[case(MergeStrategy::Newest, true, "other")]
+    #[case(MergeStrategy::Skip, true, "local")]
+    #[case(MergeStrategy::Interactive, true, "other")]
+    #[case(MergeStrategy::Interactive, false, "local")]
+    fn test_merge_one(
+        #[case] strategy: MergeStrategy,
+        #[case] answer: bool,
+        #[case] expected_title: &str,
+    ) {
+        let local = bm(1, "https://a", "local", ",aaa,", 1);
+        let other = bm(7, "https://a", "other", ",bbb,", 2);
+
+        let merged = merge_one(&local, &other, strategy, &mut |_, _| answer);
+        assert_eq!(merged.id, 1);
+        assert_eq!(merged.tags, ",aaa,bbb,");
+        assert_eq!(merged.metadata, expected_title);
+    }
+
+    #[rstest]
+    fn test_merge_duplicate() {
+        let existing = bm(3, "https://a", "existing", ",aaa,", 1);
+        let added = Bookmark {
+            desc: "added desc".to_string(),
+            ..bm(1, "https://a", " ", ",bbb,", 2)
+        };
+
+        let merged = merge_duplicate(&existing, &added);
+        assert_eq!(merged.id, 3);
+        assert_eq!(merged.tags, ",aaa,bbb,");
+        assert_eq!(merged.metadata, "existing");
+        assert_eq!(merged.desc, "added desc");
+        assert_eq!(merged.flags, existing.flags + 1);
+    }
+
+    #[rstest]
+    fn test_plan() {
+        let local = vec![bm(1, "https://a", "a", ",aaa,", 1), bm(2, "https://b", "b", ",bbb,", 1)];
+        let others = vec![
+            bm(1, "https://a", "a", ",aaa,", 1),
+            bm(2, "https://b", "b", ",xxx,", 1),
+            bm(3, "https://c", "c", ",ccc,", 1),
+            bm(4, "https://c", "c", ",ccc,", 1),
+        ];
+
+        let actions = plan(&local, &others, MergeStrategy::Skip, |_, _| false);
+        assert_eq!(actions.len(), 2);
+        assert!(matches!(&actions[0], MergeAction::Update { new, .. } if new.tags == ",bbb,xxx,"));
+        assert!(matches!(&actions[1], MergeAction::Add(bm) if bm.URL == "https://c"));
+    }
+
+    #[rstest]
+    fn test_merge_databases() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let (_other_dir, mut other_dal) = setup_temp_db()?;
+        let new_bm = bm(0, "https://merged.example", "merged", ",new,", 1);
+        other_dal.insert_bookmark(new_bm.convert_to_new_bookmark())?;
+
+        let actions = plan(
+            &dal.get_bookmarks("")?,
+            &other_dal.get_bookmarks("")?,
+            MergeStrategy::Newest,
+            |_, _| false,
+        );
+        assert_eq!(actions.len(), 1);
+        apply(&mut dal, actions)?;
+
+        assert!(dal.bm_exists("https://merged.example")?);
+        assert_eq!(dal.get_bookmarks("")?.len(), 12);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_read_database_of_older_schema() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let fixture = "tests/resources/bkmr.v2.db";
+        let hash_before = md5::compute(std::fs::read(fixture)?);
+        let others = read_database(fixture)?;
+        assert!(!others.is_empty());
+        assert_eq!(md5::compute(std::fs::read(fixture)?), hash_before);
+
+        let actions = plan(&dal.get_bookmarks("")?, &others, MergeStrategy::Skip, |_, _| false);
+        apply(&mut dal, actions)?;
+        assert!(others.iter().all(|bm| dal.bm_exists(&bm.URL).unwrap()));
+        Ok(())
+    }
+}
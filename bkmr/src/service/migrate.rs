@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Context, Result};
+use diesel::connection::SimpleConnection;
+use diesel::migration::MigrationSource;
+use diesel::sqlite::Sqlite;
+use diesel_migrations::MigrationHarness;
+use serde::Serialize;
+use tracing::{debug, info, instrument};
+
+use crate::adapter::dal::migration::MIGRATIONS;
+use crate::adapter::dal::Dal;
+use crate::service::embeddings::upgrade_content_hashes;
+use crate::util::clock;
+
+/// Version of the initial schema, assumed for databases created by bkmr v1 without migration table
+const LEGACY_VERSION: &str = "20221229110455";
+
+const MIGRATION_TABLE_SQL: &str = r#"
+    BEGIN TRANSACTION;
+    CREATE TABLE IF NOT EXISTS __diesel_schema_migrations (
+        version VARCHAR(50) PRIMARY KEY NOT NULL,
+        run_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    INSERT INTO __diesel_schema_migrations (version, run_on)
+    VALUES ('20221229110455', '2023-12-23 09:27:06');
+    COMMIT;
+"#;
+
+/// Applied migrations by version and pending migrations by name, e.g. `2026-10-14-220000_add_readable`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaStatus {
+    /// created by bkmr v1, the migration table is created by `apply`
+    pub legacy: bool,
+    pub applied: Vec<String>,
+    pub pending: Vec<String>,
+    /// applied by a newer bkmr, unknown to this version
+    pub unknown: Vec<String>,
+}
+
+impl SchemaStatus {
+    /// Latest applied migration
+    pub fn version(&self) -> Option<&str> {
+        self.applied.iter().max().map(String::as_str)
+    }
+
+    /// Error unless this bkmr supports the schema, i.e. no newer bkmr has migrated the database
+    pub fn check_supported(&self) -> Result<()> {
+        if self.unknown.is_empty() {
+            return Ok(());
+        }
+        let supported = known_migrations()?.into_iter().map(|(version, _)| version).max();
+        Err(anyhow!(
+            "Database schema version {} has been migrated by a newer bkmr, bkmr {} supports up to {}. \
+            Upgrade bkmr, or use --force at your own risk",
+            self.version().unwrap_or_default(),
+            env!("CARGO_PKG_VERSION"),
+            supported.unwrap_or_default()
+        ))
+    }
+}
+
+/// Version and name of the migrations of this bkmr version
+fn known_migrations() -> Result<Vec<(String, String)>> {
+    let migrations = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .map_err(|e| anyhow!("Failed to read migrations: {}", e))?;
+    Ok(migrations
+        .iter()
+        .map(|m| (m.name().version().to_string(), m.name().to_string()))
+        .collect())
+}
+
+/// Does not change the database
+#[instrument(level = "debug")]
+pub fn status(dal: &mut Dal) -> Result<SchemaStatus> {
+    if !dal.check_schema_migrations_exists()? {
+        let pending = known_migrations()?
+            .into_iter()
+            .filter(|(version, _)| version != LEGACY_VERSION)
+            .map(|(_, name)| name)
+            .collect();
+        return Ok(SchemaStatus {
+            legacy: true,
+            applied: vec![LEGACY_VERSION.to_string()],
+            pending,
+            unknown: Vec::new(),
+        });
+    }
+    let applied = dal
+        .conn
+        .applied_migrations()
+        .map_err(|e| anyhow!("Failed to get applied migrations: {}", e))?;
+    let pending = dal
+        .conn
+        .pending_migrations(MIGRATIONS)
+        .map_err(|e| anyhow!("Failed to get pending migrations: {}", e))?;
+    let known: Vec<String> = known_migrations()?.into_iter().map(|(version, _)| version).collect();
+    let applied: Vec<String> = applied.iter().map(|v| v.to_string()).collect();
+    Ok(SchemaStatus {
+        legacy: false,
+        unknown: applied.iter().filter(|v| !known.contains(v)).cloned().collect(),
+        applied,
+        pending: pending.iter().map(|m| m.name().to_string()).collect(),
+    })
+}
+
+/// Runs the pending migrations, returns their names and the number of recomputed content hashes
+#[instrument(level = "debug")]
+pub fn apply(dal: &mut Dal) -> Result<(Vec<String>, usize)> {
+    if !dal.check_schema_migrations_exists()? {
+        info!("Creating migration table... {:?}", dal);
+        dal.conn
+            .batch_execute(MIGRATION_TABLE_SQL)
+            .context("Failed to create migrations table")?;
+    }
+    let pending = status(dal)?.pending;
+    if pending.is_empty() {
+        return Ok((pending, 0));
+    }
+    let rehash = pending.iter().any(|name| name.ends_with("_normalize_content_hash"));
+    dal.conn
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|e| anyhow!("Failed to run pending migrations: {}", e))?;
+    let rehashed = if rehash { upgrade_content_hashes(dal)? } else { 0 };
+    debug!("Applied {:?}", pending);
+    Ok((pending, rehashed))
+}
+
+/// Applies the pending migrations to a temporary copy of the database, the database is unchanged
+#[instrument(level = "debug")]
+pub fn dry_run(dal: &mut Dal) -> Result<Vec<String>> {
+    let dir = tempfile::tempdir().context("Failed to create temp directory")?;
+    let copy = dir.path().join("bkmr.db").to_string_lossy().to_string();
+    dal.backup_into(&copy)?;
+    apply(&mut Dal::new(copy)).map(|(applied, _)| applied)
+}
+
+/// Temporary copy of the database migrated to the current schema, e.g. of an older bkmr,
+/// the database is unchanged. The copy is removed with the directory.
+#[instrument(level = "debug")]
+pub fn migrated_copy(path: &str) -> Result<(tempfile::TempDir, Dal)> {
+    let dir = tempfile::tempdir().context("Failed to create temp directory")?;
+    let copy = dir.path().join("bkmr.db").to_string_lossy().to_string();
+    Dal::new(path.to_string()).backup_into(&copy)?;
+    let mut dal = Dal::new(copy);
+    apply(&mut dal)?;
+    Ok((dir, dal))
+}
+
+/// Consistent copy of the database next to it, e.g. `bkmr.db.20261014-153000.bak`
+#[instrument(level = "debug")]
+pub fn backup(dal: &mut Dal, db_url: &str) -> Result<String> {
+    let path = format!("{}.{}.bak", db_url, clock::now_naive().format("%Y%m%d-%H%M%S"));
+    dal.backup_into(&path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    fn test_status_and_apply() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let current = status(&mut dal)?;
+        assert!(current.pending.is_empty());
+        assert!(!current.legacy);
+        let latest = known_migrations()?.into_iter().map(|(version, _)| version).max();
+        assert_eq!(current.version().map(String::from), latest);
+
+        dal.conn
+            .revert_last_migration(MIGRATIONS)
+            .map_err(|e| anyhow!("{}", e))?;
+        let outdated = status(&mut dal)?;
+        assert_eq!(outdated.pending.len(), 1);
+
+        assert_eq!(dry_run(&mut dal)?, outdated.pending);
+        assert_eq!(status(&mut dal)?, outdated);
+
+        let (applied, _) = apply(&mut dal)?;
+        assert_eq!(applied, outdated.pending);
+        assert!(status(&mut dal)?.pending.is_empty());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_check_supported() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        assert!(status(&mut dal)?.check_supported().is_ok());
+
+        dal.conn
+            .batch_execute("INSERT INTO __diesel_schema_migrations (version) VALUES ('29991231000000');")?;
+        let newer = status(&mut dal)?;
+        assert_eq!(newer.unknown, vec!["29991231000000"]);
+        assert_eq!(newer.version(), Some("29991231000000"));
+        let err = newer.check_supported().unwrap_err().to_string();
+        assert!(err.contains("migrated by a newer bkmr"), "{}", err);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_backup() -> Result<()> {
+        let (dir, mut dal) = setup_temp_db()?;
+        let path = backup(&mut dal, &dir.path().join("bkmr.db").to_string_lossy())?;
+        assert!(path.ends_with(".bak"));
+        assert_eq!(Dal::new(path).get_bookmarks("")?.len(), dal.get_bookmarks("")?.len());
+        Ok(())
+    }
+}
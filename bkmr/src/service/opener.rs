@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use reqwest::Url;
+use tracing::debug;
+
+use crate::environment::OpenerConfig;
+use crate::util::helper::{glob_to_regex, shell_quote};
+
+/// Custom open command for URIs matching a glob pattern and/or bookmarks with a tag
+#[derive(Debug, Clone)]
+pub struct Opener {
+    pub pattern: Option<String>,
+    regex: Option<Regex>,
+    pub tag: Option<String>,
+    pub command: String,
+}
+
+impl Opener {
+    pub fn new(config: &OpenerConfig) -> Result<Self> {
+        if config.pattern.is_none() && config.tag.is_none() {
+            return Err(anyhow!("Opener {:?} needs a pattern or a tag", config.command));
+        }
+        let regex = config
+            .pattern
+            .as_ref()
+            .map(|pattern| {
+                Regex::new(&format!("(?i){}", glob_to_regex(pattern)))
+                    .with_context(|| format!("Invalid opener pattern: {}", pattern))
+            })
+            .transpose()?;
+        Ok(Self {
+            pattern: config.pattern.clone(),
+            regex,
+            tag: config.tag.clone(),
+            command: config.command.clone(),
+        })
+    }
+
+    /// Pattern matches the host, the file name or the whole URI,
+    /// e.g. `*youtube.com`, `*.pdf`, `https://intranet/*`
+    fn matches_uri(&self, uri: &str) -> bool {
+        let Some(regex) = &self.regex else {
+            return true;
+        };
+        let host = Url::parse(uri)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from));
+        let file_name = uri.trim_end_matches('/').rsplit('/').next();
+
+        regex.is_match(uri)
+            || host.is_some_and(|host| regex.is_match(&host))
+            || file_name.is_some_and(|name| regex.is_match(name))
+    }
+
+    pub fn is_match(&self, uri: &str, tags: &HashSet<String>) -> bool {
+        self.tag.as_ref().is_none_or(|tag| tags.contains(tag)) && self.matches_uri(uri)
+    }
+
+    /// Shell command with `{url}` replaced by the quoted URI, appended if there is no placeholder
+    pub fn command_for(&self, uri: &str) -> String {
+        if self.command.contains("{url}") {
+            self.command.replace("{url}", &shell_quote(uri))
+        } else {
+            format!("{} {}", self.command, shell_quote(uri))
+        }
+    }
+}
+
+/// Openers in config order, the first match wins
+#[derive(Debug, Clone, Default)]
+pub struct Openers {
+    pub openers: Vec<Opener>,
+}
+
+impl Openers {
+    pub fn new(configs: &[OpenerConfig]) -> Result<Self> {
+        let openers = configs.iter().map(Opener::new).collect::<Result<Vec<_>>>()?;
+        Ok(Self { openers })
+    }
+
+    pub fn find(&self, uri: &str, tags: &HashSet<String>) -> Option<&Opener> {
+        let opener = self.openers.iter().find(|o| o.is_match(uri, tags));
+        debug!("Opener for {:?}: {:?}", uri, opener.map(|o| &o.command));
+        opener
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    fn config(pattern: Option<&str>, tag: Option<&str>, command: &str) -> OpenerConfig {
+        OpenerConfig {
+            pattern: pattern.map(String::from),
+            tag: tag.map(String::from),
+            command: command.to_string(),
+        }
+    }
+
+    #[fixture]
+    fn openers() -> Openers {
+        Openers::new(&[
+            config(Some("*youtube.com"), None, "mpv {url}"),
+            config(Some("*.pdf"), None, "zathura"),
+            config(Some("https://intranet/*"), Some("work"), "chromium --profile-directory=Work"),
+            config(None, Some("work"), "firefox -P work {url}"),
+        ])
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("https://www.youtube.com/watch?v=1", "", Some("mpv {url}"))]
+    #[case("/home/user/paper.PDF", "", Some("zathura"))]
+    #[case("https://example.com/paper.pdf", "work", Some("zathura"))]
+    #[case("https://intranet/wiki", "work", Some("chromium --profile-directory=Work"))]
+    #[case("https://intranet/wiki", "", None)]
+    #[case("https://example.com", "work", Some("firefox -P work {url}"))]
+    #[case("https://example.com", "private", None)]
+    fn test_find(
+        openers: Openers,
+        #[case] uri: &str,
+        #[case] tag: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let tags = HashSet::from([tag.to_string()]);
+        assert_eq!(openers.find(uri, &tags).map(|o| o.command.as_str()), expected);
+    }
+
+    #[rstest]
+    fn test_command_for(openers: Openers) {
+        assert_eq!(
+            openers.openers[0].command_for("https://youtube.com/?a=1&b=2"),
+            "mpv 'https://youtube.com/?a=1&b=2'"
+        );
+        assert_eq!(openers.openers[1].command_for("/a b.pdf"), "zathura '/a b.pdf'");
+    }
+
+    #[rstest]
+    fn test_opener_needs_pattern_or_tag() {
+        assert!(Opener::new(&config(None, None, "firefox")).is_err());
+    }
+}
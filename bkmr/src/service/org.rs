@@ -0,0 +1,311 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use tracing::debug;
+
+use crate::service::bulk::{BulkBookmark, BulkFile};
+
+/// Characters org allows in headline tags, other tags go to the `:TAGS:` property
+fn is_org_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(|c| c.is_alphanumeric() || "_@#%".contains(c))
+}
+
+/// Lines starting with `*` or `#+` are escaped with a comma within org blocks
+fn escape_block(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| {
+            if line.starts_with('*') || line.starts_with("#+") {
+                format!(",{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn unescape_block(line: &str) -> &str {
+    match line.strip_prefix(',') {
+        Some(rest) if rest.starts_with('*') || rest.starts_with("#+") => rest,
+        _ => line,
+    }
+}
+
+/// URLs and paths become link headlines, snippets source blocks
+fn is_link(url: &str) -> bool {
+    !url.is_empty() && !url.contains(char::is_whitespace) && !url.contains(']') && !url.contains('[')
+}
+
+/// Org file of the bookmarks: one headline per bookmark with tags and a properties drawer
+pub fn serialize(file: &BulkFile) -> String {
+    let mut out = String::from("#+TITLE: bkmr bookmarks\n");
+    for bm in &file.bookmarks {
+        let title = bm.title.replace('[', "{").replace(']', "}");
+        let (org_tags, other_tags): (Vec<&String>, Vec<&String>) = bm.tags.iter().partition(|t| is_org_tag(t));
+        let mut headline = if is_link(&bm.url) && title.is_empty() {
+            format!("* [[{}]]", bm.url)
+        } else if is_link(&bm.url) {
+            format!("* [[{}][{}]]", bm.url, title)
+        } else {
+            format!("* {}", title)
+        };
+        if !org_tags.is_empty() {
+            headline.push_str(&format!(" :{}:", org_tags.iter().map(|t| t.as_str()).collect::<Vec<_>>().join(":")));
+        }
+        out.push_str(&format!("\n{}\n:PROPERTIES:\n", headline));
+        if let Some(uuid) = bm.uuid.as_ref().filter(|uuid| !uuid.is_empty()) {
+            out.push_str(&format!(":ID: {}\n", uuid));
+        }
+        if !other_tags.is_empty() {
+            out.push_str(&format!(":TAGS: {}\n", other_tags.iter().map(|t| t.as_str()).collect::<Vec<_>>().join(",")));
+        }
+        if let Some(language) = &bm.language {
+            out.push_str(&format!(":LANGUAGE: {}\n", language));
+        }
+        if let Some(visibility) = bm.visibility {
+            out.push_str(&format!(":VISIBILITY: {}\n", visibility));
+        }
+        out.push_str(":END:\n");
+        if !is_link(&bm.url) {
+            out.push_str(&format!(
+                "#+begin_src {}\n{}\n#+end_src\n",
+                bm.language.as_deref().unwrap_or_default(),
+                escape_block(&bm.url)
+            ));
+        }
+        for line in bm.desc.lines() {
+            // must not become a headline
+            if line.starts_with('*') {
+                out.push(' ');
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Headline being parsed, bookmarks are headlines with a link, a `:URL:` property or a source block
+#[derive(Debug, Default)]
+struct Entry {
+    title: String,
+    link: Option<String>,
+    tags: Vec<String>,
+    properties: Vec<(String, String)>,
+    src: Option<Vec<String>>,
+    desc: Vec<String>,
+}
+
+impl Entry {
+    fn property(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Tags of enclosing headlines are inherited as in org
+    fn into_bookmark(self, inherited: &[String]) -> Result<Option<BulkBookmark>> {
+        let url = match (self.property("URL"), &self.link, &self.src) {
+            (Some(url), _, _) => url.to_string(),
+            (None, Some(link), _) => link.clone(),
+            (None, None, Some(src)) => src.join("\n"),
+            (None, None, None) => return Ok(None),
+        };
+        if url.trim().is_empty() {
+            return Ok(None);
+        }
+        let mut tags: Vec<String> = inherited.to_vec();
+        tags.extend(self.tags.iter().cloned());
+        if let Some(other) = self.property("TAGS") {
+            tags.extend(other.split([',', ' ']).filter(|t| !t.is_empty()).map(String::from));
+        }
+        let visibility = self.property("VISIBILITY").map(str::parse).transpose()?;
+        let desc = self.desc.join("\n").trim().to_string();
+        let language = self.property("LANGUAGE").map(str::to_lowercase);
+        Ok(Some(BulkBookmark {
+            uuid: self.property("ID").map(str::to_lowercase),
+            url,
+            title: self.title,
+            tags,
+            desc,
+            language,
+            visibility,
+            ..Default::default()
+        }))
+    }
+}
+
+/// Part of a headline's section the parser is in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Body,
+    Drawer,
+    Src,
+}
+
+/// Bookmarks of an org file: link headlines and headlines with a `:URL:` property or a source block.
+/// Properties `:ID:`, `:TAGS:`, `:LANGUAGE:` and `:VISIBILITY:` are used, text below is the description.
+pub fn parse(content: &str) -> Result<BulkFile> {
+    let headline_re = Regex::new(r"^(\*+)\s+(.*?)(?:\s+(:\S+:))?\s*$").unwrap();
+    let link_re = Regex::new(r"\[\[([^\]]+)\](?:\[([^\]]*)\])?\]").unwrap();
+    let property_re = Regex::new(r"^\s*:([^:\s]+):\s*(.*?)\s*$").unwrap();
+    let keyword_re = Regex::new(r"^(TODO|DONE)\s+").unwrap();
+
+    let mut bookmarks = Vec::new();
+    // tags of the enclosing headlines by level
+    let mut ancestors: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut entries: Vec<(Entry, Vec<String>)> = Vec::new();
+    let mut section = Section::Body;
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if section != Section::Src {
+            if let Some(caps) = headline_re.captures(line) {
+                let level = caps[1].len();
+                let text = keyword_re.replace(caps[2].trim(), "").to_string();
+                let tags: Vec<String> = caps
+                    .get(3)
+                    .map(|t| t.as_str().split(':').filter(|t| !t.is_empty()).map(String::from).collect())
+                    .unwrap_or_default();
+                let (link, title) = match link_re.captures(&text) {
+                    Some(link) => {
+                        let rest = link_re.replace(&text, "").trim().to_string();
+                        let title = link.get(2).map(|t| t.as_str().to_string()).filter(|t| !t.is_empty());
+                        (Some(link[1].to_string()), title.unwrap_or(rest))
+                    }
+                    None => (None, text),
+                };
+                ancestors.retain(|(l, _)| *l < level);
+                let inherited = ancestors.iter().flat_map(|(_, tags)| tags.clone()).collect();
+                ancestors.push((level, tags.clone()));
+                entries.push((
+                    Entry {
+                        title,
+                        link,
+                        tags,
+                        ..Default::default()
+                    },
+                    inherited,
+                ));
+                section = Section::Body;
+                continue;
+            }
+        }
+        let Some((entry, _)) = entries.last_mut() else {
+            continue;
+        };
+        match section {
+            Section::Drawer if trimmed.eq_ignore_ascii_case(":END:") => section = Section::Body,
+            Section::Drawer => {
+                let caps = property_re
+                    .captures(line)
+                    .ok_or_else(|| anyhow!("Invalid property in line {}: {:?}", i + 1, line))?;
+                entry.properties.push((caps[1].to_string(), caps[2].to_string()));
+            }
+            Section::Src if trimmed.eq_ignore_ascii_case("#+end_src") => section = Section::Body,
+            Section::Src => entry.src.get_or_insert_with(Vec::new).push(unescape_block(line).to_string()),
+            Section::Body if trimmed.eq_ignore_ascii_case(":PROPERTIES:") => section = Section::Drawer,
+            // only the first block is the content
+            Section::Body if trimmed.to_lowercase().starts_with("#+begin_src") && entry.src.is_none() => {
+                entry.src = Some(Vec::new());
+                section = Section::Src;
+            }
+            Section::Body => entry.desc.push(trimmed.to_string()),
+        }
+    }
+    for (entry, inherited) in entries {
+        bookmarks.extend(entry.into_bookmark(&inherited)?);
+    }
+    debug!("{} bookmarks", bookmarks.len());
+    Ok(BulkFile { visibility: None, bookmarks })
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::Visibility;
+
+    const ORG: &str = indoc! {r#"
+        #+TITLE: Links
+        * Rust                                                   :rust:
+        ** [[https://doc.rust-lang.org/book/][The Book]]            :book:
+        :PROPERTIES:
+        :ID: 0B3E9C1A-5F2D-4A4B-9C1E-8D2F3A4B5C6D
+        :TAGS: must-read
+        :END:
+        The official book.
+        ** TODO [[https://blog.rust-lang.org]] Blog
+        * Snippets
+        ** list files :shell:
+        #+begin_src sh
+        ls -la
+        ,* not a headline
+        #+end_src
+        Lists all files.
+        * Plain heading without link
+        Some notes.
+    "#};
+
+    #[rstest]
+    fn test_parse() -> Result<()> {
+        let file = parse(ORG)?;
+        assert_eq!(file.bookmarks.len(), 3);
+        let book = &file.bookmarks[0];
+        assert_eq!(book.url, "https://doc.rust-lang.org/book/");
+        assert_eq!(book.title, "The Book");
+        assert_eq!(book.tags, vec!["rust", "book", "must-read"]);
+        assert_eq!(book.uuid.as_deref(), Some("0b3e9c1a-5f2d-4a4b-9c1e-8d2f3a4b5c6d"));
+        assert_eq!(book.desc, "The official book.");
+
+        let blog = &file.bookmarks[1];
+        assert_eq!((blog.url.as_str(), blog.title.as_str()), ("https://blog.rust-lang.org", "Blog"));
+        assert_eq!(blog.tags, vec!["rust"]);
+
+        let snippet = &file.bookmarks[2];
+        assert_eq!(snippet.url, "ls -la\n* not a headline");
+        assert_eq!(snippet.title, "list files");
+        assert_eq!(snippet.tags, vec!["shell"]);
+        assert_eq!(snippet.desc, "Lists all files.");
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_roundtrip() -> Result<()> {
+        let file = BulkFile {
+            visibility: None,
+            bookmarks: vec![
+                BulkBookmark {
+                    uuid: Some("0b3e9c1a-5f2d-4a4b-9c1e-8d2f3a4b5c6d".to_string()),
+                    url: "https://www.rust-lang.org".to_string(),
+                    title: "Rust [lang]".to_string(),
+                    tags: vec!["lang".to_string(), "registry:team".to_string(), "web-dev".to_string()],
+                    desc: "A language\n* with a star".to_string(),
+                    visibility: Some(Visibility::Public),
+                    ..Default::default()
+                },
+                BulkBookmark {
+                    url: "SELECT *\nFROM t".to_string(),
+                    title: "query".to_string(),
+                    tags: vec!["_snip_".to_string()],
+                    language: Some("sql".to_string()),
+                    ..Default::default()
+                },
+            ],
+        };
+        let parsed = parse(&serialize(&file))?;
+        assert_eq!(parsed.bookmarks.len(), 2);
+        let web = &parsed.bookmarks[0];
+        assert_eq!(web.title, "Rust {lang}");
+        assert_eq!(web.tags, vec!["lang", "registry:team", "web-dev"]);
+        assert_eq!(web.desc, "A language\n* with a star");
+        assert_eq!(web.visibility, Some(Visibility::Public));
+        assert_eq!(web.uuid, file.bookmarks[0].uuid);
+        assert_eq!(parsed.bookmarks[1], file.bookmarks[1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use crate::model::bookmark::Bookmark;
+use crate::model::system_tag::SystemTag;
+
+/// Action of the command palette of a bookmark
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteAction {
+    Open,
+    Copy,
+    Show,
+    Edit,
+    Read,
+    FetchReadable,
+    RefreshMetadata,
+    Delete,
+}
+
+impl PaletteAction {
+    /// What the action does with the bookmark, the default action depends on its type
+    pub fn label(&self, bm: &Bookmark, system_tag: Option<&SystemTag>) -> String {
+        match self {
+            PaletteAction::Open => match system_tag {
+                Some(tag) => format!("open: {}", tag.description),
+                None if is_web(bm) => "open in the browser".to_string(),
+                None => "open".to_string(),
+            },
+            PaletteAction::Copy if is_web(bm) => "copy URL to the clipboard".to_string(),
+            PaletteAction::Copy => "copy content to the clipboard".to_string(),
+            PaletteAction::Show => "show all fields".to_string(),
+            PaletteAction::Edit => "edit in the editor".to_string(),
+            PaletteAction::Read => "read the stored page text".to_string(),
+            PaletteAction::FetchReadable => "fetch and read the page text".to_string(),
+            PaletteAction::RefreshMetadata => "fetch title and description again".to_string(),
+            PaletteAction::Delete => "delete".to_string(),
+        }
+    }
+}
+
+fn is_web(bm: &Bookmark) -> bool {
+    bm.URL.starts_with("http://") || bm.URL.starts_with("https://")
+}
+
+/// Actions applicable to the bookmark, the default action first
+pub fn actions(bm: &Bookmark, has_readable: bool) -> Vec<PaletteAction> {
+    let mut actions = vec![PaletteAction::Open, PaletteAction::Copy, PaletteAction::Show, PaletteAction::Edit];
+    if has_readable {
+        actions.push(PaletteAction::Read);
+    }
+    if is_web(bm) {
+        actions.push(PaletteAction::FetchReadable);
+        actions.push(PaletteAction::RefreshMetadata);
+    }
+    actions.push(PaletteAction::Delete);
+    actions
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+    use crate::model::system_tag::SystemTags;
+
+    fn bm(url: &str, tags: &str) -> Bookmark {
+        BookmarkBuilder::new().id(1).URL(url.to_string()).tags(tags.to_string()).build()
+    }
+
+    #[rstest]
+    fn test_actions() {
+        use PaletteAction::*;
+        assert_eq!(
+            actions(&bm("https://example.com", ",,"), true),
+            vec![Open, Copy, Show, Edit, Read, FetchReadable, RefreshMetadata, Delete]
+        );
+        assert_eq!(actions(&bm("ls -la", ",_snip_,"), false), vec![Open, Copy, Show, Edit, Delete]);
+    }
+
+    #[rstest]
+    fn test_label() {
+        let system_tags = SystemTags::new(&Default::default()).unwrap();
+        let snippet = bm("ls -la", ",_snip_,");
+        let label = PaletteAction::Open.label(&snippet, system_tags.for_bookmark(&snippet));
+        assert!(label.starts_with("open: code snippet"), "{}", label);
+        assert_eq!(PaletteAction::Copy.label(&snippet, None), "copy content to the clipboard");
+
+        let web = bm("https://example.com", ",,");
+        assert_eq!(PaletteAction::Open.label(&web, None), "open in the browser");
+        assert_eq!(PaletteAction::Copy.label(&web, None), "copy URL to the clipboard");
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::model::bookmark::Bookmark;
+use crate::service::bulk::{BulkBookmark, Change};
+use crate::service::merge::MergeAction;
+
+/// Existing bookmark addressed by id, the URL guards against ids shifted since planning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanRef {
+    pub id: i32,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanUpdate {
+    #[serde(flatten)]
+    pub target: PlanRef,
+    pub new: BulkBookmark,
+}
+
+/// Machine-readable result of `--dry-run`, executed later with `bkmr apply-plan`
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Plan {
+    pub create: Vec<BulkBookmark>,
+    pub update: Vec<PlanUpdate>,
+    pub delete: Vec<PlanRef>,
+}
+
+impl Plan {
+    pub fn from_changes(changes: &[Change]) -> Self {
+        let mut plan = Self::default();
+        for change in changes {
+            match change {
+                Change::Create(new) => plan.create.push(BulkBookmark {
+                    id: None,
+                    ..new.clone()
+                }),
+                Change::Update { old, new } => plan.update.push(PlanUpdate {
+                    target: PlanRef::from(old),
+                    new: BulkBookmark {
+                        id: Some(old.id),
+                        ..new.clone()
+                    },
+                }),
+                Change::Delete(old) => plan.delete.push(PlanRef::from(old)),
+            }
+        }
+        plan
+    }
+
+    pub fn from_merge(actions: &[MergeAction]) -> Self {
+        let changes: Vec<Change> = actions
+            .iter()
+            .map(|action| match action {
+                MergeAction::Add(bm) => Change::Create(BulkBookmark::from(bm)),
+                MergeAction::Update { old, new } => Change::Update {
+                    old: old.clone(),
+                    new: BulkBookmark::from(new),
+                },
+            })
+            .collect();
+        Self::from_changes(&changes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize plan")
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        serde_json::from_str(content).context("Failed to parse plan")
+    }
+
+    /// Changes for the current bookmarks, fails if a referenced bookmark changed its id
+    pub fn to_changes(&self, current: &[Bookmark]) -> Result<Vec<Change>> {
+        let current: BTreeMap<i32, &Bookmark> = current.iter().map(|bm| (bm.id, bm)).collect();
+        let lookup = |target: &PlanRef| -> Result<Bookmark> {
+            match current.get(&target.id) {
+                Some(bm) if bm.URL == target.url => Ok((*bm).clone()),
+                Some(bm) => Err(anyhow!(
+                    "Stale plan: bookmark {} is {:?}, expected {:?}",
+                    target.id,
+                    bm.URL,
+                    target.url
+                )),
+                None => Err(anyhow!("Stale plan: bookmark {} does not exist", target.id)),
+            }
+        };
+
+        let mut changes = Vec::new();
+        for update in &self.update {
+            changes.push(Change::Update {
+                old: lookup(&update.target)?,
+                new: update.new.clone(),
+            });
+        }
+        // highest id first, deletion compacts the ids
+        let mut deletes = self.delete.iter().collect::<Vec<_>>();
+        deletes.sort_by_key(|target| std::cmp::Reverse(target.id));
+        for target in deletes {
+            changes.push(Change::Delete(lookup(target)?));
+        }
+        changes.extend(self.create.iter().cloned().map(Change::Create));
+        Ok(changes)
+    }
+}
+
+impl From<&Bookmark> for PlanRef {
+    fn from(bm: &Bookmark) -> Self {
+        Self {
+            id: bm.id,
+            url: bm.URL.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::service::bulk::{self, BulkFile};
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    fn test_plan_roundtrip_and_apply() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let current = dal.get_bookmarks("")?;
+        let mut file = BulkFile::new(&current);
+        file.bookmarks[1].title = "changed".to_string();
+        file.bookmarks.retain(|b| b.id != Some(4) && b.id != Some(9));
+        file.bookmarks.push(BulkBookmark {
+            url: "https://planned.example".to_string(),
+            ..Default::default()
+        });
+
+        let plan = Plan::from_changes(&bulk::diff(&current, &file)?);
+        assert_eq!((plan.create.len(), plan.update.len(), plan.delete.len()), (1, 1, 2));
+        let plan = Plan::parse(&plan.to_json()?)?;
+
+        let changes = plan.to_changes(&dal.get_bookmarks("")?)?;
+        bulk::apply(&mut dal, changes)?;
+        assert_eq!(dal.get_bookmark_by_id(2)?.metadata, "changed");
+        assert!(dal.bm_exists("https://planned.example")?);
+        assert_eq!(dal.get_bookmarks("")?.len(), 10);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_stale_plan() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let current = dal.get_bookmarks("")?;
+        let plan = Plan {
+            delete: vec![PlanRef {
+                id: 1,
+                url: "https://moved.example".to_string(),
+            }],
+            ..Default::default()
+        };
+        assert!(plan.to_changes(&current).is_err());
+        Ok(())
+    }
+}
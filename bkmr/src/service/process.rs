@@ -16,7 +16,12 @@ use crate::update_bm;
 use crate::adapter::dal::Dal;
 use crate::environment::CONFIG;
 use crate::util::helper::abspath;
-use crate::model::bookmark::{Bookmark, BookmarkUpdater};
+use crate::model::bookmark::{Bookmark, BookmarkUpdater, Visibility};
+use crate::model::system_tag::SystemTags;
+use crate::service::actions::open_builtin;
+use crate::service::editor::open_in_editor;
+use crate::service::bus::{self, BookmarkEvent};
+use crate::service::opener::Openers;
 use crate::util::helper;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -31,6 +36,9 @@ pub enum DisplayField {
     LastUpdateTs,
     Embedding,
     Similarity,
+    Uuid,
+    Language,
+    Visibility,
 }
 
 #[allow(dead_code)]
@@ -46,7 +54,7 @@ pub const DEFAULT_FIELDS: [DisplayField; 6] = [
     DisplayField::Similarity,
 ];
 #[allow(dead_code)]
-pub const ALL_FIELDS: [DisplayField; 9] = [
+pub const ALL_FIELDS: [DisplayField; 12] = [
     DisplayField::Id,
     DisplayField::URL,
     DisplayField::Metadata,
@@ -56,6 +64,9 @@ pub const ALL_FIELDS: [DisplayField; 9] = [
     DisplayField::LastUpdateTs,
     DisplayField::Embedding,
     DisplayField::Similarity,
+    DisplayField::Uuid,
+    DisplayField::Language,
+    DisplayField::Visibility,
 ];
 
 #[derive(Debug, PartialEq, Clone)]
@@ -70,6 +81,9 @@ pub struct DisplayBookmark {
     pub embedding: String,
     pub content_hash: String,
     pub similarity: Option<f32>,
+    pub uuid: String,
+    pub language: Option<String>,
+    pub visibility: Visibility,
 }
 
 // method for creating DisplayBookmark from Bookmark
@@ -86,11 +100,68 @@ impl From<&Bookmark> for DisplayBookmark {
             embedding: format!("{:?}", bm.embedding),
             content_hash: format!("{:?}", bm.content_hash),
             similarity: None,
+            uuid: bm.uuid.clone(),
+            language: bm.language.clone(),
+            visibility: bm.visibility(),
         }
     }
 }
 
-pub fn show_bms(bms: &Vec<DisplayBookmark>, fields: &[DisplayField]) {
+/// One labeled line per field, without colors or alignment, for `--plain`
+fn show_bms_plain(bms: &[DisplayBookmark], fields: &[DisplayField]) {
+    for (i, bm) in bms.iter().enumerate() {
+        let mut lines: Vec<(&str, String)> = Vec::new();
+        if fields.contains(&DisplayField::Metadata) {
+            lines.push(("Title", bm.metadata.clone()));
+        }
+        if let Some(similarity) = bm.similarity.filter(|_| fields.contains(&DisplayField::Similarity)) {
+            lines.push(("Similarity", format!("{:.3}", similarity)));
+        }
+        if fields.contains(&DisplayField::Id) {
+            lines.push(("ID", bm.id.to_string()));
+        }
+        if fields.contains(&DisplayField::URL) {
+            lines.push(("URL", bm.URL.clone()));
+        }
+        if fields.contains(&DisplayField::Desc) && !bm.desc.is_empty() {
+            lines.push(("Description", bm.desc.clone()));
+        }
+        if fields.contains(&DisplayField::Tags) {
+            let tags = bm.tags.split(',').filter(|t| !t.is_empty()).collect::<Vec<_>>().join(", ");
+            if !tags.is_empty() {
+                lines.push(("Tags", tags));
+            }
+        }
+        if fields.contains(&DisplayField::Flags) {
+            lines.push(("Count", bm.flags.to_string()));
+        }
+        if fields.contains(&DisplayField::Embedding) {
+            lines.push(("Embedding", if bm.embedding.is_empty() { "no" } else { "yes" }.to_string()));
+        }
+        if fields.contains(&DisplayField::LastUpdateTs) {
+            lines.push(("Updated", bm.last_update_ts.to_string()));
+        }
+        if fields.contains(&DisplayField::Uuid) && !bm.uuid.is_empty() {
+            lines.push(("UUID", bm.uuid.clone()));
+        }
+        if let Some(language) = bm.language.as_ref().filter(|_| fields.contains(&DisplayField::Language)) {
+            lines.push(("Language", language.clone()));
+        }
+        if fields.contains(&DisplayField::Visibility) && bm.visibility != Visibility::Private {
+            lines.push(("Visibility", bm.visibility.to_string()));
+        }
+        eprintln!("Bookmark {} of {}", i + 1, bms.len());
+        for (label, value) in lines {
+            eprintln!("{}: {}", label, value);
+        }
+        eprintln!();
+    }
+}
+
+pub fn show_bms(bms: &[DisplayBookmark], fields: &[DisplayField]) {
+    if helper::is_plain() {
+        return show_bms_plain(bms, fields);
+    }
     // let mut stdout = StandardStream::stdout(ColorChoice::Always);
     // Check if the output is a TTY
     let color_choice = if io::stdout().is_terminal() {
@@ -196,6 +267,27 @@ pub fn show_bms(bms: &Vec<DisplayBookmark>, fields: &[DisplayField]) {
                 .unwrap();
         }
 
+        if fields.contains(&DisplayField::Uuid) && !bm.uuid.is_empty() {
+            stderr
+                .set_color(ColorSpec::new().set_fg(Some(Color::White)))
+                .unwrap();
+            writeln!(&mut stderr, "{:first_col_width$}  uuid: {}", "", bm.uuid).unwrap();
+        }
+
+        if let Some(language) = bm.language.as_ref().filter(|_| fields.contains(&DisplayField::Language)) {
+            stderr
+                .set_color(ColorSpec::new().set_fg(Some(Color::White)))
+                .unwrap();
+            writeln!(&mut stderr, "{:first_col_width$}  language: {}", "", language).unwrap();
+        }
+
+        if fields.contains(&DisplayField::Visibility) && bm.visibility != Visibility::Private {
+            stderr
+                .set_color(ColorSpec::new().set_fg(Some(Color::White)))
+                .unwrap();
+            writeln!(&mut stderr, "{:first_col_width$}  visibility: {}", "", bm.visibility).unwrap();
+        }
+
         stderr.reset().unwrap();
         eprintln!();
     }
@@ -212,7 +304,8 @@ fn parse(input: &str) -> Vec<String> {
     tokens
 }
 
-pub fn process(bms: &Vec<Bookmark>) {
+pub fn process(bms: &[Bookmark]) {
+    helper::require_input("selection, use --np");
     // debug!("{:?}", bms);
     let help_text = r#"
         <n1> <n2>:      opens selection in browser
@@ -225,6 +318,7 @@ pub fn process(bms: &Vec<Bookmark>) {
         h:              help
     "#;
 
+    let regex = Regex::new(r"^\d+").unwrap(); // Create a new Regex object
     loop {
         eprint!("> ");
         io::stdout().flush().unwrap();
@@ -237,11 +331,10 @@ pub fn process(bms: &Vec<Bookmark>) {
             break;
         }
 
-        let regex = Regex::new(r"^\d+").unwrap(); // Create a new Regex object
         match tokens[0].as_str() {
             "p" => {
                 if let Some(ids) = helper::ensure_int_vector(&tokens.split_off(1)) {
-                    print_ids(ids, bms.clone()).unwrap_or_else(|e| {
+                    print_ids(ids, bms.to_vec()).unwrap_or_else(|e| {
                         error!("{}", e);
                     });
                     break;
@@ -253,7 +346,7 @@ pub fn process(bms: &Vec<Bookmark>) {
             }
             "d" => {
                 if let Some(ids) = helper::ensure_int_vector(&tokens.split_off(1)) {
-                    delete_bms(ids, bms.clone()).unwrap_or_else(|e| {
+                    delete_bms(ids, bms.to_vec()).unwrap_or_else(|e| {
                         error!("{}", e);
                     });
                     break;
@@ -265,7 +358,7 @@ pub fn process(bms: &Vec<Bookmark>) {
             }
             "e" => {
                 if let Some(ids) = helper::ensure_int_vector(&tokens.split_off(1)) {
-                    edit_bms(ids, bms.clone()).unwrap_or_else(|e| {
+                    edit_bms(ids, bms.to_vec()).unwrap_or_else(|e| {
                         error!("{}", e);
                     });
                     break;
@@ -277,7 +370,7 @@ pub fn process(bms: &Vec<Bookmark>) {
             }
             "t" => {
                 if let Some(ids) = helper::ensure_int_vector(&tokens.split_off(1)) {
-                    touch_bms(ids, bms.clone()).unwrap_or_else(|e| {
+                    touch_bms(ids, bms.to_vec()).unwrap_or_else(|e| {
                         error!("{}", e);
                     });
                     break;
@@ -292,7 +385,7 @@ pub fn process(bms: &Vec<Bookmark>) {
             // Use Regex object in a guard
             s if regex.is_match(s) => {
                 if let Some(ids) = helper::ensure_int_vector(&tokens) {
-                    open_bms(ids, bms.clone()).unwrap_or_else(|e| {
+                    open_bms(ids, bms.to_vec()).unwrap_or_else(|e| {
                         error!("{}", e);
                     });
                     break;
@@ -319,6 +412,7 @@ pub fn touch_bms(ids: Vec<i32>, bms: Vec<Bookmark>) -> anyhow::Result<()> {
 }
 
 pub fn edit_bms(ids: Vec<i32>, bms: Vec<Bookmark>) -> anyhow::Result<()> {
+    helper::require_input("editor");
     debug!("ids: {:?}", ids);
     do_sth_with_bms(ids, bms, do_edit)
         .with_context(|| "Error opening bookmarks".to_string())?;
@@ -327,10 +421,33 @@ pub fn edit_bms(ids: Vec<i32>, bms: Vec<Bookmark>) -> anyhow::Result<()> {
 
 pub fn open_bm(bm: &Bookmark) -> anyhow::Result<()> {
     do_touch(bm)?;
-    _open_bm(&bm.URL)?;
+    let system_tags = SystemTags::new(&CONFIG.types)?;
+    match system_tags.for_bookmark(bm) {
+        Some(t) if t.action.is_some() => {
+            _open_bm(&format!("shell::{}", t.action_for(bm).unwrap_or_default()))?
+        }
+        Some(t) if open_builtin(&t.tag, bm)? => {}
+        _ => match custom_opener(bm)? {
+            Some(cmd) => _open_bm(&format!("shell::{}", cmd))?,
+            None => _open_bm(&bm.URL)?,
+        },
+    }
+    bus::publish(BookmarkEvent::Opened(bm.clone()));
     Ok(())
 }
 
+/// Command of the first configured opener matching URI and tags, local paths are matched absolute
+fn custom_opener(bm: &Bookmark) -> anyhow::Result<Option<String>> {
+    if bm.URL.starts_with("shell::") {
+        return Ok(None);
+    }
+    let target = abspath(&bm.URL).unwrap_or_else(|| bm.URL.clone());
+    let openers = Openers::new(&CONFIG.openers)?;
+    Ok(openers
+        .find(&target, &bm.get_tags().into_iter().collect())
+        .map(|o| o.command_for(&target)))
+}
+
 fn _open_bm(uri: &str) -> anyhow::Result<()> {
     if uri.starts_with("shell::") {
         let cmd = uri.replace("shell::", "");
@@ -353,18 +470,7 @@ fn _open_bm(uri: &str) -> anyhow::Result<()> {
             Some(p) => {
                 if Utf8Path::new(&p).extension() == Some("md") {
                     debug!("Opening markdown file with editor {:?}", p);
-                    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
-                    debug!("Using editor: {:?}",editor);
-                    Command::new(&editor)
-                        .arg(&p)
-                        .status()
-                        .with_context(|| {
-                            format!(
-                                "Error opening {} with [{}], check your EDITOR variable.",
-                                p,
-                                &editor
-                            )
-                        })?;
+                    open_in_editor(uri)?;
                 } else {
                     debug!("Opening file with default OS application {:?}", p);
                     open::that(&p).with_context(|| format!("Error OS opening {}", p))?;
@@ -391,14 +497,28 @@ pub fn delete_bms(mut ids: Vec<i32>, bms: Vec<Bookmark>) -> anyhow::Result<()> {
     ids.reverse();
     debug!("ids: {:?}, bms: {:?}", ids, bms);
     // debug!("{:?}", &ids);
-    fn delete_bm(bm: &Bookmark) -> anyhow::Result<()> {
-        let _ = Dal::new(CONFIG.db_url.clone()).delete_bookmark2(bm.id)?;
+    let mut dal = Dal::new(CONFIG.db_url.clone());
+    let deleted = dal
+        .transaction(|dal| {
+            let mut deleted = Vec::new();
+            for id in ids {
+                if id as usize > bms.len() {
+                    eprintln!("Id {} out of range", id);
+                    continue;
+                }
+                let bm = &bms[id as usize - 1];
+                dal.delete_and_compact(bm.id)
+                    .with_context(|| format!("bm {:?}", bm))?;
+                deleted.push(bm);
+            }
+            Ok(deleted)
+        })
+        .with_context(|| "Error deleting bookmarks".to_string())?;
+    // subscribers only see committed deletions
+    for bm in deleted {
         eprintln!("Deleted: {}", bm.URL);
-        Ok(())
+        bus::publish(BookmarkEvent::Deleted(bm.clone()));
     }
-    do_sth_with_bms(ids, bms, delete_bm).with_context(|| {
-        "Error deleting bookmarks".to_string()
-    })?;
     Ok(())
 }
 
@@ -424,10 +544,10 @@ fn do_sth_with_bms(
 /// increases flag (counter) by 1 and prints it
 pub fn do_touch(bm: &Bookmark) -> anyhow::Result<()> {
     let mut dal = Dal::new(CONFIG.db_url.clone());
-    update_bm(bm.id, &vec![], &vec![], &mut dal, false)?;
+    update_bm(bm.id, &[], &[], &mut dal, false)?;
     let bm = dal.get_bookmark_by_id(bm.id)?;
 
-    show_bms(&vec![DisplayBookmark::from(&bm)], &ALL_FIELDS);
+    show_bms(&[DisplayBookmark::from(&bm)], &ALL_FIELDS);
     Ok(())
 }
 
@@ -497,6 +617,14 @@ pub fn do_edit(bm: &Bookmark) -> anyhow::Result<()> {
         last_update_ts: Default::default(), // will be overwritten by diesel
         embedding: None,
         content_hash: None,
+        uuid: bm.uuid.clone(),
+        language: bm.language.clone(),
+        embedding_model: None,
+        embedding_dim: None,
+        version: bm.version,
+        visibility: bm.visibility.clone(),
+        created_ts: bm.created_ts,
+        meta: bm.meta.clone(),
     };
     debug!("lines: {:?}", lines);
     new_bm.update();
@@ -504,6 +632,9 @@ pub fn do_edit(bm: &Bookmark) -> anyhow::Result<()> {
     let updated = Dal::new(CONFIG.db_url.clone())
         .update_bookmark(new_bm)
         .with_context(|| "Error updating bookmark".to_string())?;
+    updated
+        .iter()
+        .for_each(|bm| bus::publish(BookmarkEvent::Updated(bm.clone())));
     // Delete the temporary file
     fs::remove_file("temp.txt")?;
 
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::NaiveDateTime;
+use tracing::debug;
+
+use crate::environment::RankingConfig;
+use crate::model::bookmark::Bookmark;
+
+/// Days after which the recency component has decayed to 0.5
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+/// Bookmarks with this tag get the pin boost
+pub const PIN_TAG: &str = "pinned";
+
+/// Score of a search result with its unweighted components
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Score {
+    /// negated FTS5 bm25 rank, higher is better
+    pub fts: Option<f64>,
+    pub similarity: Option<f32>,
+    /// 1.0 for just used, decaying with days since last use
+    pub recency: f64,
+    /// ln(1 + open count)
+    pub hits: f64,
+    /// number of searched tags the bookmark has
+    pub tag_matches: usize,
+    pub pinned: bool,
+    pub total: f64,
+}
+
+impl Score {
+    pub fn new(
+        bm: &Bookmark,
+        fts_rank: Option<f64>,
+        similarity: Option<f32>,
+        tags: &[String],
+        now: NaiveDateTime,
+        weights: &RankingConfig,
+    ) -> Self {
+        let days = (now - bm.last_update_ts).num_seconds().max(0) as f64 / 86400.0;
+        let bm_tags = bm.get_tags();
+        let mut score = Self {
+            fts: fts_rank.map(|rank| -rank),
+            similarity,
+            recency: RECENCY_HALF_LIFE_DAYS / (RECENCY_HALF_LIFE_DAYS + days),
+            hits: (1.0 + bm.flags.max(0) as f64).ln(),
+            tag_matches: tags.iter().filter(|t| bm_tags.contains(t)).count(),
+            pinned: bm_tags.iter().any(|t| t == PIN_TAG),
+            total: 0.0,
+        };
+        score.total = weights.fts * score.fts.unwrap_or_default()
+            + weights.similarity * score.similarity.unwrap_or_default() as f64
+            + weights.recency * score.recency
+            + weights.hits * score.hits
+            + weights.tags * score.tag_matches as f64
+            + if score.pinned { weights.pin_boost } else { 0.0 };
+        score
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "score {:.3} =", self.total)?;
+        if let Some(fts) = self.fts {
+            write!(f, " fts {:.3}", fts)?;
+        }
+        if let Some(similarity) = self.similarity {
+            write!(f, " similarity {:.3}", similarity)?;
+        }
+        write!(
+            f,
+            " recency {:.3} hits {:.3} tags {}",
+            self.recency, self.hits, self.tag_matches
+        )?;
+        if self.pinned {
+            write!(f, " pinned")?;
+        }
+        Ok(())
+    }
+}
+
+/// Sorts the bookmarks by descending score, used by search and its fzf mode
+pub fn rank(
+    bms: &mut [Bookmark],
+    fts_ranks: &HashMap<i32, f64>,
+    tags: &[String],
+    now: NaiveDateTime,
+    weights: &RankingConfig,
+) {
+    let scores: HashMap<i32, f64> = bms
+        .iter()
+        .map(|bm| {
+            let score = Score::new(bm, fts_ranks.get(&bm.id).copied(), None, tags, now, weights);
+            (bm.id, score.total)
+        })
+        .collect();
+    bms.sort_by(|a, b| scores[&b.id].total_cmp(&scores[&a.id]));
+    debug!("{:?}", scores);
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    #[rstest]
+    fn test_score() {
+        let mut bm = BookmarkBuilder::new()
+            .tags(",aaa,bbb,".to_string())
+            .flags(3)
+            .build();
+        let now = bm.last_update_ts + Duration::days(30);
+        let tags = vec!["aaa".to_string(), "ccc".to_string()];
+
+        let score = Score::new(&bm, Some(-2.0), None, &tags, now, &RankingConfig::default());
+        assert_eq!(score.fts, Some(2.0));
+        assert_eq!(score.recency, 0.5);
+        assert_eq!(score.hits, 4f64.ln());
+        assert_eq!(score.tag_matches, 1);
+        assert!((score.total - (2.0 + 0.15 + 0.2 * 4f64.ln() + 1.0)).abs() < 1e-9);
+
+        // recently used bookmarks rank higher
+        bm.last_update_ts = now;
+        let recent = Score::new(&bm, Some(-2.0), None, &tags, now, &RankingConfig::default());
+        assert!(recent.total > score.total);
+        assert!(recent.to_string().starts_with("score "));
+
+        let pinned = BookmarkBuilder::new().tags(",pinned,".to_string()).build();
+        let score = Score::new(&pinned, None, None, &[], now, &RankingConfig::default());
+        assert!(score.pinned);
+        assert!(score.total >= 5.0);
+    }
+
+    #[rstest]
+    fn test_rank() {
+        let mut bms: Vec<Bookmark> = [(1, ",a,"), (2, ",pinned,"), (3, ",a,")]
+            .iter()
+            .map(|(id, tags)| BookmarkBuilder::new().id(*id).tags(tags.to_string()).build())
+            .collect();
+        let now = bms[0].last_update_ts;
+        let fts_ranks = HashMap::from([(1, -1.0), (3, -3.0)]);
+
+        rank(&mut bms, &fts_ranks, &[], now, &RankingConfig::default());
+        assert_eq!(bms.iter().map(|bm| bm.id).collect::<Vec<_>>(), vec![2, 3, 1]);
+    }
+}
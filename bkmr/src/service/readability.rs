@@ -0,0 +1,178 @@
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Attr, Class, Name, Or, Predicate};
+
+use crate::model::readable::Readable;
+use crate::util::clock;
+
+/// Page chrome and content without readable text
+const SKIPPED: [&str; 12] = [
+    "script", "style", "noscript", "template", "svg", "nav", "header", "footer", "aside", "form",
+    "button", "iframe",
+];
+
+const BLOCKS: [&str; 18] = [
+    "p", "div", "section", "article", "main", "h1", "h2", "h3", "h4", "h5", "h6", "li", "pre",
+    "blockquote", "tr", "br", "figcaption", "dd",
+];
+
+/// Readable part of a page: title, byline and main text as paragraphs separated by blank lines
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Article {
+    pub title: String,
+    pub byline: String,
+    pub text: String,
+}
+
+impl Article {
+    pub fn into_readable(self, uuid: &str) -> Readable {
+        Readable {
+            uuid: uuid.to_string(),
+            title: self.title,
+            byline: self.byline,
+            text: self.text,
+            fetched_ts: clock::now_naive(),
+        }
+    }
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn meta(document: &Document, attr: &'static str, value: &'static str) -> Option<String> {
+    document
+        .find(Name("meta").and(Attr(attr, value)))
+        .filter_map(|n| n.attr("content"))
+        .map(collapse)
+        .find(|content| !content.is_empty())
+}
+
+/// Pages asking robots not to keep a copy are not extracted
+fn forbids_archiving(document: &Document) -> bool {
+    document
+        .find(Name("meta"))
+        .filter(|n| n.attr("name").is_some_and(|name| name.eq_ignore_ascii_case("robots")))
+        .filter_map(|n| n.attr("content"))
+        .flat_map(|content| content.split(',').map(|d| d.trim().to_lowercase()).collect::<Vec<_>>())
+        .any(|directive| directive == "noarchive" || directive == "none")
+}
+
+fn collect(node: Node, paragraphs: &mut Vec<String>, current: &mut String) {
+    if let Some(text) = node.as_text() {
+        current.push_str(text);
+        return;
+    }
+    let name = node.name().unwrap_or_default();
+    if SKIPPED.contains(&name) || node.attr("hidden").is_some() {
+        return;
+    }
+    let is_block = BLOCKS.contains(&name);
+    if is_block {
+        paragraphs.push(std::mem::take(current));
+    }
+    for child in node.children() {
+        collect(child, paragraphs, current);
+    }
+    if is_block {
+        paragraphs.push(std::mem::take(current));
+    }
+}
+
+/// Title, byline and main text of the page, None if the page has no text or forbids archiving
+pub fn extract(html: &str) -> Option<Article> {
+    let document = Document::from(html);
+    if forbids_archiving(&document) {
+        return None;
+    }
+    let title = meta(&document, "property", "og:title")
+        .or_else(|| document.find(Name("title")).next().map(|n| collapse(&n.text())))
+        .or_else(|| document.find(Name("h1")).next().map(|n| collapse(&n.text())))
+        .unwrap_or_default();
+    let byline = meta(&document, "name", "author")
+        .or_else(|| meta(&document, "property", "article:author"))
+        .or_else(|| {
+            document
+                .find(Or(Attr("rel", "author"), Class("byline")))
+                .map(|n| collapse(&n.text()))
+                .find(|text| !text.is_empty())
+        })
+        .unwrap_or_default();
+
+    // the main content if marked up, the whole body otherwise
+    let main = document
+        .find(Name("article"))
+        .next()
+        .or_else(|| document.find(Or(Name("main"), Attr("role", "main"))).next())
+        .or_else(|| document.find(Name("body")).next())?;
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    collect(main, &mut paragraphs, &mut current);
+    paragraphs.push(current);
+    let text = paragraphs
+        .iter()
+        .map(|p| collapse(p))
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if text.is_empty() {
+        return None;
+    }
+    Some(Article { title, byline, text })
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use rstest::*;
+
+    use super::*;
+
+    const PAGE: &str = indoc! {r#"
+        <html><head>
+            <title>Fearless Concurrency | The Blog</title>
+            <meta name="author" content="Jane Doe">
+            <script>var tracking = 1;</script>
+        </head><body>
+            <header><nav><a href="/">Home</a> <a href="/about">About</a></nav></header>
+            <article>
+                <h1>Fearless   Concurrency</h1>
+                <p class="byline">by Someone Else</p>
+                <p>Threads are <em>hard</em>.
+                   Rust makes them easier.</p>
+                <noscript>Enable JavaScript for comments</noscript>
+                <aside>Related posts</aside>
+                <ul><li>Send</li><li>Sync</li></ul>
+            </article>
+            <footer>Copyright</footer>
+        </body></html>
+    "#};
+
+    #[rstest]
+    fn test_extract() {
+        let article = extract(PAGE).unwrap();
+        assert_eq!(article.title, "Fearless Concurrency | The Blog");
+        assert_eq!(article.byline, "Jane Doe");
+        assert_eq!(
+            article.text,
+            "Fearless Concurrency\n\nby Someone Else\n\nThreads are hard. Rust makes them easier.\n\nSend\n\nSync"
+        );
+    }
+
+    #[rstest]
+    fn test_extract_without_article() {
+        let html = r#"<html><head><meta property="og:title" content="OG"></head>
+            <body><nav>Menu</nav><div>Plain <b>text</b></div><span rel="author">Max</span></body></html>"#;
+        let article = extract(html).unwrap();
+        assert_eq!(article.title, "OG");
+        assert_eq!(article.byline, "Max");
+        assert_eq!(article.text, "Plain text\n\nMax");
+    }
+
+    #[rstest]
+    #[case(r#"<html><head><meta name="Robots" content="index, noarchive"></head><body><p>x</p></body></html>"#)]
+    #[case(r#"<html><head><title>Empty</title></head><body><script>app()</script></body></html>"#)]
+    fn test_extract_none(#[case] html: &str) {
+        assert_eq!(extract(html), None);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use tracing::{debug, instrument};
+
+use crate::adapter::dal::Dal;
+use crate::environment::CONFIG;
+use crate::model::bookmark::Bookmark;
+use crate::model::registry::Registry;
+use crate::model::system_tag::{is_system_tag, SystemTags};
+use crate::service::bulk::{self, BulkBookmark, BulkFile, BulkFormat, Change};
+use crate::service::bus;
+use crate::service::signing;
+
+/// Pack file at the root of a git registry, https registries point directly to it
+pub const PACK_FILE: &str = "bkmr-pack.json";
+
+/// Checkout of a git registry: `<cache dir>/bkmr/registries/<name>`
+pub fn checkout_dir(registry: &Registry) -> Result<PathBuf> {
+    dirs::cache_dir()
+        .map(|dir| dir.join("bkmr").join("registries").join(&registry.name))
+        .ok_or_else(|| anyhow!("No cache directory for registry checkouts"))
+}
+
+fn git(args: &[&str], dir: Option<&Path>) -> Result<()> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.arg("-C").arg(dir);
+    }
+    let status = cmd
+        .args(args)
+        .status()
+        .with_context(|| "Failed to run git")?;
+    if !status.success() {
+        return Err(anyhow!("git {} failed: {}", args.join(" "), status));
+    }
+    Ok(())
+}
+
+/// Clones or pulls git registries, downloads https registries and reads local paths.
+/// Registries with a public key only accept packs signed with it.
+#[instrument(level = "debug")]
+pub fn fetch_pack(registry: &Registry) -> Result<BulkFile> {
+    let content = if registry.is_git() {
+        let dir = checkout_dir(registry)?;
+        if dir.join(".git").exists() {
+            git(&["pull", "--ff-only", "--quiet"], Some(&dir))?;
+        } else {
+            fs::create_dir_all(&dir)?;
+            git(
+                &["clone", "--depth", "1", "--quiet", &registry.url, &dir.to_string_lossy()],
+                None,
+            )?;
+        }
+        let path = dir.join(PACK_FILE);
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?
+    } else if registry.url.starts_with("https://") || registry.url.starts_with("http://") {
+        reqwest::blocking::get(&registry.url)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .with_context(|| format!("Failed to download {}", registry.url))?
+    } else {
+        fs::read_to_string(&registry.url)
+            .with_context(|| format!("Failed to read {}", registry.url))?
+    };
+    let trusted_keys = registry.public_key.as_ref().map(std::slice::from_ref);
+    signing::open(&content, BulkFormat::Json, trusted_keys)
+        .map_err(|e| anyhow!("Invalid pack of registry {}: {:#}", registry.name, e))
+}
+
+/// Unverified packs must not run anything on open: executable system tags are removed
+/// and `shell::` entries are skipped
+pub fn strip_executable(pack: BulkFile, system_tags: &SystemTags) -> BulkFile {
+    let bookmarks = pack
+        .bookmarks
+        .into_iter()
+        .filter(|entry| {
+            let shell = entry.url.starts_with("shell::");
+            if shell {
+                eprintln!("Skipping {}: shell command in an unsigned pack", entry.url);
+            }
+            !shell
+        })
+        .map(|entry| {
+            let (executable, tags): (Vec<String>, Vec<String>) = entry
+                .tags
+                .iter()
+                .cloned()
+                .partition(|tag| is_system_tag(tag) && system_tags.is_executable(tag));
+            if !executable.is_empty() {
+                eprintln!("Removing {} of {}: unsigned pack", executable.join(","), entry.url);
+            }
+            BulkBookmark { tags, ..entry }
+        })
+        .collect();
+    BulkFile { bookmarks, ..pack }
+}
+
+/// Changes turning the bookmarks of the registry into the pack.
+/// Pack entries get the registry tag and are matched by uuid or URL, ids in the pack are ignored.
+/// URLs already bookmarked outside of the registry are skipped, local bookmarks are never touched.
+pub fn plan_sync(registry: &Registry, current: &[Bookmark], pack: &BulkFile) -> Result<Vec<Change>> {
+    let tag = registry.tag();
+    let (own, local): (Vec<Bookmark>, Vec<Bookmark>) = current
+        .iter()
+        .cloned()
+        .partition(|bm| bm.get_tags().contains(&tag));
+    let local_urls: HashSet<&str> = local.iter().map(|bm| bm.URL.as_str()).collect();
+    let local_uuids: HashSet<&str> = local.iter().map(|bm| bm.uuid.as_str()).collect();
+
+    let mut entries = Vec::new();
+    for entry in &pack.bookmarks {
+        if local_urls.contains(entry.url.as_str()) {
+            eprintln!("Skipping {}: already bookmarked locally", entry.url);
+            continue;
+        }
+        let existing = own.iter().find(|bm| {
+            entry.uuid.as_deref() == Some(bm.uuid.as_str()) || entry.url == bm.URL
+        });
+        let mut tags = entry.tags.clone();
+        tags.push(tag.clone());
+        entries.push(BulkBookmark {
+            id: existing.map(|bm| bm.id),
+            uuid: entry
+                .uuid
+                .clone()
+                .filter(|uuid| !local_uuids.contains(uuid.as_str())),
+            tags,
+            ..entry.clone()
+        });
+    }
+    bulk::diff(&own, &BulkFile { visibility: None, bookmarks: entries })
+}
+
+/// Fetches the pack and applies it, returns the changes.
+/// Packs of untrusted registries are stripped of everything executable.
+#[instrument(level = "debug", skip(dal))]
+pub fn sync(dal: &mut Dal, registry: &Registry, dry_run: bool) -> Result<Vec<Change>> {
+    let mut pack = fetch_pack(registry)?;
+    if !registry.is_trusted() {
+        pack = strip_executable(pack, &SystemTags::new(&CONFIG.types)?);
+    }
+    let changes = plan_sync(registry, &dal.get_bookmarks("")?, &pack)?;
+    debug!("{} changes", changes.len());
+    if !dry_run {
+        bulk::apply(dal, changes.clone())?;
+        dal.set_registry_synced(&registry.name)?;
+    }
+    Ok(changes)
+}
+
+/// Removes the registry and its bookmarks
+#[instrument(level = "debug", skip(dal))]
+pub fn remove(dal: &mut Dal, name: &str) -> Result<usize> {
+    let registry = dal.get_registry(name)?;
+    let changes = plan_sync(&registry, &dal.get_bookmarks("")?, &BulkFile::default())?;
+    let n = changes.len();
+    let events = dal.transaction(|dal| {
+        let events = bulk::apply_changes(dal, &changes)?;
+        dal.delete_registry(name)?;
+        Ok(events)
+    })?;
+    bus::publish_all(events);
+    if let Ok(dir) = checkout_dir(&registry) {
+        let _ = fs::remove_dir_all(dir);
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::util::testing::setup_temp_db;
+
+    fn pack(entries: &[(&str, &str)]) -> BulkFile {
+        BulkFile {
+            visibility: None,
+            bookmarks: entries
+                .iter()
+                .map(|(url, title)| BulkBookmark {
+                    id: Some(1),
+                    url: url.to_string(),
+                    title: title.to_string(),
+                    tags: vec!["_snip_".to_string()],
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[rstest]
+    fn test_sync_local_pack() -> Result<()> {
+        let (dir, mut dal) = setup_temp_db()?;
+        let n_local = dal.get_bookmarks("")?.len();
+        let existing = dal.get_bookmark_by_id(1)?.URL;
+        let path = dir.path().join(PACK_FILE);
+        let registry = Registry::new("Team", &path.to_string_lossy(), None);
+        dal.insert_registry(&registry)?;
+
+        fs::write(
+            &path,
+            pack(&[("echo one", "one"), ("echo two", "two"), (&existing, "local")])
+                .serialize(BulkFormat::Json)?,
+        )?;
+        let changes = sync(&mut dal, &registry, false)?;
+        assert_eq!(changes.len(), 2);
+        let bm = dal.get_bookmark_by_url("echo one")?;
+        assert_eq!(bm.tags, ",_snip_,registry:team,");
+        assert_eq!(dal.get_bookmarks("")?.len(), n_local + 2);
+        assert!(dal.get_registry("team")?.last_sync.is_some());
+
+        // second sync updates matching entries and removes the dropped ones
+        fs::write(&path, pack(&[("echo one", "uno")]).serialize(BulkFormat::Json)?)?;
+        let changes = sync(&mut dal, &registry, false)?;
+        assert_eq!(changes.len(), 2);
+        assert_eq!(dal.get_bookmark_by_url("echo one")?.metadata, "uno");
+        assert!(!dal.bm_exists("echo two")?);
+        assert_eq!(dal.get_bookmark_by_url(&existing)?.metadata, dal.get_bookmark_by_id(1)?.metadata);
+
+        assert_eq!(remove(&mut dal, "team")?, 1);
+        assert_eq!(dal.get_bookmarks("")?.len(), n_local);
+        assert!(dal.get_registries()?.is_empty());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_strip_executable() {
+        let mut pack = pack(&[("shell::rm -rf ~", "shell"), ("ssh host uptime", "uptime")]);
+        pack.bookmarks[1].tags = vec!["_ssh_".to_string(), "ops".to_string()];
+        let stripped = strip_executable(pack, &SystemTags::default());
+        assert_eq!(stripped.bookmarks.len(), 1);
+        assert_eq!(stripped.bookmarks[0].tags, vec!["ops"]);
+
+        assert!(Registry::new("team", "/tmp/pack.json", None).is_trusted());
+        assert!(Registry::new("team", "https://example.com/pack.json", Some("key".to_string())).is_trusted());
+        assert!(!Registry::new("team", "https://example.com/pack.json", None).is_trusted());
+        assert!(!Registry::new("team", "git@github.com:team/pack.git", None).is_trusted());
+    }
+
+    #[rstest]
+    fn test_signed_pack() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(PACK_FILE);
+        let (key, _) = signing::SigningKey::generate()?;
+        let registry = Registry::new("team", &path.to_string_lossy(), Some(key.public_key()));
+        let content = pack(&[("echo one", "one")]).serialize(BulkFormat::Json)?;
+
+        fs::write(&path, &content)?;
+        assert!(fetch_pack(&registry).is_err());
+
+        fs::write(&path, key.sign(content, BulkFormat::Json).to_json()?)?;
+        assert_eq!(fetch_pack(&registry)?.bookmarks.len(), 1);
+
+        let (other, _) = signing::SigningKey::generate()?;
+        let untrusted = Registry::new("team", &path.to_string_lossy(), Some(other.public_key()));
+        assert!(fetch_pack(&untrusted).is_err());
+        Ok(())
+    }
+}
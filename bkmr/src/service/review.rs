@@ -0,0 +1,207 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::model::bookmark::Bookmark;
+use crate::model::tag::Tags;
+
+pub const REVIEW_HELP: &str = r#"
+    k | ENTER:      keep, reviewed
+    t tags:         tag, adding the tags (separated by comma), reviewed
+    d:              delete
+    o:              open
+    s:              skip, presented again next session
+    q:              quit, progress is kept
+"#;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReviewAction {
+    Keep,
+    Tag(Vec<String>),
+    Delete,
+    Open,
+    Skip,
+    Quit,
+}
+
+pub fn parse_action(input: &str) -> Result<ReviewAction> {
+    let input = input.trim();
+    let (command, args) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    match command {
+        "" | "k" | "keep" => Ok(ReviewAction::Keep),
+        "t" | "tag" => {
+            let tags = Tags::normalize_tag_string(Some(args.trim().to_string()));
+            if tags.is_empty() {
+                return Err(anyhow!("Tag needs tags, e.g. t rust,web"));
+            }
+            Ok(ReviewAction::Tag(tags))
+        }
+        "d" | "delete" => Ok(ReviewAction::Delete),
+        "o" | "open" => Ok(ReviewAction::Open),
+        "s" | "skip" => Ok(ReviewAction::Skip),
+        "q" | "quit" => Ok(ReviewAction::Quit),
+        _ => Err(anyhow!("Unknown action {:?}, use:{}", command, REVIEW_HELP)),
+    }
+}
+
+/// Restricts the bookmarks of a review, all criteria must hold
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReviewFilter {
+    /// only bookmarks created after
+    pub created_after: Option<NaiveDateTime>,
+    pub untagged: bool,
+    pub unopened: bool,
+}
+
+impl ReviewFilter {
+    pub fn matches(&self, bm: &Bookmark) -> bool {
+        // bookmarks from before creation times were recorded fall back to their last update
+        let created = bm.created_ts.unwrap_or(bm.last_update_ts);
+        self.created_after.is_none_or(|cutoff| created >= cutoff)
+            && (!self.untagged || bm.get_tags().is_empty())
+            && (!self.unopened || bm.flags == 0)
+    }
+}
+
+/// Bookmarks reviewed so far by uuid, kept between sessions
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ReviewState {
+    pub reviewed: BTreeSet<String>,
+}
+
+impl ReviewState {
+    pub fn file() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("bkmr").join("review.json"))
+    }
+
+    /// Empty state if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read review state {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid review state {:?}", path))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create directory {:?}", dir))?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write review state {:?}", path))?;
+        debug!("{} reviewed", self.reviewed.len());
+        Ok(())
+    }
+}
+
+/// Bookmarks to review oldest first, without the reviewed and skipped ones (by uuid, ids change on delete)
+pub fn pending(
+    bms: &[Bookmark],
+    filter: &ReviewFilter,
+    state: &ReviewState,
+    skipped: &HashSet<String>,
+) -> Vec<Bookmark> {
+    let mut pending: Vec<Bookmark> = bms
+        .iter()
+        .filter(|bm| filter.matches(bm) && !state.reviewed.contains(&bm.uuid) && !skipped.contains(&bm.uuid))
+        .cloned()
+        .collect();
+    pending.sort_by_key(|bm| (bm.created_ts.unwrap_or(bm.last_update_ts), bm.id));
+    pending
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use rstest::*;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn ts(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap().and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    fn bm(id: i32, tags: &str, flags: i32, created: u32) -> Bookmark {
+        let bm = BookmarkBuilder::new()
+            .id(id)
+            .URL(format!("https://example.com/{}", id))
+            .tags(tags.to_string())
+            .flags(flags)
+            .uuid(format!("uuid-{}", id))
+            .build();
+        Bookmark {
+            created_ts: Some(ts(created)),
+            ..bm
+        }
+    }
+
+    #[rstest]
+    #[case("", ReviewAction::Keep)]
+    #[case("t rust,Web", ReviewAction::Tag(vec!["rust".to_string(), "web".to_string()]))]
+    #[case(" d ", ReviewAction::Delete)]
+    #[case("s", ReviewAction::Skip)]
+    #[case("q", ReviewAction::Quit)]
+    fn test_parse_action(#[case] input: &str, #[case] expected: ReviewAction) -> Result<()> {
+        assert_eq!(parse_action(input)?, expected);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("x")]
+    #[case("t")]
+    fn test_parse_action_invalid(#[case] input: &str) {
+        assert!(parse_action(input).is_err());
+    }
+
+    #[rstest]
+    fn test_pending() {
+        let bms = vec![
+            bm(1, ",rust,", 0, 12),
+            bm(2, ",,", 0, 10),
+            bm(3, ",,", 2, 11),
+            bm(4, ",web,", 0, 1),
+            bm(5, ",,", 0, 13),
+        ];
+        let recent = ReviewFilter {
+            created_after: Some(ts(8)),
+            ..Default::default()
+        };
+        let state = ReviewState {
+            reviewed: BTreeSet::from(["uuid-5".to_string()]),
+        };
+        let skipped = HashSet::from(["uuid-1".to_string()]);
+        let ids = |filter: &ReviewFilter, skipped: &HashSet<String>| -> Vec<i32> {
+            pending(&bms, filter, &state, skipped).iter().map(|bm| bm.id).collect()
+        };
+        assert_eq!(ids(&recent, &HashSet::new()), vec![2, 3, 1]);
+        assert_eq!(ids(&recent, &skipped), vec![2, 3]);
+        let untagged_unopened = ReviewFilter {
+            untagged: true,
+            unopened: true,
+            ..Default::default()
+        };
+        assert_eq!(ids(&untagged_unopened, &HashSet::new()), vec![2]);
+    }
+
+    #[rstest]
+    fn test_state_roundtrip() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("bkmr").join("review.json");
+        assert_eq!(ReviewState::load(&path)?, ReviewState::default());
+        let state = ReviewState {
+            reviewed: BTreeSet::from(["a".to_string(), "b".to_string()]),
+        };
+        state.save(&path)?;
+        assert_eq!(ReviewState::load(&path)?, state);
+        Ok(())
+    }
+}
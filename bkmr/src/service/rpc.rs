@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::adapter::dal::Dal;
+use crate::adapter::json::BookmarkView;
+use crate::model::bookmark::{Bookmark, BookmarkBuilder, BookmarkUpdater};
+use crate::model::tag::Tags;
+use crate::service::language::fill_language;
+use crate::service::rules::TagRules;
+
+/// Search results unless the request has a limit
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Bookmark by id or uuid
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Key {
+    Id(i32),
+    Uuid(String),
+}
+
+/// Request of `bkmr rpc`, e.g. `{"method": "search", "params": {"query": "rust", "tags": ["_snip_"]}}`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "lowercase")]
+pub enum Request {
+    /// FTS query, all bookmarks if empty, restricted to bookmarks with all tags
+    Search {
+        #[serde(default)]
+        query: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        limit: Option<usize>,
+    },
+    Add {
+        url: String,
+        #[serde(default)]
+        title: String,
+        #[serde(default)]
+        desc: String,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    Get {
+        id: Key,
+    },
+}
+
+pub fn parse(content: &str) -> Result<Request> {
+    serde_json::from_str(content).map_err(|e| anyhow!("Invalid request: {}", e))
+}
+
+/// Result of the request: bookmarks as in `--json` output, the added bookmark for `add`
+pub fn handle(dal: &mut Dal, rules: &TagRules, request: Request) -> Result<(Value, Option<Bookmark>)> {
+    debug!("{:?}", request);
+    match request {
+        Request::Search { query, tags, limit } => {
+            let tags = Tags::clean_tags(tags);
+            let bms: Vec<BookmarkView> = dal
+                .get_bookmarks(query.trim())?
+                .iter()
+                .filter(|bm| Tags::match_all_tags(&tags, &bm.get_tags()))
+                .take(limit.unwrap_or(DEFAULT_LIMIT))
+                .map(BookmarkView::from)
+                .collect();
+            Ok((json!(bms), None))
+        }
+        Request::Add { url, title, desc, tags } => {
+            if url.trim().is_empty() {
+                return Err(anyhow!("Bookmark needs a url"));
+            }
+            if dal.bm_exists(&url)? {
+                let existing = dal.get_bookmark_by_url(&url)?;
+                return Err(anyhow!("Bookmark already exists: {} [{}]", url, existing.id));
+            }
+            let mut bm = BookmarkBuilder::new().id(1).URL(url).metadata(title).desc(desc).flags(0).build();
+            bm.set_tags(tags);
+            rules.apply(&mut bm);
+            fill_language(&mut bm);
+            bm.update();
+            let added = dal.insert_bookmark(bm.convert_to_new_bookmark())?.remove(0);
+            Ok((json!(BookmarkView::from(&added)), Some(added)))
+        }
+        Request::Get { id } => {
+            let bm = match id {
+                Key::Id(id) => dal.get_bookmark_by_id(id)?,
+                Key::Uuid(uuid) => dal.get_bookmark_by_uuid(&uuid)?,
+            };
+            Ok((json!(BookmarkView::from(&bm)), None))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    #[case(r#"{"method": "search", "params": {"query": "rust"}}"#, Request::Search { query: "rust".to_string(), tags: vec![], limit: None })]
+    #[case(r#"{"method": "get", "params": {"id": 3}}"#, Request::Get { id: Key::Id(3) })]
+    #[case(r#"{"method": "get", "params": {"id": "0b3e9c1a"}}"#, Request::Get { id: Key::Uuid("0b3e9c1a".to_string()) })]
+    fn test_parse(#[case] content: &str, #[case] expected: Request) -> Result<()> {
+        assert_eq!(parse(content)?, expected);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(r#"{"method": "delete", "params": {"id": 3}}"#)]
+    #[case(r#"{"method": "add", "params": {"title": "no url"}}"#)]
+    #[case("not json")]
+    fn test_parse_invalid(#[case] content: &str) {
+        assert!(parse(content).is_err());
+    }
+
+    #[rstest]
+    fn test_handle() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        let rules = TagRules::default();
+
+        let add = parse(r#"{"method": "add", "params": {"url": "https://rpc.example", "title": "RPC", "tags": ["Editor"]}}"#)?;
+        let (result, added) = handle(&mut dal, &rules, add.clone())?;
+        assert_eq!(result["metadata"], "RPC");
+        assert_eq!(result["tags"], ",editor,");
+        let id = added.unwrap().id;
+        assert!(handle(&mut dal, &rules, add).is_err());
+
+        let (result, _) = handle(&mut dal, &rules, Request::Get { id: Key::Id(id) })?;
+        assert_eq!(result["URL"], "https://rpc.example");
+
+        let search = parse(r#"{"method": "search", "params": {"tags": ["editor"]}}"#)?;
+        let (result, _) = handle(&mut dal, &rules, search)?;
+        assert_eq!(result.as_array().map(Vec::len), Some(1));
+        let search = parse(r#"{"method": "search", "params": {"limit": 2}}"#)?;
+        assert_eq!(handle(&mut dal, &rules, search)?.0.as_array().map(Vec::len), Some(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::Url;
+use tracing::debug;
+
+use crate::model::bookmark::Bookmark;
+use crate::model::tag::Tags;
+use crate::util::helper::glob_to_regex;
+
+/// Part of a bookmark a rule pattern is matched against
+#[derive(Debug, PartialEq, Clone)]
+pub enum RuleTarget {
+    URL,
+    Domain,
+    Title,
+    Desc,
+}
+
+/// Single tagging rule compiled from the config file
+///
+/// Rule keys select the target by prefix:
+/// - `domain:<glob>`: glob against the URL host, e.g. `domain:*.github.com`
+/// - `title:<regex>`: regex against the title
+/// - `desc:<regex>`: regex against the description
+/// - `<regex>`: regex against the URL
+#[derive(Debug, Clone)]
+pub struct TagRule {
+    pub key: String,
+    pub target: RuleTarget,
+    pattern: Regex,
+    pub tags: Vec<String>,
+}
+
+impl TagRule {
+    pub fn new(key: &str, tags: &[String]) -> Result<Self> {
+        let (target, pattern) = match key.split_once(':') {
+            Some(("domain", glob)) => (RuleTarget::Domain, glob_to_regex(glob)),
+            Some(("title", regex)) => (RuleTarget::Title, regex.to_string()),
+            Some(("desc", regex)) => (RuleTarget::Desc, regex.to_string()),
+            _ => (RuleTarget::URL, key.to_string()),
+        };
+        let pattern =
+            Regex::new(&pattern).with_context(|| format!("Invalid tag rule pattern: {}", key))?;
+
+        Ok(Self {
+            key: key.to_string(),
+            target,
+            pattern,
+            tags: Tags::clean_tags(tags.to_vec()),
+        })
+    }
+
+    pub fn is_match(&self, bm: &Bookmark) -> bool {
+        match self.target {
+            RuleTarget::URL => self.pattern.is_match(&bm.URL),
+            RuleTarget::Domain => Url::parse(&bm.URL)
+                .ok()
+                .and_then(|url| url.host_str().map(|host| self.pattern.is_match(host)))
+                .unwrap_or(false),
+            RuleTarget::Title => self.pattern.is_match(&bm.metadata),
+            RuleTarget::Desc => self.pattern.is_match(&bm.desc),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TagRules {
+    pub rules: Vec<TagRule>,
+}
+
+impl TagRules {
+    pub fn new(rules: &BTreeMap<String, Vec<String>>) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|(key, tags)| TagRule::new(key, tags))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Tags of all matching rules which the bookmark does not have yet
+    pub fn missing_tags(&self, bm: &Bookmark) -> Vec<String> {
+        let bm_tags = bm.get_tags();
+        let tags = self
+            .rules
+            .iter()
+            .filter(|rule| rule.is_match(bm))
+            .flat_map(|rule| rule.tags.clone())
+            .filter(|tag| !bm_tags.contains(tag))
+            .collect();
+        Tags::clean_tags(tags)
+    }
+
+    /// Adds the tags of all matching rules, returns the added tags
+    pub fn apply(&self, bm: &mut Bookmark) -> Vec<String> {
+        let missing = self.missing_tags(bm);
+        if !missing.is_empty() {
+            debug!("Rules add tags {:?} to {:?}", missing, bm.URL);
+            let mut tags = bm.get_tags();
+            tags.extend(missing.clone());
+            bm.set_tags(tags);
+        }
+        missing
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    #[fixture]
+    fn bm() -> Bookmark {
+        BookmarkBuilder::new()
+            .id(1)
+            .URL("https://github.com/sysid/bkmr".to_string())
+            .metadata("bkmr: bookmark manager".to_string())
+            .tags(",xxx,".to_string())
+            .desc("Ultrafast Bookmark Manager and Launcher".to_string())
+            .build()
+    }
+
+    #[fixture]
+    fn rules() -> TagRules {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "^https://github.com".to_string(),
+            vec!["dev".to_string(), "github".to_string()],
+        );
+        config.insert("domain:*.rust-lang.org".to_string(), vec!["rust".to_string()]);
+        config.insert("desc:(?i)launcher".to_string(), vec!["tool".to_string()]);
+        config.insert("title:^never".to_string(), vec!["never".to_string()]);
+        TagRules::new(&config).unwrap()
+    }
+
+    #[rstest]
+    #[case("domain:*.github.com", RuleTarget::Domain)]
+    #[case("title:^bkmr", RuleTarget::Title)]
+    #[case("desc:manager", RuleTarget::Desc)]
+    #[case("^https://", RuleTarget::URL)]
+    fn test_rule_target(#[case] key: &str, #[case] expected: RuleTarget) {
+        let rule = TagRule::new(key, &["a".to_string()]).unwrap();
+        assert_eq!(rule.target, expected);
+    }
+
+    #[rstest]
+    fn test_invalid_rule() {
+        assert!(TagRule::new("desc:(", &["a".to_string()]).is_err());
+    }
+
+    #[rstest]
+    fn test_missing_tags(bm: Bookmark, rules: TagRules) {
+        assert_eq!(rules.missing_tags(&bm), vec!["dev", "github", "tool"]);
+    }
+
+    #[rstest]
+    fn test_apply(mut bm: Bookmark, rules: TagRules) {
+        let added = rules.apply(&mut bm);
+        assert_eq!(added, vec!["dev", "github", "tool"]);
+        assert_eq!(bm.tags, ",dev,github,tool,xxx,");
+
+        // applying again is a no-op
+        assert!(rules.apply(&mut bm).is_empty());
+        assert_eq!(bm.tags, ",dev,github,tool,xxx,");
+    }
+
+    #[rstest]
+    fn test_apply_domain(rules: TagRules) {
+        let mut bm = BookmarkBuilder::new()
+            .URL("https://doc.rust-lang.org/book/".to_string())
+            .tags(",,".to_string())
+            .build();
+        assert_eq!(rules.apply(&mut bm), vec!["rust"]);
+    }
+}
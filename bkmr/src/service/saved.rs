@@ -0,0 +1,284 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use select::document::Document;
+use select::predicate::{Attr, Class, Name, Predicate};
+use serde::Deserialize;
+use tracing::{debug, instrument};
+
+use crate::model::bookmark::{Bookmark, BookmarkBuilder};
+
+pub const HN_URL: &str = "https://news.ycombinator.com";
+pub const REDDIT_API_URL: &str = "https://oauth.reddit.com";
+const REDDIT_URL: &str = "https://www.reddit.com";
+/// Safety net against endless pagination
+const MAX_PAGES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SavedSource {
+    /// Hacker News favorites, public, no token needed
+    Hn,
+    /// saved Reddit posts and comments, needs an OAuth token with the history scope
+    Reddit,
+}
+
+/// Saved item of a source, turned into a bookmark
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SavedItem {
+    pub url: String,
+    pub title: String,
+    pub desc: String,
+    pub tags: Vec<String>,
+}
+
+impl SavedItem {
+    pub fn to_bookmark(&self) -> Bookmark {
+        let mut bm = BookmarkBuilder::new()
+            .id(1)
+            .URL(self.url.clone())
+            .metadata(self.title.clone())
+            .desc(self.desc.clone())
+            .flags(0)
+            .build();
+        bm.set_tags(self.tags.clone());
+        bm
+    }
+}
+
+/// Favorites of a favorites page and whether there is a next page
+pub fn parse_hn_favorites(html: &str) -> (Vec<SavedItem>, bool) {
+    let document = Document::from(html);
+    let items = document
+        .find(Class("athing"))
+        .filter_map(|row| {
+            let link = row.find(Class("titleline").descendant(Name("a"))).next()?;
+            let href = link.attr("href")?;
+            // Ask HN and other text posts link to their item page
+            let url = if href.starts_with("http") {
+                href.to_string()
+            } else {
+                format!("{}/{}", HN_URL, href.trim_start_matches('/'))
+            };
+            let discussion = row
+                .attr("id")
+                .map(|id| format!("{}/item?id={}", HN_URL, id))
+                .unwrap_or_default();
+            Some(SavedItem {
+                url,
+                title: link.text(),
+                desc: discussion,
+                tags: vec!["hn".to_string()],
+            })
+        })
+        .collect();
+    let has_more = document.find(Attr("rel", "next")).next().is_some();
+    (items, has_more)
+}
+
+#[instrument(level = "debug")]
+fn fetch_hn_page(base_url: &str, user: &str, page: usize) -> Result<String> {
+    let url = format!("{}/favorites?id={}&p={}", base_url.trim_end_matches('/'), user, page);
+    reqwest::blocking::Client::new()
+        .get(&url)
+        .header("User-Agent", "bkmr")
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .with_context(|| format!("Failed to fetch {}", url))
+}
+
+#[derive(Debug, Deserialize)]
+struct Listing {
+    data: ListingData,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListingData {
+    after: Option<String>,
+    children: Vec<Thing>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Thing {
+    /// t3 for posts, t1 for comments
+    kind: String,
+    data: ThingData,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ThingData {
+    title: Option<String>,
+    link_title: Option<String>,
+    url: Option<String>,
+    permalink: String,
+    subreddit: String,
+    selftext: String,
+    body: String,
+}
+
+impl From<Thing> for SavedItem {
+    fn from(thing: Thing) -> Self {
+        let data = thing.data;
+        let permalink = format!("{}{}", REDDIT_URL, data.permalink);
+        let is_comment = thing.kind == "t1";
+        Self {
+            // saved comments and self posts are bookmarked by their permalink
+            url: data
+                .url
+                .filter(|url| !is_comment && url.starts_with("http"))
+                .unwrap_or(permalink),
+            title: data.title.or(data.link_title).unwrap_or_default(),
+            desc: if is_comment { data.body } else { data.selftext },
+            tags: vec!["reddit".to_string(), data.subreddit.to_lowercase()],
+        }
+    }
+}
+
+/// Saved items of a listing page and the cursor of the next page
+pub fn parse_reddit_saved(json: &str) -> Result<(Vec<SavedItem>, Option<String>)> {
+    let listing: Listing = serde_json::from_str(json).context("Invalid reddit listing")?;
+    let after = listing.data.after;
+    Ok((listing.data.children.into_iter().map(SavedItem::from).collect(), after))
+}
+
+#[instrument(level = "debug", skip(token))]
+fn fetch_reddit_page(base_url: &str, user: &str, token: &str, after: Option<&str>) -> Result<String> {
+    let url = format!("{}/user/{}/saved", base_url.trim_end_matches('/'), user);
+    let mut query = vec![("limit", "100"), ("raw_json", "1")];
+    query.extend(after.map(|after| ("after", after)));
+    reqwest::blocking::Client::new()
+        .get(&url)
+        .query(&query)
+        .bearer_auth(token)
+        .header("User-Agent", "bkmr")
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .with_context(|| format!("Failed to fetch {}", url))
+}
+
+/// Saved items not known yet, newest first. Pages are fetched until a page has no new item,
+/// so re-runs only fetch the items saved since the last import.
+pub fn new_items(
+    source: SavedSource,
+    base_url: &str,
+    user: &str,
+    token: Option<&str>,
+    mut is_known: impl FnMut(&str) -> Result<bool>,
+) -> Result<Vec<SavedItem>> {
+    let mut items = Vec::new();
+    let mut after: Option<String> = None;
+    for page in 1..=MAX_PAGES {
+        let (fetched, has_more) = match source {
+            SavedSource::Hn => parse_hn_favorites(&fetch_hn_page(base_url, user, page)?),
+            SavedSource::Reddit => {
+                let token = token.ok_or_else(|| anyhow!("Reddit needs an OAuth token"))?;
+                let (fetched, next) =
+                    parse_reddit_saved(&fetch_reddit_page(base_url, user, token, after.as_deref())?)?;
+                after = next;
+                (fetched, after.is_some())
+            }
+        };
+        let n = fetched.len();
+        let mut new = Vec::new();
+        for item in fetched {
+            if !is_known(&item.url)? && !new.iter().any(|i: &SavedItem| i.url == item.url) {
+                new.push(item);
+            }
+        }
+        debug!("page {}: {} items, {} new", page, n, new.len());
+        let done = new.is_empty() || !has_more;
+        items.extend(new);
+        if done {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use mockito::Matcher;
+    use rstest::*;
+
+    use super::*;
+
+    const HN_PAGE: &str = indoc! {r#"
+        <table>
+        <tr class="athing submission" id="101"><td class="title"><span class="titleline">
+            <a href="https://example.com/post">A post</a><span class="sitebit comhead"> (example.com)</span>
+        </span></td></tr>
+        <tr class="athing submission" id="102"><td class="title"><span class="titleline">
+            <a href="item?id=102">Ask HN: Favorite tools?</a>
+        </span></td></tr>
+        </table>
+        <a href="favorites?id=pg&amp;p=2" class="morelink" rel="next">More</a>
+    "#};
+
+    #[rstest]
+    fn test_parse_hn_favorites() {
+        let (items, has_more) = parse_hn_favorites(HN_PAGE);
+        assert!(has_more);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].url, "https://example.com/post");
+        assert_eq!(items[0].title, "A post");
+        assert_eq!(items[0].desc, "https://news.ycombinator.com/item?id=101");
+        assert_eq!(items[1].url, "https://news.ycombinator.com/item?id=102");
+        assert_eq!(items[1].tags, vec!["hn"]);
+    }
+
+    #[rstest]
+    fn test_parse_reddit_saved() -> Result<()> {
+        let json = r#"{"kind": "Listing", "data": {"after": "t3_b", "children": [
+            {"kind": "t3", "data": {"title": "Rust 2024", "url": "https://blog.rust-lang.org/x",
+                "permalink": "/r/rust/comments/a/rust_2024/", "subreddit": "rust", "selftext": ""}},
+            {"kind": "t3", "data": {"title": "Ask", "url": "/r/AskReddit/comments/b/ask/",
+                "permalink": "/r/AskReddit/comments/b/ask/", "subreddit": "AskReddit", "selftext": "why?"}},
+            {"kind": "t1", "data": {"link_title": "Thread", "body": "great answer",
+                "permalink": "/r/rust/comments/c/thread/d/", "subreddit": "rust"}}
+        ]}}"#;
+        let (items, after) = parse_reddit_saved(json)?;
+        assert_eq!(after.as_deref(), Some("t3_b"));
+        assert_eq!(items[0].url, "https://blog.rust-lang.org/x");
+        assert_eq!(items[0].tags, vec!["reddit", "rust"]);
+        assert_eq!(items[1].url, "https://www.reddit.com/r/AskReddit/comments/b/ask/");
+        assert_eq!(items[1].desc, "why?");
+        assert_eq!(items[1].tags, vec!["reddit", "askreddit"]);
+        assert_eq!(items[2].url, "https://www.reddit.com/r/rust/comments/c/thread/d/");
+        assert_eq!(items[2].title, "Thread");
+        assert_eq!(items[2].desc, "great answer");
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_new_items_hn() -> Result<()> {
+        let mut server = mockito::Server::new();
+        let page = |p: &str| {
+            Matcher::AllOf(vec![
+                Matcher::UrlEncoded("id".into(), "pg".into()),
+                Matcher::UrlEncoded("p".into(), p.into()),
+            ])
+        };
+        server
+            .mock("GET", "/favorites")
+            .match_query(page("1"))
+            .with_body(HN_PAGE)
+            .create();
+        let last = HN_PAGE.replace("101", "201").replace("example.com/post", "example.com/old");
+        server
+            .mock("GET", "/favorites")
+            .match_query(page("2"))
+            .with_body(last.replace(r#"rel="next""#, ""))
+            .create();
+
+        let items = new_items(SavedSource::Hn, &server.url(), "pg", None, |url| {
+            Ok(url.ends_with("item?id=102"))
+        })?;
+        let urls: Vec<_> = items.iter().map(|i| i.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://example.com/post", "https://example.com/old"]);
+
+        assert!(new_items(SavedSource::Reddit, &server.url(), "me", None, |_| Ok(false)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use clap::ValueEnum;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::environment::SigningConfig;
+use crate::service::bulk::{BulkFile, BulkFormat};
+
+pub const BUNDLE_VERSION: &str = "bkmr-bundle-1";
+
+/// Export with a detached Ed25519 signature over `content`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedBundle {
+    pub bundle: String,
+    /// format of `content`: toml or json
+    pub format: String,
+    /// base64 public key of the signer
+    pub public_key: String,
+    /// base64 signature of `content`
+    pub signature: String,
+    pub content: String,
+}
+
+impl SignedBundle {
+    /// None if the content is not a signed bundle
+    pub fn parse(content: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(content)
+            .ok()
+            .filter(|bundle| bundle.bundle == BUNDLE_VERSION)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize bundle")
+    }
+
+    /// Checks the signature against the embedded public key, only proves integrity
+    pub fn check(&self) -> Result<BulkFile> {
+        let public_key = BASE64
+            .decode(&self.public_key)
+            .context("Invalid public key in bundle")?;
+        let signature = BASE64
+            .decode(&self.signature)
+            .context("Invalid signature in bundle")?;
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(self.content.as_bytes(), &signature)
+            .map_err(|_| anyhow!("Bad signature: bundle has been modified"))?;
+        let format = BulkFormat::from_str(&self.format, true)
+            .map_err(|_| anyhow!("Unknown bundle format {}", self.format))?;
+        BulkFile::parse(&self.content, format)
+    }
+
+    /// Checks the signature and that the signer is one of the trusted keys
+    pub fn verify(&self, trusted_keys: &[String]) -> Result<BulkFile> {
+        if !trusted_keys.contains(&self.public_key) {
+            return Err(anyhow!(
+                "Untrusted signing key {}, add it to [signing] trusted_keys",
+                self.public_key
+            ));
+        }
+        self.check()
+    }
+}
+
+/// Parses an import: signed bundles are checked, with trusted keys they are required and verified
+pub fn open(content: &str, format: BulkFormat, trusted_keys: Option<&[String]>) -> Result<BulkFile> {
+    match (SignedBundle::parse(content), trusted_keys) {
+        (Some(bundle), Some(keys)) => bundle.verify(keys),
+        (Some(bundle), None) => bundle.check(),
+        (None, Some(_)) => Err(anyhow!("Not a signed bundle, export with --sign")),
+        (None, None) => BulkFile::parse(content, format),
+    }
+}
+
+pub struct SigningKey(Ed25519KeyPair);
+
+impl SigningKey {
+    /// New key and its base64 PKCS#8 document for storage
+    pub fn generate() -> Result<(Self, String)> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| anyhow!("Failed to generate signing key"))?;
+        let encoded = BASE64.encode(pkcs8.as_ref());
+        Ok((Self::from_base64(&encoded)?, encoded))
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let pkcs8 = BASE64
+            .decode(encoded.trim())
+            .context("Invalid signing key encoding")?;
+        Ed25519KeyPair::from_pkcs8(&pkcs8)
+            .map(Self)
+            .map_err(|_| anyhow!("Invalid signing key"))
+    }
+
+    /// Loads the key file, creates it (readable by the owner only) if missing
+    #[instrument(level = "debug")]
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if path.exists() {
+            let encoded =
+                fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+            return Self::from_base64(&encoded);
+        }
+        let (key, encoded) = Self::generate()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(path)
+            .with_context(|| format!("Failed to create {:?}", path))?;
+        writeln!(file, "{}", encoded)?;
+        eprintln!("Created signing key {:?}, public key: {}", path, key.public_key());
+        Ok(key)
+    }
+
+    pub fn public_key(&self) -> String {
+        BASE64.encode(self.0.public_key().as_ref())
+    }
+
+    pub fn sign(&self, content: String, format: BulkFormat) -> SignedBundle {
+        let signature = self.0.sign(content.as_bytes());
+        debug!("Signed {} bytes", content.len());
+        SignedBundle {
+            bundle: BUNDLE_VERSION.to_string(),
+            format: format!("{:?}", format).to_lowercase(),
+            public_key: self.public_key(),
+            signature: BASE64.encode(signature.as_ref()),
+            content,
+        }
+    }
+}
+
+/// Configured key file or `<config dir>/bkmr/signing.key`
+pub fn key_path(config: &SigningConfig) -> Result<PathBuf> {
+    match &config.key_file {
+        Some(path) => Ok(PathBuf::from(shellexpand::tilde(path).as_ref())),
+        None => dirs::config_dir()
+            .map(|dir| dir.join("bkmr").join("signing.key"))
+            .ok_or_else(|| anyhow!("No config directory for the signing key")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::service::bulk::BulkBookmark;
+
+    fn bulk_file() -> BulkFile {
+        BulkFile {
+            visibility: None,
+            bookmarks: vec![BulkBookmark {
+                url: "https://example.com".to_string(),
+                title: "example".to_string(),
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[rstest]
+    fn test_sign_and_verify() -> Result<()> {
+        let (key, _) = SigningKey::generate()?;
+        let file = bulk_file();
+        let bundle = key.sign(file.serialize(BulkFormat::Toml)?, BulkFormat::Toml);
+        let parsed = SignedBundle::parse(&bundle.to_json()?).unwrap();
+
+        assert_eq!(parsed.check()?, file);
+        assert_eq!(parsed.verify(&[key.public_key()])?, file);
+        assert!(parsed.verify(&[]).unwrap_err().to_string().starts_with("Untrusted"));
+
+        let tampered = SignedBundle {
+            content: parsed.content.replace("example", "evil"),
+            ..parsed
+        };
+        assert!(tampered.check().unwrap_err().to_string().starts_with("Bad signature"));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_open() -> Result<()> {
+        let (key, _) = SigningKey::generate()?;
+        let plain = bulk_file().serialize(BulkFormat::Json)?;
+        let signed = key.sign(plain.clone(), BulkFormat::Json).to_json()?;
+        let trusted = vec![key.public_key()];
+
+        assert_eq!(open(&plain, BulkFormat::Json, None)?, bulk_file());
+        assert!(open(&plain, BulkFormat::Json, Some(&trusted)).is_err());
+        assert_eq!(open(&signed, BulkFormat::Toml, Some(&trusted))?, bulk_file());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_or_create() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("keys").join("signing.key");
+        let created = SigningKey::load_or_create(&path)?;
+        let loaded = SigningKey::load_or_create(&path)?;
+        assert_eq!(created.public_key(), loaded.public_key());
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use tracing::debug;
+
+use crate::model::bookmark::Bookmark;
+use crate::model::system_tag::is_system_tag;
+use crate::service::clipboard::write_clipboard;
+
+pub const SNIPPET_TAG: &str = "_snip_";
+
+/// Prints the snippet to stdout and copies it to the clipboard
+pub fn open_snippet(content: &str) -> Result<()> {
+    println!("{}", content);
+    match write_clipboard(content) {
+        Ok(()) => eprintln!("Copied to clipboard."),
+        Err(e) => eprintln!("{:#}", e),
+    }
+    Ok(())
+}
+
+/// VS Code snippets file of the `_snip_` bookmarks, keyed by title.
+/// The first tag which is not a system tag is used as prefix, the language as scope.
+pub fn vscode_snippets(bms: &[Bookmark]) -> Value {
+    let mut snippets = Map::new();
+    for bm in bms.iter().filter(|bm| bm.get_tags().iter().any(|t| t == SNIPPET_TAG)) {
+        let prefix = bm
+            .get_tags()
+            .into_iter()
+            .find(|t| !is_system_tag(t))
+            .unwrap_or_else(|| bm.metadata.to_lowercase().replace(char::is_whitespace, "-"));
+        let mut snippet = json!({
+            "prefix": prefix,
+            "body": bm.URL.lines().collect::<Vec<_>>(),
+            "description": bm.desc,
+        });
+        if let Some(language) = &bm.language {
+            snippet["scope"] = json!(language);
+        }
+        let name = if snippets.contains_key(&bm.metadata) {
+            format!("{} ({})", bm.metadata, bm.id)
+        } else {
+            bm.metadata.clone()
+        };
+        snippets.insert(name, snippet);
+    }
+    debug!("{} snippets", snippets.len());
+    Value::Object(snippets)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    #[rstest]
+    fn test_vscode_snippets() {
+        let bms = vec![
+            BookmarkBuilder::new()
+                .id(1)
+                .URL("fn main() {\n}".to_string())
+                .metadata("Rust main".to_string())
+                .tags(",_snip_,rmain,".to_string())
+                .desc("entry point".to_string())
+                .language(Some("rust".to_string()))
+                .build(),
+            BookmarkBuilder::new()
+                .id(2)
+                .URL("https://example.com".to_string())
+                .metadata("no snippet".to_string())
+                .tags(",web,".to_string())
+                .build(),
+            BookmarkBuilder::new()
+                .id(3)
+                .URL("echo hi".to_string())
+                .metadata("Say hi".to_string())
+                .tags(",_snip_,".to_string())
+                .build(),
+        ];
+
+        assert_eq!(
+            vscode_snippets(&bms),
+            json!({
+                "Rust main": {
+                    "prefix": "rmain",
+                    "body": ["fn main() {", "}"],
+                    "description": "entry point",
+                    "scope": "rust",
+                },
+                "Say hi": {
+                    "prefix": "say-hi",
+                    "body": ["echo hi"],
+                    "description": "",
+                },
+            })
+        );
+    }
+}
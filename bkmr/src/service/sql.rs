@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+use regex::Regex;
+use reqwest::Url;
+#[cfg(feature = "sqlite")]
+use rusqlite::types::ValueRef;
+use tracing::debug;
+#[cfg(feature = "sqlite")]
+use tracing::instrument;
+
+use crate::environment::{SqlDriver, SqlProfile, CONFIG};
+use crate::util::helper::prompt;
+
+const DEFAULT_PROFILE: &str = "default";
+
+/// `:name`, but not the `::` of casts
+fn param_re() -> Regex {
+    Regex::new(r"(^|[^:]):([A-Za-z_][A-Za-z0-9_]*)").unwrap()
+}
+
+/// Parameterized query of a `_sql_` bookmark.
+/// An optional first line `-- profile: <name>` selects the connection profile,
+/// parameters are written as `:name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlQuery {
+    pub profile: String,
+    pub sql: String,
+}
+
+impl SqlQuery {
+    pub fn parse(content: &str) -> Self {
+        let content = content.trim();
+        let profile_re = Regex::new(r"^--\s*profile:\s*(\S+)\s*\n?").unwrap();
+        match profile_re.captures(content) {
+            Some(caps) => Self {
+                profile: caps[1].to_string(),
+                sql: content[caps[0].len()..].trim().to_string(),
+            },
+            None => Self {
+                profile: DEFAULT_PROFILE.to_string(),
+                sql: content.to_string(),
+            },
+        }
+    }
+
+    /// Unique parameter names in order of appearance, `::` casts are ignored
+    pub fn parameters(&self) -> Vec<String> {
+        param_re()
+            .captures_iter(&self.sql)
+            .map(|caps| caps[2].to_string())
+            .unique()
+            .collect()
+    }
+
+    /// Script for the CLI client of the driver, it never contains the parameter values as SQL literals.
+    /// psql gets `:'name'`, the values are passed with `--variable` and quoted by psql.
+    /// mysql gets a prepared statement, the values are assigned from hex literals,
+    /// which need no escaping in any sql_mode (e.g. NO_BACKSLASH_ESCAPES).
+    pub fn script(&self, params: &BTreeMap<String, String>, driver: SqlDriver) -> String {
+        match driver {
+            SqlDriver::Mysql => {
+                let mut names = Vec::new();
+                let sql = param_re().replace_all(self.sql.trim_end_matches(';'), |caps: &regex::Captures| {
+                    names.push(format!("@bkmr_{}", &caps[2]));
+                    format!("{}?", &caps[1])
+                });
+                let mut script: Vec<String> = self
+                    .parameters()
+                    .iter()
+                    .map(|name| {
+                        let value = params.get(name).map(String::as_str).unwrap_or_default();
+                        format!("SET @bkmr_{} = {};", name, hex_literal(value))
+                    })
+                    .collect();
+                script.push(format!("SET @bkmr_query = {};", hex_literal(&sql)));
+                script.push("PREPARE bkmr_query FROM @bkmr_query;".to_string());
+                match names.is_empty() {
+                    true => script.push("EXECUTE bkmr_query;".to_string()),
+                    false => script.push(format!("EXECUTE bkmr_query USING {};", names.join(", "))),
+                }
+                script.push("DEALLOCATE PREPARE bkmr_query;".to_string());
+                script.join("\n")
+            }
+            _ => param_re().replace_all(&self.sql, "$1:'$2'").to_string(),
+        }
+    }
+}
+
+fn hex_literal(value: &str) -> String {
+    let hex: String = value.bytes().map(|b| format!("{:02X}", b)).collect();
+    format!("CONVERT(X'{}' USING utf8mb4)", hex)
+}
+
+/// Renders rows as aligned text table
+pub fn render_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(h.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut table = vec![
+        line(headers),
+        widths.iter().map(|w| "-".repeat(*w)).join("-+-"),
+    ];
+    table.extend(rows.iter().map(|row| line(row)));
+    table.join("\n")
+}
+
+#[cfg(feature = "sqlite")]
+fn format_value(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).to_string(),
+        ValueRef::Blob(b) => format!("<blob {} bytes>", b.len()),
+    }
+}
+
+/// Runs the query against a sqlite database, returns header and rows
+#[cfg(feature = "sqlite")]
+#[instrument(level = "debug", skip(params))]
+pub fn query_sqlite(
+    path: &str,
+    query: &SqlQuery,
+    params: &BTreeMap<String, String>,
+) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+    let path = shellexpand::tilde(path).to_string();
+    let conn = rusqlite::Connection::open(&path)
+        .with_context(|| format!("Failed to open sqlite database {}", path))?;
+    let mut stmt = conn
+        .prepare(&query.sql)
+        .with_context(|| format!("Invalid SQL: {}", query.sql))?;
+
+    let headers: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let named: Vec<(String, &String)> = params
+        .iter()
+        .map(|(name, value)| (format!(":{}", name), value))
+        .collect();
+    let named: Vec<(&str, &dyn rusqlite::ToSql)> = named
+        .iter()
+        .map(|(name, value)| (name.as_str(), *value as &dyn rusqlite::ToSql))
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut result = stmt.query(named.as_slice())?;
+    while let Some(row) = result.next()? {
+        rows.push(
+            (0..headers.len())
+                .map(|i| row.get_ref(i).map(format_value))
+                .collect::<rusqlite::Result<Vec<_>>>()?,
+        );
+    }
+    Ok((headers, rows))
+}
+
+/// Fails for drivers left out of the build by their feature flag
+fn check_driver(driver: SqlDriver) -> Result<()> {
+    let (enabled, feature) = match driver {
+        SqlDriver::Sqlite => (cfg!(feature = "sqlite"), "sqlite"),
+        SqlDriver::Postgres => (cfg!(feature = "postgres"), "postgres"),
+        SqlDriver::Mysql => (cfg!(feature = "mysql"), "mysql"),
+    };
+    match enabled {
+        true => Ok(()),
+        false => Err(anyhow!("bkmr was built without the {} feature", feature)),
+    }
+}
+
+/// psql/mysql client invocation for the profile, the script is read from stdin
+fn client_command(profile: &SqlProfile, params: &BTreeMap<String, String>) -> Result<Command> {
+    match profile.driver {
+        SqlDriver::Sqlite => Err(anyhow!("sqlite is queried natively")),
+        SqlDriver::Postgres => {
+            let mut cmd = Command::new("psql");
+            // the password is passed via environment, arguments are visible to other users in ps
+            let url = match Url::parse(&profile.url) {
+                Ok(mut url) if url.password().is_some() => {
+                    cmd.env("PGPASSWORD", url.password().unwrap_or_default());
+                    url.set_password(None)
+                        .map_err(|_| anyhow!("Invalid postgres URL {}", profile.url))?;
+                    url.to_string()
+                }
+                _ => profile.url.clone(),
+            };
+            // --command does not interpolate variables, stdin does
+            cmd.arg(url).arg("--set=ON_ERROR_STOP=1");
+            for (name, value) in params {
+                cmd.arg(format!("--variable={}={}", name, value));
+            }
+            cmd.arg("--file=-");
+            Ok(cmd)
+        }
+        SqlDriver::Mysql => {
+            let url = Url::parse(&profile.url)
+                .with_context(|| format!("Invalid mysql URL {}", profile.url))?;
+            let mut cmd = Command::new("mysql");
+            cmd.arg("--table");
+            if let Some(host) = url.host_str() {
+                cmd.arg("--host").arg(host);
+            }
+            if let Some(port) = url.port() {
+                cmd.arg("--port").arg(port.to_string());
+            }
+            if !url.username().is_empty() {
+                cmd.arg("--user").arg(url.username());
+            }
+            if let Some(password) = url.password() {
+                cmd.env("MYSQL_PWD", password);
+            }
+            cmd.arg(url.path().trim_start_matches('/'));
+            Ok(cmd)
+        }
+    }
+}
+
+/// Prompts for the parameters and runs the query against its connection profile
+pub fn execute_query(content: &str) -> Result<()> {
+    let query = SqlQuery::parse(content);
+    let profile = CONFIG.sql_profiles.get(&query.profile).ok_or_else(|| {
+        anyhow!(
+            "Unknown SQL profile {:?}, configure it in [sql_profiles.{}]",
+            query.profile,
+            query.profile
+        )
+    })?;
+
+    let params: BTreeMap<String, String> = query
+        .parameters()
+        .into_iter()
+        .map(|name| {
+            let value = prompt(&format!("{}: ", name));
+            (name, value)
+        })
+        .collect();
+    debug!("{:?} {:?}", query, params);
+
+    check_driver(profile.driver)?;
+    match profile.driver {
+        #[cfg(feature = "sqlite")]
+        SqlDriver::Sqlite => {
+            let (headers, rows) = query_sqlite(&profile.url, &query, &params)?;
+            println!("{}", render_table(&headers, &rows));
+            eprintln!("({} rows)", rows.len());
+        }
+        _ => {
+            let mut child = client_command(profile, &params)?
+                .stdin(Stdio::piped())
+                .spawn()
+                .context("Failed to run database client, is it installed?")?;
+            if let Some(mut stdin) = child.stdin.take() {
+                // a client failing to connect exits before reading, its status tells why
+                if let Err(e) = stdin.write_all(query.script(&params, profile.driver).as_bytes()) {
+                    debug!("Database client did not read the query: {}", e);
+                }
+            }
+            let status = child.wait()?;
+            if !status.success() {
+                return Err(anyhow!("Database client exited with {}", status));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("select 1", "default", "select 1")]
+    #[case("-- profile: prod\nselect * from t", "prod", "select * from t")]
+    fn test_parse(#[case] content: &str, #[case] profile: &str, #[case] sql: &str) {
+        let query = SqlQuery::parse(content);
+        assert_eq!(query.profile, profile);
+        assert_eq!(query.sql, sql);
+    }
+
+    #[rstest]
+    fn test_parameters_and_psql_script() {
+        let query = SqlQuery::parse("select id::text from t where a = :a and b = :b or a = :a");
+        assert_eq!(query.parameters(), vec!["a", "b"]);
+
+        let params = BTreeMap::from([("a".to_string(), r"\' or 1=1 -- ".to_string())]);
+        assert_eq!(
+            query.script(&params, SqlDriver::Postgres),
+            "select id::text from t where a = :'a' and b = :'b' or a = :'a'"
+        );
+    }
+
+    #[rstest]
+    fn test_mysql_script() {
+        let query = SqlQuery::parse("select * from t where a = :a or b = :b or a = :a;");
+        let params = BTreeMap::from([
+            ("a".to_string(), r"\'".to_string()),
+            ("b".to_string(), "".to_string()),
+        ]);
+        let sql_hex: String = "select * from t where a = ? or b = ? or a = ?"
+            .bytes()
+            .map(|b| format!("{:02X}", b))
+            .collect();
+        assert_eq!(
+            query.script(&params, SqlDriver::Mysql),
+            [
+                "SET @bkmr_a = CONVERT(X'5C27' USING utf8mb4);".to_string(),
+                "SET @bkmr_b = CONVERT(X'' USING utf8mb4);".to_string(),
+                format!("SET @bkmr_query = CONVERT(X'{}' USING utf8mb4);", sql_hex),
+                "PREPARE bkmr_query FROM @bkmr_query;".to_string(),
+                "EXECUTE bkmr_query USING @bkmr_a, @bkmr_b, @bkmr_a;".to_string(),
+                "DEALLOCATE PREPARE bkmr_query;".to_string(),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[rstest]
+    fn test_postgres_client_command_hides_password() {
+        let profile = SqlProfile {
+            driver: SqlDriver::Postgres,
+            url: "postgres://user:pw@db.local:5433/app".to_string(),
+        };
+        let params = BTreeMap::from([("a".to_string(), "it's".to_string())]);
+        let cmd = client_command(&profile, &params).unwrap();
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy().to_string()).collect();
+        assert_eq!(
+            args,
+            vec!["postgres://user@db.local:5433/app", "--set=ON_ERROR_STOP=1", "--variable=a=it's", "--file=-"]
+        );
+        let env: Vec<_> = cmd.get_envs().collect();
+        assert_eq!(env, vec![(std::ffi::OsStr::new("PGPASSWORD"), Some(std::ffi::OsStr::new("pw")))]);
+    }
+
+    #[rstest]
+    fn test_render_table() {
+        let headers = vec!["id".to_string(), "name".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "bkmr".to_string()],
+            vec!["10".to_string(), "x".to_string()],
+        ];
+        assert_eq!(
+            render_table(&headers, &rows),
+            "id | name\n---+-----\n1  | bkmr\n10 | x"
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[rstest]
+    fn test_query_sqlite() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("test.db");
+        let conn = rusqlite::Connection::open(&path)?;
+        conn.execute_batch(
+            "CREATE TABLE t (id INTEGER, name TEXT, data BLOB);
+             INSERT INTO t VALUES (1, 'a', NULL), (2, 'b', x'0102');",
+        )?;
+
+        let query = SqlQuery::parse("select * from t where id >= :min");
+        let params = BTreeMap::from([("min".to_string(), "2".to_string())]);
+        let (headers, rows) = query_sqlite(path.to_str().unwrap(), &query, &params)?;
+
+        assert_eq!(headers, vec!["id", "name", "data"]);
+        assert_eq!(rows, vec![vec!["2", "b", "<blob 2 bytes>"]]);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_mysql_client_command() {
+        let profile = SqlProfile {
+            driver: SqlDriver::Mysql,
+            url: "mysql://user:pw@db.local:3307/app".to_string(),
+        };
+        let cmd = client_command(&profile, &BTreeMap::new()).unwrap();
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy().to_string()).collect();
+        assert_eq!(
+            args,
+            vec!["--table", "--host", "db.local", "--port", "3307", "--user", "user", "app"]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::io::IsTerminal;
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use tracing::debug;
+
+use crate::util::helper::interpolate_env;
+
+/// Remote command of a `_ssh_` bookmark: `<destination> <command>`,
+/// destination is `[user@]host` or `ssh://[user@]host[:port]`.
+/// Environment variables are interpolated, e.g. `${DEPLOY_USER}@${DEPLOY_HOST} uptime`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SshCommand {
+    pub destination: String,
+    pub command: String,
+}
+
+impl SshCommand {
+    pub fn parse(content: &str) -> Result<Self> {
+        let content = interpolate_env(content.trim())?;
+        let (destination, command) = content
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| anyhow!("Expected '<destination> <command>', got {:?}", content))?;
+        if destination.starts_with('-') {
+            // would be taken as option, e.g. -oProxyCommand=...
+            return Err(anyhow!("Invalid ssh destination {:?}", destination));
+        }
+        Ok(Self {
+            destination: destination.to_string(),
+            command: command.trim().to_string(),
+        })
+    }
+
+    pub fn to_command(&self) -> Command {
+        let mut cmd = Command::new("ssh");
+        if std::io::stdin().is_terminal() {
+            // allocate a tty for interactive remote commands
+            cmd.arg("-t");
+        }
+        cmd.arg("--").arg(&self.destination).arg(&self.command);
+        cmd
+    }
+}
+
+/// Runs the command on the remote host, like shell:: commands without confirmation
+pub fn execute_ssh(content: &str) -> Result<()> {
+    let ssh = SshCommand::parse(content)?;
+    debug!("{:?}", ssh);
+    let status = ssh
+        .to_command()
+        .status()
+        .with_context(|| format!("Failed to run ssh {}", ssh.destination))?;
+    if !status.success() {
+        return Err(anyhow!("ssh {} exited with {}", ssh.destination, status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_parse() {
+        std::env::set_var("BKMR_TEST_SSH_HOST", "build01");
+        let ssh = SshCommand::parse("deploy@${BKMR_TEST_SSH_HOST} systemctl status 'my app'").unwrap();
+        assert_eq!(ssh.destination, "deploy@build01");
+        assert_eq!(ssh.command, "systemctl status 'my app'");
+
+        let args: Vec<_> = ssh
+            .to_command()
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .filter(|a| a != "-t")
+            .collect();
+        assert_eq!(args, vec!["--", "deploy@build01", "systemctl status 'my app'"]);
+    }
+
+    #[rstest]
+    #[case("build01")]
+    #[case("")]
+    #[case("-oProxyCommand=id uptime")]
+    fn test_parse_invalid(#[case] content: &str) {
+        assert!(SshCommand::parse(content).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+
+use crate::adapter::dal::Dal;
+use crate::service::triage::INBOX_TAG;
+
+/// Counters of `bkmr status`, read by single queries to be fast enough for shell prompts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Status {
+    /// bookmarks waiting for triage
+    pub inbox: i64,
+}
+
+impl Status {
+    pub fn read(dal: &mut Dal) -> Result<Status> {
+        Ok(Status {
+            inbox: dal.count_tagged(INBOX_TAG)?,
+        })
+    }
+
+    /// One line of `key=value` pairs, e.g. `inbox=3`, keys are only ever added
+    pub fn porcelain(&self) -> String {
+        format!("inbox={}", self.inbox)
+    }
+
+    /// Prompt segment of the template, empty if there is nothing to show so the segment disappears
+    pub fn render(&self, template: &str) -> String {
+        if self.inbox == 0 {
+            return String::new();
+        }
+        template.replace("{inbox}", &self.inbox.to_string())
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        vec![format!("Inbox: {}", self.inbox)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::util::testing::setup_temp_db;
+
+    #[rstest]
+    fn test_read() -> Result<()> {
+        let (_dir, mut dal) = setup_temp_db()?;
+        assert_eq!(Status::read(&mut dal)?, Status { inbox: 0 });
+
+        let mut bm = dal.get_bookmark_by_id(1)?;
+        bm.tags = format!(",{},", INBOX_TAG);
+        dal.update_bookmark(bm)?;
+        let status = Status::read(&mut dal)?;
+        assert_eq!(status.porcelain(), "inbox=1");
+        assert_eq!(status.lines(), vec!["Inbox: 1"]);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(3, "🔖 {inbox}", "🔖 3")]
+    #[case(3, "{inbox} to triage", "3 to triage")]
+    #[case(0, "🔖 {inbox}", "")]
+    fn test_render(#[case] inbox: i64, #[case] template: &str, #[case] expected: &str) {
+        assert_eq!(Status { inbox }.render(template), expected);
+    }
+}
@@ -0,0 +1,100 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::model::bookmark::Bookmark;
+
+/// Restricts the bookmarks surprise picks from
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SurpriseFilter {
+    /// bookmarks must have all tags
+    pub tags: Vec<String>,
+    pub unopened_only: bool,
+    /// only bookmarks created before
+    pub created_before: Option<NaiveDateTime>,
+}
+
+impl SurpriseFilter {
+    pub fn matches(&self, bm: &Bookmark) -> bool {
+        let tags = bm.get_tags();
+        // bookmarks from before creation times were recorded fall back to their last update
+        let created = bm.created_ts.unwrap_or(bm.last_update_ts);
+        self.tags.iter().all(|tag| tags.contains(tag))
+            && (!self.unopened_only || bm.flags == 0)
+            && self.created_before.is_none_or(|cutoff| created < cutoff)
+    }
+}
+
+/// Seed of a day, the same picks all day as long as the bookmarks do not change
+pub fn daily_seed(date: NaiveDate) -> u64 {
+    date.num_days_from_ce() as u64
+}
+
+/// Up to n random bookmarks matching the filter, a seed makes the picks repeatable
+pub fn pick(bms: &[Bookmark], n: usize, filter: &SurpriseFilter, seed: Option<u64>) -> Vec<Bookmark> {
+    let mut candidates: Vec<Bookmark> = bms.iter().filter(|bm| filter.matches(bm)).cloned().collect();
+    // independent of the query order, so that a seed always picks the same bookmarks
+    candidates.sort_by_key(|bm| bm.id);
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    candidates.shuffle(&mut rng);
+    candidates.truncate(n);
+    candidates
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn bm(id: i32, tags: &str, flags: i32, year: i32) -> Bookmark {
+        Bookmark {
+            created_ts: NaiveDate::from_ymd_opt(year, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+            ..BookmarkBuilder::new()
+                .id(id)
+                .URL(format!("https://example.com/{}", id))
+                .tags(tags.to_string())
+                .flags(flags)
+                .build()
+        }
+    }
+
+    fn bms() -> Vec<Bookmark> {
+        (1..=20).map(|id| bm(id, if id % 2 == 0 { ",rust,web," } else { ",web," }, id % 3, 2010 + id)).collect()
+    }
+
+    fn ids(bms: &[Bookmark]) -> Vec<i32> {
+        bms.iter().map(|bm| bm.id).collect()
+    }
+
+    #[rstest]
+    fn test_pick_filtered() {
+        let cutoff = NaiveDate::from_ymd_opt(2025, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0));
+        let filter = SurpriseFilter {
+            tags: vec!["rust".to_string()],
+            unopened_only: true,
+            created_before: cutoff,
+        };
+        let mut picked = ids(&pick(&bms(), 10, &filter, None));
+        picked.sort();
+        // even ids with flags 0 created before 2025
+        assert_eq!(picked, vec![6, 12]);
+    }
+
+    #[rstest]
+    fn test_pick_daily_is_repeatable() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let filter = SurpriseFilter::default();
+        let mut shuffled = bms();
+        shuffled.reverse();
+        let first = ids(&pick(&bms(), 3, &filter, Some(daily_seed(day))));
+        assert_eq!(first.len(), 3);
+        assert_eq!(first, ids(&pick(&shuffled, 3, &filter, Some(daily_seed(day)))));
+        assert_ne!(daily_seed(day), daily_seed(day.succ_opt().unwrap()));
+    }
+}
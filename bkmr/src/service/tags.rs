@@ -0,0 +1,97 @@
+use clap::ValueEnum;
+use crossterm::style::Stylize;
+
+use crate::model::bookmark::TagsFrequency;
+
+const CLOUD_LEVELS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TagSort {
+    /// most frequent first
+    Freq,
+    /// alphabetical
+    Alpha,
+}
+
+/// Keeps tags starting with prefix and sorts them
+pub fn select_tags(
+    mut tags: Vec<TagsFrequency>,
+    prefix: Option<&str>,
+    sort: TagSort,
+) -> Vec<TagsFrequency> {
+    if let Some(prefix) = prefix {
+        tags.retain(|t| t.tag.starts_with(prefix));
+    }
+    match sort {
+        TagSort::Freq => tags.sort_by(|a, b| b.n.cmp(&a.n).then_with(|| a.tag.cmp(&b.tag))),
+        TagSort::Alpha => tags.sort_by(|a, b| a.tag.cmp(&b.tag)),
+    }
+    tags
+}
+
+/// Size level 1..=CLOUD_LEVELS of a tag, logarithmic in its frequency
+pub fn cloud_level(n: i32, max: i32) -> usize {
+    if max <= 1 || n <= 1 {
+        return 1;
+    }
+    let ratio = (n as f64).ln() / (max as f64).ln();
+    1 + (ratio * (CLOUD_LEVELS - 1) as f64).round() as usize
+}
+
+/// Renders tags as a single flowing line, more frequent tags are emphasized
+pub fn render_cloud(tags: &[TagsFrequency]) -> String {
+    let max = tags.iter().map(|t| t.n).max().unwrap_or(0);
+    tags.iter()
+        .map(|t| match cloud_level(t.n, max) {
+            1 => t.tag.clone().dark_grey().to_string(),
+            2 => t.tag.clone().to_string(),
+            3 => t.tag.clone().bold().to_string(),
+            _ => t.tag.to_uppercase().bold().green().to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[fixture]
+    fn tags() -> Vec<TagsFrequency> {
+        [("dev", 3), ("rust", 5), ("devops", 1), ("apple", 3)]
+            .into_iter()
+            .map(|(tag, n)| TagsFrequency {
+                n,
+                tag: tag.to_string(),
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case(None, TagSort::Freq, vec!["rust", "apple", "dev", "devops"])]
+    #[case(None, TagSort::Alpha, vec!["apple", "dev", "devops", "rust"])]
+    #[case(Some("dev"), TagSort::Freq, vec!["dev", "devops"])]
+    fn test_select_tags(
+        tags: Vec<TagsFrequency>,
+        #[case] prefix: Option<&str>,
+        #[case] sort: TagSort,
+        #[case] expected: Vec<&str>,
+    ) {
+        let selected: Vec<_> = select_tags(tags, prefix, sort)
+            .into_iter()
+            .map(|t| t.tag)
+            .collect();
+        assert_eq!(selected, expected);
+    }
+
+    #[rstest]
+    #[case(1, 100, 1)]
+    #[case(10, 100, 3)]
+    #[case(100, 100, 4)]
+    #[case(5, 1, 1)]
+    fn test_cloud_level(#[case] n: i32, #[case] max: i32, #[case] expected: usize) {
+        assert_eq!(cloud_level(n, max), expected);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+
+use crate::model::bookmark::{Bookmark, BookmarkBuilder};
+
+/// System tag of template bookmarks, the template name is the bookmark title
+pub const TEMPLATE_TAG: &str = "_template_";
+
+/// Scaffold for new bookmarks, stored as bookmark tagged `_template_`:
+/// content, tags (without `_template_`), description, language and meta are copied on `add --template <name>`.
+/// Placeholders: `{date}` (`YYYY-MM-DD`), `{title}`, `{meta.<key>}` (meta of the template bookmark).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub name: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub desc: String,
+    pub language: Option<String>,
+    pub meta: BTreeMap<String, String>,
+}
+
+impl Template {
+    pub fn from_bookmark(bm: &Bookmark) -> Option<Self> {
+        let tags = bm.get_tags();
+        if !tags.iter().any(|t| t == TEMPLATE_TAG) {
+            return None;
+        }
+        Some(Self {
+            name: bm.metadata.clone(),
+            content: bm.URL.clone(),
+            tags: tags.into_iter().filter(|t| t != TEMPLATE_TAG).collect(),
+            desc: bm.desc.clone(),
+            language: bm.language.clone(),
+            meta: bm.meta(),
+        })
+    }
+
+    /// Template with the given name, case-insensitive
+    pub fn find(bms: &[Bookmark], name: &str) -> Result<Self> {
+        bms.iter()
+            .filter_map(Self::from_bookmark)
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("Template {:?} not found, tag a bookmark with {}", name, TEMPLATE_TAG))
+    }
+
+    /// New bookmark from the template, the title defaults to `<name> <date>`
+    pub fn instantiate(&self, title: Option<&str>, today: NaiveDate) -> Bookmark {
+        let date = today.format("%Y-%m-%d").to_string();
+        let title = title
+            .map(String::from)
+            .unwrap_or_else(|| format!("{} {}", self.name, date));
+        let render = |text: &str| {
+            self.meta.iter().fold(
+                text.replace("{date}", &date).replace("{title}", &title),
+                |text, (key, value)| text.replace(&format!("{{meta.{}}}", key), value),
+            )
+        };
+
+        let mut bm = BookmarkBuilder::new()
+            .URL(render(&self.content))
+            .metadata(title.clone())
+            .desc(render(&self.desc))
+            .flags(0)
+            .language(self.language.clone())
+            .build();
+        bm.set_tags(self.tags.clone());
+        for (key, value) in &self.meta {
+            bm.set_meta(key, Some(value));
+        }
+        bm
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[fixture]
+    fn bms() -> Vec<Bookmark> {
+        vec![
+            BookmarkBuilder::new()
+                .URL("https://example.com".to_string())
+                .metadata("Meeting-Notes".to_string())
+                .tags(",meeting,".to_string())
+                .build(),
+            BookmarkBuilder::new()
+                .URL("~/notes/{date}-{title}.md".to_string())
+                .metadata("meeting-notes".to_string())
+                .tags(",_edit_,_template_,meeting,".to_string())
+                .desc("Notes of {title}".to_string())
+                .build(),
+        ]
+    }
+
+    #[rstest]
+    fn test_find(bms: Vec<Bookmark>) {
+        let template = Template::find(&bms, "MEETING-NOTES").unwrap();
+        assert_eq!(template.tags, vec!["_edit_", "meeting"]);
+        assert!(Template::find(&bms, "unknown").is_err());
+    }
+
+    #[rstest]
+    fn test_instantiate_meta(bms: Vec<Bookmark>) {
+        let mut templ = bms[1].clone();
+        templ.desc = "Notes of {title} for {meta.project}".to_string();
+        templ.set_meta("project", Some("alpha"));
+        let bm = Template::from_bookmark(&templ)
+            .unwrap()
+            .instantiate(Some("retro"), NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert_eq!(bm.desc, "Notes of retro for alpha");
+        assert_eq!(bm.meta().get("project").map(String::as_str), Some("alpha"));
+    }
+
+    #[rstest]
+    #[case(Some("retro"), "~/notes/2024-03-01-retro.md", "retro")]
+    #[case(None, "~/notes/2024-03-01-meeting-notes 2024-03-01.md", "meeting-notes 2024-03-01")]
+    fn test_instantiate(
+        bms: Vec<Bookmark>,
+        #[case] title: Option<&str>,
+        #[case] url: &str,
+        #[case] expected_title: &str,
+    ) {
+        let template = Template::find(&bms, "meeting-notes").unwrap();
+        let bm = template.instantiate(title, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert_eq!(bm.URL, url);
+        assert_eq!(bm.metadata, expected_title);
+        assert_eq!(bm.tags, ",_edit_,meeting,");
+        assert_eq!(bm.desc, format!("Notes of {}", expected_title));
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::model::bookmark::Bookmark;
+
+/// Width of the bar of the largest bucket
+const BAR_WIDTH: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Period {
+    /// e.g. 2026-10
+    Month,
+    /// ISO week, e.g. 2026-W41
+    Week,
+}
+
+impl Period {
+    pub fn key(&self, ts: NaiveDateTime) -> String {
+        match self {
+            Period::Month => ts.format("%Y-%m").to_string(),
+            Period::Week => ts.format("%G-W%V").to_string(),
+        }
+    }
+
+    /// Checks a drill-down key, e.g. 2026-10 for months
+    pub fn parse_key(&self, key: &str) -> Result<String> {
+        let key = key.trim().to_uppercase();
+        let parsed = match self {
+            Period::Month => NaiveDate::parse_from_str(&format!("{}-01", key), "%Y-%m-%d"),
+            Period::Week => NaiveDate::parse_from_str(&format!("{}-1", key), "%G-W%V-%u"),
+        };
+        let example = match self {
+            Period::Month => "2026-10",
+            Period::Week => "2026-W41",
+        };
+        parsed
+            .map(|date| self.key(date.and_hms_opt(0, 0, 0).unwrap_or_default()))
+            .map_err(|_| anyhow!("Invalid {:?} {:?}, expected e.g. {}", self, key, example))
+    }
+}
+
+/// Bookmarks created in a period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bucket {
+    pub period: String,
+    pub count: usize,
+    pub ids: Vec<i32>,
+}
+
+/// Buckets in chronological order, periods without bookmarks are left out,
+/// as are bookmarks without creation time
+pub fn timeline(bms: &[Bookmark], period: Period) -> Vec<Bucket> {
+    let mut buckets: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+    for bm in bms {
+        if let Some(ts) = bm.created_ts {
+            buckets.entry(period.key(ts)).or_default().push(bm.id);
+        }
+    }
+    buckets
+        .into_iter()
+        .map(|(period, mut ids)| {
+            ids.sort();
+            Bucket {
+                period,
+                count: ids.len(),
+                ids,
+            }
+        })
+        .collect()
+}
+
+/// Bookmarks created in the period of the key, oldest first
+pub fn drill_down(bms: &[Bookmark], period: Period, key: &str) -> Vec<Bookmark> {
+    let mut selected: Vec<Bookmark> = bms
+        .iter()
+        .filter(|bm| bm.created_ts.is_some_and(|ts| period.key(ts) == key))
+        .cloned()
+        .collect();
+    selected.sort_by_key(|bm| (bm.created_ts, bm.id));
+    selected
+}
+
+/// One line per bucket with count and a bar relative to the largest bucket
+pub fn render_table(buckets: &[Bucket]) -> String {
+    let max = buckets.iter().map(|b| b.count).max().unwrap_or(0);
+    let width = buckets.iter().map(|b| b.period.len()).max().unwrap_or(0);
+    let count_width = max.to_string().len();
+    let mut out = String::new();
+    for bucket in buckets {
+        let bar = (bucket.count * BAR_WIDTH).div_ceil(max.max(1));
+        out.push_str(&format!(
+            "{:<width$}  {:>count_width$}  {}\n",
+            bucket.period,
+            bucket.count,
+            "#".repeat(bar),
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn bm(id: i32, date: (i32, u32, u32)) -> Bookmark {
+        Bookmark {
+            created_ts: NaiveDate::from_ymd_opt(date.0, date.1, date.2).and_then(|d| d.and_hms_opt(8, 0, 0)),
+            ..BookmarkBuilder::new()
+                .id(id)
+                .URL(format!("https://example.com/{}", id))
+                .build()
+        }
+    }
+
+    fn bms() -> Vec<Bookmark> {
+        vec![
+            bm(3, (2026, 10, 12)),
+            bm(1, (2026, 9, 28)),
+            bm(2, (2026, 10, 1)),
+            bm(4, (2025, 12, 29)),
+            BookmarkBuilder::new().id(5).URL("https://example.com/5".to_string()).build(),
+        ]
+    }
+
+    #[rstest]
+    fn test_timeline_by_month() {
+        let buckets = timeline(&bms(), Period::Month);
+        let periods: Vec<(&str, Vec<i32>)> = buckets.iter().map(|b| (b.period.as_str(), b.ids.clone())).collect();
+        assert_eq!(
+            periods,
+            vec![("2025-12", vec![4]), ("2026-09", vec![1]), ("2026-10", vec![2, 3])]
+        );
+    }
+
+    #[rstest]
+    fn test_timeline_by_week() {
+        let buckets = timeline(&bms(), Period::Week);
+        let periods: Vec<(&str, usize)> = buckets.iter().map(|b| (b.period.as_str(), b.count)).collect();
+        // 2025-12-29 is in the first ISO week of 2026, 2026-09-28 and 2026-10-01 share a week
+        assert_eq!(periods, vec![("2026-W01", 1), ("2026-W40", 2), ("2026-W42", 1)]);
+    }
+
+    #[rstest]
+    #[case(Period::Month, "2026-10", "2026-10")]
+    #[case(Period::Week, "2026-w40", "2026-W40")]
+    #[case(Period::Week, "2026-W1", "2026-W01")]
+    fn test_parse_key(#[case] period: Period, #[case] key: &str, #[case] expected: &str) -> Result<()> {
+        assert_eq!(period.parse_key(key)?, expected);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Period::Month, "2026-13")]
+    #[case(Period::Month, "2026-W40")]
+    #[case(Period::Week, "2026-10")]
+    fn test_parse_key_invalid(#[case] period: Period, #[case] key: &str) {
+        assert!(period.parse_key(key).is_err());
+    }
+
+    #[rstest]
+    fn test_drill_down() {
+        let ids: Vec<i32> = drill_down(&bms(), Period::Week, "2026-W40").iter().map(|bm| bm.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[rstest]
+    fn test_render_table() {
+        let buckets = timeline(&bms(), Period::Month);
+        assert_eq!(
+            render_table(&buckets),
+            format!("2025-12  1  {}\n2026-09  1  {}\n2026-10  2  {}\n", "#".repeat(20), "#".repeat(20), "#".repeat(40))
+        );
+    }
+}
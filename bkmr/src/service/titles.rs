@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::Url;
+
+use crate::environment::TitlesConfig;
+
+/// Separators between page title and site name, e.g. `Title | GitHub`
+const SITE_SEPARATORS: [&str; 6] = [" | ", " - ", " – ", " — ", " · ", " :: "];
+/// Host labels too generic to identify a site
+const GENERIC_LABELS: [&str; 3] = ["www", "com", "org"];
+
+const ENTITIES: [(&str, &str); 18] = [
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", " "),
+    ("ndash", "–"),
+    ("mdash", "—"),
+    ("hellip", "…"),
+    ("lsquo", "‘"),
+    ("rsquo", "’"),
+    ("ldquo", "“"),
+    ("rdquo", "”"),
+    ("middot", "·"),
+    ("bull", "•"),
+    ("copy", "©"),
+    ("reg", "®"),
+    ("trade", "™"),
+];
+
+/// Decodes named and numeric HTML entities, unknown entities are kept
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..].find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..=end];
+            let c = if let Some(hex) = entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32).map(String::from)
+            } else if let Some(dec) = entity.strip_prefix('#') {
+                dec.parse::<u32>().ok().and_then(char::from_u32).map(String::from)
+            } else {
+                ENTITIES.iter().find(|(name, _)| *name == entity).map(|(_, c)| c.to_string())
+            };
+            c.map(|c| (c, end + 2))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push_str(&c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn alphanumeric(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// The site name names the host, e.g. `GitHub` for github.com or `The Rust Blog` for blog.rust-lang.org
+fn names_site(name: &str, host: &str) -> bool {
+    let name = alphanumeric(name);
+    if name.is_empty() {
+        return false;
+    }
+    let labels: Vec<&str> = host.split('.').collect();
+    // without top level domain
+    let labels = &labels[..labels.len().saturating_sub(1)];
+    alphanumeric(host).contains(&name)
+        || labels
+            .iter()
+            .map(|label| alphanumeric(label))
+            .filter(|label| label.len() >= 3 && !GENERIC_LABELS.contains(&label.as_str()))
+            .any(|label| name.contains(&label))
+}
+
+/// Cleanup of fetched and stored titles as configured
+#[derive(Debug, Clone)]
+pub struct TitleRules {
+    pub decode_entities: bool,
+    pub collapse_whitespace: bool,
+    pub strip_site_name: bool,
+    patterns: Vec<Regex>,
+}
+
+impl TitleRules {
+    pub fn new(config: &TitlesConfig) -> Result<Self> {
+        let patterns = config
+            .strip_patterns
+            .iter()
+            .map(|p| Regex::new(p).with_context(|| format!("Invalid title pattern: {}", p)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            decode_entities: config.decode_entities,
+            collapse_whitespace: config.collapse_whitespace,
+            strip_site_name: config.strip_site_name,
+            patterns,
+        })
+    }
+
+    /// Title of the bookmark with the URL after applying the rules
+    pub fn normalize(&self, title: &str, url: &str) -> String {
+        let mut title = title.to_string();
+        if self.decode_entities {
+            title = decode_entities(&title);
+        }
+        if self.collapse_whitespace {
+            title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        for pattern in &self.patterns {
+            title = pattern.replace_all(&title, "").to_string();
+        }
+        if self.strip_site_name {
+            let host = Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_lowercase));
+            if let Some(host) = host {
+                let suffix = SITE_SEPARATORS
+                    .iter()
+                    .filter_map(|sep| title.rfind(sep).map(|i| (i, sep.len())))
+                    .max_by_key(|(i, _)| *i);
+                if let Some((i, len)) = suffix {
+                    if i > 0 && names_site(&title[i + len..], &host) {
+                        title.truncate(i);
+                    }
+                }
+            }
+        }
+        title.trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    fn rules() -> TitleRules {
+        TitleRules::new(&TitlesConfig {
+            strip_patterns: vec![r"^\(\d+\) ".to_string()],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("Tom &amp; Jerry &#8211; &#x27;cats&#39; &copy;", "Tom & Jerry – 'cats' ©")]
+    #[case("a & b &unknown; &#xZZ; &", "a & b &unknown; &#xZZ; &")]
+    #[case("&amp;amp;", "&amp;")]
+    fn test_decode_entities(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(decode_entities(text), expected);
+    }
+
+    #[rstest]
+    #[case("sysid/bkmr: A CLI bookmark manager | GitHub", "https://github.com/sysid/bkmr", "sysid/bkmr: A CLI bookmark manager")]
+    #[case("Fearless Concurrency - The Rust Blog", "https://blog.rust-lang.org/x", "Fearless Concurrency")]
+    #[case("(3) Never   Gonna Give You Up - YouTube", "https://www.youtube.com/watch?v=1", "Never Gonna Give You Up")]
+    #[case("Rust - A language empowering everyone", "https://www.rust-lang.org", "Rust - A language empowering everyone")]
+    #[case("Pros &amp; Cons | Example Corp", "https://example.com", "Pros & Cons")]
+    #[case("GitHub", "https://github.com", "GitHub")]
+    #[case("  snippet   title ", "ls -la", "snippet title")]
+    fn test_normalize(#[case] title: &str, #[case] url: &str, #[case] expected: &str) {
+        assert_eq!(rules().normalize(title, url), expected);
+    }
+
+    #[rstest]
+    fn test_normalize_disabled() -> Result<()> {
+        let rules = TitleRules::new(&TitlesConfig {
+            decode_entities: false,
+            collapse_whitespace: false,
+            strip_site_name: false,
+            strip_patterns: vec![],
+        })?;
+        assert_eq!(rules.normalize("A &amp;  B | GitHub", "https://github.com"), "A &amp;  B | GitHub");
+        assert!(TitleRules::new(&TitlesConfig {
+            strip_patterns: vec!["(".to_string()],
+            ..Default::default()
+        })
+        .is_err());
+        Ok(())
+    }
+}
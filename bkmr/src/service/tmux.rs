@@ -0,0 +1,85 @@
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use tracing::debug;
+
+use crate::util::helper::shell_quote;
+
+/// Environment of the popup, it inherits the environment of the tmux server, not of the pane
+pub fn forwarded_env(vars: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = vars.into_iter().filter(|(k, _)| k.starts_with("BKMR_")).collect();
+    vars.sort();
+    vars
+}
+
+/// `tmux display-popup` arguments running the picker of bkmr for the originating pane
+pub fn popup_args(exe: &str, pane: &str, tags: Option<&str>, env: &[(String, String)]) -> Vec<String> {
+    let mut args: Vec<String> = ["display-popup", "-E", "-w", "80%", "-h", "60%"].map(String::from).to_vec();
+    for (k, v) in env {
+        args.push("-e".to_string());
+        args.push(format!("{}={}", k, v));
+    }
+    let mut command = format!("{} tmux-popup --pane {}", shell_quote(exe), shell_quote(pane));
+    if let Some(tags) = tags {
+        command.push_str(&format!(" --tags {}", shell_quote(tags)));
+    }
+    args.push(command);
+    args
+}
+
+/// `tmux send-keys` arguments typing the text literally into the pane, without a final Enter
+pub fn send_keys_args(pane: &str, text: &str) -> Vec<String> {
+    vec![
+        "send-keys".to_string(),
+        "-t".to_string(),
+        pane.to_string(),
+        "-l".to_string(),
+        text.trim_end().to_string(),
+    ]
+}
+
+pub fn run(args: &[String]) -> Result<()> {
+    debug!("tmux {:?}", args);
+    let status = Command::new("tmux").args(args).status().context("Failed to run tmux")?;
+    if !status.success() {
+        return Err(anyhow!("tmux {} failed: {}", args[0], status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_popup_args() {
+        let env = forwarded_env([
+            ("HOME".to_string(), "/home/me".to_string()),
+            ("BKMR_DB_URL".to_string(), "/tmp/a b.db".to_string()),
+        ]);
+        assert_eq!(
+            popup_args("/usr/bin/bkmr", "%3", Some("_snip_"), &env),
+            vec![
+                "display-popup",
+                "-E",
+                "-w",
+                "80%",
+                "-h",
+                "60%",
+                "-e",
+                "BKMR_DB_URL=/tmp/a b.db",
+                "'/usr/bin/bkmr' tmux-popup --pane '%3' --tags '_snip_'",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_send_keys_args() {
+        assert_eq!(
+            send_keys_args("%3", "git log --oneline\n"),
+            vec!["send-keys", "-t", "%3", "-l", "git log --oneline"]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+use crate::model::bookmark::Bookmark;
+use crate::model::tag::Tags;
+
+/// Quickly captured bookmarks waiting for `bkmr triage`
+pub const INBOX_TAG: &str = "_inbox_";
+
+pub const TRIAGE_HELP: &str = r#"
+    k [tags]:       keep, adding the tags (separated by comma), leaves the inbox
+    d:              discard (delete)
+    o:              open
+    e:              edit
+    s | ENTER:      skip, stays in the inbox
+    q:              quit
+"#;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriageAction {
+    Keep(Vec<String>),
+    Discard,
+    Open,
+    Edit,
+    Skip,
+    Quit,
+}
+
+pub fn parse_action(input: &str) -> Result<TriageAction> {
+    let input = input.trim();
+    let (command, args) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    match command {
+        "k" | "keep" => Ok(TriageAction::Keep(Tags::normalize_tag_string(Some(
+            args.trim().to_string(),
+        )))),
+        "d" | "discard" => Ok(TriageAction::Discard),
+        "o" | "open" => Ok(TriageAction::Open),
+        "e" | "edit" => Ok(TriageAction::Edit),
+        "" | "s" | "skip" => Ok(TriageAction::Skip),
+        "q" | "quit" => Ok(TriageAction::Quit),
+        _ => Err(anyhow!("Unknown action {:?}, use:{}", command, TRIAGE_HELP)),
+    }
+}
+
+pub fn is_inbox(bm: &Bookmark) -> bool {
+    bm.get_tags().iter().any(|tag| tag == INBOX_TAG)
+}
+
+/// Inbox bookmarks in capture order, without the skipped ones (by uuid, ids change on discard)
+pub fn pending(bms: &[Bookmark], skipped: &HashSet<String>) -> Vec<Bookmark> {
+    let mut pending: Vec<Bookmark> = bms
+        .iter()
+        .filter(|bm| is_inbox(bm) && !skipped.contains(&bm.uuid))
+        .cloned()
+        .collect();
+    pending.sort_by_key(|bm| bm.id);
+    pending
+}
+
+/// Bookmark leaving the inbox with the added tags, a missing title and description are
+/// taken from the fetched URL details
+pub fn keep(bm: &Bookmark, tags: Vec<String>, title: String, desc: String) -> Bookmark {
+    let mut kept = bm.clone();
+    let mut all_tags: Vec<String> = bm.get_tags().into_iter().filter(|tag| tag != INBOX_TAG).collect();
+    all_tags.extend(tags);
+    kept.set_tags(all_tags);
+    if kept.metadata.is_empty() {
+        kept.metadata = title;
+    }
+    if kept.desc.is_empty() {
+        kept.desc = desc;
+    }
+    kept
+}
+
+/// Decisions of a triage session
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TriageProgress {
+    pub kept: usize,
+    pub discarded: usize,
+    pub skipped: usize,
+}
+
+impl TriageProgress {
+    pub fn done(&self) -> usize {
+        self.kept + self.discarded + self.skipped
+    }
+}
+
+impl fmt::Display for TriageProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "kept {}, discarded {}, skipped {}",
+            self.kept, self.discarded, self.skipped
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+    use crate::model::bookmark::BookmarkBuilder;
+
+    fn inbox_bm(id: i32, uuid: &str, tags: &str) -> Bookmark {
+        BookmarkBuilder::new()
+            .id(id)
+            .URL(format!("https://example.com/{}", id))
+            .tags(tags.to_string())
+            .uuid(uuid.to_string())
+            .build()
+    }
+
+    #[rstest]
+    #[case("k rust,Web", TriageAction::Keep(vec!["rust".to_string(), "web".to_string()]))]
+    #[case("k", TriageAction::Keep(vec![]))]
+    #[case("", TriageAction::Skip)]
+    #[case(" d ", TriageAction::Discard)]
+    #[case("q", TriageAction::Quit)]
+    fn test_parse_action(#[case] input: &str, #[case] expected: TriageAction) -> Result<()> {
+        assert_eq!(parse_action(input)?, expected);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_parse_action_unknown() {
+        assert!(parse_action("x").is_err());
+    }
+
+    #[rstest]
+    fn test_pending() {
+        let bms = vec![
+            inbox_bm(3, "c", ",_inbox_,"),
+            inbox_bm(1, "a", ",_inbox_,"),
+            inbox_bm(2, "b", ",rust,"),
+            inbox_bm(4, "d", ",_inbox_,"),
+        ];
+        let skipped = HashSet::from(["d".to_string()]);
+        let ids: Vec<i32> = pending(&bms, &skipped).iter().map(|bm| bm.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+
+    #[rstest]
+    fn test_keep() {
+        let bm = inbox_bm(1, "a", ",_inbox_,web,");
+        let kept = keep(&bm, vec!["rust".to_string()], "Title".to_string(), String::new());
+        assert_eq!(kept.tags, ",rust,web,");
+        assert_eq!(kept.metadata, "Title");
+        assert!(!is_inbox(&kept));
+
+        let titled = Bookmark {
+            metadata: "Mine".to_string(),
+            ..bm
+        };
+        assert_eq!(keep(&titled, vec![], "Web".to_string(), String::new()).metadata, "Mine");
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SubsecRound, Utc};
+
+use crate::context::Context;
+
+/// Source of the current time, replaceable in tests via `Context::with_clock`
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Always returns the same instant
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// Current time of the global context clock
+pub fn now() -> DateTime<Utc> {
+    Context::read_global().now()
+}
+
+/// Current time as stored in the database: UTC, whole seconds
+pub fn now_naive() -> NaiveDateTime {
+    now().trunc_subsecs(0).naive_utc()
+}
+
+/// Current local date
+pub fn today() -> NaiveDate {
+    now().with_timezone(&Local).date_naive()
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+    use serial_test::serial;
+
+    use super::*;
+    use crate::adapter::embeddings::DummyEmbedding;
+
+    #[rstest]
+    #[serial]
+    fn test_fixed_clock() -> anyhow::Result<()> {
+        let instant = DateTime::parse_from_rfc3339("2024-02-29T12:30:45.5Z")?.to_utc();
+        Context::update_global(
+            Context::new(Box::new(DummyEmbedding)).with_clock(Box::new(FixedClock(instant))),
+        )?;
+        assert_eq!(now(), instant);
+        assert_eq!(now_naive().to_string(), "2024-02-29 12:30:45");
+
+        Context::update_global(Context::new(Box::new(DummyEmbedding)))?;
+        assert_ne!(now(), instant);
+        Ok(())
+    }
+}
@@ -1,14 +1,17 @@
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
-use std::{env, io};
+use std::{env, io, process};
 use anyhow::Result;
 use camino::{Utf8Path, Utf8PathBuf};
 use camino_tempfile::tempdir;
 use fs_extra::{copy_items, dir};
 use regex::Regex;
-use reqwest::blocking;
+use reqwest::Method;
 use tracing::debug;
 
+use crate::service::fetch;
+
 
 /// Prepare test directory with test data and return path
 pub fn temp_dir() -> Utf8PathBuf {
@@ -54,11 +57,147 @@ pub fn abspath(p: &str) -> Option<String> {
     abs_p
 }
 
+/// true if text contains Chinese, Japanese or Korean characters,
+/// which are not separated by word boundaries
+pub fn contains_cjk(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(c,
+            '\u{1100}'..='\u{11FF}'   // Hangul Jamo
+            | '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+            | '\u{3130}'..='\u{318F}' // Hangul Compatibility Jamo
+            | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+            | '\u{AC00}'..='\u{D7AF}' // Hangul Syllables
+            | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+            | '\u{FF66}'..='\u{FF9F}' // Halfwidth Katakana
+        )
+    })
+}
+
+/// Converts a glob with `*` and `?` wildcards into an anchored regex
+pub fn glob_to_regex(glob: &str) -> String {
+    let pattern = glob
+        .split('*')
+        .map(|part| {
+            part.split('?')
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(".")
+        })
+        .collect::<Vec<_>>()
+        .join(".*");
+    format!("^{}$", pattern)
+}
+
+/// Single-quotes s for use as one shell word
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Replaces `$VAR` and `${VAR}` with environment variables, unknown variables are an error
+pub fn interpolate_env(template: &str) -> anyhow::Result<String> {
+    shellexpand::env(template)
+        .map(|expanded| expanded.into_owned())
+        .map_err(|e| anyhow::anyhow!("Failed to interpolate {:?}: {}", e.var_name, e.cause))
+}
+
+/// Version byte prefixed to content hashes, unversioned 16 byte hashes are legacy md5 of the raw content
+pub const CONTENT_HASH_VERSION: u8 = 2;
+
+/// Content hash v2: the version byte followed by the md5 of the content with whitespace runs
+/// collapsed to a single blank and trimmed, so reformatting does not cause re-embedding.
 pub fn calc_content_hash(content: &str) -> Vec<u8> {
-    md5::compute(content).0.to_vec()
+    let normalized = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut hash = vec![CONTENT_HASH_VERSION];
+    hash.extend(md5::compute(normalized).0);
+    hash
+}
+
+/// True if hash is the content hash of content, legacy hashes are still recognized
+pub fn content_hash_matches(hash: &[u8], content: &str) -> bool {
+    hash == calc_content_hash(content) || hash == md5::compute(content).0
+}
+
+pub fn is_legacy_content_hash(hash: &[u8]) -> bool {
+    hash.len() == 16
+}
+
+/// Parses an age like `12h`, `30d`, `2w`, `6m` (30 days) or `1y` (365 days)
+pub fn parse_age(age: &str) -> Result<chrono::Duration> {
+    let age = age.trim();
+    let invalid = || anyhow::anyhow!("Invalid age {:?}, use e.g. 30d, 2w, 6m, 1y", age);
+    let split = age.len() - age.chars().last().map_or(0, char::len_utf8);
+    let n: i64 = age[..split].parse().map_err(|_| invalid())?;
+    let days = match &age[split..] {
+        "h" => return Ok(chrono::Duration::hours(n)),
+        "d" => n,
+        "w" => n * 7,
+        "m" => n * 30,
+        "y" => n * 365,
+        _ => return Err(invalid()),
+    };
+    Ok(chrono::Duration::days(days))
+}
+
+/// Random UUID v4, e.g. `0b3e9c1a-5f2d-4c8e-9a71-3d6f0e2b8c45`
+pub fn new_uuid() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Exit code when input is required but disabled by `--no-input`
+pub const NO_INPUT_EXIT_CODE: i32 = 3;
+
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_no_input(no_input: bool) {
+    NO_INPUT.store(no_input, Ordering::Relaxed);
+}
+
+pub fn input_allowed() -> bool {
+    !NO_INPUT.load(Ordering::Relaxed)
+}
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Output without colors, and line based prompts instead of the fzf screen, for screen readers
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+    if plain {
+        crossterm::style::force_color_output(false);
+    }
+}
+
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Fails deterministically with NO_INPUT_EXIT_CODE instead of waiting for input, if `--no-input` is set
+pub fn require_input(what: &str) {
+    if NO_INPUT.load(Ordering::Relaxed) {
+        eprintln!("Error: input required ({}), but --no-input is set", what.trim());
+        process::exit(NO_INPUT_EXIT_CODE);
+    }
+}
+
+/// Reads a line from stdin after showing the prompt on stderr
+pub fn prompt(prompt: &str) -> String {
+    require_input(prompt);
+    eprint!("{}", prompt);
+    io::stderr().flush().unwrap();
+
+    let mut user_input = String::new();
+    io::stdin()
+        .read_line(&mut user_input)
+        .expect("Failed to read line");
+    user_input.trim_end_matches(['\n', '\r']).to_string()
 }
 
 pub fn confirm(prompt: &str) -> bool {
+    require_input(prompt);
     print!("{} (y/N): ", prompt);
     io::stdout().flush().unwrap(); // Ensure the prompt is displayed immediately
 
@@ -70,14 +209,18 @@ pub fn confirm(prompt: &str) -> bool {
     matches!(user_input.trim().to_lowercase().as_str(), "y" | "yes")
 }
 
+/// URLs of domains which must not be fetched are reported as unreachable
 pub fn check_website(url: &str, timeout_milliseconds: u64) -> (bool, u128) {
-    let client = blocking::Client::builder()
-        .timeout(Duration::from_millis(timeout_milliseconds))
-        .build()
-        .unwrap_or_else(|_| blocking::Client::new()); // Fallback to default client in case of builder failure
+    let request = match fetch::request(Method::HEAD, url, Some(Duration::from_millis(timeout_milliseconds))) {
+        Ok(request) => request,
+        Err(e) => {
+            debug!("{}", e);
+            return (false, 0);
+        }
+    };
 
     let start = Instant::now();
-    let response = client.head(url).send();
+    let response = request.send();
 
     match response {
         Ok(resp) if resp.status().is_success() => {
@@ -148,6 +291,56 @@ mod test {
         assert_eq!(abspath(x), expected);
     }
 
+    #[rstest]
+    #[case("api.github.com", "*.github.com", true)]
+    #[case("github.com", "*.github.com", false)]
+    #[case("github.com", "github.???", true)]
+    #[case("githubxcom", "github.com", false)]
+    fn test_glob_to_regex(#[case] host: &str, #[case] glob: &str, #[case] expected: bool) {
+        let regex = Regex::new(&glob_to_regex(glob)).unwrap();
+        assert_eq!(regex.is_match(host), expected);
+    }
+
+    #[rstest]
+    #[case("12h", Some(chrono::Duration::hours(12)))]
+    #[case("30d", Some(chrono::Duration::days(30)))]
+    #[case("2w", Some(chrono::Duration::days(14)))]
+    #[case("6m", Some(chrono::Duration::days(180)))]
+    #[case("1y", Some(chrono::Duration::days(365)))]
+    #[case("1", None)]
+    #[case("y", None)]
+    #[case("3x", None)]
+    #[case("", None)]
+    fn test_parse_age(#[case] age: &str, #[case] expected: Option<chrono::Duration>) {
+        assert_eq!(parse_age(age).ok(), expected);
+    }
+
+    #[rstest]
+    fn test_new_uuid() {
+        let uuid = new_uuid();
+        let re = Regex::new(r"^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$").unwrap();
+        assert!(re.is_match(&uuid), "{}", uuid);
+        assert_ne!(uuid, new_uuid());
+    }
+
+    #[rstest]
+    #[case("it's", r"'it'\''s'")]
+    #[case("https://a?b=1&c", "'https://a?b=1&c'")]
+    fn test_shell_quote(#[case] s: &str, #[case] expected: &str) {
+        assert_eq!(shell_quote(s), expected);
+    }
+
+    #[rstest]
+    #[case("中文标题", true)]
+    #[case("日本語のタイトル", true)]
+    #[case("한국어", true)]
+    #[case("rust 中文", true)]
+    #[case("Grüße, naïve café", false)]
+    #[case("", false)]
+    fn test_contains_cjk(#[case] text: &str, #[case] expected: bool) {
+        assert_eq!(contains_cjk(text), expected);
+    }
+
     #[rstest]
     fn abspath_returns_none_if_path_does_not_exist() {
         let input = "/non/existent/path";
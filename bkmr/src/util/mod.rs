@@ -1,2 +1,4 @@
 pub mod testing;
-pub mod helper;
\ No newline at end of file
+pub mod helper;
+pub mod clock;
+pub mod logging;
\ No newline at end of file
@@ -96,6 +96,15 @@ pub fn setup_test_db() -> Result<Dal> {
     migration::init_db(&mut dal.conn).context("Failed to initialize test database")?;
     Ok(dal)
 }
+/// Creates an initialized database in its own temp directory for tests which modify data.
+/// The database is removed when the returned directory is dropped.
+pub fn setup_temp_db() -> Result<(tempfile::TempDir, Dal)> {
+    let dir = tempfile::tempdir().context("Failed to create temp directory")?;
+    let mut dal = Dal::new(dir.path().join("bkmr.db").to_string_lossy().to_string());
+    migration::init_db(&mut dal.conn).context("Failed to initialize temp database")?;
+    Ok((dir, dal))
+}
+
 #[fixture]
 pub fn test_dal() -> Dal {
     setup_test_db()
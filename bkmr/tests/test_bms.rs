@@ -2,6 +2,7 @@ use rstest::*;
 use tracing::debug;
 use bkmr::adapter::dal::{migration, Dal};
 use bkmr::model::bms::Bookmarks;
+use bkmr::model::bookmark::Bookmark;
 use bkmr::util::testing::init_test_setup;
 
 #[ctor::ctor]
@@ -72,3 +73,28 @@ fn given_tag_set_when_filtering_exact_match_then_returns_exact_matches() {
     bms.filter(None, None, None, None, Some(",aaa,bbb,".to_string()));
     assert_eq!(bms.bms.len(), 2);
 }
+
+#[rstest]
+fn given_untagged_filter_when_filtering_then_returns_only_untagged_bookmarks() {
+    let mut bms = Bookmarks::new("".to_string());
+    bms.filter_untagged();
+    assert_eq!(bms.bms.len(), 5);
+    assert!(bms.bms.iter().all(|bm| bm.tags == ",,"));
+}
+
+#[rstest]
+fn given_usage_filter_when_filtering_then_returns_matching_bookmarks() {
+    let mut bms = Bookmarks::new("".to_string());
+    let ts = |days: i64| chrono::Utc::now().naive_utc() - chrono::Duration::days(days);
+    bms.bms = vec![
+        Bookmark { id: 1, flags: 5, last_update_ts: ts(1), ..Default::default() },
+        Bookmark { id: 2, flags: 0, last_update_ts: ts(400), ..Default::default() },
+        Bookmark { id: 3, flags: 3, last_update_ts: ts(200), ..Default::default() },
+    ];
+
+    bms.filter_usage(Some(3), None);
+    assert_eq!(bms.bms.iter().map(|bm| bm.id).collect::<Vec<_>>(), vec![1, 3]);
+
+    bms.filter_usage(None, Some(ts(180)));
+    assert_eq!(bms.bms.iter().map(|bm| bm.id).collect::<Vec<_>>(), vec![3]);
+}
@@ -7,8 +7,10 @@ use tracing::{debug, info};
 use bkmr::adapter::dal::Dal;
 use bkmr::adapter::embeddings::DummyEmbedding;
 use bkmr::context::Context;
-use bkmr::model::bookmark::{BookmarkBuilder, BookmarkUpdater};
-use bkmr::util::testing::{test_dal};
+use bkmr::model::bookmark::{Bookmark, BookmarkBuilder, BookmarkUpdater};
+use bkmr::model::readable::Readable;
+use bkmr::util::clock;
+use bkmr::util::testing::{setup_temp_db, test_dal};
 
 #[rstest]
 fn given_database_when_initializing_then_succeeds(_test_dal: Dal) {
@@ -43,6 +45,30 @@ fn given_search_query_when_getting_bookmarks_then_returns_matching_results(mut t
     Ok(())
 }
 
+#[rstest]
+#[case("数据库", 1)]
+#[case("数据", 1)]
+#[case("管理 数据库", 1)]
+#[case("東京", 1)]
+#[case("数据 東京", 0)]
+fn given_cjk_query_when_getting_bookmarks_then_matches_substrings(#[case] input: &str, #[case] expected: usize) -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    for (url, title) in [("https://cjk.example/zh", "中文数据库管理"), ("https://cjk.example/ja", "東京の天気")] {
+        let bm = BookmarkBuilder::new()
+            .URL(url.to_string())
+            .metadata(title.to_string())
+            .tags(",,".to_string())
+            .desc("".to_string())
+            .flags(0)
+            .build();
+        dal.insert_bookmark(bm.convert_to_new_bookmark())?;
+    }
+
+    let bms = dal.get_bookmarks(input)?;
+    assert_eq!(bms.len(), expected);
+    Ok(())
+}
+
 #[rstest]
 fn given_database_when_getting_bookmarks_without_embedding_then_returns_only_empty_embeddings(mut test_dal: Dal) -> Result<()> {
     let bookmarks_without_embedding = test_dal.get_bookmarks_without_embedding()?;
@@ -232,4 +258,238 @@ fn given_database_when_checking_embedding_column_then_confirms_existence(mut tes
     println!("Result: {:?}", exists);
     assert!(exists);
     Ok(())
-}
\ No newline at end of file
+}
+#[rstest]
+fn given_failing_transaction_when_executing_then_rolls_back_changes() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let bm = dal.get_bookmark_by_id(1)?;
+
+    let result: Result<()> = dal.transaction(|dal| {
+        dal.update_bookmark(Bookmark {
+            tags: ",changed,".to_string(),
+            ..bm.clone()
+        })?;
+        Err(anyhow::anyhow!("abort"))
+    });
+
+    assert!(result.is_err());
+    assert_eq!(dal.get_bookmark_by_id(1)?.tags, bm.tags);
+    Ok(())
+}
+
+#[rstest]
+fn given_successful_transaction_when_executing_then_commits_changes() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let bm = dal.get_bookmark_by_id(1)?;
+
+    dal.transaction(|dal| {
+        dal.update_bookmark(Bookmark {
+            tags: ",changed,".to_string(),
+            ..bm
+        })
+    })?;
+
+    assert_eq!(dal.get_bookmark_by_id(1)?.tags, ",changed,");
+    Ok(())
+}
+
+#[rstest]
+fn given_outdated_version_when_updating_then_fails_with_conflict() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let bm = dal.get_bookmark_by_id(1)?;
+
+    let updated = dal
+        .update_bookmark(Bookmark {
+            tags: ",first,".to_string(),
+            ..bm.clone()
+        })?
+        .remove(0);
+    assert_eq!(updated.version, bm.version + 1);
+
+    // second writer still holds the version read before
+    let error = dal
+        .update_bookmark(Bookmark {
+            tags: ",second,".to_string(),
+            ..bm
+        })
+        .unwrap_err();
+    assert!(error.to_string().starts_with("Conflict: bookmark 1"), "{}", error);
+    assert_eq!(dal.get_bookmark_by_id(1)?.tags, ",first,");
+    Ok(())
+}
+
+#[rstest]
+fn given_deleted_bookmark_when_updating_stale_copy_then_fails_with_conflict() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let stale = dal.get_bookmark_by_id(1)?;
+    let moved = dal.get_bookmark_by_id(2)?;
+
+    // compaction moves the following bookmarks down to close the gap
+    dal.delete_and_compact(1)?;
+    assert_eq!(dal.get_bookmark_by_id(1)?.uuid, moved.uuid);
+
+    let error = dal
+        .update_bookmark(Bookmark {
+            tags: ",stale,".to_string(),
+            version: moved.version,
+            ..stale
+        })
+        .unwrap_err();
+    assert!(error.to_string().starts_with("Conflict: bookmark 1 has been deleted"), "{}", error);
+    assert_eq!(dal.get_bookmark_by_id(1)?.tags, moved.tags);
+    Ok(())
+}
+
+#[rstest]
+fn given_deleted_last_bookmark_when_updating_stale_copy_then_fails_with_conflict() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let last = dal.get_bookmarks("")?.pop().unwrap();
+    dal.delete_and_compact(last.id)?;
+
+    let error = dal.update_bookmark(last.clone()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("Conflict: bookmark {} was deleted concurrently, reload and retry", last.id)
+    );
+    Ok(())
+}
+
+#[rstest]
+fn given_nested_transactions_when_outer_fails_then_rolls_back_all_changes() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let (bm1, bm2) = (dal.get_bookmark_by_id(1)?, dal.get_bookmark_by_id(2)?);
+
+    let result: Result<()> = dal.transaction(|dal| {
+        // failing inner unit of work only rolls back its own changes
+        let inner: Result<()> = dal.transaction(|dal| {
+            dal.update_bookmark(Bookmark {
+                tags: ",inner,".to_string(),
+                ..bm2.clone()
+            })?;
+            Err(anyhow::anyhow!("inner abort"))
+        });
+        assert!(inner.is_err());
+        dal.transaction(|dal| {
+            dal.update_bookmark(Bookmark {
+                tags: ",changed,".to_string(),
+                ..bm1.clone()
+            })
+        })?;
+        assert_eq!(dal.get_bookmark_by_id(1)?.tags, ",changed,");
+        assert_eq!(dal.get_bookmark_by_id(2)?.tags, bm2.tags);
+        Err(anyhow::anyhow!("abort"))
+    });
+
+    assert!(result.is_err());
+    assert_eq!(dal.get_bookmark_by_id(1)?.tags, bm1.tags);
+    Ok(())
+}
+
+#[rstest]
+fn given_new_bookmark_when_inserting_then_gets_uuid_resolvable_by_prefix() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let bm = BookmarkBuilder::new()
+        .URL("https://uuid.example".to_string())
+        .build();
+    let inserted = dal.insert_bookmark(bm.convert_to_new_bookmark())?.remove(0);
+    assert_eq!(inserted.uuid.len(), 36);
+
+    let found = dal.get_bookmark_by_uuid(&inserted.uuid[..8].to_uppercase())?;
+    assert_eq!(found.id, inserted.id);
+    assert!(dal.get_bookmark_by_uuid("ffffffff-0000").is_err());
+
+    // existing uuids are preserved, e.g. on merge
+    let bm = BookmarkBuilder::new()
+        .URL("https://uuid2.example".to_string())
+        .uuid("0b3e9c1a-5f2d-4c8e-9a71-3d6f0e2b8c45".to_string())
+        .build();
+    let inserted = dal.insert_bookmark(bm.convert_to_new_bookmark())?.remove(0);
+    assert_eq!(inserted.uuid, "0b3e9c1a-5f2d-4c8e-9a71-3d6f0e2b8c45");
+    Ok(())
+}
+
+#[rstest]
+fn given_bookmark_with_language_when_updating_then_language_is_stored() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let bm = BookmarkBuilder::new()
+        .URL("fn main() {}".to_string())
+        .language(Some("rust".to_string()))
+        .build();
+    let mut inserted = dal.insert_bookmark(bm.convert_to_new_bookmark())?.remove(0);
+    assert_eq!(inserted.language.as_deref(), Some("rust"));
+
+    inserted.language = None;
+    dal.update_bookmark(inserted.clone())?;
+    assert_eq!(dal.get_bookmark_by_id(inserted.id)?.language, None);
+    Ok(())
+}
+
+#[rstest]
+fn given_readable_text_when_searching_and_deleting_then_follows_bookmark() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let bm = dal.get_bookmark_by_id(2)?;
+    let mut readable = Readable {
+        uuid: bm.uuid.clone(),
+        title: "Title".to_string(),
+        byline: "Jane".to_string(),
+        text: "ownership and borrowing".to_string(),
+        fetched_ts: clock::now_naive(),
+    };
+    dal.upsert_readable(&readable)?;
+    readable.text = "lifetimes and borrowing".to_string();
+    dal.upsert_readable(&readable)?;
+    assert_eq!(dal.get_readable(&bm.uuid)?, Some(readable));
+    assert_eq!(dal.get_readable_fts("lifetimes")?, vec![2]);
+    assert!(dal.get_readable_fts("ownership")?.is_empty());
+
+    // survives the id compaction of a preceding delete, is deleted with its bookmark
+    dal.delete_bookmark2(1)?;
+    assert_eq!(dal.get_readable_fts("lifetimes")?, vec![1]);
+    dal.delete_bookmark2(1)?;
+    assert_eq!(dal.get_readable(&bm.uuid)?, None);
+    assert!(dal.get_readable_fts("lifetimes")?.is_empty());
+    Ok(())
+}
+
+#[rstest]
+fn given_database_when_reporting_sizes_and_vacuuming_then_free_pages_are_released() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let sizes = dal.get_object_sizes()?;
+    let bookmarks = sizes.iter().find(|s| s.name == "bookmarks").unwrap();
+    assert_eq!(bookmarks.kind, "table");
+    assert!(bookmarks.size > 0);
+
+    let big = BookmarkBuilder::new()
+        .URL("https://big.example".to_string())
+        .desc("x".repeat(100_000))
+        .build();
+    let inserted = dal.insert_bookmark(big.convert_to_new_bookmark())?.remove(0);
+    dal.delete_bookmark2(inserted.id)?;
+    assert!(dal.get_page_stats()?.freelist_count > 0);
+    dal.vacuum()?;
+    dal.analyze()?;
+    assert_eq!(dal.get_page_stats()?.freelist_count, 0);
+    Ok(())
+}
+
+#[rstest]
+fn given_embedded_bookmark_when_listing_then_embedding_is_loaded_only_on_demand() -> Result<()> {
+    let (_dir, mut dal) = setup_temp_db()?;
+    let mut bm = dal.get_bookmark_by_id(1)?;
+    bm.embedding = Some(vec![1, 2, 3, 4]);
+    dal.update_bookmark(bm)?;
+
+    let listed = dal.get_bookmarks("")?.into_iter().find(|bm| bm.id == 1).unwrap();
+    assert!(listed.has_unloaded_embedding());
+    assert!(dal.get_bookmarks("")?.iter().filter(|bm| bm.id != 1).all(|bm| bm.embedding.is_none()));
+
+    // an update of the listed bookmark keeps the stored embedding
+    let mut listed = listed;
+    listed.desc = "changed".to_string();
+    dal.update_bookmark(listed)?;
+    assert_eq!(dal.get_bookmark_by_id(1)?.embedding, Some(vec![1, 2, 3, 4]));
+
+    let full = dal.get_bookmarks_with_embeddings("")?;
+    assert_eq!(full.iter().find(|bm| bm.id == 1).unwrap().embedding, Some(vec![1, 2, 3, 4]));
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use anyhow::Result;
 use rstest::*;
 
@@ -5,7 +7,8 @@ use bkmr::adapter::dal::Dal;
 use bkmr::context::CTX;
 use bkmr::util::testing::init_test_setup;
 use bkmr::util::testing::test_dal;
-use bkmr::{load_url_details, update_bm, update_bookmarks};
+use bkmr::model::bookmark::Bookmark;
+use bkmr::{load_url_details, retag, update_bm, update_bookmarks};
 
 #[ctor::ctor]
 fn init() {
@@ -44,7 +47,7 @@ fn given_bookmark_id_when_updating_with_tags_then_modifies_correctly(
 
 #[rstest]
 fn given_bookmark_when_updating_then_succeeds(mut test_dal: Dal) -> Result<()> {
-    update_bm(1, &vec![], &vec![], &mut test_dal, false)?;
+    update_bm(1, &[], &[], &mut test_dal, false)?;
     Ok(())
 }
 
@@ -75,3 +78,24 @@ fn given_bookmark_list_when_updating_multiple_then_succeeds() {
 fn given_context_when_initializing_then_exists() {
     assert!(CTX.get().is_some());
 }
+
+#[rstest]
+#[case(",ccc,yyy,", vec!["t1"], vec![], false, ",ccc,t1,yyy,")]
+#[case(",ccc,yyy,", vec![], vec!["ccc"], false, ",yyy,")]
+#[case(",ccc,yyy,", vec!["ccc"], vec![], false, ",ccc,yyy,")]
+#[case(",ccc,yyy,", vec!["t1"], vec![], true, ",t1,")]
+fn given_tag_changes_when_retagging_then_returns_new_tag_string(
+    #[case] bm_tags: &str,
+    #[case] tags: Vec<&str>,
+    #[case] tags_not: Vec<&str>,
+    #[case] force: bool,
+    #[case] expected: &str,
+) {
+    let bm = Bookmark {
+        tags: bm_tags.to_string(),
+        ..Default::default()
+    };
+    let tags: HashSet<String> = tags.into_iter().map(String::from).collect();
+    let tags_not: HashSet<String> = tags_not.into_iter().map(String::from).collect();
+    assert_eq!(retag(&bm, &tags, &tags_not, force), expected);
+}
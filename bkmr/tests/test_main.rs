@@ -36,3 +36,12 @@ fn given_bookmark_ids_when_showing_then_displays_correct_entries() {
         .stderr(predicate::str::contains("Debug mode: debug"))
         .stderr(predicate::str::contains("Google"));
 }
+
+#[rstest]
+fn given_no_input_flag_when_confirmation_required_then_fails_with_exit_code() {
+    let mut cmd = Command::cargo_bin("bkmr").unwrap();
+    cmd.args(["--no-input", "update", "--query", "google", "--add-tags", "xxx"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("--no-input is set"));
+}